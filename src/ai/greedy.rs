@@ -0,0 +1,220 @@
+use std::collections::VecDeque;
+
+use interfaces::ClientPerspective;
+use num_rational::Rational32;
+use rustc_hash::FxHashMap as HashMap;
+
+use crate::config::OwnershipPredicate;
+use crate::config::tag_config::TagType;
+use crate::game::commands::Command;
+use crate::game::game::Game;
+use crate::map::board::BoardView;
+use crate::map::direction::Direction;
+use crate::map::map::{get_neighbor, valid_points};
+use crate::map::point::Point;
+use crate::tags::TagValue;
+use crate::units::commands::{UnitAction, UnitCommand};
+use crate::units::unit::Unit;
+
+use super::AiPlayer;
+
+/**
+ * repeatedly picks the legal command with the best score until no command improves the position.
+ * attacks and terrain actions (e.g. building units) are scored by simulating them
+ * and comparing the value of both armies before and after.
+ * captures and plain movement are scored with simple heuristics.
+ */
+#[derive(Debug, Clone)]
+pub struct GreedyAi {
+    /// name of the custom action (custom_actions.csv) that captures a property
+    pub capture_action: String,
+    /// column in units.csv that contains the value of a unit type
+    pub value_column: String,
+    /// score of capturing a property with an income factor of 1
+    pub capture_score: i32,
+    /// score per field a unit moves closer to an enemy or a property it doesn't own
+    pub approach_score: i32,
+    /// upper limit for commands per turn, EndTurn excluded
+    pub max_commands: usize,
+}
+
+impl Default for GreedyAi {
+    fn default() -> Self {
+        Self {
+            capture_action: "Capture".to_string(),
+            value_column: "Value".to_string(),
+            capture_score: 300,
+            approach_score: 10,
+            max_commands: 100,
+        }
+    }
+}
+
+impl<D: Direction> AiPlayer<D> for GreedyAi {
+    fn plan_turn(&mut self, game: &Game<D>) -> Vec<Command<D>> {
+        let team = game.current_team();
+        let mut game = game.clone();
+        let mut result = Vec::new();
+        while result.len() < self.max_commands && !game.has_ended() {
+            let distances = target_distances(&game);
+            let mut best: Option<(i32, Command<D>)> = None;
+            for command in game.legal_commands() {
+                if command == Command::EndTurn {
+                    continue;
+                }
+                let Some(score) = self.score(&game, &command, team, &distances) else {
+                    continue;
+                };
+                if score > 0 && best.as_ref().map(|(s, _)| score > *s).unwrap_or(true) {
+                    best = Some((score, command));
+                }
+            }
+            let Some((_, command)) = best else {
+                break;
+            };
+            if game.handle_command(command.clone()).is_err() {
+                break;
+            }
+            result.push(command);
+        }
+        result.push(Command::EndTurn);
+        result
+    }
+}
+
+impl GreedyAi {
+    fn score<D: Direction>(
+        &self,
+        game: &Game<D>,
+        command: &Command<D>,
+        team: ClientPerspective,
+        distances: &HashMap<Point, usize>,
+    ) -> Option<i32> {
+        let config = &game.environment().config;
+        match command {
+            Command::UnitCommand(UnitCommand { path, action, .. }) => match action {
+                UnitAction::Attack(_) => self.simulate(game, command, team),
+                UnitAction::Custom(index, _)
+                    if config.custom_actions()[index.0].name == self.capture_action =>
+                {
+                    let (destination, _) = path.end(game).ok()?;
+                    let terrain = game.get_terrain(destination)?;
+                    let factor = config.terrain_base_income_factor(terrain.typ());
+                    Some((Rational32::from_integer(self.capture_score) * factor).to_integer())
+                }
+                UnitAction::Wait => {
+                    let (destination, _) = path.end(game).ok()?;
+                    let before = *distances.get(&path.start)? as i32;
+                    let after = *distances.get(&destination)? as i32;
+                    Some((before - after) * self.approach_score)
+                }
+                _ => None,
+            },
+            Command::TerrainAction(_, _) => self.simulate(game, command, team),
+            _ => None,
+        }
+    }
+
+    /**
+     * executes the command on a copy of the game.
+     * returns how much the value of the own army grew compared to the value of the enemy armies
+     */
+    fn simulate<D: Direction>(
+        &self,
+        game: &Game<D>,
+        command: &Command<D>,
+        team: ClientPerspective,
+    ) -> Option<i32> {
+        let (own_before, enemy_before) = self.army_values(game, team);
+        let mut game = game.clone();
+        game.handle_command(command.clone()).ok()?;
+        let (own_after, enemy_after) = self.army_values(&game, team);
+        Some((own_after - own_before) - (enemy_after - enemy_before))
+    }
+
+    fn army_values<D: Direction>(&self, game: &Game<D>, team: ClientPerspective) -> (i32, i32) {
+        let mut own = 0;
+        let mut enemy = 0;
+        for p in valid_points(game) {
+            let Some(unit) = game.get_unit(p) else {
+                continue;
+            };
+            match unit.get_team() {
+                ClientPerspective::Neutral => (),
+                t if t == team => own += self.unit_value(unit),
+                _ => enemy += self.unit_value(unit),
+            }
+        }
+        (own, enemy)
+    }
+
+    /**
+     * value of the unit type, scaled by remaining hp. includes transported units
+     */
+    fn unit_value<D: Direction>(&self, unit: &Unit<D>) -> i32 {
+        let environment = unit.environment();
+        let base = environment
+            .unit_custom_attribute(unit.typ(), self.value_column.as_str().into())
+            .and_then(|value| value.parse::<i32>().ok())
+            .unwrap_or(0);
+        let mut value = base;
+        if let Some(key) = environment.config.health_tag() {
+            if let (TagType::Int { max, .. }, Some(TagValue::Int(hp))) =
+                (environment.config.tag_type(key), unit.get_tag(key))
+            {
+                if *max > 0 {
+                    value = base * hp.0 / *max;
+                }
+            }
+        }
+        value
+            + unit
+                .get_transported()
+                .iter()
+                .map(|u| self.unit_value(u))
+                .sum::<i32>()
+    }
+}
+
+/**
+ * for every point, the number of steps to the nearest enemy unit or income-generating property
+ * the current player doesn't own. ignores terrain movement costs
+ */
+fn target_distances<D: Direction>(game: &Game<D>) -> HashMap<Point, usize> {
+    let config = &game.environment().config;
+    let owner_id = game.current_owner();
+    let team = game.current_team();
+    let mut result = HashMap::default();
+    let mut queue = VecDeque::new();
+    for p in valid_points(game) {
+        let enemy = game
+            .get_unit(p)
+            .map(|unit| unit.get_team() != team && unit.get_team() != ClientPerspective::Neutral)
+            .unwrap_or(false);
+        let property = game
+            .get_terrain(p)
+            .map(|terrain| {
+                terrain.get_owner_id() != owner_id
+                    && config.terrain_ownership(terrain.typ()) != OwnershipPredicate::Never
+                    && config.terrain_base_income_factor(terrain.typ())
+                        > Rational32::from_integer(0)
+            })
+            .unwrap_or(false);
+        if enemy || property {
+            result.insert(p, 0);
+            queue.push_back(p);
+        }
+    }
+    while let Some(p) = queue.pop_front() {
+        let distance = result[&p] + 1;
+        for d in D::list() {
+            if let Some((next, _)) = get_neighbor(game, p, d) {
+                if !result.contains_key(&next) {
+                    result.insert(next, distance);
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+    result
+}
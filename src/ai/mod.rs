@@ -0,0 +1,77 @@
+pub mod greedy;
+#[cfg(test)]
+mod test;
+
+use interfaces::{ClientPerspective, GameInterface};
+
+use crate::game::commands::Command;
+use crate::game::game::{EventsMap, Game};
+use crate::map::board::BoardView;
+use crate::map::direction::Direction;
+
+/**
+ * how often play_turn asks the AI for a new plan after the server rejected one of its commands.
+ * a rejected command usually means the AI ran into something hidden by fog
+ */
+const MAX_REPLANS: usize = 4;
+
+/**
+ * a computer-controlled player.
+ * plan_turn receives the game from the point of view of the AI's team,
+ * so it can't cheat by looking through fog.
+ */
+pub trait AiPlayer<D: Direction> {
+    /**
+     * the commands the AI wants to send this turn, in order.
+     * the sequence should end with Command::EndTurn
+     */
+    fn plan_turn(&mut self, game: &Game<D>) -> Vec<Command<D>>;
+}
+
+/**
+ * a copy of the game as the current team sees it.
+ * the server validates commands against Board::fog_view instead, which hides the same things
+ * without exporting the game. test::team_view_matches_fog_view checks that both agree
+ */
+pub fn team_view<D: Direction>(game: &Game<D>) -> Game<D> {
+    if game.has_secrets() {
+        game.reimport_as_client(ClientPerspective::Team(game.current_owner() as u8))
+    } else {
+        game.clone()
+    }
+}
+
+/**
+ * lets the AI play the current turn on the server.
+ * if a planned command gets rejected, the AI is asked for a new plan,
+ * unless none of the previous plan's commands were accepted.
+ * the turn is always ended, unless the game ended before that.
+ */
+pub fn play_turn<D: Direction>(
+    server: &mut Game<D>,
+    ai: &mut dyn AiPlayer<D>,
+) -> Vec<EventsMap<D>> {
+    let turn = server.current_turn();
+    let mut result = Vec::new();
+    for _ in 0..MAX_REPLANS {
+        let mut accepted_any = false;
+        for command in ai.plan_turn(&team_view(server)) {
+            let Ok(events) = server.handle_command(command) else {
+                break;
+            };
+            accepted_any = true;
+            result.push(events);
+            if server.has_ended() || server.current_turn() != turn {
+                return result;
+            }
+        }
+        if !accepted_any {
+            // rejected commands don't change the view, so the AI would plan the same commands again
+            break;
+        }
+    }
+    if let Ok(events) = server.handle_command(Command::EndTurn) {
+        result.push(events);
+    }
+    result
+}
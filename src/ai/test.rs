@@ -0,0 +1,169 @@
+use interfaces::ClientPerspective;
+use uniform_smart_pointer::Urc;
+
+use crate::ai::greedy::GreedyAi;
+use crate::ai::*;
+use crate::config::config::Config;
+use crate::game::commands::Command;
+use crate::game::fog::{FogMode, FogSetting};
+use crate::game::game::Game;
+use crate::game::rng::GameRng;
+use crate::map::board::{Board, BoardView};
+use crate::map::direction::*;
+use crate::map::map::{Map, valid_points};
+use crate::map::point::*;
+use crate::map::point_map::PointMap;
+use crate::map::wrapping_map::WMBuilder;
+use crate::units::commands::{UnitAction, UnitCommand};
+use crate::units::movement::Path;
+use crate::units::unit_types::UnitType;
+
+#[test]
+fn greedy_ai_attacks() {
+    let config = Urc::new(Config::default());
+    let map = PointMap::new(5, 5, false);
+    let map = WMBuilder::<Direction4>::new(map);
+    let mut map = Map::new(map.build(), &config);
+    let environment = map.environment().clone();
+    map.set_unit(
+        Point::new(0, 0),
+        Some(
+            UnitType::BAZOOKA
+                .instance(&environment)
+                .set_owner_id(0)
+                .build(),
+        ),
+    );
+    map.set_unit(
+        Point::new(1, 1),
+        Some(
+            UnitType::SMALL_TANK
+                .instance(&environment)
+                .set_owner_id(1)
+                .build(),
+        ),
+    );
+    let game_config = map.settings().unwrap();
+    let settings = game_config.build_default();
+    let (mut server, _) = Game::new_server(map, &game_config, settings, GameRng::default());
+
+    let mut ai = GreedyAi::default();
+    let commands = ai.plan_turn(&team_view(&server));
+    assert_eq!(commands.last(), Some(&Command::EndTurn));
+    assert!(commands.iter().any(|command| matches!(
+        command,
+        Command::UnitCommand(UnitCommand {
+            action: UnitAction::Attack(_),
+            ..
+        })
+    )));
+
+    let events = play_turn(&mut server, &mut ai);
+    assert!(events.len() >= 2);
+    assert_eq!(server.current_turn(), 1);
+    assert_eq!(server.current_owner(), 1);
+}
+
+// always tries to move a unit that doesn't exist
+struct StubbornAi {
+    plans: usize,
+}
+
+impl<D: Direction> AiPlayer<D> for StubbornAi {
+    fn plan_turn(&mut self, _game: &Game<D>) -> Vec<Command<D>> {
+        self.plans += 1;
+        vec![
+            Command::UnitCommand(UnitCommand {
+                unload_index: None,
+                path: Path::new(Point::new(4, 4)),
+                action: UnitAction::Wait,
+            }),
+            Command::EndTurn,
+        ]
+    }
+}
+
+#[test]
+fn rejected_plan_isnt_repeated() {
+    let config = Urc::new(Config::default());
+    let map = PointMap::new(5, 5, false);
+    let map = WMBuilder::<Direction4>::new(map);
+    let mut map = Map::new(map.build(), &config);
+    let environment = map.environment().clone();
+    for (p, owner) in [(Point::new(0, 0), 0), (Point::new(1, 1), 1)] {
+        map.set_unit(
+            p,
+            Some(
+                UnitType::SMALL_TANK
+                    .instance(&environment)
+                    .set_owner_id(owner)
+                    .build(),
+            ),
+        );
+    }
+    let game_config = map.settings().unwrap();
+    let settings = game_config.build_default();
+    let (mut server, _) = Game::new_server(map, &game_config, settings, GameRng::default());
+
+    let mut ai = StubbornAi { plans: 0 };
+    let events = play_turn(&mut server, &mut ai);
+    assert_eq!(ai.plans, 1);
+    assert_eq!(events.len(), 1);
+    assert_eq!(server.current_turn(), 1);
+}
+
+#[test]
+fn team_view_matches_fog_view() {
+    let config = Urc::new(Config::default());
+    let map = PointMap::new(8, 8, false);
+    let map = WMBuilder::<Direction4>::new(map);
+    let mut map = Map::new(map.build(), &config);
+    let environment = map.environment().clone();
+    for (p, owner) in [(Point::new(0, 0), 0), (Point::new(7, 7), 1)] {
+        map.set_unit(
+            p,
+            Some(
+                UnitType::SMALL_TANK
+                    .instance(&environment)
+                    .set_owner_id(owner)
+                    .build(),
+            ),
+        );
+    }
+    let mut game_config = map.settings().unwrap();
+    game_config.fog_mode = FogMode::Constant(FogSetting::Sharp(0));
+    let settings = game_config.build_default();
+    let (server, _) = Game::new_server(map, &game_config, settings, GameRng::default());
+
+    let team = ClientPerspective::Team(0);
+    let view = team_view(&server);
+    let board = Board::from(&server);
+    let fog_view = board.fog_view(team);
+    for p in valid_points(&server) {
+        assert_eq!(
+            view.get_terrain(p),
+            fog_view.get_terrain(p),
+            "terrain at {p:?}"
+        );
+        assert_eq!(
+            view.get_tokens(p),
+            fog_view.get_tokens(p),
+            "tokens at {p:?}"
+        );
+        assert_eq!(view.get_unit(p), fog_view.get_unit(p), "unit at {p:?}");
+        // fields are redacted with the intensity that get_fog_at reports
+        let intensity = fog_view.get_fog_at(team, p);
+        assert_eq!(
+            server
+                .get_unit(p)
+                .and_then(|unit| unit.fog_replacement(&board, p, intensity)),
+            fog_view.get_unit(p).cloned(),
+            "unit at {p:?}"
+        );
+    }
+    assert!(fog_view.get_unit(Point::new(7, 7)).is_none());
+    assert_eq!(
+        view.legal_commands().collect::<Vec<_>>(),
+        server.legal_commands().collect::<Vec<_>>()
+    );
+}
@@ -0,0 +1,39 @@
+//! Checks a ruleset folder without starting a game.
+//! Usage: tanktics-lint <config folder>...
+//! Exits with 1 if any errors were found, warnings don't affect the exit code.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use tanktics_core::config::lint::lint_folder;
+
+fn main() -> ExitCode {
+    let folders: Vec<PathBuf> = std::env::args().skip(1).map(PathBuf::from).collect();
+    if folders.is_empty() {
+        eprintln!("usage: tanktics-lint <config folder>...");
+        return ExitCode::from(2);
+    }
+    let mut error_count = 0;
+    let mut warning_count = 0;
+    for folder in folders {
+        let diagnostics = lint_folder(folder.clone());
+        println!("{}", folder.display());
+        for diagnostic in &diagnostics {
+            println!("  {diagnostic}");
+            if diagnostic.is_error() {
+                error_count += 1;
+            } else {
+                warning_count += 1;
+            }
+        }
+        if diagnostics.is_empty() {
+            println!("  no problems found");
+        }
+    }
+    println!("{error_count} error(s), {warning_count} warning(s)");
+    if error_count > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
@@ -16,6 +16,7 @@ use crate::map::direction::Direction;
 use crate::map::map::{NeighborMode, get_line};
 use crate::map::point::*;
 use crate::map::wrapping_map::OrientedPoint;
+use crate::script::profiler::ScriptHook;
 use crate::units::hero::{HeroMap, HeroMapWithId};
 use crate::units::movement::{Path, PathStep, TBallast};
 use crate::units::{UnitData, UnitId, unit::*};
@@ -330,7 +331,7 @@ impl AttackInstance {
                     ),
                 );
                 let executor = attack_context.executor(first_argument);
-                match executor.run::<D, ()>(*build_script, ()) {
+                match executor.run::<D, ()>(ScriptHook::Attack, *build_script, ()) {
                     Ok(()) => {
                         result = attack_context.scripts.drain(..).collect();
                     }
@@ -569,7 +570,12 @@ impl<D: Direction> AttackExecutable<D> {
                     Dynamic::from(attack_priority),
                 );
                 let executor = handler.executor(first_argument);
-                match executor.run_ast::<D, Dynamic>(&ast, &script, self.arguments) {
+                match executor.run_ast::<D, Dynamic>(
+                    ScriptHook::Attack,
+                    &ast,
+                    &script,
+                    self.arguments,
+                ) {
                     Ok(result) => {
                         // script had no errors
                         match result.try_cast_result::<Array>() {
@@ -14,6 +14,7 @@ use crate::map::direction::Direction;
 use crate::map::map::*;
 use crate::map::point::*;
 use crate::map::wrapping_map::{Distortion, OrientedPoint};
+use crate::script::profiler::ScriptHook;
 use crate::script::{CONST_NAME_ATTACK_DIRECTION, CONST_NAME_POSITION};
 use crate::tags::{TagKey, TagValue};
 use crate::units::hero::HeroMap;
@@ -34,7 +35,7 @@ crate::enum_with_custom! {
     }
 }
 
-// TODO: ensure that min and max are >0 in config and min < max
+// TODO: ensure that min and max are >0 in config (min <= max is checked while parsing)
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AttackPattern {
     None,
@@ -71,6 +72,11 @@ impl FromConfig for AttackPattern {
                 s @ "Straight" | s @ "TriangleDiagonal" | s @ "TriangleStraight" => {
                     let (min, max, r) = parse_tuple2::<Rational32, Rational32>(remainder, loader)?;
                     remainder = r;
+                    if min > max {
+                        return Err(ConfigParseError::InvalidAttackRange(format!(
+                            "{s}({min}, {max})"
+                        )));
+                    }
                     match s {
                         "Straight" => Self::Straight { min, max },
                         "TriangleDiagonal" => Self::TriangleDiagonal { min, max },
@@ -394,7 +400,11 @@ impl AttackPattern {
                 first_argument.insert(CONST_NAME_POSITION.into(), Dynamic::from(attacker_pos));
                 first_argument.insert(CONST_NAME_ATTACK_DIRECTION.into(), Dynamic::from(d));
                 let executor = game.executor(first_argument);
-                match executor.run::<D, Array>(*function_index, parameter_values.clone()) {
+                match executor.run::<D, Array>(
+                    ScriptHook::AttackPattern,
+                    *function_index,
+                    parameter_values.clone(),
+                ) {
                     Ok(list) => {
                         // Array -> Vec<Vec<PointWithDistortion>>
                         for (i, p) in list.into_iter().enumerate() {
@@ -2,14 +2,18 @@ use rustc_hash::{FxHashMap, FxHashSet};
 
 mod attack;
 mod attack_pattern;
+mod preview;
 pub mod rhai_combat;
 mod splash_damage;
 #[cfg(test)]
 mod test;
+mod threat;
 
 pub use attack::*;
 pub use attack_pattern::*;
+pub use preview::*;
 pub use splash_damage::*;
+pub use threat::*;
 
 use crate::config::ConfigParseError;
 use crate::config::file_loader::FileLoader;
@@ -21,6 +25,7 @@ use crate::map::board::{Board, BoardView};
 use crate::map::direction::Direction;
 use crate::map::point::*;
 use crate::map::wrapping_map::OrientedPoint;
+use crate::script::profiler::ScriptHook;
 use crate::units::hero::HeroMap;
 use crate::units::movement::TBallast;
 use crate::units::unit::Unit;
@@ -581,7 +586,7 @@ impl ValidAttackTargets {
                     heroes,
                     is_counter,
                 ));
-                match executor.run::<D, bool>(*function_index, ()) {
+                match executor.run::<D, bool>(ScriptHook::AttackFilter, *function_index, ()) {
                     Ok(result) => result,
                     Err(e) => {
                         let environment = game.environment();
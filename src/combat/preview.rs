@@ -0,0 +1,138 @@
+use crate::game::commands::CommandError;
+use crate::game::event_handler::EventHandler;
+use crate::game::game::Game;
+use crate::map::board::BoardView;
+use crate::map::direction::Direction;
+use crate::map::map::valid_points;
+use crate::map::point::Point;
+use crate::tags::TagValue;
+use crate::units::commands::UnitCommand;
+use crate::units::unit::Unit;
+
+/**
+ * what happens to a single unit if a command is executed
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnitOutcome<D: Direction> {
+    pub position: Point,
+    pub unload_index: Option<usize>,
+    pub unit: Unit<D>,
+    /// None if the unit died or was removed otherwise
+    pub new_position: Option<(Point, Option<usize>)>,
+    pub new_unit: Option<Unit<D>>,
+}
+
+impl<D: Direction> UnitOutcome<D> {
+    pub fn died(&self) -> bool {
+        self.new_unit.is_none()
+    }
+
+    /**
+     * difference in hp, from the unit's point of view.
+     * a unit that died counts as having lost all of its hp
+     */
+    pub fn hp_change(&self) -> i32 {
+        let before = hp(&self.unit).unwrap_or(0);
+        let after = self.new_unit.as_ref().and_then(hp).unwrap_or(0);
+        after - before
+    }
+
+    /**
+     * true if the unit survived but ended up on a different field or transport slot
+     */
+    pub fn was_moved(&self) -> bool {
+        match self.new_position {
+            Some(new_position) => new_position != (self.position, self.unload_index),
+            None => false,
+        }
+    }
+}
+
+fn hp<D: Direction>(unit: &Unit<D>) -> Option<i32> {
+    let key = unit.environment().config.health_tag()?;
+    match unit.get_tag(key) {
+        Some(TagValue::Int(value)) => Some(value.0),
+        _ => None,
+    }
+}
+
+/**
+ * the outcome of a command, usually an attack, as far as the current player can know it.
+ * created by Game::preview_attack
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttackPreview<D: Direction> {
+    /// the unit that executes the command. counter damage shows up as its hp_change
+    pub attacker: UnitOutcome<D>,
+    /// every other unit that's affected by the command
+    pub others: Vec<UnitOutcome<D>>,
+}
+
+impl<D: Direction> AttackPreview<D> {
+    pub(crate) fn new(game: &mut Game<D>, command: &UnitCommand<D>) -> Result<Self, CommandError> {
+        let mut handler = EventHandler::new(game);
+        // observe every unit so they can be found again after moving
+        let mut observed = Vec::new();
+        for p in valid_points(handler.get_game()) {
+            let Some(unit) = handler.get_game().get_unit(p).cloned() else {
+                continue;
+            };
+            for (i, transported) in unit.get_transported().iter().enumerate() {
+                let id = handler.observe_unit(p, Some(i));
+                observed.push((id.0, p, Some(i), transported.clone()));
+            }
+            let id = handler.observe_unit(p, None);
+            observed.push((id.0, p, None, unit));
+        }
+        let result = command.clone().execute(&mut handler);
+        let mut attacker = None;
+        let mut others = Vec::new();
+        for (id, position, unload_index, unit) in observed {
+            let new_position = handler.get_observed_unit_pos(id);
+            let new_unit = new_position.and_then(|(p, unload_index)| {
+                let unit = handler.get_game().get_unit(p)?;
+                match unload_index {
+                    Some(i) => unit.get_transported().get(i).cloned(),
+                    None => Some(unit.clone()),
+                }
+            });
+            let outcome = UnitOutcome {
+                position,
+                unload_index,
+                unit,
+                new_position,
+                new_unit,
+            };
+            if position == command.path.start && unload_index == command.unload_index.map(|i| i.0) {
+                attacker = Some(outcome);
+            } else if outcome.new_position != Some((position, unload_index))
+                || outcome.new_unit.as_ref() != Some(&outcome.unit)
+            {
+                others.push(outcome);
+            }
+        }
+        handler.cancel();
+        result?;
+        Ok(Self {
+            attacker: attacker.ok_or(CommandError::MissingUnit(command.path.start))?,
+            others,
+        })
+    }
+
+    pub fn deaths(&self) -> impl Iterator<Item = &UnitOutcome<D>> {
+        std::iter::once(&self.attacker)
+            .chain(self.others.iter())
+            .filter(|outcome| outcome.died())
+    }
+
+    pub fn pushed(&self) -> impl Iterator<Item = &UnitOutcome<D>> {
+        self.others.iter().filter(|outcome| outcome.was_moved())
+    }
+
+    /**
+     * damage the attacker takes, e.g. from counter-attacks
+     */
+    pub fn counter_damage(&self) -> i32 {
+        -self.attacker.hp_change().min(0)
+    }
+}
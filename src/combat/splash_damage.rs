@@ -10,6 +10,7 @@ use crate::map::direction::Direction;
 use crate::map::map::*;
 use crate::map::point::*;
 use crate::map::wrapping_map::{Distortion, OrientedPoint};
+use crate::script::profiler::ScriptHook;
 use crate::script::*;
 use crate::tags::{TagKey, TagValue};
 use crate::units::movement::TBallast;
@@ -162,7 +163,7 @@ impl SplashDamagePointSource {
                     Dynamic::from(main_target.mirrored),
                 );
                 let executor = game.executor(first_argument);
-                match executor.run::<D, Array>(*function_index, ()) {
+                match executor.run::<D, Array>(ScriptHook::AttackPattern, *function_index, ()) {
                     Ok(lists) => {
                         // Array -> Vec<Vec<PointWithDistortion>>
                         for (i, list) in lists.into_iter().enumerate().take(range) {
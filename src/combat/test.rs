@@ -1,9 +1,12 @@
+use interfaces::ClientPerspective;
 use uniform_smart_pointer::Urc;
 
 use crate::config::config::Config;
 use crate::config::environment::Environment;
 use crate::game::commands::Command;
+use crate::game::fog::{FogMode, FogSetting};
 use crate::game::game::Game;
+use crate::game::rng::GameRng;
 use crate::map::direction::Direction4;
 use crate::map::map::Map;
 use crate::map::point::{Point, Position};
@@ -107,19 +110,16 @@ fn hp_factor() {
     );
     let map_settings = map.settings().unwrap();
     let settings = map_settings.build_default();
-    let (mut game, _) = Game::new_server(map, &map_settings, settings, Urc::new(|| 0.));
+    let (mut game, _) = Game::new_server(map, &map_settings, settings, GameRng::default());
     for x in 0..4 {
-        game.handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(Point::new(x, 1)),
-                action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                    Point::new(x, 0),
-                    Direction4::D90,
-                ))),
-            }),
-            Urc::new(|| 0.),
-        )
+        game.handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(Point::new(x, 1)),
+            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+                Point::new(x, 0),
+                Direction4::D90,
+            ))),
+        }))
         .unwrap();
     }
     let base_damage = 100. - game.get_unit(Point::new(0, 0)).unwrap().get_hp() as f32;
@@ -243,19 +243,16 @@ fn terrain_defense() {
     );
     let map_settings = map.settings().unwrap();
     let settings = map_settings.build_default();
-    let (mut game, _) = Game::new_server(map, &map_settings, settings, Urc::new(|| 0.));
+    let (mut game, _) = Game::new_server(map, &map_settings, settings, GameRng::default());
     for x in 0..4 {
-        game.handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(Point::new(x, 1)),
-                action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                    Point::new(x, 0),
-                    Direction4::D90,
-                ))),
-            }),
-            Urc::new(|| 0.),
-        )
+        game.handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(Point::new(x, 1)),
+            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+                Point::new(x, 0),
+                Direction4::D90,
+            ))),
+        }))
         .unwrap();
         crate::debug!(
             "attacker hp: {}, defender hp: {}",
@@ -338,35 +335,29 @@ fn displacement() {
     );
     let map_settings = map.settings().unwrap();
     let settings = map_settings.build_default();
-    let (mut game, _) = Game::new_server(map, &map_settings, settings, Urc::new(|| 0.));
+    let (mut game, _) = Game::new_server(map, &map_settings, settings, GameRng::default());
     let unchanged = game.clone();
 
-    game.handle_command(
-        Command::UnitCommand(UnitCommand {
-            unload_index: None,
-            path: Path::new(Point::new(1, 0)),
-            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                Point::new(3, 0),
-                Direction4::D0,
-            ))),
-        }),
-        Urc::new(|| 0.),
-    )
+    game.handle_command(Command::UnitCommand(UnitCommand {
+        unload_index: None,
+        path: Path::new(Point::new(1, 0)),
+        action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+            Point::new(3, 0),
+            Direction4::D0,
+        ))),
+    }))
     .unwrap();
     assert_eq!(100, game.get_unit(Point::new(1, 0)).unwrap().get_hp());
     assert_eq!(100, game.get_unit(Point::new(2, 0)).unwrap().get_hp());
     assert_eq!(None, game.get_unit(Point::new(3, 0)));
-    game.handle_command(
-        Command::UnitCommand(UnitCommand {
-            unload_index: None,
-            path: Path::new(Point::new(1, 1)),
-            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                Point::new(2, 1),
-                Direction4::D0,
-            ))),
-        }),
-        Urc::new(|| 0.),
-    )
+    game.handle_command(Command::UnitCommand(UnitCommand {
+        unload_index: None,
+        path: Path::new(Point::new(1, 1)),
+        action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+            Point::new(2, 1),
+            Direction4::D0,
+        ))),
+    }))
     .unwrap();
     //assert!(game.get_unit(Point::new(0, 1)).unwrap().get_hp() < 100);
     for x in 2..=2 {
@@ -377,17 +368,14 @@ fn displacement() {
 
     // WarShip can't be displaced
     let mut game = unchanged.clone();
-    game.handle_command(
-        Command::UnitCommand(UnitCommand {
-            unload_index: None,
-            path: Path::new(Point::new(1, 1)),
-            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                Point::new(1, 2),
-                Direction4::D270,
-            ))),
-        }),
-        Urc::new(|| 0.),
-    )
+    game.handle_command(Command::UnitCommand(UnitCommand {
+        unload_index: None,
+        path: Path::new(Point::new(1, 1)),
+        action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+            Point::new(1, 2),
+            Direction4::D270,
+        ))),
+    }))
     .unwrap();
     assert!(game.get_unit(Point::new(1, 2)).unwrap().get_hp() < 100);
 }
@@ -443,19 +431,13 @@ fn dragon_head() {
     // create game
     let map_settings = map.settings().unwrap();
     let settings = map_settings.build_default();
-    let (mut game, _) = Game::new_server(map, &map_settings, settings, Urc::new(|| 0.));
+    let (mut game, _) = Game::new_server(map, &map_settings, settings, GameRng::default());
     let unchanged = game.clone();
-    game.handle_command(
-        Command::UnitCommand(UnitCommand {
-            unload_index: None,
-            path: Path::new(Point::new(0, 0)),
-            action: UnitAction::Attack(AttackInput::AttackPattern(
-                Point::new(1, 0),
-                Direction4::D0,
-            )),
-        }),
-        Urc::new(|| 0.),
-    )
+    game.handle_command(Command::UnitCommand(UnitCommand {
+        unload_index: None,
+        path: Path::new(Point::new(0, 0)),
+        action: UnitAction::Attack(AttackInput::AttackPattern(Point::new(1, 0), Direction4::D0)),
+    }))
     .unwrap();
     let hp1 = game.get_unit(Point::new(1, 0)).unwrap().get_hp();
     let hp2 = game.get_unit(Point::new(2, 0)).unwrap().get_hp();
@@ -465,17 +447,11 @@ fn dragon_head() {
     assert_eq!(100, game.get_unit(Point::new(3, 0)).unwrap().get_hp());
     // target the other enemy
     game = unchanged;
-    game.handle_command(
-        Command::UnitCommand(UnitCommand {
-            unload_index: None,
-            path: Path::new(Point::new(0, 0)),
-            action: UnitAction::Attack(AttackInput::AttackPattern(
-                Point::new(2, 0),
-                Direction4::D0,
-            )),
-        }),
-        Urc::new(|| 0.),
-    )
+    game.handle_command(Command::UnitCommand(UnitCommand {
+        unload_index: None,
+        path: Path::new(Point::new(0, 0)),
+        action: UnitAction::Attack(AttackInput::AttackPattern(Point::new(2, 0), Direction4::D0)),
+    }))
     .unwrap();
     assert_eq!(hp1, game.get_unit(Point::new(1, 0)).unwrap().get_hp());
     assert_eq!(hp2, game.get_unit(Point::new(2, 0)).unwrap().get_hp());
@@ -523,7 +499,7 @@ fn cannot_attack_friendly() {
     );
     let map_settings = map.settings().unwrap();
     let settings = map_settings.build_default();
-    let (mut game, _) = Game::new_server(map, &map_settings, settings, Urc::new(|| 0.));
+    let (mut game, _) = Game::new_server(map, &map_settings, settings, GameRng::default());
     let path = Path::new(Point::new(0, 1));
     let board = Board::new(&game);
     let options =
@@ -531,28 +507,169 @@ fn cannot_attack_friendly() {
             .unwrap()
             .options_after_path(&board, &path, None, &[]);
     assert_eq!(options, vec![UnitAction::Wait]);
-    game.handle_command(
-        Command::UnitCommand(UnitCommand {
-            unload_index: None,
-            path: path.clone(),
-            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                Point::new(1, 1),
-                Direction4::D0,
-            ))),
-        }),
-        Urc::new(|| 0.),
-    )
+    game.handle_command(Command::UnitCommand(UnitCommand {
+        unload_index: None,
+        path: path.clone(),
+        action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+            Point::new(1, 1),
+            Direction4::D0,
+        ))),
+    }))
     .unwrap_err();
-    game.handle_command(
-        Command::UnitCommand(UnitCommand {
-            unload_index: None,
-            path,
-            action: UnitAction::Attack(AttackInput::AttackPattern(
-                Point::new(1, 1),
-                Direction4::D0,
-            )),
-        }),
-        Urc::new(|| 0.),
-    )
+    game.handle_command(Command::UnitCommand(UnitCommand {
+        unload_index: None,
+        path,
+        action: UnitAction::Attack(AttackInput::AttackPattern(Point::new(1, 1), Direction4::D0)),
+    }))
     .unwrap_err();
 }
+
+#[test]
+fn preview_matches_attack() {
+    let map = PointMap::new(4, 4, false);
+    let environment = Environment::new_map(Urc::new(Config::default()), map.size());
+    let wmap: WrappingMap<Direction4> = WMBuilder::new(map).build();
+    let mut map = Map::new2(wmap, &environment);
+    for p in map.all_points() {
+        map.set_terrain(p, TerrainType::Street.instance(&environment).build());
+    }
+    map.set_unit(
+        Point::new(1, 0),
+        Some(
+            UnitType::BAZOOKA
+                .instance(&environment)
+                .set_owner_id(0)
+                .set_hp(100)
+                .build(),
+        ),
+    );
+    map.set_unit(
+        Point::new(2, 0),
+        Some(
+            UnitType::BAZOOKA
+                .instance(&environment)
+                .set_owner_id(1)
+                .set_hp(100)
+                .build(),
+        ),
+    );
+    let map_settings = map.settings().unwrap();
+    let settings = map_settings.build_default();
+    let (mut game, _) = Game::new_server(map, &map_settings, settings, GameRng::default());
+    let command = UnitCommand {
+        unload_index: None,
+        path: Path::new(Point::new(1, 0)),
+        action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+            Point::new(2, 0),
+            Direction4::D0,
+        ))),
+    };
+    let unchanged = game.clone();
+    let preview = game.preview_attack(&command).unwrap();
+    assert_eq!(game, unchanged);
+    assert_eq!(
+        preview.attacker.new_position,
+        Some((Point::new(1, 0), None))
+    );
+    assert_eq!(preview.others.len(), 1);
+    assert_eq!(preview.others[0].position, Point::new(2, 0));
+    assert!(preview.others[0].hp_change() < 0);
+
+    game.handle_command(Command::UnitCommand(command)).unwrap();
+    assert_eq!(
+        game.get_unit(Point::new(2, 0)).unwrap().get_hp() as i32 - 100,
+        preview.others[0].hp_change()
+    );
+    assert_eq!(
+        game.get_unit(Point::new(1, 0)).unwrap().get_hp() as i32,
+        100 - preview.counter_damage()
+    );
+}
+
+fn threat_game(fog: bool) -> Game<Direction4> {
+    let map = PointMap::new(8, 1, false);
+    let environment = Environment::new_map(Urc::new(Config::default()), map.size());
+    let wmap: WrappingMap<Direction4> = WMBuilder::new(map).build();
+    let mut map = Map::new2(wmap, &environment);
+    for p in map.all_points() {
+        map.set_terrain(p, TerrainType::Street.instance(&environment).build());
+    }
+    for (p, typ, owner) in [
+        (Point::new(2, 0), UnitType::BAZOOKA, 0),
+        (Point::new(6, 0), UnitType::MARINE, 1),
+        (Point::new(7, 0), UnitType::ARTILLERY, 1),
+    ] {
+        map.set_unit(
+            p,
+            Some(
+                typ.instance(&environment)
+                    .set_owner_id(owner)
+                    .set_hp(100)
+                    .build(),
+            ),
+        );
+    }
+    let mut map_settings = map.settings().unwrap();
+    if fog {
+        map_settings.fog_mode = FogMode::Constant(FogSetting::ExtraDark(0));
+    }
+    let settings = map_settings.build_default();
+    let (mut game, _) = Game::new_server(map, &map_settings, settings, GameRng::default());
+    // no fog during the first turn
+    game.handle_command(Command::EndTurn).unwrap();
+    game.handle_command(Command::EndTurn).unwrap();
+    game
+}
+
+#[test]
+fn threat_map() {
+    let mut game = threat_game(false);
+    let threats = game.threat_map(ClientPerspective::Team(0));
+    // the marine moves next to the bazooka, but can't get past it
+    let bazooka_threats = &threats[&Point::new(2, 0)];
+    assert_eq!(bazooka_threats.len(), 1);
+    assert_eq!(bazooka_threats[0].attacker_position, Point::new(6, 0));
+    assert_eq!(bazooka_threats[0].attacker.typ(), UnitType::MARINE);
+    assert_eq!(bazooka_threats[0].splash_distance, 0);
+    let expected_damage = bazooka_threats[0].expected_damage.unwrap();
+    assert!(expected_damage > 0);
+    assert!(!threats.contains_key(&Point::new(1, 0)));
+    // the artillery can't move before attacking
+    let threatened_by_artillery = threats
+        .iter()
+        .filter(|(_, threats)| {
+            threats
+                .iter()
+                .any(|threat| threat.attacker.typ() == UnitType::ARTILLERY)
+        })
+        .map(|(p, _)| *p)
+        .collect::<FxHashSet<_>>();
+    assert_eq!(
+        threatened_by_artillery,
+        [Point::new(4, 0), Point::new(5, 0)].into_iter().collect()
+    );
+    // the bazooka could only reach empty fields next to the marine
+    let threats = game.threat_map(ClientPerspective::Team(1));
+    assert!(threats.contains_key(&Point::new(5, 0)));
+    assert!(!threats.contains_key(&Point::new(6, 0)));
+    assert!(
+        threats
+            .values()
+            .flatten()
+            .all(|threat| threat.attacker_position == Point::new(2, 0)
+                && threat.expected_damage.is_none())
+    );
+
+    // the marine deals the expected damage once it's its turn
+    let command = bazooka_threats[0].command.clone();
+    game.handle_command(Command::EndTurn).unwrap();
+    game.handle_command(Command::UnitCommand(command)).unwrap();
+    assert_eq!(
+        100 - game.get_unit(Point::new(2, 0)).unwrap().get_hp() as i32,
+        expected_damage
+    );
+
+    // the bazooka can't see the enemies
+    let game = threat_game(true);
+    assert_eq!(game.threat_map(ClientPerspective::Team(0)).len(), 0);
+}
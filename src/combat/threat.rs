@@ -0,0 +1,195 @@
+use interfaces::ClientPerspective;
+use rustc_hash::FxHashMap;
+
+use crate::game::fog::get_visible_unit;
+use crate::game::game::Game;
+use crate::map::board::{Board, BoardView};
+use crate::map::direction::Direction;
+use crate::map::map::valid_points;
+use crate::map::point::Point;
+use crate::units::commands::{UnitAction, UnitCommand};
+use crate::units::hero::HeroMap;
+use crate::units::movement::Path;
+use crate::units::unit::Unit;
+
+use super::{AttackCounterState, AttackInput, AttackPreview};
+
+/// an enemy unit that could hit a field during its next turn
+#[derive(Debug, Clone, PartialEq)]
+pub struct Threat<D: Direction> {
+    /// where the attacker stands now
+    pub attacker_position: Point,
+    pub attacker: Unit<D>,
+    /// 0 if the field can be targeted directly, otherwise the smallest splash distance that reaches it
+    pub splash_distance: usize,
+    /// an attack of the attacker that hits the field at splash_distance
+    pub command: UnitCommand<D>,
+    /**
+     * damage dealt to the visible unit on the field by executing command, see Game::threat_map.
+     * None if there's no such unit, if the field is only hit by splash
+     * or if the ruleset has no health tag
+     */
+    pub expected_damage: Option<i32>,
+}
+
+impl<'a, D: Direction> Board<'a, D> {
+    /**
+     * every field that enemies of `team` could attack during their next turn.
+     * only enemies visible to `team` are considered, and they move on the board as `team` sees it.
+     * transported units have to be unloaded first and aren't considered either.
+     * expected_damage is left empty, Game::threat_map fills it in
+     */
+    pub(crate) fn threat_map(&self, team: ClientPerspective) -> FxHashMap<Point, Vec<Threat<D>>> {
+        let board = self.fog_view(team);
+        let mut result: FxHashMap<Point, Vec<Threat<D>>> = FxHashMap::default();
+        for p in valid_points(self) {
+            let Some(attacker) = get_visible_unit(self, team, p) else {
+                continue;
+            };
+            if attacker.get_team() == team {
+                continue;
+            }
+            for (target, (splash_distance, command)) in threatened_points(&board, &attacker, p) {
+                result.entry(target).or_default().push(Threat {
+                    attacker_position: p,
+                    attacker: attacker.clone(),
+                    splash_distance,
+                    command,
+                    expected_damage: None,
+                });
+            }
+        }
+        result
+    }
+}
+
+// the smallest splash distance for every point the attacker can hit from any field it can reach,
+// and an attack that hits the point at that distance
+fn threatened_points<D: Direction>(
+    board: &Board<D>,
+    attacker: &Unit<D>,
+    attacker_pos: Point,
+) -> FxHashMap<Point, (usize, UnitCommand<D>)> {
+    let mut result: FxHashMap<Point, (usize, UnitCommand<D>)> = FxHashMap::default();
+    let movement_map = attacker.movement_map(board, &Path::new(attacker_pos), None);
+    let counter_state = AttackCounterState::NoCounter;
+    for (destination, entry) in movement_map.iter() {
+        let Some(path) = movement_map.path_to(*destination) else {
+            continue;
+        };
+        if !entry.can_stop || (path.len() > 0 && !attacker.can_attack_after_moving()) {
+            continue;
+        }
+        let Some((moved, destination, unit)) = board.unit_path_without_placing(None, path) else {
+            continue;
+        };
+        if moved.get_unit(destination).is_some() {
+            continue;
+        }
+        let moved = moved.replace_unit(destination, Some(unit.clone()));
+        let heroes = HeroMap::new(&moved, Some(unit.get_owner_id()));
+        let ballast = if path.len() == 0 {
+            &[]
+        } else {
+            entry.ballast.get_entries()
+        };
+        let configured_attacks = unit.environment().config.unit_configured_attacks(
+            &moved,
+            &unit,
+            destination,
+            None,
+            &counter_state,
+            &heroes,
+            ballast,
+        );
+        let attack_pattern =
+            unit.attack_pattern(&moved, destination, &counter_state, &heroes, ballast);
+        for input in
+            AttackInput::attackable_positions(&moved, &unit, destination, None, ballast, &heroes)
+        {
+            let command = UnitCommand {
+                unload_index: None,
+                path: path.clone(),
+                action: UnitAction::Attack(input),
+            };
+            let entry = result
+                .entry(input.target())
+                .or_insert_with(|| (0, command.clone()));
+            if entry.0 > 0 {
+                *entry = (0, command.clone());
+            }
+            // SplashPattern inputs don't need the attack pattern to find their splash
+            let possible_attack_targets = match input {
+                AttackInput::AttackPattern(_, d) => {
+                    attack_pattern.possible_attack_targets(&moved, destination, d)
+                }
+                AttackInput::SplashPattern(_) => Vec::new(),
+            };
+            for attack in &configured_attacks {
+                let Some(splash_range) = attack.splash.iter().map(|a| a.splash_distance).max()
+                else {
+                    continue;
+                };
+                let ranges = attack.splash_pattern.get_splash(
+                    &moved,
+                    &unit,
+                    ballast,
+                    &possible_attack_targets,
+                    input,
+                    splash_range,
+                );
+                for splash in &attack.splash {
+                    let Some(range) = ranges.get(splash.splash_distance) else {
+                        // can happen if splash_pattern uses SplashDamagePointSource::AttackPattern
+                        continue;
+                    };
+                    for dp in range {
+                        let entry = result
+                            .entry(dp.point)
+                            .or_insert_with(|| (splash.splash_distance, command.clone()));
+                        if splash.splash_distance < entry.0 {
+                            *entry = (splash.splash_distance, command.clone());
+                        }
+                    }
+                }
+            }
+        }
+    }
+    result
+}
+
+/**
+ * executes the threat's attack on `game` during the attacker's next turn
+ * and returns how much health the unit of `team` at `target` loses
+ */
+pub(crate) fn expected_damage<D: Direction>(
+    game: &mut Game<D>,
+    team: ClientPerspective,
+    target: Point,
+    threat: &Threat<D>,
+) -> Option<i32> {
+    game.environment().config.health_tag()?;
+    if threat.splash_distance > 0 || game.get_unit(target)?.get_team() != team {
+        return None;
+    }
+    let owner = threat.attacker.get_owner_id();
+    let player_count = game.players.len();
+    let index = game
+        .players
+        .iter()
+        .position(|player| player.get_owner_id() == owner)?;
+    let current_turn = game.current_turn;
+    game.current_turn +=
+        ((index + player_count - current_turn as usize % player_count) % player_count) as u32;
+    let preview = AttackPreview::new(game, &threat.command);
+    game.current_turn = current_turn;
+    let preview = preview.ok()?;
+    Some(
+        preview
+            .others
+            .iter()
+            .find(|outcome| outcome.position == target && outcome.unload_index.is_none())
+            .map(|outcome| -outcome.hp_change().min(0))
+            .unwrap_or(0),
+    )
+}
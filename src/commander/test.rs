@@ -6,6 +6,7 @@ use crate::config::environment::Environment;
 use crate::game::commands::*;
 use crate::game::fog::*;
 use crate::game::game::Game;
+use crate::game::rng::GameRng;
 use crate::map::board::Board;
 use crate::map::board::BoardView;
 use crate::map::direction::*;
@@ -122,21 +123,19 @@ fn gain_charge() {
     let mut settings = map_settings.build_default();
     settings.players[0].set_commander(CommanderType::Zombie);
     settings.players[1].set_commander(CommanderType::Zombie);
-    let (mut server, _) = Game::new_server(map.clone(), &map_settings, settings, Urc::new(|| 0.));
+    let (mut server, _) =
+        Game::new_server(map.clone(), &map_settings, settings, GameRng::default());
     assert_eq!(server.players.get(0).unwrap().commander.get_charge(), 0);
     assert_eq!(server.players.get(1).unwrap().commander.get_charge(), 0);
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(Point::new(1, 1)),
-                action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                    Point::new(2, 1),
-                    Direction6::D0,
-                ))),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(Point::new(1, 1)),
+            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+                Point::new(2, 1),
+                Direction6::D0,
+            ))),
+        }))
         .unwrap();
     let charge_0 = server.players.get(0).unwrap().commander.get_charge();
     let charge_1 = server.players.get(1).unwrap().commander.get_charge();
@@ -219,7 +218,8 @@ fn zombie() {
     map_settings.fog_mode = FogMode::Constant(FogSetting::None);
     let mut settings = map_settings.build_default();
     settings.players[0].set_commander(CommanderType::Zombie);
-    let (mut server, _) = Game::new_server(map.clone(), &map_settings, settings, Urc::new(|| 0.));
+    let (mut server, _) =
+        Game::new_server(map.clone(), &map_settings, settings, GameRng::default());
     server.players.get_mut(0).unwrap().commander.charge = server
         .players
         .get_mut(0)
@@ -233,17 +233,14 @@ fn zombie() {
     skull.set_tag(TAG_UNIT_TYPE, TagValue::UnitType(UnitType::SMALL_TANK));
     // no power
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(Point::new(1, 1)),
-                action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                    Point::new(2, 1),
-                    Direction6::D0,
-                ))),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(Point::new(1, 1)),
+            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+                Point::new(2, 1),
+                Direction6::D0,
+            ))),
+        }))
         .unwrap();
     assert_eq!(server.get_unit(Point::new(2, 1)), None);
     assert_eq!(server.get_tokens(Point::new(1, 1)), vec![]);
@@ -251,7 +248,7 @@ fn zombie() {
     // small power
     let mut server = unchanged.clone();
     server
-        .handle_command(Command::commander_power(1, Vec::new()), Urc::new(|| 0.))
+        .handle_command(Command::commander_power(1, Vec::new()))
         .unwrap();
     assert_eq!(server.get_tokens(Point::new(0, 4)), Vec::new());
     assert_eq!(
@@ -279,24 +276,21 @@ fn zombie() {
         )
     );
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(Point::new(1, 1)),
-                action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                    Point::new(2, 1),
-                    Direction6::D0,
-                ))),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(Point::new(1, 1)),
+            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+                Point::new(2, 1),
+                Direction6::D0,
+            ))),
+        }))
         .unwrap();
     assert_eq!(server.get_unit(Point::new(2, 1)), None);
     assert_eq!(server.get_tokens(Point::new(2, 1)), vec![skull]);
     // big power
     let mut server = unchanged.clone();
     server
-        .handle_command(Command::commander_power(2, Vec::new()), Urc::new(|| 0.))
+        .handle_command(Command::commander_power(2, Vec::new()))
         .unwrap();
     assert_eq!(server.get_tokens(Point::new(0, 4)), Vec::new());
     assert_eq!(
@@ -312,17 +306,14 @@ fn zombie() {
         )
     );
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(Point::new(1, 1)),
-                action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                    Point::new(2, 1),
-                    Direction6::D0,
-                ))),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(Point::new(1, 1)),
+            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+                Point::new(2, 1),
+                Direction6::D0,
+            ))),
+        }))
         .unwrap();
     assert_eq!(server.get_tokens(Point::new(2, 1)), Vec::new());
     assert_eq!(
@@ -344,15 +335,12 @@ fn zombie() {
         .unwrap()
         .set_tag(&environment, TAG_FUNDS, 1000.into());
     server
-        .handle_command(
-            Command::TerrainAction(
-                Point::new(3, 1),
-                vec![CustomActionInput::ShopItem(0.into())]
-                    .try_into()
-                    .unwrap(),
-            ),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::TerrainAction(
+            Point::new(3, 1),
+            vec![CustomActionInput::ShopItem(0.into())]
+                .try_into()
+                .unwrap(),
+        ))
         .unwrap();
     assert!(server.get_unit(Point::new(3, 1)).is_some());
     for p in valid_points(&server) {
@@ -426,7 +414,8 @@ fn simo() {
     map_settings.fog_mode = FogMode::Constant(FogSetting::None);
     let mut settings = map_settings.build_default();
     settings.players[0].set_commander(CommanderType::Simo);
-    let (mut server, _) = Game::new_server(map.clone(), &map_settings, settings, Urc::new(|| 0.));
+    let (mut server, _) =
+        Game::new_server(map.clone(), &map_settings, settings, GameRng::default());
     server.players.get_mut(0).unwrap().commander.charge = server
         .players
         .get_mut(0)
@@ -447,17 +436,14 @@ fn simo() {
         None
     );
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(arty_pos),
-                action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                    target_close,
-                    Direction4::D0,
-                ))),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(arty_pos),
+            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+                target_close,
+                Direction4::D0,
+            ))),
+        }))
         .unwrap();
     assert!(server.get_unit(target_close).unwrap().get_hp() < 100);
     assert_eq!(server.get_unit(target_far).unwrap().get_hp(), 100);
@@ -465,14 +451,14 @@ fn simo() {
     // embrace chaos
     let mut server = unchanged.clone();
     server
-        .handle_command(Command::commander_power(1, Vec::new()), Urc::new(|| 0.))
+        .handle_command(Command::commander_power(1, Vec::new()))
         .unwrap();
     server
-        .handle_command(Command::commander_power(4, Vec::new()), Urc::new(|| 0.))
+        .handle_command(Command::commander_power(4, Vec::new()))
         .err()
         .unwrap();
     server
-        .handle_command(Command::commander_power(5, Vec::new()), Urc::new(|| 0.))
+        .handle_command(Command::commander_power(5, Vec::new()))
         .err()
         .unwrap();
     let arty = server.get_unit(arty_pos).unwrap();
@@ -486,17 +472,14 @@ fn simo() {
             .is_none()
     );
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(arty_pos),
-                action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                    target_close,
-                    Direction4::D0,
-                ))),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(arty_pos),
+            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+                target_close,
+                Direction4::D0,
+            ))),
+        }))
         .unwrap();
     let hp_close = server.get_unit(target_close).unwrap().get_hp();
     let hp_far = server.get_unit(target_far).unwrap().get_hp();
@@ -506,38 +489,32 @@ fn simo() {
     // chaos power
     let mut server = unchanged.clone();
     server
-        .handle_command(Command::commander_power(1, Vec::new()), Urc::new(|| 0.))
+        .handle_command(Command::commander_power(1, Vec::new()))
         .unwrap();
     server
-        .handle_command(Command::commander_power(3, Vec::new()), Urc::new(|| 0.))
+        .handle_command(Command::commander_power(3, Vec::new()))
         .unwrap();
     //let arty = server.get_unit(arty_pos).unwrap();
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(arty_pos),
-                action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                    target_far,
-                    Direction4::D0,
-                ))),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(arty_pos),
+            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+                target_far,
+                Direction4::D0,
+            ))),
+        }))
         .err()
         .expect("range shouldn't be increased");
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(arty_pos),
-                action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                    target_close,
-                    Direction4::D0,
-                ))),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(arty_pos),
+            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+                target_close,
+                Direction4::D0,
+            ))),
+        }))
         .unwrap();
     assert!(server.get_unit(target_close).unwrap().get_hp() < hp_close);
     assert!(server.get_unit(target_far).unwrap().get_hp() < hp_far);
@@ -546,14 +523,14 @@ fn simo() {
     // order power (small)
     let mut server = unchanged.clone();
     server
-        .handle_command(Command::commander_power(2, Vec::new()), Urc::new(|| 0.))
+        .handle_command(Command::commander_power(2, Vec::new()))
         .unwrap();
     server
-        .handle_command(Command::commander_power(3, Vec::new()), Urc::new(|| 0.))
+        .handle_command(Command::commander_power(3, Vec::new()))
         .err()
         .unwrap();
     server
-        .handle_command(Command::commander_power(4, Vec::new()), Urc::new(|| 0.))
+        .handle_command(Command::commander_power(4, Vec::new()))
         .unwrap();
     let arty = server.get_unit(arty_pos).unwrap();
     let board = Board::new(&server);
@@ -566,17 +543,14 @@ fn simo() {
             .is_none()
     );
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(arty_pos),
-                action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                    target_far,
-                    Direction4::D0,
-                ))),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(arty_pos),
+            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+                target_far,
+                Direction4::D0,
+            ))),
+        }))
         .unwrap();
     assert_eq!(server.get_unit(target_close).unwrap().get_hp(), 100);
     assert!(server.get_unit(target_far).unwrap().get_hp() < 100);
@@ -584,10 +558,10 @@ fn simo() {
     // order power (big)
     let mut server = unchanged.clone();
     server
-        .handle_command(Command::commander_power(2, Vec::new()), Urc::new(|| 0.))
+        .handle_command(Command::commander_power(2, Vec::new()))
         .unwrap();
     server
-        .handle_command(Command::commander_power(5, Vec::new()), Urc::new(|| 0.))
+        .handle_command(Command::commander_power(5, Vec::new()))
         .unwrap();
     let arty = server.get_unit(arty_pos).unwrap();
     let board = Board::new(&server);
@@ -604,17 +578,14 @@ fn simo() {
             .is_some()
     );
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(arty_pos),
-                action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                    target_farthest,
-                    Direction4::D0,
-                ))),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(arty_pos),
+            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+                target_farthest,
+                Direction4::D0,
+            ))),
+        }))
         .unwrap();
     assert_eq!(server.get_unit(target_far).unwrap().get_hp(), 100);
     assert!(server.get_unit(target_farthest).unwrap().get_hp() < 100);
@@ -674,21 +645,19 @@ fn vlad() {
     map_settings.fog_mode = FogMode::Constant(FogSetting::None);
     let mut settings = map_settings.build_default();
     settings.players[0].set_commander(CommanderType::Vlad);
-    let (mut server, _) = Game::new_server(map.clone(), &map_settings, settings, Urc::new(|| 0.));
+    let (mut server, _) =
+        Game::new_server(map.clone(), &map_settings, settings, GameRng::default());
 
     // d2d daylight
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(arty_pos),
-                action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                    target_close,
-                    Direction4::D0,
-                ))),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(arty_pos),
+            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+                target_close,
+                Direction4::D0,
+            ))),
+        }))
         .unwrap();
     assert_eq!(server.get_unit(arty_pos).unwrap().get_hp(), 50);
 
@@ -696,7 +665,8 @@ fn vlad() {
     map_settings.fog_mode = FogMode::Constant(FogSetting::Sharp(0));
     let mut settings = map_settings.build_default();
     settings.players[0].set_commander(CommanderType::Vlad);
-    let (mut server, _) = Game::new_server(map.clone(), &map_settings, settings, Urc::new(|| 0.));
+    let (mut server, _) =
+        Game::new_server(map.clone(), &map_settings, settings, GameRng::default());
     server.players.get_mut(0).unwrap().commander.charge = server
         .players
         .get_mut(0)
@@ -705,24 +675,21 @@ fn vlad() {
         .get_max_charge();
     let unchanged = server.clone();
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(arty_pos),
-                action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                    target_close,
-                    Direction4::D0,
-                ))),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(arty_pos),
+            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+                target_close,
+                Direction4::D0,
+            ))),
+        }))
         .unwrap();
     assert!(server.get_unit(arty_pos).unwrap().get_hp() > 50);
 
     // small power
     let mut server = unchanged.clone();
     server
-        .handle_command(Command::commander_power(1, Vec::new()), Urc::new(|| 0.))
+        .handle_command(Command::commander_power(1, Vec::new()))
         .unwrap();
     assert_eq!(server.get_unit(arty_pos).unwrap().get_hp(), 50);
     assert!(server.get_unit(target_close).unwrap().get_hp() < 50);
@@ -731,7 +698,7 @@ fn vlad() {
     // big power
     let mut server = unchanged.clone();
     server
-        .handle_command(Command::commander_power(2, Vec::new()), Urc::new(|| 0.))
+        .handle_command(Command::commander_power(2, Vec::new()))
         .unwrap();
     assert!(server.get_unit(arty_pos).unwrap().get_hp() > 50);
     assert!(server.get_unit(target_close).unwrap().get_hp() < 50);
@@ -844,7 +811,8 @@ fn tapio() {
     map_settings.fog_mode = FogMode::Constant(FogSetting::Sharp(0));
     let mut settings = map_settings.build_default();
     settings.players[0].set_commander(CommanderType::Tapio);
-    let (mut server, _) = Game::new_server(map.clone(), &map_settings, settings, Urc::new(|| 0.));
+    let (mut server, _) =
+        Game::new_server(map.clone(), &map_settings, settings, GameRng::default());
     server.players.get_mut(0).unwrap().commander.charge = server
         .players
         .get_mut(0)
@@ -856,17 +824,14 @@ fn tapio() {
     // passive: deal more damage when attacking from forest / grass
     for i in 0..4 {
         server
-            .handle_command(
-                Command::UnitCommand(UnitCommand {
-                    unload_index: None,
-                    path: Path::new(Point::new(0, i)),
-                    action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                        Point::new(1, i),
-                        Direction4::D0,
-                    ))),
-                }),
-                Urc::new(|| 0.),
-            )
+            .handle_command(Command::UnitCommand(UnitCommand {
+                unload_index: None,
+                path: Path::new(Point::new(0, i)),
+                action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+                    Point::new(1, i),
+                    Direction4::D0,
+                ))),
+            }))
             .unwrap();
     }
     assert!(
@@ -887,9 +852,7 @@ fn tapio() {
     let fairy_forest_hp = server.get_unit(Point::new(0, 3)).unwrap().get_hp();
 
     // fairy forest heals
-    server
-        .handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
+    server.handle_command(Command::EndTurn).unwrap();
     assert!(
         server.get_unit(Point::new(5, 4)).unwrap().get_hp() > 1,
         "heals even enemy units"
@@ -899,9 +862,7 @@ fn tapio() {
         fairy_forest_hp,
         "only heal on your own start turn event"
     );
-    server
-        .handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
+    server.handle_command(Command::EndTurn).unwrap();
     assert!(
         server.get_unit(Point::new(0, 3)).unwrap().get_hp() > fairy_forest_hp,
         "heal on your own start turn event"
@@ -910,19 +871,19 @@ fn tapio() {
     // ACTIVE: turn grass into fairy forests
     let mut server = unchanged.clone();
     server
-        .handle_command(
-            Command::commander_power(1, vec![CustomActionInput::Point(Point::new(3, 0))]),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::commander_power(
+            1,
+            vec![CustomActionInput::Point(Point::new(3, 0))],
+        ))
         .err()
         .expect("can't turn street into fairy forest");
     for i in 0..2 {
         let charge_before = server.players[0].commander.charge;
         server
-            .handle_command(
-                Command::commander_power(1, vec![CustomActionInput::Point(Point::new(2, i))]),
-                Urc::new(|| 0.),
-            )
+            .handle_command(Command::commander_power(
+                1,
+                vec![CustomActionInput::Point(Point::new(2, i))],
+            ))
             .expect(&format!("loop {i}"));
         assert!(server.players[0].commander.charge < charge_before);
         assert_eq!(
@@ -934,7 +895,7 @@ fn tapio() {
     // ACTIVE: destroy own fairy forests, dealing damage to enemies nearby
     let mut server = unchanged.clone();
     server
-        .handle_command(Command::commander_power(2, Vec::new()), Urc::new(|| 0.))
+        .handle_command(Command::commander_power(2, Vec::new()))
         .unwrap();
     assert_eq!(
         server.get_terrain(Point::new(5, 3)).unwrap().typ(),
@@ -960,45 +921,35 @@ fn tapio() {
         .get_mut(0)
         .unwrap()
         .set_tag(&environment, TAG_FUNDS, 10000.into());
-    server
-        .handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
-    server
-        .handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
+    server.handle_command(Command::EndTurn).unwrap();
+    server.handle_command(Command::EndTurn).unwrap();
     assert_ne!(
         server.get_fog_at(ClientPerspective::Team(0), Point::new(5, 3)),
         FogIntensity::TrueSight
     );
     server
-        .handle_command(
-            Command::TerrainAction(
-                Point::new(5, 3),
-                vec![CustomActionInput::ShopItem(UnitType::SMALL_TANK.0.into())]
-                    .try_into()
-                    .unwrap(),
-            ),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::TerrainAction(
+            Point::new(5, 3),
+            vec![CustomActionInput::ShopItem(UnitType::SMALL_TANK.0.into())]
+                .try_into()
+                .unwrap(),
+        ))
         .err()
         .unwrap();
     server
-        .handle_command(Command::commander_power(3, Vec::new()), Urc::new(|| 0.))
+        .handle_command(Command::commander_power(3, Vec::new()))
         .unwrap();
     assert_eq!(
         server.get_fog_at(ClientPerspective::Team(0), Point::new(5, 3)),
         FogIntensity::TrueSight
     );
     server
-        .handle_command(
-            Command::TerrainAction(
-                Point::new(5, 3),
-                vec![CustomActionInput::ShopItem(UnitType::SMALL_TANK.0.into())]
-                    .try_into()
-                    .unwrap(),
-            ),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::TerrainAction(
+            Point::new(5, 3),
+            vec![CustomActionInput::ShopItem(UnitType::SMALL_TANK.0.into())]
+                .try_into()
+                .unwrap(),
+        ))
         .unwrap();
     assert!(
         server
@@ -1107,7 +1058,8 @@ fn sludge_monster() {
     map_settings.fog_mode = FogMode::Constant(FogSetting::None);
     let mut settings = map_settings.build_default();
     settings.players[0].set_commander(CommanderType::SludgeMonster);
-    let (mut server, _) = Game::new_server(map.clone(), &map_settings, settings, Urc::new(|| 0.));
+    let (mut server, _) =
+        Game::new_server(map.clone(), &map_settings, settings, GameRng::default());
     server.players.get_mut(0).unwrap().commander.charge = server
         .players
         .get_mut(0)
@@ -1120,78 +1072,60 @@ fn sludge_monster() {
     // 1. SludgeMonster's units don't get damaged by sludge, but other players' units do
     assert_eq!(server.get_unit(p_sludge_owned).unwrap().get_hp(), 100);
     assert_eq!(server.get_unit(p_sludge_enemy).unwrap().get_hp(), 100);
-    server
-        .handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
+    server.handle_command(Command::EndTurn).unwrap();
     assert_eq!(server.get_unit(p_sludge_owned).unwrap().get_hp(), 100);
     assert!(server.get_unit(p_sludge_enemy).unwrap().get_hp() < 100);
 
     // 2. SludgeMonster gets attack bonus when attacking from sludge token, other players don't
     let mut server = unchanged.clone();
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::with_steps(p_attack_owned, vec![PathStep::Dir(Direction4::D270)]),
-                action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                    p_attack_enemy,
-                    Direction4::D0,
-                ))),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::with_steps(p_attack_owned, vec![PathStep::Dir(Direction4::D270)]),
+            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+                p_attack_enemy,
+                Direction4::D0,
+            ))),
+        }))
         .unwrap();
     let base_dmg_owned = 100 - server.get_unit(p_attack_enemy).unwrap().get_hp();
     let mut server = unchanged.clone();
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::with_steps(p_attack_owned, vec![PathStep::Dir(Direction4::D0)]),
-                action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                    p_attack_enemy,
-                    Direction4::D270,
-                ))),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::with_steps(p_attack_owned, vec![PathStep::Dir(Direction4::D0)]),
+            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+                p_attack_enemy,
+                Direction4::D270,
+            ))),
+        }))
         .unwrap();
     assert!(server.get_unit(p_attack_enemy).unwrap().get_hp() < 100 - base_dmg_owned);
     // now check that enemies don't get the same bonus
     let mut server = unchanged.clone();
+    server.handle_command(Command::EndTurn).unwrap();
     server
-        .handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
-    server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::with_steps(p_attack_enemy, vec![PathStep::Dir(Direction4::D90)]),
-                action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                    p_attack_owned,
-                    Direction4::D180,
-                ))),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::with_steps(p_attack_enemy, vec![PathStep::Dir(Direction4::D90)]),
+            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+                p_attack_owned,
+                Direction4::D180,
+            ))),
+        }))
         .unwrap();
     let base_dmg_owned = 100 - server.get_unit(p_attack_owned).unwrap().get_hp();
     let mut server = unchanged.clone();
+    server.handle_command(Command::EndTurn).unwrap();
     server
-        .handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
-    server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::with_steps(p_attack_enemy, vec![PathStep::Dir(Direction4::D180)]),
-                action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                    p_attack_owned,
-                    Direction4::D90,
-                ))),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::with_steps(p_attack_enemy, vec![PathStep::Dir(Direction4::D180)]),
+            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+                p_attack_owned,
+                Direction4::D90,
+            ))),
+        }))
         .unwrap();
     assert_eq!(
         server.get_unit(p_attack_owned).unwrap().get_hp(),
@@ -1201,36 +1135,28 @@ fn sludge_monster() {
     // 3. SludgeMonster's units leave a sludge token behind when dying, other players don't
     let mut server = unchanged.clone();
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(p_die_owned),
-                action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                    p_die_enemy,
-                    Direction4::D270,
-                ))),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(p_die_owned),
+            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+                p_die_enemy,
+                Direction4::D270,
+            ))),
+        }))
         .unwrap();
     assert_eq!(server.get_tokens(p_die_owned).len(), 0);
     assert_eq!(server.get_tokens(p_die_enemy).len(), 0);
     let mut server = unchanged.clone();
+    server.handle_command(Command::EndTurn).unwrap();
     server
-        .handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
-    server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(p_die_enemy),
-                action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                    p_die_owned,
-                    Direction4::D90,
-                ))),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(p_die_enemy),
+            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+                p_die_owned,
+                Direction4::D90,
+            ))),
+        }))
         .unwrap();
     assert_eq!(server.get_tokens(p_die_owned).len(), 1);
     assert_eq!(server.get_tokens(p_die_enemy).len(), 0);
@@ -1238,7 +1164,7 @@ fn sludge_monster() {
     // 4. SludgeMonster's power spreads sludge (at its unit positions, around its units if there's already sludge under the unit)
     let mut server = unchanged.clone();
     server
-        .handle_command(Command::commander_power(1, Vec::new()), Urc::new(|| 0.))
+        .handle_command(Command::commander_power(1, Vec::new()))
         .unwrap();
     for p in get_neighbors(
         &server,
@@ -1251,21 +1177,16 @@ fn sludge_monster() {
     assert_eq!(server.get_tokens(p_die_enemy), Vec::new()); // enemy doesn't spread sludge
 
     // 5. While SludgeMonster's power is active, more sludge is spread when its units die
+    server.handle_command(Command::EndTurn).unwrap();
     server
-        .handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
-    server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(p_die_enemy),
-                action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                    p_die_owned,
-                    Direction4::D90,
-                ))),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(p_die_enemy),
+            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+                p_die_owned,
+                Direction4::D90,
+            ))),
+        }))
         .unwrap();
     assert_eq!(server.get_tokens(p_die_owned).len(), 1);
     for p in get_neighbors(&server, p_die_owned, crate::map::map::NeighborMode::Direct) {
@@ -1274,9 +1195,7 @@ fn sludge_monster() {
 
     // 6. sludge token can be removed by Infantry
     let mut server = unchanged.clone();
-    server
-        .handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
+    server.handle_command(Command::EndTurn).unwrap();
     let board = Board::new(&server);
     let path = Path::new(p_die_enemy);
     assert!(
@@ -1297,14 +1216,11 @@ fn sludge_monster() {
         "{options:?}"
     );
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path,
-                action: UnitAction::custom(CA_UNIT_CLEAN_SLUDGE, Vec::new()),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path,
+            action: UnitAction::custom(CA_UNIT_CLEAN_SLUDGE, Vec::new()),
+        }))
         .unwrap();
     assert_eq!(server.get_tokens(p_sludge_enemy).len(), 0);
 }
@@ -1421,32 +1337,26 @@ fn celerity() {
         map.clone(),
         &map_settings,
         map_settings.build_default(),
-        Urc::new(|| 0.),
+        GameRng::default(),
     );
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(Point::new(2, 2)),
-                action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                    Point::new(3, 2),
-                    Direction4::D0,
-                ))),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(Point::new(2, 2)),
+            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+                Point::new(3, 2),
+                Direction4::D0,
+            ))),
+        }))
         .unwrap();
     let default_attack = 100 - server.get_unit(Point::new(3, 2)).unwrap().get_hp();
     server
-        .handle_command(
-            Command::TerrainAction(
-                Point::new(0, 4),
-                vec![CustomActionInput::ShopItem(UnitType::SMALL_TANK.0.into())]
-                    .try_into()
-                    .unwrap(),
-            ),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::TerrainAction(
+            Point::new(0, 4),
+            vec![CustomActionInput::ShopItem(UnitType::SMALL_TANK.0.into())]
+                .try_into()
+                .unwrap(),
+        ))
         .unwrap();
     let default_cost = funds
         - server
@@ -1465,7 +1375,8 @@ fn celerity() {
 
     let mut settings = map_settings.build_default();
     settings.players[0].set_commander(CommanderType::Celerity);
-    let (mut server, _) = Game::new_server(map.clone(), &map_settings, settings, Urc::new(|| 0.));
+    let (mut server, _) =
+        Game::new_server(map.clone(), &map_settings, settings, GameRng::default());
     let environment = server.environment().clone();
     server.players.get_mut(0).unwrap().commander.charge = server
         .players
@@ -1476,15 +1387,12 @@ fn celerity() {
     let unchanged = server.clone();
 
     server
-        .handle_command(
-            Command::TerrainAction(
-                Point::new(0, 4),
-                vec![CustomActionInput::ShopItem(UnitType::SMALL_TANK.0.into())]
-                    .try_into()
-                    .unwrap(),
-            ),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::TerrainAction(
+            Point::new(0, 4),
+            vec![CustomActionInput::ShopItem(UnitType::SMALL_TANK.0.into())]
+                .try_into()
+                .unwrap(),
+        ))
         .unwrap();
     assert!(
         funds
@@ -1512,23 +1420,16 @@ fn celerity() {
         for d in Direction4::list().into_iter().take(i + 1).rev() {
             let target = map.get_neighbor(Point::new(2, 2), d).unwrap().0;
             server
-                .handle_command(
-                    Command::UnitCommand(UnitCommand {
-                        unload_index: None,
-                        path: Path::new(Point::new(2, 2)),
-                        action: UnitAction::Attack(AttackInput::SplashPattern(
-                            OrientedPoint::simple(target, d),
-                        )),
-                    }),
-                    Urc::new(|| 0.),
-                )
-                .unwrap();
-            server
-                .handle_command(Command::EndTurn, Urc::new(|| 0.))
-                .unwrap();
-            server
-                .handle_command(Command::EndTurn, Urc::new(|| 0.))
+                .handle_command(Command::UnitCommand(UnitCommand {
+                    unload_index: None,
+                    path: Path::new(Point::new(2, 2)),
+                    action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+                        target, d,
+                    ))),
+                }))
                 .unwrap();
+            server.handle_command(Command::EndTurn).unwrap();
+            server.handle_command(Command::EndTurn).unwrap();
         }
         if i > 0 {
             assert_eq!(server.get_unit(Point::new(2, 1)), None);
@@ -1554,21 +1455,18 @@ fn celerity() {
     // small power
     let mut server = unchanged.clone();
     server
-        .handle_command(Command::commander_power(1, Vec::new()), Urc::new(|| 0.))
+        .handle_command(Command::commander_power(1, Vec::new()))
         .unwrap();
     assert!(server.get_unit(Point::new(0, 0)).unwrap().get_hp() > 1);
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(Point::new(2, 2)),
-                action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                    Point::new(3, 2),
-                    Direction4::D0,
-                ))),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(Point::new(2, 2)),
+            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+                Point::new(3, 2),
+                Direction4::D0,
+            ))),
+        }))
         .unwrap();
     assert_eq!(
         default_attack,
@@ -1578,21 +1476,18 @@ fn celerity() {
     // big power
     let mut server = unchanged.clone();
     server
-        .handle_command(Command::commander_power(2, Vec::new()), Urc::new(|| 0.))
+        .handle_command(Command::commander_power(2, Vec::new()))
         .unwrap();
     assert_eq!(server.get_unit(Point::new(0, 0)).unwrap().get_hp(), 1);
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(Point::new(2, 2)),
-                action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                    Point::new(3, 2),
-                    Direction4::D0,
-                ))),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(Point::new(2, 2)),
+            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+                Point::new(3, 2),
+                Direction4::D0,
+            ))),
+        }))
         .unwrap();
     assert_eq!(
         attack_damage[3],
@@ -1694,35 +1589,28 @@ fn lageos() {
     // get some default values without using Lageos
     let mut settings = map_settings.build_default();
     settings.fog_mode = FogMode::Constant(FogSetting::Sharp(0));
-    let (mut server, _) = Game::new_server(map.clone(), &map_settings, settings, Urc::new(|| 0.));
-    server
-        .handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
-    server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(Point::new(0, 0)),
-                action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                    Point::new(1, 0),
-                    Direction4::D0,
-                ))),
-            }),
-            Urc::new(|| 0.),
-        )
-        .unwrap();
-    server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(Point::new(0, 1)),
-                action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                    Point::new(1, 1),
-                    Direction4::D0,
-                ))),
-            }),
-            Urc::new(|| 0.),
-        )
+    let (mut server, _) =
+        Game::new_server(map.clone(), &map_settings, settings, GameRng::default());
+    server.handle_command(Command::EndTurn).unwrap();
+    server
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(Point::new(0, 0)),
+            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+                Point::new(1, 0),
+                Direction4::D0,
+            ))),
+        }))
+        .unwrap();
+    server
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(Point::new(0, 1)),
+            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+                Point::new(1, 1),
+                Direction4::D0,
+            ))),
+        }))
         .unwrap();
     let damage_to_neutral_heli = 100 - server.get_unit(Point::new(1, 0)).unwrap().get_hp();
     let damage_to_neutral_tank = 100 - server.get_unit(Point::new(1, 1)).unwrap().get_hp();
@@ -1735,7 +1623,8 @@ fn lageos() {
     let mut settings = map_settings.build_default();
     settings.fog_mode = FogMode::Constant(FogSetting::Sharp(0));
     settings.players[0].set_commander(CommanderType::Lageos);
-    let (mut server, _) = Game::new_server(map.clone(), &map_settings, settings, Urc::new(|| 0.));
+    let (mut server, _) =
+        Game::new_server(map.clone(), &map_settings, settings, GameRng::default());
     server.players.get_mut(0).unwrap().commander.charge = server
         .players
         .get_mut(0)
@@ -1745,34 +1634,26 @@ fn lageos() {
     let unchanged = server.clone();
 
     // Lageos' air-units have have higher defense, Lageos has +1 vision
-    server
-        .handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
-    server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(Point::new(0, 0)),
-                action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                    Point::new(1, 0),
-                    Direction4::D0,
-                ))),
-            }),
-            Urc::new(|| 0.),
-        )
-        .unwrap();
-    server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(Point::new(0, 1)),
-                action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                    Point::new(1, 1),
-                    Direction4::D0,
-                ))),
-            }),
-            Urc::new(|| 0.),
-        )
+    server.handle_command(Command::EndTurn).unwrap();
+    server
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(Point::new(0, 0)),
+            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+                Point::new(1, 0),
+                Direction4::D0,
+            ))),
+        }))
+        .unwrap();
+    server
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(Point::new(0, 1)),
+            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+                Point::new(1, 1),
+                Direction4::D0,
+            ))),
+        }))
         .unwrap();
     let damage_to_lageos_heli = 100 - server.get_unit(Point::new(1, 0)).unwrap().get_hp();
     assert!(
@@ -1796,20 +1677,17 @@ fn lageos() {
     // small power
     let mut server = unchanged.clone();
     server
-        .handle_command(Command::commander_power(1, Vec::new()), Urc::new(|| 0.))
+        .handle_command(Command::commander_power(1, Vec::new()))
         .unwrap_err();
     server
-        .handle_command(
-            Command::commander_power(
-                1,
-                vec![
-                    CustomActionInput::Point(Point::new(0, 1)),
-                    CustomActionInput::Point(Point::new(0, 1)),
-                    CustomActionInput::Point(Point::new(2, 1)),
-                ],
-            ),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::commander_power(
+            1,
+            vec![
+                CustomActionInput::Point(Point::new(0, 1)),
+                CustomActionInput::Point(Point::new(0, 1)),
+                CustomActionInput::Point(Point::new(2, 1)),
+            ],
+        ))
         .unwrap();
     assert_eq!(server.get_unit(Point::new(1, 0)).unwrap().get_hp(), 100);
     assert!(server.get_unit(Point::new(0, 1)).unwrap().get_hp() < 100);
@@ -1828,25 +1706,22 @@ fn lageos() {
     // big power
     let mut server = unchanged.clone();
     server
-        .handle_command(Command::commander_power(2, Vec::new()), Urc::new(|| 0.))
+        .handle_command(Command::commander_power(2, Vec::new()))
         .unwrap_err();
     server
-        .handle_command(
-            Command::commander_power(
-                2,
-                vec![
-                    CustomActionInput::Point(Point::new(0, 1)),
-                    CustomActionInput::Point(Point::new(0, 1)),
-                ],
-            ),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::commander_power(
+            2,
+            vec![
+                CustomActionInput::Point(Point::new(0, 1)),
+                CustomActionInput::Point(Point::new(0, 1)),
+            ],
+        ))
         .unwrap_err();
     server
-        .handle_command(
-            Command::commander_power(2, vec![CustomActionInput::Point(Point::new(0, 1))]),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::commander_power(
+            2,
+            vec![CustomActionInput::Point(Point::new(0, 1))],
+        ))
         .unwrap();
     assert_eq!(server.get_unit(Point::new(1, 0)).unwrap().get_hp(), 100);
     assert!(server.get_unit(Point::new(0, 1)).unwrap().get_hp() < 100);
@@ -1869,9 +1744,7 @@ fn lageos() {
             .unwrap()
             .has_flag(FLAG_STUNNED)
     );
-    server
-        .handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
+    server.handle_command(Command::EndTurn).unwrap();
     assert!(
         server
             .get_unit(Point::new(0, 1))
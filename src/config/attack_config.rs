@@ -72,8 +72,10 @@ impl TableLine for AttackConfig {
 
 impl AttackConfig {
     pub(super) fn parse_deferred(&mut self, loader: &mut FileLoader) -> Result<(), Box<dyn Error>> {
-        self.condition =
-            parse_inner_vec(&std::mem::take(&mut self.unparsed_condition), false, loader)?.0;
+        let condition = std::mem::take(&mut self.unparsed_condition);
+        self.condition = parse_inner_vec(&condition, false, loader)
+            .map_err(|e| e.in_cell("Condition", &condition))?
+            .0;
         Ok(())
     }
 }
@@ -128,7 +130,10 @@ impl TableLine for AttackSplashConfig {
                 }
             }
             s => AttackInstanceScript::Rhai {
-                build_script: loader.rhai_function(&format!("{s}"), 1..=1)?.index,
+                build_script: loader
+                    .rhai_function(&format!("{s}"), 1..=1)
+                    .map_err(|e| e.in_cell("Script", s))?
+                    .index,
             },
         };
         /*let mut custom_columns = HashMap::default();
@@ -181,8 +186,10 @@ impl TableLine for AttackSplashConfig {
 
 impl AttackSplashConfig {
     pub(super) fn parse_deferred(&mut self, loader: &mut FileLoader) -> Result<(), Box<dyn Error>> {
-        self.condition =
-            parse_inner_vec(&std::mem::take(&mut self.unparsed_condition), false, loader)?.0;
+        let condition = std::mem::take(&mut self.unparsed_condition);
+        self.condition = parse_inner_vec(&condition, false, loader)
+            .map_err(|e| e.in_cell("Condition", &condition))?
+            .0;
         Ok(())
     }
 }
@@ -10,6 +10,7 @@ use crate::config::parse::*;
 use crate::dyn_opt;
 use crate::map::board::Board;
 use crate::map::direction::Direction;
+use crate::script::profiler::ScriptHook;
 use crate::script::*;
 use crate::units::UnitData;
 use crate::units::hero::HeroMap;
@@ -79,22 +80,13 @@ impl TableLine for AttackPoweredConfig {
             affects: parse_vec_def(data, H::Filter, Vec::new(), loader)?,
             attack_priority: parse_def(data, H::AttackPriority, NumberMod::Keep, loader)?,
             splash_range: parse_def(data, H::SplashRange, NumberMod::Keep, loader)?,
-            focus: match data.get(&H::Targeting) {
-                Some(s) if s.len() > 0 => Some(AttackTargetingFocus::from_conf(s, loader)?.0),
-                _ => None,
-            },
-            allows_counter_attack: match data.get(&H::AllowsCounterAttack) {
-                Some(s) if s.len() > 0 => Some(
-                    s.parse()
-                        .map_err(|_| ConfigParseError::InvalidBool(s.to_string()))?,
-                ),
-                _ => None,
-            },
+            focus: parse_opt(data, H::Targeting, loader)?,
+            allows_counter_attack: map_cell(data, H::AllowsCounterAttack, |s| {
+                s.parse()
+                    .map_err(|_| ConfigParseError::InvalidBool(s.to_string()))
+            })?,
             splash_priority: parse_def(data, H::SplashPriority, NumberMod::Keep, loader)?,
-            direction_modifier: match data.get(&H::DirectionModifier) {
-                Some(s) if s.len() > 0 => Some(DisplaceDirectionModifier::from_conf(s, loader)?.0),
-                _ => None,
-            },
+            direction_modifier: parse_opt(data, H::DirectionModifier, loader)?,
             custom_columns,
             scripts,
         })
@@ -252,10 +244,15 @@ impl AttackFilter {
                     is_counter,
                 );
                 let executor = game.executor(scope);
-                match executor.run::<D, bool>(*function_index, ()) {
+                match executor.run::<D, bool>(ScriptHook::AttackFilter, *function_index, ()) {
                     Ok(result) => result,
-                    Err(_e) => {
-                        // TODO: log error
+                    Err(e) => {
+                        let environment = executor.environment();
+                        environment.log_rhai_error(
+                            "AttackFilter::Rhai",
+                            environment.get_rhai_function_name(*function_index),
+                            &e,
+                        );
                         false
                     }
                 }
@@ -29,18 +29,15 @@ impl TableLine for CommanderPowerConfig {
         use CommanderPowerConfigHeader as H;
         use ConfigParseError as E;
         let get = |key| data.get(&key).ok_or(E::MissingColumn(format!("{key:?}")));
-        let script = match data.get(&H::Script) {
-            Some(s) if s.len() > 0 => {
-                let exe = loader.rhai_function(s, 1..=2)?;
-                let input = if exe.parameters.len() > 1 {
-                    Some(loader.rhai_function(&format!("{s}_input"), 1..=1)?.index)
-                } else {
-                    None
-                };
-                Some((input, exe.index))
-            }
-            _ => None,
-        };
+        let script = map_cell(data, H::Script, |s| {
+            let exe = loader.rhai_function(s, 1..=2)?;
+            let input = if exe.parameters.len() > 1 {
+                Some(loader.rhai_function(&format!("{s}_input"), 1..=1)?.index)
+            } else {
+                None
+            };
+            Ok((input, exe.index))
+        })?;
         let result = Self {
             id: parse(data, H::Id, loader)?,
             name: get(H::Name)?.to_string(),
@@ -9,6 +9,7 @@ use crate::combat::AttackType;
 use crate::combat::ValidAttackTargets;
 use crate::config::parse::*;
 use crate::units::UnitVisibility;
+use crate::units::zone_of_control::ZoneOfControl;
 
 use super::ConfigParseError;
 use super::file_loader::FileLoader;
@@ -24,6 +25,7 @@ pub(super) struct CommanderPowerUnitConfig {
     pub(super) vision: NumberMod<u8>,
     pub(super) true_vision: NumberMod<u8>,
     pub(super) pass_enemy_units: Option<bool>,
+    pub(super) zone_of_control: Option<ZoneOfControl>,
     pub(super) attack_pattern: Option<AttackPattern>,
     pub(super) attack_type: Option<AttackType>,
     pub(super) attack_targets: Option<ValidAttackTargets>,
@@ -56,53 +58,29 @@ impl TableLine for CommanderPowerUnitConfig {
         }
         Ok(Self {
             affects: power.into_iter().chain(affects.into_iter()).collect(),
-            visibility: match data.get(&H::Visibility) {
-                Some(s) if s.len() > 0 => Some(UnitVisibility::from_conf(s, loader)?.0),
-                _ => None,
-            },
+            visibility: parse_opt(data, H::Visibility, loader)?,
             movement_points: parse_def(data, H::MovementPoints, NumberMod::Keep, loader)?,
             vision: parse_def(data, H::Vision, NumberMod::Keep, loader)?,
             true_vision: parse_def(data, H::TrueVision, NumberMod::Keep, loader)?,
-            pass_enemy_units: match data.get(&H::PassEnemyUnits) {
-                Some(s) if s.len() > 0 => Some(
-                    s.parse()
-                        .map_err(|_| ConfigParseError::InvalidBool(s.to_string()))?,
-                ),
-                _ => None,
-            },
-            attack_pattern: match data.get(&H::AttackPattern) {
-                Some(s) if s.len() > 0 => Some(AttackPattern::from_conf(s, loader)?.0),
-                _ => None,
-            },
-            attack_type: match data.get(&H::AttackType) {
-                Some(s) if s.len() > 0 => Some(AttackType::from_conf(s, loader)?.0),
-                _ => None,
-            },
-            attack_targets: match data.get(&H::Targeting) {
-                Some(s) if s.len() > 0 => Some(ValidAttackTargets::from_conf(s, loader)?.0),
-                _ => None,
-            },
-            attack_direction: match data.get(&H::AttackDirection) {
-                Some(s) if s.len() > 0 => {
-                    Some(AllowedAttackInputDirectionSource::from_conf(s, loader)?.0)
-                }
-                _ => None,
-            },
-            can_be_displaced: match data.get(&H::CanBeDisplaced) {
-                Some(s) if s.len() > 0 => Some(
-                    s.parse()
-                        .map_err(|_| ConfigParseError::InvalidBool(s.to_string()))?,
-                ),
-                _ => None,
-            },
-            on_death: match data.get(&H::OnDeath) {
-                Some(s) if s.len() > 0 => Some(loader.rhai_function(s, 1..=1)?.index),
-                _ => None,
-            },
-            on_normal_action: match data.get(&H::OnNormalAction) {
-                Some(s) if s.len() > 0 => Some(loader.rhai_function(s, 1..=1)?.index),
-                _ => None,
-            },
+            pass_enemy_units: map_cell(data, H::PassEnemyUnits, |s| {
+                s.parse()
+                    .map_err(|_| ConfigParseError::InvalidBool(s.to_string()))
+            })?,
+            zone_of_control: parse_opt(data, H::ZoneOfControl, loader)?,
+            attack_pattern: parse_opt(data, H::AttackPattern, loader)?,
+            attack_type: parse_opt(data, H::AttackType, loader)?,
+            attack_targets: parse_opt(data, H::Targeting, loader)?,
+            attack_direction: parse_opt(data, H::AttackDirection, loader)?,
+            can_be_displaced: map_cell(data, H::CanBeDisplaced, |s| {
+                s.parse()
+                    .map_err(|_| ConfigParseError::InvalidBool(s.to_string()))
+            })?,
+            on_death: map_cell(data, H::OnDeath, |s| {
+                Ok(loader.rhai_function(s, 1..=1)?.index)
+            })?,
+            on_normal_action: map_cell(data, H::OnNormalAction, |s| {
+                Ok(loader.rhai_function(s, 1..=1)?.index)
+            })?,
             aura_range: parse_def(data, H::AuraRange, NumberMod::Keep, loader)?,
             custom_columns,
         })
@@ -132,6 +110,7 @@ crate::enum_with_custom! {
         Vision,
         TrueVision,
         PassEnemyUnits,
+        ZoneOfControl,
         CanBeMovedThrough,
         CanTake,
         CanBeTaken,
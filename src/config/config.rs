@@ -23,6 +23,7 @@ use crate::map::map::MapType;
 use crate::map::map::import_map;
 use crate::map::point::Point;
 use crate::script::executor::Executor;
+use crate::script::limits::ScriptLimits;
 use crate::script::*;
 use crate::terrain::terrain::Terrain;
 use crate::terrain::*;
@@ -31,6 +32,7 @@ use crate::units::hero::*;
 use crate::units::movement::*;
 use crate::units::unit::Unit;
 use crate::units::unit_types::UnitType;
+use crate::units::zone_of_control::ZoneOfControl;
 use crate::units::{UnitData, UnitVisibility};
 
 use super::OwnershipPredicate;
@@ -58,6 +60,7 @@ use super::unit_type_config::UnitTypeConfig;
 pub struct Config {
     pub(super) name: String,
     pub(super) owner_colors: Vec<[u8; 4]>,
+    pub(super) max_map_size: u8,
     // tags
     pub(super) flags: Vec<TagConfig>,
     pub(super) tags: Vec<TagConfig>,
@@ -71,6 +74,10 @@ pub struct Config {
     //pub(super) attack_damage: HashMap<UnitType, HashMap<UnitType, u16>>,
     pub(super) custom_actions: Vec<CustomActionConfig>,
     pub(super) max_transported: usize,
+    // whether unit types or powers can give units a zone of control
+    pub(super) has_zone_of_control: bool,
+    // Int-tag that stores the health of units
+    pub(super) health_tag: Option<usize>,
     pub(super) unit_flags: HashMap<(usize, UnitType), TagEditorVisibility>,
     pub(super) unit_tags: HashMap<(usize, UnitType), TagEditorVisibility>,
     pub(super) attack_types: Vec<(String, Vec<AttackConfig>)>,
@@ -103,9 +110,12 @@ pub struct Config {
     // rhai
     pub(crate) engines: Vec<Engine>, // [D4, D6]
     pub(super) asts: Vec<Rc<AST>>,
+    // filename (without scripts/ and .rhai) of each AST
+    pub(super) script_files: Vec<String>,
     pub(super) functions: Vec<(usize, String)>,
     pub(super) is_unit_dead_rhai: usize,
     pub(super) is_unit_movable_rhai: usize,
+    pub(super) script_limits: ScriptLimits,
     pub(super) custom_tables: HashMap<String, CustomTable>,
 }
 
@@ -159,6 +169,11 @@ impl Config {
         16
     }
 
+    /// maximum width and height of maps, at most point_map::MAX_SIZE
+    pub fn max_map_size(&self) -> u8 {
+        self.max_map_size
+    }
+
     pub fn max_aura_range(&self) -> i8 {
         self.max_aura_range
     }
@@ -209,6 +224,10 @@ impl Config {
     pub fn tag_by_name(&self, name: &str) -> Option<usize> {
         self.tags.iter().position(|tag| tag.name.as_str() == name)
     }
+    /// the Int-tag that stores the health of units, None if the ruleset doesn't name one
+    pub fn health_tag(&self) -> Option<usize> {
+        self.health_tag
+    }
 
     pub fn flag_ev_terrain(&self, typ: TerrainType, flag: usize) -> TagEditorVisibility {
         self.terrain_flags
@@ -1101,6 +1120,41 @@ impl Config {
         )
     }
 
+    /// false if neither unit types nor powers can give units a zone of control
+    pub fn has_zone_of_control(&self) -> bool {
+        self.has_zone_of_control
+    }
+
+    pub fn unit_zone_of_control<D: Direction>(
+        &self,
+        game: &Board<D>,
+        unit: &Unit<D>,
+        unit_pos: Point,
+        heroes: &HeroMap<D>,
+    ) -> ZoneOfControl {
+        self.unit_power_configs(
+            game,
+            UnitData {
+                unit,
+                pos: unit_pos,
+                unload_index: None,
+                ballast: &[],
+                original_transporter: None,
+            },
+            None,
+            heroes,
+            false,
+            |iter, _| {
+                for conf in iter.rev() {
+                    if let Some(zone_of_control) = conf.zone_of_control {
+                        return zone_of_control;
+                    }
+                }
+                self.unit_config(unit.typ()).zone_of_control
+            },
+        )
+    }
+
     fn attack_power_configs<'a, D: Direction, R>(
         &'a self,
         game: &'a Board<D>,
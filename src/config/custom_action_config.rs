@@ -28,18 +28,16 @@ impl TableLine for CustomActionConfig {
                 .ok_or(E::MissingColumn(format!("CustomActionConfig::{key:?}")))
         };
         let name = get(H::Name)?.to_string();
-        let script = match data.get(&H::Script) {
-            Some(s) if s.len() > 0 => {
-                let exe = loader.rhai_function(s, 1..=2)?;
-                let input = if exe.parameters.len() > 1 {
-                    Some(loader.rhai_function(&format!("{s}_input"), 1..=1)?.index)
-                } else {
-                    None
-                };
-                Ok((input, exe.index))
-            }
-            _ => Err(ConfigParseError::CustomActionScriptMissing(name.clone())),
-        }?;
+        let script = map_cell(data, H::Script, |s| {
+            let exe = loader.rhai_function(s, 1..=2)?;
+            let input = if exe.parameters.len() > 1 {
+                Some(loader.rhai_function(&format!("{s}_input"), 1..=1)?.index)
+            } else {
+                None
+            };
+            Ok((input, exe.index))
+        })?
+        .ok_or(ConfigParseError::CustomActionScriptMissing(name.clone()))?;
         let result = Self {
             unit_filter: parse_vec_dyn_def(data, H::UnitFilter, Vec::new(), |s| {
                 UnitFilter::from_conf(s, loader)
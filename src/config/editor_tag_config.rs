@@ -7,7 +7,7 @@ use rustc_hash::FxHashMap as HashMap;
 use crate::tags::{FlagKey, TagKey};
 
 use super::ConfigParseError;
-use super::file_loader::FileLoader;
+use super::file_loader::{FileLoader, csv_error};
 use super::parse::FromConfig;
 
 crate::listable_enum! {
@@ -42,29 +42,55 @@ pub(super) fn parse<T: FromConfig + Clone + Hash + Eq>(
     let mut reader = csv::ReaderBuilder::new()
         .delimiter(b';')
         .from_reader(data.as_bytes());
-    // TODO: ensure uniqueness of column and row IDs
+    let header_record = reader.headers().map_err(csv_error(filename))?.clone();
     let mut headers: Vec<Either<FlagKey, TagKey>> = Vec::new();
-    for h in reader.headers()?.into_iter().skip(1) {
+    for h in header_record.iter().skip(1) {
         let header = FlagKey::from_conf(h, loader)
             .map(|k| Either::Left(k.0))
-            .or(TagKey::from_conf(h, loader).map(|k| Either::Right(k.0)))?;
+            .or(TagKey::from_conf(h, loader).map(|k| Either::Right(k.0)))
+            .map_err(|e| e.in_file(filename, Some(1)))?;
         if headers.contains(&header) {
-            return Err(Box::new(ConfigParseError::DuplicateHeader(h.to_string())));
+            return Err(ConfigParseError::DuplicateHeader(h.to_string())
+                .in_file(filename, Some(1))
+                .into());
         }
         headers.push(header);
     }
     let mut flags = HashMap::default();
     let mut tags = HashMap::default();
+    let mut row_keys: Vec<T> = Vec::new();
     for line in reader.records() {
-        let line = line?;
+        let line = line.map_err(csv_error(filename))?;
+        let line_number = line.position().map(|p| p.line());
         let mut line = line.into_iter();
-        let row_key: T = match line.next() {
-            Some(t) => T::from_conf(t, loader)?.0,
-            _ => continue,
+        let Some(t) = line.next() else {
+            continue;
         };
+        let row_key = match T::from_conf(t, loader) {
+            Ok((row_key, _)) => row_key,
+            Err(e) => {
+                loader.report(e.in_file(filename, line_number))?;
+                continue;
+            }
+        };
+        if row_keys.contains(&row_key) {
+            let error = ConfigParseError::DuplicateEntry(t.to_string());
+            loader.report(error.in_file(filename, line_number))?;
+            continue;
+        }
+        row_keys.push(row_key.clone());
         for (i, val) in line.enumerate() {
             if val.len() > 0 && i < headers.len() {
-                let visibility = TagEditorVisibility::from_conf(val, loader)?.0;
+                let visibility = match TagEditorVisibility::from_conf(val, loader) {
+                    Ok((visibility, _)) => visibility,
+                    Err(e) => {
+                        loader.report(
+                            e.in_cell(&header_record[i + 1], val)
+                                .in_file(filename, line_number),
+                        )?;
+                        continue;
+                    }
+                };
                 match headers[i].clone() {
                     Either::Left(flag) => flags.insert((flag.0, row_key.clone()), visibility),
                     Either::Right(tag) => tags.insert((tag.0, row_key.clone()), visibility),
@@ -10,6 +10,7 @@ use crate::game::fog::{FogIntensity, is_unit_visible, visible_unit_with_attribut
 use crate::map::board::{Board, BoardView, current_team};
 use crate::map::direction::Direction;
 use crate::map::point::Point;
+use crate::script::profiler::ScriptHook;
 use crate::script::*;
 use crate::tags::{FlagKey, TagKey};
 
@@ -190,7 +191,11 @@ impl EffectVisibility {
                     first_argument.insert(CONST_NAME_POSITION.into(), Dynamic::from(p));
                 }
                 let executor = game.executor(first_argument);
-                match executor.run::<D, Option<EffectWithoutPosition<D>>>(*function_index, ()) {
+                match executor.run::<D, Option<EffectWithoutPosition<D>>>(
+                    ScriptHook::Effect,
+                    *function_index,
+                    (),
+                ) {
                     Ok(result) => result,
                     Err(e) => {
                         let environment = game.environment();
@@ -1,5 +1,7 @@
 use std::fmt::Debug;
 use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 
 use interfaces::ClientPerspective;
 use rhai::*;
@@ -13,6 +15,9 @@ use crate::map::board::Board;
 use crate::map::direction::Direction;
 use crate::map::point::Point;
 use crate::map::point_map::MapSize;
+use crate::script::diagnostics::{ScriptDiagnostics, ScriptFailure};
+use crate::script::executor::ScriptError;
+use crate::script::profiler::{ScriptHook, ScriptProfile, ScriptProfiler};
 use crate::tags::*;
 use crate::terrain::TerrainType;
 use crate::terrain::terrain::*;
@@ -32,6 +37,13 @@ pub struct Environment {
     pub config: Urc<Config>,
     pub settings: Option<Urc<GameSettings>>,
     unique_ids: Urc<Umutex<HashMap<String, FxHashSet<usize>>>>,
+    script_diagnostics: Urc<Umutex<ScriptDiagnostics>>,
+    // only Some while profiling
+    script_profiler: Urc<Umutex<Option<ScriptProfiler>>>,
+    // mirrors script_profiler.is_some(), so script calls don't have to lock the mutex
+    script_profiling: Urc<AtomicBool>,
+    // set by the server, clients never limit the wall time of scripts
+    script_time_limit: Urc<Umutex<Option<Duration>>>,
 }
 
 impl Environment {
@@ -40,6 +52,10 @@ impl Environment {
             map_size,
             settings: None,
             unique_ids: Self::setup_unique_ids(&config),
+            script_diagnostics: Urc::new(Umutex::new(ScriptDiagnostics::default())),
+            script_profiler: Urc::new(Umutex::new(None)),
+            script_profiling: Urc::new(AtomicBool::new(false)),
+            script_time_limit: Urc::new(Umutex::new(None)),
             config,
         }
     }
@@ -49,6 +65,10 @@ impl Environment {
             map_size,
             settings: Some(Urc::new(settings)),
             unique_ids: Self::setup_unique_ids(&config),
+            script_diagnostics: Urc::new(Umutex::new(ScriptDiagnostics::default())),
+            script_profiler: Urc::new(Umutex::new(None)),
+            script_profiling: Urc::new(AtomicBool::new(false)),
+            script_time_limit: Urc::new(Umutex::new(None)),
             config,
         }
     }
@@ -105,13 +125,84 @@ impl Environment {
         &self,
         location: &str,
         function_name: impl AsRef<str>,
-        error: &EvalAltResult,
+        error: &ScriptError,
     ) {
-        let config_name = &self.config.name;
-        let function_name = function_name.as_ref();
-        crate::warn!(
-            "RHAI error in {location}, config '{config_name}', function '{function_name}':\n{error:?}"
-        );
+        self.record_script_failure(ScriptFailure::new(location, function_name.as_ref(), error));
+    }
+
+    pub(crate) fn record_script_failure(&self, failure: ScriptFailure) {
+        self.script_diagnostics.lock().record(failure);
+    }
+
+    /// failed script calls since the last call to take_script_failures
+    pub fn script_failures(&self) -> Vec<ScriptFailure> {
+        self.script_diagnostics.lock().failures().to_vec()
+    }
+
+    /// how many failures were dropped because too many were recorded
+    pub fn dropped_script_failures(&self) -> usize {
+        self.script_diagnostics.lock().dropped()
+    }
+
+    /// the recorded failures and how many were dropped since the last call
+    pub fn take_script_failures(&self) -> (Vec<ScriptFailure>, usize) {
+        self.script_diagnostics.lock().take()
+    }
+
+    /// script calls are counted and timed until disable_script_profiling is called
+    pub fn enable_script_profiling(&self) {
+        let mut profiler = self.script_profiler.lock();
+        if profiler.is_none() {
+            *profiler = Some(ScriptProfiler::default());
+        }
+        self.script_profiling.store(true, Ordering::Relaxed);
+    }
+
+    /// returns what was recorded since profiling was enabled
+    pub fn disable_script_profiling(&self) -> Option<ScriptProfile> {
+        let mut profiler = self.script_profiler.lock();
+        self.script_profiling.store(false, Ordering::Relaxed);
+        profiler.take().map(|profiler| profiler.report())
+    }
+
+    pub fn is_script_profiling(&self) -> bool {
+        self.script_profiling.load(Ordering::Relaxed)
+    }
+
+    pub fn script_profile(&self) -> Option<ScriptProfile> {
+        self.script_profiler
+            .lock()
+            .as_ref()
+            .map(|profiler| profiler.report())
+    }
+
+    pub(crate) fn set_script_profiling_turn(&self, turn: usize) {
+        if !self.is_script_profiling() {
+            return;
+        }
+        if let Some(profiler) = self.script_profiler.lock().as_mut() {
+            profiler.set_turn(turn);
+        }
+    }
+
+    pub(crate) fn record_script_call(&self, hook: ScriptHook, function: &str, time: Duration) {
+        if let Some(profiler) = self.script_profiler.lock().as_mut() {
+            profiler.record(hook, function, time);
+        }
+    }
+
+    pub fn script_time_limit(&self) -> Option<Duration> {
+        *self.script_time_limit.lock()
+    }
+
+    pub(crate) fn set_script_time_limit(&self, time_limit: Option<Duration>) {
+        *self.script_time_limit.lock() = time_limit;
+    }
+
+    /// "file > function", the way the function is referenced in the config
+    pub fn get_rhai_function_label(&self, index: usize) -> String {
+        let (ast_index, name) = &self.config.functions[index];
+        format!("{} > {name}", self.config.script_files[*ast_index])
     }
 
     pub fn get_rhai_function_name(&self, index: usize) -> &String {
@@ -8,8 +8,8 @@ use uniform_smart_pointer::Urc;
 
 use crate::script::create_base_engine;
 
-use super::ConfigParseError;
 use super::parse::{FromConfig, GLOBAL_SCRIPT};
+use super::{ConfigDiagnostic, ConfigParseError, DiagnosticSeverity};
 
 pub struct FunctionPointer {
     pub index: usize,
@@ -21,6 +21,9 @@ pub struct FileLoader {
     engine: Engine,
     unoptimized_asts: HashMap<String, Urc<AST>>,
     rhai_functions: Vec<(String, String, Urc<Vec<String>>)>,
+    // only Some while validating
+    diagnostics: Option<Vec<ConfigDiagnostic>>,
+    script_sources: HashMap<String, String>,
     pub movement_types: Vec<String>,
     pub unit_types: Vec<String>,
     pub terrain_types: Vec<String>,
@@ -45,6 +48,8 @@ impl FileLoader {
             engine,
             unoptimized_asts: HashMap::default(),
             rhai_functions: Vec::new(),
+            diagnostics: None,
+            script_sources: HashMap::default(),
             movement_types: Vec::new(),
             unit_types: Vec::new(),
             terrain_types: Vec::new(),
@@ -59,6 +64,46 @@ impl FileLoader {
         }
     }
 
+    /// errors passed to report() don't stop parsing anymore, they're collected instead
+    pub(super) fn collect_diagnostics(&mut self) {
+        self.diagnostics = Some(Vec::new());
+    }
+
+    pub(super) fn is_collecting_diagnostics(&self) -> bool {
+        self.diagnostics.is_some()
+    }
+
+    /**
+     * while validating, the error is stored and Ok is returned so parsing can continue.
+     * otherwise the error is returned
+     */
+    pub(super) fn report(&mut self, error: ConfigParseError) -> Result<(), ConfigParseError> {
+        match &mut self.diagnostics {
+            Some(diagnostics) => {
+                diagnostics.push(ConfigDiagnostic {
+                    severity: DiagnosticSeverity::Error,
+                    error,
+                });
+                Ok(())
+            }
+            None => Err(error),
+        }
+    }
+
+    /// warnings are ignored unless validating
+    pub(super) fn warn(&mut self, warning: ConfigParseError) {
+        if let Some(diagnostics) = &mut self.diagnostics {
+            diagnostics.push(ConfigDiagnostic {
+                severity: DiagnosticSeverity::Warning,
+                error: warning,
+            });
+        }
+    }
+
+    pub(super) fn take_diagnostics(&mut self) -> Vec<ConfigDiagnostic> {
+        self.diagnostics.take().unwrap_or_default()
+    }
+
     // TODO: delete this function
     pub(super) fn load_config(&mut self, filename: &str) -> Result<String, Box<dyn Error>> {
         (self.load_file)(filename)
@@ -70,13 +115,26 @@ impl FileLoader {
         filename: &str,
         mut f: impl FnMut(&str, &str, &mut Self) -> Result<(), Box<dyn Error>>,
     ) -> Result<(), Box<dyn Error>> {
-        let data = self.load_config(filename)?;
+        let data = match self.load_config(filename) {
+            Ok(data) => data,
+            Err(e) => {
+                self.report(e.into())?;
+                return Ok(());
+            }
+        };
         let mut reader = csv::ReaderBuilder::new()
             .delimiter(b';')
             .has_headers(false)
             .from_reader(data.as_bytes());
         for line in reader.records() {
-            let line = line?;
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => {
+                    self.report(csv_error(filename)(e))?;
+                    continue;
+                }
+            };
+            let line_number = line.position().map(|p| p.line());
             let mut line = line.iter();
             let Some(key) = line.next() else {
                 continue;
@@ -84,7 +142,14 @@ impl FileLoader {
             let Some(value) = line.next() else {
                 continue;
             };
-            f(key.trim(), value.trim(), self)?;
+            let (key, value) = (key.trim(), value.trim());
+            if let Err(e) = f(key, value, self) {
+                self.report(
+                    ConfigParseError::from(e)
+                        .in_cell(key, value)
+                        .in_file(filename, line_number),
+                )?;
+            }
         }
         Ok(())
     }
@@ -97,27 +162,60 @@ impl FileLoader {
         filename: &str,
         mut f: impl FnMut(Line) -> Result<(), Box<dyn Error>>,
     ) -> Result<(), Box<dyn Error>> {
-        let data = self.load_config(filename)?;
+        // while validating, a broken file or header skips the whole table and a broken line skips that line
+        let data = match self.load_config(filename) {
+            Ok(data) => data,
+            Err(e) => {
+                self.report(e.into())?;
+                return Ok(());
+            }
+        };
         let mut reader = csv::ReaderBuilder::new()
             .delimiter(b';')
             .from_reader(data.as_bytes());
+        let header_record = match reader.headers() {
+            Ok(headers) => headers.clone(),
+            Err(e) => {
+                self.report(csv_error(filename)(e))?;
+                return Ok(());
+            }
+        };
         let mut headers: Vec<Header> = Vec::new();
-        for h in reader.headers()? {
-            let header = Header::from_conf(h, self)?.0;
+        for h in &header_record {
+            let header = match Header::from_conf(h, self) {
+                Ok((header, _)) => header,
+                Err(e) => {
+                    self.report(e.in_file(filename, Some(1)))?;
+                    return Ok(());
+                }
+            };
             if headers.contains(&header) {
-                return Err(Box::new(ConfigParseError::DuplicateHeader(h.to_string())));
+                let error = ConfigParseError::DuplicateHeader(h.to_string());
+                self.report(error.in_file(filename, Some(1)))?;
+                return Ok(());
             }
             headers.push(header);
         }
         for line in reader.records() {
             let mut map = HashMap::default();
-            let line = line?;
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => {
+                    self.report(csv_error(filename)(e))?;
+                    continue;
+                }
+            };
+            let line_number = line.position().map(|p| p.line());
             for (i, s) in line.iter().enumerate().take(headers.len()) {
                 map.insert(headers[i].clone(), s.trim());
             }
-            let line = Line::parse(&map, self)?;
-            line.simple_validation()?;
-            f(line)?;
+            let result = Line::parse(&map, self).and_then(|line| {
+                line.simple_validation()?;
+                f(line)
+            });
+            if let Err(e) = result {
+                self.report(ConfigParseError::from(e).in_file(filename, line_number))?;
+            }
         }
         Ok(())
     }
@@ -184,8 +282,11 @@ impl FileLoader {
                 (self.load_file)(&path).map_err(|_| ConfigParseError::FileMissing(path.clone()))?;
             let ast = self
                 .engine
-                .compile(script)
+                .compile(&script)
                 .map_err(|e| ConfigParseError::ScriptCompilation(path.clone(), e.to_string()))?;
+            if self.is_collecting_diagnostics() {
+                self.script_sources.insert(filename.clone(), script);
+            }
             let ast = Urc::new(ast);
             if filename != GLOBAL_SCRIPT {
                 self.unoptimized_asts.insert(filename.clone(), ast.clone());
@@ -194,20 +295,89 @@ impl FileLoader {
         }
     }
 
-    pub(super) fn finish(self) -> (Vec<AST>, Vec<(usize, String)>) {
+    /// true if any loaded script contains the given text
+    pub(super) fn mentioned_in_scripts(&self, text: &str) -> bool {
+        self.script_sources
+            .values()
+            .any(|source| source.contains(text))
+    }
+
+    /**
+     * makes sure every registered function index still points to a function
+     * with the same name and parameter count in its script
+     */
+    pub(super) fn check_rhai_functions(&mut self) -> Result<(), ConfigParseError> {
+        for (filename, name, parameters) in self.rhai_functions.clone() {
+            let ast = match self.load_rhai_module(&filename) {
+                Ok(ast) => ast,
+                Err(e) => {
+                    self.report(e)?;
+                    continue;
+                }
+            };
+            if !ast
+                .iter_functions()
+                .any(|f| f.name == name && f.params.len() == parameters.len())
+            {
+                self.report(ConfigParseError::ScriptSignatureMismatch(
+                    filename,
+                    name,
+                    parameters.len(),
+                ))?;
+            }
+        }
+        Ok(())
+    }
+
+    /**
+     * functions in loaded scripts (except the global script) that aren't referenced by the config
+     * and don't seem to be called by any script either.
+     * only works while validating, since script sources aren't kept otherwise
+     */
+    pub(super) fn unused_functions(&self) -> Vec<(String, String)> {
+        let mut result = Vec::new();
+        for (filename, ast) in &self.unoptimized_asts {
+            for function in ast.iter_functions() {
+                let name = function.name;
+                if self
+                    .rhai_functions
+                    .iter()
+                    .any(|(f, n, _)| f == filename && n == name)
+                {
+                    continue;
+                }
+                let call = format!("{name}(");
+                let definition = format!("fn {name}(");
+                let quoted = format!("\"{name}\"");
+                let is_called = self.script_sources.values().any(|source| {
+                    source.matches(&call).count() > source.matches(&definition).count()
+                        || source.contains(&quoted)
+                });
+                if !is_called {
+                    result.push((filename.clone(), name.to_string()));
+                }
+            }
+        }
+        result.sort();
+        result.dedup();
+        result
+    }
+
+    /// returns the scripts (with their filenames) and which script each registered function is in
+    pub(super) fn finish(&mut self) -> (Vec<(String, AST)>, Vec<(usize, String)>) {
         let mut indices = HashMap::default();
         let mut asts = Vec::with_capacity(self.unoptimized_asts.len());
-        for (i, (filename, ast)) in self.unoptimized_asts.into_iter().enumerate() {
+        let unoptimized_asts = std::mem::take(&mut self.unoptimized_asts);
+        for (filename, ast) in unoptimized_asts {
             match filename.as_str() {
                 GLOBAL_SCRIPT => (),
                 _ => {
-                    asts.push(Urc::into_inner(ast).unwrap());
-                    indices.insert(filename, i);
+                    indices.insert(filename.clone(), asts.len());
+                    asts.push((filename, Urc::into_inner(ast).unwrap()));
                 }
             }
         }
-        let functions: Vec<(usize, String)> = self
-            .rhai_functions
+        let functions: Vec<(usize, String)> = std::mem::take(&mut self.rhai_functions)
             .into_iter()
             .map(|(filename, name, _)| (*indices.get(&filename).unwrap(), name))
             .collect();
@@ -215,6 +385,14 @@ impl FileLoader {
     }
 }
 
+/// csv errors know their line, but not which file they came from
+pub(super) fn csv_error(filename: &str) -> impl Fn(csv::Error) -> ConfigParseError {
+    move |e| {
+        let line = e.position().map(|p| p.line());
+        ConfigParseError::Other(e.to_string()).in_file(filename, line)
+    }
+}
+
 pub(super) trait TableLine: Sized {
     type Header: FromConfig;
     fn parse(
@@ -11,6 +11,7 @@ use crate::game::game::Game;
 use crate::map::board::{Board, BoardView};
 use crate::map::direction::Direction;
 use crate::map::point::Point;
+use crate::script::profiler::ScriptHook;
 use crate::script::*;
 use crate::tags::FlagKey;
 use crate::units::UnitData;
@@ -51,14 +52,12 @@ impl TableLine for GlobalEventConfig {
         };
         Ok(Self {
             typ,
-            on_start_turn: match data.get(&H::StartTurn) {
-                Some(s) if s.len() > 0 => Some(loader.rhai_function(s, 1..=1)?.index),
-                _ => None,
-            },
-            on_end_turn: match data.get(&H::EndTurn) {
-                Some(s) if s.len() > 0 => Some(loader.rhai_function(s, 1..=1)?.index),
-                _ => None,
-            },
+            on_start_turn: map_cell(data, H::StartTurn, |s| {
+                Ok(loader.rhai_function(s, 1..=1)?.index)
+            })?,
+            on_end_turn: map_cell(data, H::EndTurn, |s| {
+                Ok(loader.rhai_function(s, 1..=1)?.index)
+            })?,
         })
     }
 
@@ -274,10 +273,15 @@ impl GlobalFilter {
     pub fn check<D: Direction>(&self, game: &Game<D>, executor: &Executor) -> bool {
         match self {
             Self::Rhai(function_index) => {
-                match executor.run::<D, bool>(*function_index, ()) {
+                match executor.run::<D, bool>(ScriptHook::GlobalFilter, *function_index, ()) {
                     Ok(result) => result,
-                    Err(_e) => {
-                        // TODO: log error
+                    Err(e) => {
+                        let environment = executor.environment();
+                        environment.log_rhai_error(
+                            "GlobalFilter::Rhai",
+                            environment.get_rhai_function_name(*function_index),
+                            &e,
+                        );
                         false
                     }
                 }
@@ -0,0 +1,103 @@
+use std::fs;
+use std::path::PathBuf;
+
+use super::config::Config;
+use super::file_loader::csv_error;
+use super::parse::MOVEMENT_CONFIG;
+use super::{ConfigDiagnostic, ConfigParseError, DiagnosticSeverity};
+
+/// matrix of which unit uses which attack type, not read by Config itself
+pub const UNIT_ATTACKS: &'static str = "unit_attacks.csv";
+
+/**
+ * Everything Config::validate_folder checks, plus checks that only make sense
+ * for a config that parsed successfully.
+ * Meant for CI pipelines of ruleset authors, see the tanktics-lint binary.
+ */
+pub fn lint_folder(folder: PathBuf) -> Vec<ConfigDiagnostic> {
+    let mut diagnostics = Config::validate_folder(folder.clone());
+    if diagnostics.iter().any(ConfigDiagnostic::is_error) {
+        return diagnostics;
+    }
+    let config = match Config::parse_folder("lint", folder.clone()) {
+        Ok(config) => config,
+        Err(e) => {
+            diagnostics.push(ConfigDiagnostic {
+                severity: DiagnosticSeverity::Error,
+                error: e.into(),
+            });
+            return diagnostics;
+        }
+    };
+    let mut errors = Vec::new();
+    check_movement_costs(&config, &mut errors);
+    if let Ok(data) = fs::read_to_string(folder.join(UNIT_ATTACKS)) {
+        check_unit_attacks(&config, &data, &mut errors);
+    }
+    diagnostics.extend(errors.into_iter().map(|error| ConfigDiagnostic {
+        severity: DiagnosticSeverity::Error,
+        error,
+    }));
+    diagnostics
+}
+
+/// every terrain needs a row in movement.csv, even if it's impassable for everyone
+pub(super) fn check_movement_costs(config: &Config, errors: &mut Vec<ConfigParseError>) {
+    for terrain in config.terrain_types() {
+        if !config.movement_cost.contains_key(&terrain) {
+            let name = config.terrain_name(terrain).to_string();
+            errors
+                .push(ConfigParseError::MissingMovementCosts(name).in_file(MOVEMENT_CONFIG, None));
+        }
+    }
+}
+
+/// header has to consist of attack types, first column of unit types
+pub(super) fn check_unit_attacks(config: &Config, data: &str, errors: &mut Vec<ConfigParseError>) {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(b';')
+        .from_reader(data.as_bytes());
+    let headers = match reader.headers() {
+        Ok(headers) => headers.clone(),
+        Err(e) => {
+            errors.push(csv_error(UNIT_ATTACKS)(e));
+            return;
+        }
+    };
+    for h in headers.iter().skip(1) {
+        if !config.attack_types.iter().any(|(name, _)| name == h) {
+            errors.push(
+                ConfigParseError::UnknownEnumMember(format!("AttackType::{h}"))
+                    .in_cell(h, h)
+                    .in_file(UNIT_ATTACKS, Some(1)),
+            );
+        }
+    }
+    for line in reader.records() {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                errors.push(csv_error(UNIT_ATTACKS)(e));
+                continue;
+            }
+        };
+        let line_number = line.position().map(|p| p.line());
+        let Some(unit) = line.get(0) else {
+            continue;
+        };
+        if config.find_unit_by_name(unit).is_none() {
+            errors.push(
+                ConfigParseError::MissingUnit(unit.to_string()).in_file(UNIT_ATTACKS, line_number),
+            );
+        }
+        for (i, val) in line.iter().enumerate().skip(1) {
+            if val.len() > 0 && val.parse::<u8>().is_err() {
+                errors.push(
+                    ConfigParseError::InvalidInteger(val.to_string())
+                        .in_cell(&headers[i], val)
+                        .in_file(UNIT_ATTACKS, line_number),
+                );
+            }
+        }
+    }
+}
@@ -12,13 +12,18 @@ pub mod file_loader;
 pub mod global_events;
 mod hero_power_config;
 mod hero_type_config;
+#[cfg(not(target_family = "wasm"))]
+pub mod lint;
 pub mod movement_type_config;
 pub mod number_modification;
 pub mod parse;
+pub mod reload;
 pub mod table_config;
 pub mod tag_config;
 mod terrain_powered;
 mod terrain_type_config;
+#[cfg(test)]
+pub(crate) mod test;
 pub mod token_filter;
 pub mod token_typ_config;
 pub(crate) mod unit_filter;
@@ -71,22 +76,201 @@ pub enum ConfigParseError {
     MissingNeutralColor,
     MissingToken(String),
     InvalidColor(String),
+    InvalidAttackRange(String),
+    InvalidNextPower(String, u8),
+    MissingPowers(String),
+    UnusedScript(String, String),
+    UnusedTable(String),
+    MissingMovementCosts(String),
+    ScriptSignatureMismatch(String, String, usize),
     Other(String),
+    Located(ConfigErrorLocation, Box<ConfigParseError>),
+}
+
+impl ConfigParseError {
+    /// Records which column and cell an error originated from.
+    /// Locations that are already known aren't overwritten, so the innermost parser wins.
+    pub(crate) fn in_cell(self, column: impl Display, cell: &str) -> Self {
+        self.with_location(|location| {
+            location.column.get_or_insert_with(|| column.to_string());
+            location.cell.get_or_insert_with(|| cell.to_string());
+        })
+    }
+
+    /// Records which file (and line within that file) an error originated from.
+    pub(crate) fn in_file(self, file: &str, line: Option<u64>) -> Self {
+        self.with_location(|location| {
+            location.file.get_or_insert_with(|| file.to_string());
+            if location.line.is_none() {
+                location.line = line;
+            }
+        })
+    }
+
+    fn with_location(self, f: impl FnOnce(&mut ConfigErrorLocation)) -> Self {
+        let (mut location, error) = match self {
+            Self::Located(location, error) => (location, error),
+            error => (ConfigErrorLocation::default(), Box::new(error)),
+        };
+        f(&mut location);
+        Self::Located(location, error)
+    }
+
+    pub fn location(&self) -> Option<&ConfigErrorLocation> {
+        match self {
+            Self::Located(location, _) => Some(location),
+            _ => None,
+        }
+    }
+
+    /// the error without location information
+    pub fn inner(&self) -> &Self {
+        match self {
+            Self::Located(_, error) => error.inner(),
+            error => error,
+        }
+    }
+}
+
+impl From<Box<dyn Error>> for ConfigParseError {
+    fn from(error: Box<dyn Error>) -> Self {
+        match error.downcast::<Self>() {
+            Ok(error) => *error,
+            Err(error) => Self::Other(error.to_string()),
+        }
+    }
 }
 
 impl Display for ConfigParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        // TODO
-        write!(f, "{self:?}")
-        /*match self {
+        match self {
+            Self::CommanderMaxChargeExceeded(max) => {
+                write!(f, "commander charge can't exceed {max}")
+            }
+            Self::CustomActionScriptMissing(name) => {
+                write!(f, "no script found for custom action '{name}'")
+            }
+            Self::DontCallGlobalScriptDirectly(name) => write!(
+                f,
+                "'{name}' is in the global script, which can't be called directly"
+            ),
+            Self::DuplicateEntry(name) => write!(f, "duplicate entry '{name}'"),
+            Self::DuplicateHeader(name) => write!(f, "duplicate header '{name}'"),
+            Self::EmptyList => write!(f, "list can't be empty"),
+            Self::FileMissing(file) => write!(f, "missing file {file}"),
+            Self::FolderMissing(path) => write!(f, "missing folder {}", path.display()),
+            Self::HeroMaxChargeExceeded(max) => write!(f, "hero charge can't exceed {max}"),
             Self::InvalidCellData(file, line, column, cell_data) => {
-                write!(f, "Invalid Cell Data in file {file} at line {line}, column {column}: '{cell_data}'")
+                write!(f, "{file}:{line}:{column}: invalid cell data '{cell_data}'")
+            }
+            Self::InvalidColumnValue(column, value) => {
+                write!(f, "invalid value for {column}: '{value}'")
+            }
+            Self::InvalidBool(s) => write!(f, "expected a boolean, found '{s}'"),
+            Self::InvalidInteger(s) => write!(f, "expected an integer, found '{s}'"),
+            Self::DivisionByZero(numerator) => write!(f, "division by zero: {numerator}/0"),
+            Self::InvalidNumber(s) => write!(f, "expected a number, found '{s}'"),
+            Self::InvalidNumberModifier(s) => write!(f, "unknown number modifier '{s}'"),
+            Self::MissingColumn(column) => write!(f, "missing column '{column}'"),
+            Self::MissingCommander(name) => write!(f, "unknown commander '{name}'"),
+            Self::MissingCommanderForPower(commander) => {
+                write!(f, "power refers to missing commander {commander:?}")
+            }
+            Self::MissingHero(name) => write!(f, "unknown hero '{name}'"),
+            Self::MissingHeroForPower(hero) => write!(f, "power refers to missing hero {hero:?}"),
+            Self::NameTooShort => write!(f, "name can't be empty"),
+            Self::NotEnoughValues(s) => write!(f, "not enough values in '{s}'"),
+            Self::NumberTooBig(s) => write!(f, "number too big: '{s}'"),
+            Self::ScriptCompilation(path, e) => write!(f, "failed to compile {path}: {e}"),
+            Self::ScriptFunctionNotFound(file, name) => {
+                write!(f, "function '{name}' not found in scripts/{file}.rhai")
+            }
+            Self::ScriptNeedsFileAndFunctionName(s) => {
+                write!(f, "expected 'file > function', found '{s}'")
+            }
+            Self::TooManyPowers(commander, count) => {
+                write!(f, "commander {commander:?} has too many powers ({count})")
+            }
+            Self::UnknownEnumMember(s) => match s.split_once("::") {
+                Some((typ, member)) => write!(f, "unknown {typ} '{member}'"),
+                None => write!(f, "unknown value '{s}'"),
+            },
+            Self::TableAxesShouldDiffer(s) => write!(f, "table axes should differ: {s}"),
+            Self::TableEmpty => write!(f, "table is empty"),
+            Self::NotEnoughPlayerColors => write!(f, "at least 2 player colors are needed"),
+            Self::MissingUnit(name) => write!(f, "unknown unit '{name}'"),
+            Self::MissingTerrain(name) => write!(f, "unknown terrain '{name}'"),
+            Self::MissingNeutralColor => write!(f, "missing neutral color"),
+            Self::MissingToken(name) => write!(f, "unknown token '{name}'"),
+            Self::InvalidColor(s) => write!(f, "invalid color '{s}'"),
+            Self::InvalidAttackRange(s) => {
+                write!(f, "minimum attack range exceeds maximum in '{s}'")
+            }
+            Self::InvalidNextPower(name, index) => {
+                write!(f, "{name} has no power with index {index}")
+            }
+            Self::MissingPowers(name) => write!(f, "{name} has no powers"),
+            Self::UnusedScript(file, name) => {
+                write!(f, "function '{name}' in scripts/{file}.rhai is never used")
+            }
+            Self::UnusedTable(id) => write!(f, "table '{id}' is never used by a script"),
+            Self::MissingMovementCosts(terrain) => {
+                write!(f, "no movement costs for terrain '{terrain}'")
             }
-        }*/
+            Self::ScriptSignatureMismatch(file, name, parameter_count) => write!(
+                f,
+                "scripts/{file}.rhai has no function '{name}' with {parameter_count} parameters"
+            ),
+            Self::Other(s) => write!(f, "{s}"),
+            Self::Located(location, error) => {
+                write!(f, "{location}: {error}")?;
+                match &location.cell {
+                    // the cell is only worth repeating if the message doesn't already contain it
+                    Some(cell)
+                        if !cell.is_empty() && !error.to_string().contains(cell.as_str()) =>
+                    {
+                        write!(f, " (in '{cell}')")
+                    }
+                    _ => Ok(()),
+                }
+            }
+        }
     }
 }
 
-impl Error for ConfigParseError {}
+impl Error for ConfigParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Located(_, error) => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// Where in a ruleset folder a ConfigParseError was found.
+/// Every field is optional since not every error can be traced back to a single cell.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigErrorLocation {
+    pub file: Option<String>,
+    /// 1-based line number within the file
+    pub line: Option<u64>,
+    pub column: Option<String>,
+    pub cell: Option<String>,
+}
+
+impl Display for ConfigErrorLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let parts: Vec<String> = [
+            self.file.clone(),
+            self.line.map(|line| line.to_string()),
+            self.column.clone(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        write!(f, "{}", parts.join(":"))
+    }
+}
 
 crate::listable_enum! {
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -103,3 +287,37 @@ pub struct Pronouns {
     pub their: String, // "{} hand was waving"
     pub them: String,  // "I gave {} a present"
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DiagnosticSeverity {
+    Warning,
+    Error,
+}
+
+impl Display for DiagnosticSeverity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Warning => write!(f, "warning"),
+            Self::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// One problem found by Config::validate
+#[derive(Debug, Clone)]
+pub struct ConfigDiagnostic {
+    pub severity: DiagnosticSeverity,
+    pub error: ConfigParseError,
+}
+
+impl ConfigDiagnostic {
+    pub fn is_error(&self) -> bool {
+        self.severity == DiagnosticSeverity::Error
+    }
+}
+
+impl Display for ConfigDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.severity, self.error)
+    }
+}
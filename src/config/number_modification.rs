@@ -6,6 +6,7 @@ use rhai::Dynamic;
 
 use crate::map::direction::Direction;
 use crate::script::executor::Executor;
+use crate::script::profiler::ScriptHook;
 
 use super::ConfigParseError;
 use super::file_loader::FileLoader;
@@ -106,7 +107,11 @@ impl<T: MulRational32 + FromConfig + Ord + Clone + Send + Sync + 'static> Number
             Self::MulAdd(a, b) => value.mul_r32(a) + b,
             Self::MulSub(a, b) => value.mul_r32(a) - b,
             Self::Rhai(function_index) => {
-                match executor.run::<D, Dynamic>(function_index, (value.clone(),)) {
+                match executor.run::<D, Dynamic>(
+                    ScriptHook::NumberModifier,
+                    function_index,
+                    (value.clone(),),
+                ) {
                     Ok(t) => {
                         let fraction = match t.type_name().split("::").last().unwrap() {
                             "i32" => Rational32::from_integer(t.cast()),
@@ -136,7 +141,7 @@ impl<T: MulRational32 + FromConfig + Ord + Clone + Send + Sync + 'static> Number
                 }
             }
             Self::RhaiReplace(function_index) => {
-                match executor.run::<D, Dynamic>(function_index, ()) {
+                match executor.run::<D, Dynamic>(ScriptHook::NumberModifier, function_index, ()) {
                     Ok(t) => {
                         let fraction = match t.type_name().split("::").last().unwrap() {
                             "i32" => Rational32::from_integer(t.cast()),
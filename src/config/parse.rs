@@ -19,6 +19,8 @@ use crate::config::Pronouns;
 use crate::game::event_fx::effect_constructor_module;
 use crate::game::fog::FogIntensity;
 use crate::map::direction::{Direction, Direction4, Direction6};
+use crate::map::point_map;
+use crate::script::limits::ScriptLimits;
 use crate::script::{MyPackage4, MyPackage6, create_base_engine};
 use crate::tags::{FlagKey, TagKey};
 use crate::terrain::TerrainType;
@@ -26,8 +28,8 @@ use crate::tokens::TokenType;
 use crate::units::hero::HeroType;
 use crate::units::movement::MovementType;
 use crate::units::unit_types::UnitType;
+use crate::units::zone_of_control::ZoneOfControl;
 
-use super::ConfigParseError;
 use super::attack_config::{AttackConfig, AttackSplashConfig};
 use super::attack_powered::AttackPoweredConfig;
 use super::commander_power_config::*;
@@ -35,7 +37,7 @@ use super::commander_type_config::*;
 use super::commander_unit_config::*;
 use super::config::Config;
 use super::effect_config::{EffectConfig, EffectVisibility};
-use super::file_loader::FileLoader;
+use super::file_loader::{FileLoader, csv_error};
 use super::global_events::GlobalEventConfig;
 use super::hero_power_config::*;
 use super::hero_type_config::*;
@@ -46,6 +48,7 @@ use super::terrain_powered::*;
 use super::terrain_type_config::*;
 use super::token_typ_config::TokenTypeConfig;
 use super::unit_type_config::*;
+use super::{ConfigDiagnostic, ConfigParseError, DiagnosticSeverity};
 use super::{custom_action_config::*, editor_tag_config};
 
 const RULESET_CONFIG: &'static str = "ruleset.csv";
@@ -67,7 +70,7 @@ const TERRAIN_TAGS: &'static str = "terrain_tags.csv";
 const TOKEN_CONFIG: &'static str = "tokens.csv";
 const EFFECT_CONFIG: &'static str = "effects.csv";
 const TOKEN_TAGS: &'static str = "token_tags.csv";
-const MOVEMENT_CONFIG: &'static str = "movement.csv";
+pub(super) const MOVEMENT_CONFIG: &'static str = "movement.csv";
 const COMMANDER_CONFIG: &'static str = "commanders.csv";
 const COMMANDER_POWERS: &'static str = "commander_powers.csv";
 const POWERED_UNITS: &'static str = "unit_powered.csv";
@@ -82,7 +85,31 @@ impl Config {
         name: String,
         load_config: Box<dyn Fn(&str) -> Result<String, Box<dyn Error>>>,
     ) -> Result<Self, Box<dyn Error>> {
+        Self::parse_with(name, &mut FileLoader::new(load_config))
+    }
+
+    /**
+     * Parses the config like Config::parse, but doesn't stop at the first error.
+     * Returns every error and warning that was found, an empty Vec means the config is fine.
+     * Errors that make further parsing impossible (e.g. a missing global script) end the validation early.
+     */
+    pub fn validate(
+        load_config: Box<dyn Fn(&str) -> Result<String, Box<dyn Error>>>,
+    ) -> Vec<ConfigDiagnostic> {
         let mut file_loader = FileLoader::new(load_config);
+        file_loader.collect_diagnostics();
+        let result = Self::parse_with(String::new(), &mut file_loader);
+        let mut diagnostics = file_loader.take_diagnostics();
+        if let Err(e) = result {
+            diagnostics.push(ConfigDiagnostic {
+                severity: DiagnosticSeverity::Error,
+                error: e.into(),
+            });
+        }
+        diagnostics
+    }
+
+    fn parse_with(name: String, file_loader: &mut FileLoader) -> Result<Self, Box<dyn Error>> {
         let mut constants = HashMap::default();
         constants.insert(
             "FOG_TrueSight".to_string(),
@@ -101,12 +128,16 @@ impl Config {
         file_loader.table_with_headers(TAG_CONFIG, |line: TagConfig| {
             if line.tag_type == TagType::Flag {
                 if flags.iter().any(|conf| conf.name == line.name) {
-                    // TODO: error
+                    return Err(
+                        ConfigParseError::DuplicateEntry(format!("Flag::{}", line.name)).into(),
+                    );
                 }
                 flags.push(line);
             } else {
                 if tags.iter().any(|conf| conf.name == line.name) {
-                    // TODO: error
+                    return Err(
+                        ConfigParseError::DuplicateEntry(format!("Tag::{}", line.name)).into(),
+                    );
                 }
                 tags.push(line);
             }
@@ -149,6 +180,7 @@ impl Config {
         let mut result = Self {
             name,
             owner_colors: Vec::new(),
+            max_map_size: point_map::DEFAULT_MAX_SIZE,
             // tags
             flags,
             tags,
@@ -160,6 +192,8 @@ impl Config {
             unit_transports: HashMap::default(),
             custom_actions: Vec::new(),
             max_transported: 0,
+            has_zone_of_control: false,
+            health_tag: None,
             unit_flags: HashMap::default(),
             unit_tags: HashMap::default(),
             attack_types: Vec::new(),
@@ -192,9 +226,11 @@ impl Config {
             // rhai
             engines: Vec::with_capacity(2),
             asts: Vec::new(),
+            script_files: Vec::new(),
             functions: Vec::new(),
             is_unit_dead_rhai: usize::MAX,
             is_unit_movable_rhai: usize::MAX,
+            script_limits: ScriptLimits::default(),
             custom_tables: HashMap::default(),
         };
 
@@ -213,28 +249,60 @@ impl Config {
                 }
                 "UnknownUnit" => unknown_unit = value.to_string(),
                 "DefaultTerrain" => default_terrain = value.to_string(),
+                "HealthTag" => {
+                    let tag = TagKey::from_conf(value, file_loader)?.0.0;
+                    if !matches!(result.tags[tag].tag_type, TagType::Int { .. }) {
+                        return Err(ConfigParseError::InvalidColumnValue(
+                            key.to_string(),
+                            value.to_string(),
+                        )
+                        .into());
+                    }
+                    result.health_tag = Some(tag);
+                }
+                "MaxMapSize" => {
+                    let size: u8 = parse_limit(value)?;
+                    if size < point_map::MIN_SIZE || size as u32 > point_map::MAX_SIZE {
+                        return Err(ConfigParseError::InvalidColumnValue(
+                            key.to_string(),
+                            value.to_string(),
+                        )
+                        .into());
+                    }
+                    result.max_map_size = size;
+                }
                 "UnitDeathTest" => {
                     result.is_unit_dead_rhai = file_loader.rhai_function(value, 1..=1)?.index
                 }
                 "UnitMovableTest" => {
                     result.is_unit_movable_rhai = file_loader.rhai_function(value, 1..=1)?.index
                 }
+                "ScriptMaxOperations" => result.script_limits.max_operations = parse_limit(value)?,
+                "ScriptMaxStringSize" => result.script_limits.max_string_size = parse_limit(value)?,
+                "ScriptMaxArraySize" => result.script_limits.max_array_size = parse_limit(value)?,
+                "ScriptMaxMapSize" => result.script_limits.max_map_size = parse_limit(value)?,
                 _ => (),
             }
             Ok(())
         })?;
         if result.owner_colors.len() < 2 {
-            return Err(Box::new(ConfigParseError::NotEnoughPlayerColors));
+            file_loader
+                .report(ConfigParseError::NotEnoughPlayerColors.in_file(RULESET_CONFIG, None))?;
         }
         match neutral_color {
             Some(color) => result.owner_colors.insert(0, color),
-            None => return Err(Box::new(ConfigParseError::MissingNeutralColor)),
-        }
-        if result.is_unit_dead_rhai == usize::MAX {
-            return Err(format!("missing entry in {RULESET_CONFIG}: 'UnitDeathTest'").into());
+            None => file_loader
+                .report(ConfigParseError::MissingNeutralColor.in_file(RULESET_CONFIG, None))?,
         }
-        if result.is_unit_movable_rhai == usize::MAX {
-            return Err(format!("missing entry in {RULESET_CONFIG}: 'UnitMovableTest'").into());
+        for (key, index) in [
+            ("UnitDeathTest", result.is_unit_dead_rhai),
+            ("UnitMovableTest", result.is_unit_movable_rhai),
+        ] {
+            if index == usize::MAX {
+                file_loader.report(ConfigParseError::Other(format!(
+                    "missing entry in {RULESET_CONFIG}: '{key}'"
+                )))?;
+            }
         }
 
         // movement types
@@ -244,7 +312,11 @@ impl Config {
                 .iter()
                 .any(|conf| conf.name == line.name)
             {
-                // TODO: error
+                return Err(ConfigParseError::DuplicateEntry(format!(
+                    "MovementType::{}",
+                    line.name
+                ))
+                .into());
             }
             constants.insert(
                 format!("MOVEMENT_{}", line.name),
@@ -292,6 +364,7 @@ impl Config {
                 );
             }
             result.max_transported = result.max_transported.max(line.transport_capacity);
+            result.has_zone_of_control |= line.zone_of_control != ZoneOfControl::None;
             result.units.push(line);
             Ok(())
         })?;
@@ -305,19 +378,21 @@ impl Config {
             .position(|conf| conf.name == unknown_unit)
         {
             Some(i) => result.unknown_unit = UnitType(i),
-            None => {
-                return Err(Box::new(ConfigParseError::MissingUnit(
-                    "Unknown".to_string(),
-                )));
-            }
+            None => file_loader.report(
+                ConfigParseError::MissingUnit(unknown_unit.clone())
+                    .in_cell("UnknownUnit", &unknown_unit)
+                    .in_file(RULESET_CONFIG, None),
+            )?,
         }
 
         // simple terrain data
         file_loader.table_with_headers(TERRAIN_CONFIG, |line: TerrainTypeConfig| {
             if result.terrains.iter().any(|conf| conf.name == line.name) {
-                return Err(
-                    ConfigParseError::DuplicateEntry(format!("TerrainType::{}", line.name)).into(),
-                );
+                return Err(ConfigParseError::DuplicateEntry(format!(
+                    "TerrainType::{}",
+                    line.name
+                ))
+                .into());
             }
             result.terrains.push(line);
             Ok(())
@@ -335,9 +410,11 @@ impl Config {
             .position(|conf| conf.name == default_terrain)
         {
             Some(i) => result.default_terrain = TerrainType(i),
-            None => {
-                return Err(format!("missing entry in {RULESET_CONFIG}: 'DefaultTerrain'").into());
-            }
+            None => file_loader.report(
+                ConfigParseError::MissingTerrain(default_terrain.clone())
+                    .in_cell("DefaultTerrain", &default_terrain)
+                    .in_file(RULESET_CONFIG, None),
+            )?,
         }
 
         // simple token data
@@ -392,7 +469,11 @@ impl Config {
         let mut bonus_transported = 0;
         file_loader.table_with_headers(COMMANDER_CONFIG, |line: CommanderTypeConfig| {
             if result.commanders.iter().any(|conf| conf.name == line.name) {
-                // TODO: error
+                return Err(ConfigParseError::DuplicateEntry(format!(
+                    "CommanderType::{}",
+                    line.name
+                ))
+                .into());
             }
             result.max_commander_charge = result.max_commander_charge.max(line.max_charge);
             bonus_transported = bonus_transported.max(line.transport_capacity as usize);
@@ -408,13 +489,15 @@ impl Config {
             );
         }
         if result.commanders.len() == 0 {
-            // TODO: error
+            file_loader.report(ConfigParseError::TableEmpty.in_file(COMMANDER_CONFIG, None))?;
         }
 
         // simple hero data
         file_loader.table_with_headers(HERO_CONFIG, |line: HeroTypeConfig| {
             if result.heroes.iter().any(|conf| conf.name == line.name) {
-                // TODO: error
+                return Err(
+                    ConfigParseError::DuplicateEntry(format!("HeroType::{}", line.name)).into(),
+                );
             }
             if line.max_charge > i32::MAX as u32 {
                 return Err(Box::new(ConfigParseError::HeroMaxChargeExceeded(
@@ -440,20 +523,41 @@ impl Config {
             .delimiter(b';')
             .from_reader(data.as_bytes());
         let mut transported: Vec<UnitType> = Vec::new();
-        for h in reader.headers()?.into_iter().skip(1) {
-            let header = UnitType::from_conf(h, &mut file_loader)?.0;
+        for h in reader
+            .headers()
+            .map_err(csv_error(UNIT_TRANSPORT))?
+            .into_iter()
+            .skip(1)
+        {
+            let header = UnitType::from_conf(h, file_loader)
+                .map_err(|e| e.in_file(UNIT_TRANSPORT, Some(1)))?
+                .0;
             if transported.contains(&header) {
-                return Err(Box::new(ConfigParseError::DuplicateHeader(h.to_string())));
+                return Err(ConfigParseError::DuplicateHeader(h.to_string())
+                    .in_file(UNIT_TRANSPORT, Some(1))
+                    .into());
             }
             transported.push(header);
         }
         for line in reader.records() {
-            let line = line?;
+            let line = line.map_err(csv_error(UNIT_TRANSPORT))?;
+            let line_number = line.position().map(|p| p.line());
             let mut line = line.iter();
-            let typ: UnitType = match line.next() {
-                Some(t) => UnitType::from_conf(t, &mut file_loader)?.0,
-                _ => continue,
+            let Some(t) = line.next() else {
+                continue;
             };
+            let typ = match UnitType::from_conf(t, file_loader) {
+                Ok((typ, _)) => typ,
+                Err(e) => {
+                    file_loader.report(e.in_file(UNIT_TRANSPORT, line_number))?;
+                    continue;
+                }
+            };
+            if result.unit_transports.contains_key(&typ) {
+                let error = ConfigParseError::DuplicateEntry(t.to_string());
+                file_loader.report(error.in_file(UNIT_TRANSPORT, line_number))?;
+                continue;
+            }
             let mut values: Vec<UnitType> = Vec::new();
             for (i, val) in line.enumerate() {
                 if val.len() > 0 && i < transported.len() {
@@ -477,9 +581,12 @@ impl Config {
             Ok(())
         })?;
         for hero in &result.heroes {
-            if hero.powers.len() == 0 {
-                // TODO: error
-            }
+            check_powers(
+                &format!("HeroType::{}", hero.name),
+                hero.powers.iter().map(|power| power.next_power),
+                HERO_POWERS,
+                file_loader,
+            )?;
         }
 
         // movement cost
@@ -487,26 +594,54 @@ impl Config {
         let mut reader = csv::ReaderBuilder::new()
             .delimiter(b';')
             .from_reader(data.as_bytes());
-        // TODO: ensure uniqueness of column and row IDs
+        let headers = reader
+            .headers()
+            .map_err(csv_error(MOVEMENT_CONFIG))?
+            .clone();
         let mut movement_types: Vec<MovementType> = Vec::new();
-        for h in reader.headers()?.into_iter().skip(1) {
-            let header = MovementType::from_conf(h, &mut file_loader)?.0;
+        for h in headers.iter().skip(1) {
+            let header = MovementType::from_conf(h, file_loader)
+                .map_err(|e| e.in_file(MOVEMENT_CONFIG, Some(1)))?
+                .0;
             if movement_types.contains(&header) {
-                return Err(Box::new(ConfigParseError::DuplicateHeader(h.to_string())));
+                return Err(ConfigParseError::DuplicateHeader(h.to_string())
+                    .in_file(MOVEMENT_CONFIG, Some(1))
+                    .into());
             }
             movement_types.push(header);
         }
         for line in reader.records() {
-            let line = line?;
+            let line = line.map_err(csv_error(MOVEMENT_CONFIG))?;
+            let line_number = line.position().map(|p| p.line());
             let mut line = line.into_iter();
-            let typ: TerrainType = match line.next() {
-                Some(t) => TerrainType::from_conf(t, &mut file_loader)?.0,
-                _ => continue,
+            let Some(t) = line.next() else {
+                continue;
+            };
+            let typ = match TerrainType::from_conf(t, file_loader) {
+                Ok((typ, _)) => typ,
+                Err(e) => {
+                    file_loader.report(e.in_file(MOVEMENT_CONFIG, line_number))?;
+                    continue;
+                }
             };
+            if result.movement_cost.contains_key(&typ) {
+                let error = ConfigParseError::DuplicateEntry(t.to_string());
+                file_loader.report(error.in_file(MOVEMENT_CONFIG, line_number))?;
+                continue;
+            }
             let mut values = HashMap::default();
             for (i, val) in line.enumerate() {
                 if val.len() > 0 && i < movement_types.len() {
-                    values.insert(movement_types[i], val.parse()?);
+                    match val.parse() {
+                        Ok(cost) => {
+                            values.insert(movement_types[i], cost);
+                        }
+                        Err(_) => file_loader.report(
+                            ConfigParseError::InvalidNumber(val.to_string())
+                                .in_cell(&headers[i + 1], val)
+                                .in_file(MOVEMENT_CONFIG, line_number),
+                        )?,
+                    }
                 }
             }
             if movement_types.len() > 0 {
@@ -518,31 +653,58 @@ impl Config {
             if conf.sub_types.len() < 2 {
                 continue;
             }
-            let data =
-                file_loader.load_config(&format!("{SUB_MOVEMENT_TYPE_CONFIG}{}.csv", conf.name))?;
+            let filename = format!("{SUB_MOVEMENT_TYPE_CONFIG}{}.csv", conf.name);
+            let data = file_loader.load_config(&filename)?;
             let mut reader = csv::ReaderBuilder::new()
                 .delimiter(b';')
                 .from_reader(data.as_bytes());
-            // TODO: ensure uniqueness of column and row IDs
+            let header_record = reader.headers().map_err(csv_error(&filename))?.clone();
             let mut headers: Vec<MovementType> = Vec::new();
-            for h in reader.headers()?.into_iter().skip(1) {
-                let header = MovementType::from_conf(h, &mut file_loader)?.0;
+            for h in header_record.iter().skip(1) {
+                let header = MovementType::from_conf(h, file_loader)
+                    .map_err(|e| e.in_file(&filename, Some(1)))?
+                    .0;
                 if headers.contains(&header) {
-                    return Err(Box::new(ConfigParseError::DuplicateHeader(h.to_string())));
+                    return Err(ConfigParseError::DuplicateHeader(h.to_string())
+                        .in_file(&filename, Some(1))
+                        .into());
                 }
                 headers.push(header);
             }
             let mut map = HashMap::default();
+            let mut terrains: Vec<TerrainType> = Vec::new();
             for line in reader.records() {
-                let line = line?;
+                let line = line.map_err(csv_error(&filename))?;
+                let line_number = line.position().map(|p| p.line());
                 let mut line = line.into_iter();
-                let terrain: TerrainType = match line.next() {
-                    Some(t) => TerrainType::from_conf(t, &mut file_loader)?.0,
-                    _ => continue,
+                let Some(t) = line.next() else {
+                    continue;
+                };
+                let terrain = match TerrainType::from_conf(t, file_loader) {
+                    Ok((terrain, _)) => terrain,
+                    Err(e) => {
+                        file_loader.report(e.in_file(&filename, line_number))?;
+                        continue;
+                    }
                 };
+                if terrains.contains(&terrain) {
+                    let error = ConfigParseError::DuplicateEntry(t.to_string());
+                    file_loader.report(error.in_file(&filename, line_number))?;
+                    continue;
+                }
+                terrains.push(terrain);
                 for (i, val) in line.enumerate() {
                     if val.len() > 0 && i < headers.len() {
-                        let movement_type = MovementType::from_conf(val, &mut file_loader)?.0;
+                        let movement_type = match MovementType::from_conf(val, file_loader) {
+                            Ok((movement_type, _)) => movement_type,
+                            Err(e) => {
+                                file_loader.report(
+                                    e.in_cell(&header_record[i + 1], val)
+                                        .in_file(&filename, line_number),
+                                )?;
+                                continue;
+                            }
+                        };
                         if movement_type != headers[i] {
                             map.insert((terrain, headers[i]), movement_type);
                         }
@@ -563,9 +725,12 @@ impl Config {
             Ok(())
         })?;
         for commander in &result.commanders {
-            if commander.powers.len() == 0 {
-                // TODO: error
-            }
+            check_powers(
+                &format!("CommanderType::{}", commander.name),
+                commander.powers.iter().map(|power| power.next_power),
+                COMMANDER_POWERS,
+                file_loader,
+            )?;
         }
 
         // attack overrides, has to be after commander and hero parsing
@@ -576,14 +741,19 @@ impl Config {
 
         // parse attack conditions now that all other attack data and simple units, ... are loaded
         for attack_type in result.attack_types.iter_mut().map(|(_, at)| at).flatten() {
-            attack_type.parse_deferred(&mut file_loader)?;
+            attack_type
+                .parse_deferred(file_loader)
+                .map_err(|e| ConfigParseError::from(e).in_file(ATTACK_TYPES, None))?;
         }
         for splash_type in result.splash_types.iter_mut().map(|(_, at)| at).flatten() {
-            splash_type.parse_deferred(&mut file_loader)?;
+            splash_type
+                .parse_deferred(file_loader)
+                .map_err(|e| ConfigParseError::from(e).in_file(ATTACK_SPLASH, None))?;
         }
 
         // unit overrides, has to be after commander and hero parsing
         file_loader.table_with_headers(POWERED_UNITS, |line: CommanderPowerUnitConfig| {
+            result.has_zone_of_control |= line.zone_of_control.is_some();
             result.unit_overrides.push(line);
             Ok(())
         })?;
@@ -607,17 +777,16 @@ impl Config {
             Ok(())
         })?;
         for conf in table_configs {
-            let table = conf.build_table(&mut file_loader)?;
+            let table = conf.build_table(file_loader)?;
             result.custom_tables.insert(conf.id, table);
         }
 
         // editor tags
         [result.terrain_flags, result.terrain_tags] =
-            editor_tag_config::parse(TERRAIN_TAGS, &mut file_loader)?;
+            editor_tag_config::parse(TERRAIN_TAGS, file_loader)?;
         [result.token_flags, result.token_tags] =
-            editor_tag_config::parse(TOKEN_TAGS, &mut file_loader)?;
-        [result.unit_flags, result.unit_tags] =
-            editor_tag_config::parse(UNIT_TAGS, &mut file_loader)?;
+            editor_tag_config::parse(TOKEN_TAGS, file_loader)?;
+        [result.unit_flags, result.unit_tags] = editor_tag_config::parse(UNIT_TAGS, file_loader)?;
 
         let constants = Urc::new(constants);
         result.engines.push(construct_engine::<Direction4>(
@@ -626,6 +795,7 @@ impl Config {
             &result.effect_types,
             global_module.clone(),
             constants.clone(),
+            &result.script_limits,
         ));
         result.engines.push(construct_engine::<Direction6>(
             &my_package_4,
@@ -633,41 +803,106 @@ impl Config {
             &result.effect_types,
             global_module,
             constants,
+            &result.script_limits,
         ));
+        if file_loader.is_collecting_diagnostics() {
+            file_loader.check_rhai_functions()?;
+            for (filename, function) in file_loader.unused_functions() {
+                file_loader.warn(ConfigParseError::UnusedScript(filename, function));
+            }
+            let mut table_ids: Vec<&String> = result.custom_tables.keys().collect();
+            table_ids.sort();
+            for id in table_ids {
+                if !file_loader.mentioned_in_scripts(&format!("\"{id}\"")) {
+                    file_loader
+                        .warn(ConfigParseError::UnusedTable(id.clone()).in_file(TABLES, None));
+                }
+            }
+        }
+
         let (asts, functions) = file_loader.finish();
         result.functions = functions;
-        result.asts = asts
+        (result.script_files, result.asts) = asts
             .into_iter()
-            .map(|ast| {
-                Rc::new(engine.optimize_ast(&global_constants, ast, OptimizationLevel::Simple))
+            .map(|(filename, ast)| {
+                let ast = engine.optimize_ast(&global_constants, ast, OptimizationLevel::Simple);
+                (filename, Rc::new(ast))
             })
-            .collect();
+            .unzip();
 
         Ok(result)
     }
 
     #[cfg(not(target_family = "wasm"))]
     pub fn parse_folder(name: impl ToString, folder: PathBuf) -> Result<Self, Box<dyn Error>> {
-        if !folder.exists() || !folder.is_dir() {
-            return Err(Box::new(ConfigParseError::FolderMissing(
-                folder.to_path_buf(),
-            )));
+        Self::parse(name.to_string(), folder_loader(folder)?)
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    pub fn validate_folder(folder: PathBuf) -> Vec<ConfigDiagnostic> {
+        match folder_loader(folder) {
+            Ok(load_config) => Self::validate(load_config),
+            Err(error) => vec![ConfigDiagnostic {
+                severity: DiagnosticSeverity::Error,
+                error,
+            }],
+        }
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn folder_loader(
+    folder: PathBuf,
+) -> Result<Box<dyn Fn(&str) -> Result<String, Box<dyn Error>>>, ConfigParseError> {
+    if !folder.exists() || !folder.is_dir() {
+        return Err(ConfigParseError::FolderMissing(folder.to_path_buf()));
+    }
+    let load_config: Box<dyn Fn(&str) -> Result<String, Box<dyn Error>>> =
+        Box::new(move |filename: &str| {
+            // canonicalize and then check if still in same folder
+            // to prevent path traversal attacks
+            let file = folder.join(filename);
+            let file = file.canonicalize()?;
+            if !file.starts_with(&folder) || !file.exists() || !file.is_file() {
+                return Err(Box::new(ConfigParseError::FileMissing(
+                    filename.to_string(),
+                )));
+            }
+            Ok(fs::read_to_string(file)?)
+        });
+    Ok(load_config)
+}
+
+fn parse_limit<T: std::str::FromStr>(value: &str) -> Result<T, ConfigParseError> {
+    value
+        .parse()
+        .map_err(|_| ConfigParseError::InvalidInteger(value.to_string()))
+}
+
+/**
+ * every hero and commander needs at least one power,
+ * and NextPower has to point to one of them
+ */
+fn check_powers(
+    owner: &str,
+    next_powers: impl ExactSizeIterator<Item = u8>,
+    filename: &str,
+    file_loader: &mut FileLoader,
+) -> Result<(), ConfigParseError> {
+    let power_count = next_powers.len();
+    if power_count == 0 {
+        file_loader
+            .report(ConfigParseError::MissingPowers(owner.to_string()).in_file(filename, None))?;
+    }
+    for next_power in next_powers {
+        if next_power as usize >= power_count {
+            file_loader.report(
+                ConfigParseError::InvalidNextPower(owner.to_string(), next_power)
+                    .in_file(filename, None),
+            )?;
         }
-        let load_config: Box<dyn Fn(&str) -> Result<String, Box<dyn Error>>> =
-            Box::new(move |filename: &str| {
-                // canonicalize and then check if still in same folder
-                // to prevent path traversal attacks
-                let file = folder.join(filename);
-                let file = file.canonicalize()?;
-                if !file.starts_with(&folder) || !file.exists() || !file.is_file() {
-                    return Err(Box::new(ConfigParseError::FileMissing(
-                        filename.to_string(),
-                    )));
-                }
-                Ok(fs::read_to_string(file)?)
-            });
-        Self::parse(name.to_string(), load_config)
     }
+    Ok(())
 }
 
 fn construct_engine<D: Direction>(
@@ -676,8 +911,10 @@ fn construct_engine<D: Direction>(
     effects: &[EffectConfig],
     global_module: Shared<Module>,
     constants: Urc<HashMap<String, Dynamic>>,
+    script_limits: &ScriptLimits,
 ) -> Engine {
     let mut engine = create_base_engine();
+    script_limits.apply(&mut engine);
     if D::is_hex() {
         my_package_6.register_into_engine(&mut engine);
     } else {
@@ -839,7 +1076,34 @@ pub fn parse_dyn<H: Hash + Eq + Debug, T>(
     if value.len() == 0 && def.is_some() {
         return Ok(def.unwrap());
     }
-    from_conf(value).map(|r| r.0)
+    from_conf(value)
+        .map(|r| r.0)
+        .map_err(|e| e.in_cell(format_args!("{key:?}"), value))
+}
+
+/**
+ * returns None if the column is missing or the cell is empty.
+ * errors returned by f remember the column and cell they came from
+ */
+pub fn map_cell<H: Hash + Eq + Debug, T>(
+    data: &HashMap<H, &str>,
+    key: H,
+    f: impl FnOnce(&str) -> Result<T, ConfigParseError>,
+) -> Result<Option<T>, ConfigParseError> {
+    match data.get(&key) {
+        Some(s) if s.len() > 0 => f(s)
+            .map(Some)
+            .map_err(|e| e.in_cell(format_args!("{key:?}"), s)),
+        _ => Ok(None),
+    }
+}
+
+pub fn parse_opt<H: Hash + Eq + Debug, T: FromConfig>(
+    data: &HashMap<H, &str>,
+    key: H,
+    loader: &mut FileLoader,
+) -> Result<Option<T>, ConfigParseError> {
+    map_cell(data, key, |s| T::from_conf(s, loader).map(|r| r.0))
 }
 
 fn _parse<H: Hash + Eq + Debug, T: FromConfig>(
@@ -995,7 +1259,9 @@ fn _parse_vec_dyn<H: Hash + Eq + Debug, T>(
         Some(s) => s,
         None => return def.ok_or(ConfigParseError::MissingColumn(format!("{key:?}"))),
     };
-    parse_inner_vec_dyn(value, false, from_conf).map(|(r, _)| r)
+    parse_inner_vec_dyn(value, false, from_conf)
+        .map(|(r, _)| r)
+        .map_err(|e| e.in_cell(format_args!("{key:?}"), value))
 }
 
 pub fn parse_vec<H: Hash + Eq + Debug, T: FromConfig>(
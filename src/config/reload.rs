@@ -0,0 +1,337 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::mem::discriminant;
+
+use rustc_hash::FxHashMap as HashMap;
+
+use crate::terrain::TerrainType;
+use crate::tokens::token_types::TokenType;
+use crate::units::hero::HeroType;
+use crate::units::movement::MovementType;
+use crate::units::unit_types::UnitType;
+
+use super::config::Config;
+
+impl Config {
+    /**
+     * Parses the ruleset again, e.g. after a map designer changed units.csv.
+     * The diff tells which types changed and whether maps of this config can be
+     * moved to the new one with Map::migrate_config.
+     * Renames aren't guessed, the diff only lists candidates that the caller
+     * can confirm with ConfigRenames
+     */
+    pub fn reload_from(
+        &self,
+        load_config: Box<dyn Fn(&str) -> Result<String, Box<dyn Error>>>,
+    ) -> Result<(Config, ConfigDiff), Box<dyn Error>> {
+        let config = Self::parse(self.name.clone(), load_config)?;
+        let diff = ConfigDiff::new(self, &config);
+        Ok((config, diff))
+    }
+}
+
+/**
+ * Types that were renamed between two versions of a ruleset, (old name, new name) for each kind.
+ * A type that disappeared is only matched to a new one if it's listed here
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigRenames {
+    pub units: HashMap<String, String>,
+    pub terrains: HashMap<String, String>,
+    pub tokens: HashMap<String, String>,
+    pub flags: HashMap<String, String>,
+    pub tags: HashMap<String, String>,
+    pub heroes: HashMap<String, String>,
+    pub movement_types: HashMap<String, String>,
+}
+
+/**
+ * What changed about one kind of type (e.g. unit types) between two versions of a ruleset.
+ * Types are matched by name, or by the renames given by the caller.
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TypeChanges {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    /// (old name, new name), only contains renames given by the caller
+    pub renamed: Vec<(String, String)>,
+    /**
+     * (old name, new name) for removed types whose row now contains an added type.
+     * they might have been renamed, but are treated as removed and added
+     * unless the caller confirms them in ConfigRenames
+     */
+    pub rename_candidates: Vec<(String, String)>,
+    old_names: Vec<String>,
+    // index in the new config for each index of the old config, None if removed
+    new_indices: Vec<Option<usize>>,
+}
+
+impl TypeChanges {
+    /// renames whose old name still exists or whose new name existed before are ignored
+    pub(super) fn new<'a>(
+        old: impl IntoIterator<Item = &'a str>,
+        new: impl IntoIterator<Item = &'a str>,
+        renames: &HashMap<String, String>,
+    ) -> Self {
+        let old: Vec<&str> = old.into_iter().collect();
+        let new: Vec<&str> = new.into_iter().collect();
+        let mut result = Self::default();
+        for name in &old {
+            let new_index = match new.iter().position(|n| n == name) {
+                Some(index) => Some(index),
+                None => renames
+                    .get(*name)
+                    .filter(|renamed| !old.contains(&renamed.as_str()))
+                    .and_then(|renamed| new.iter().position(|n| *n == renamed.as_str()))
+                    .filter(|index| !result.new_indices.contains(&Some(*index))),
+            };
+            match new_index {
+                Some(index) if new[index] != *name => result
+                    .renamed
+                    .push((name.to_string(), new[index].to_string())),
+                Some(_) => (),
+                None => result.removed.push(name.to_string()),
+            }
+            result.new_indices.push(new_index);
+        }
+        for (i, name) in new.iter().enumerate() {
+            if !result.new_indices.contains(&Some(i)) {
+                result.added.push(name.to_string());
+            }
+        }
+        for (i, name) in old.iter().enumerate() {
+            if result.new_indices[i].is_some() {
+                continue;
+            }
+            if let Some(added) = new
+                .get(i)
+                .filter(|n| result.added.iter().any(|a| a.as_str() == **n))
+            {
+                result
+                    .rename_candidates
+                    .push((name.to_string(), added.to_string()));
+            }
+        }
+        result.old_names = old.into_iter().map(str::to_string).collect();
+        result
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.renamed.is_empty()
+    }
+
+    /// index in the new config, None if the type was removed
+    pub fn new_index(&self, old_index: usize) -> Option<usize> {
+        self.new_indices.get(old_index).cloned().flatten()
+    }
+
+    fn migrate(&self, kind: &'static str, old_index: usize) -> Result<usize, ConfigMigrationError> {
+        self.new_index(old_index).ok_or_else(|| {
+            let name = self
+                .old_names
+                .get(old_index)
+                .cloned()
+                .unwrap_or_else(|| old_index.to_string());
+            ConfigMigrationError::TypeRemoved(kind, name)
+        })
+    }
+
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>, kind: &str) -> std::fmt::Result {
+        for name in &self.added {
+            writeln!(f, "added {kind} '{name}'")?;
+        }
+        for name in &self.removed {
+            writeln!(f, "removed {kind} '{name}'")?;
+        }
+        for (old, new) in &self.renamed {
+            writeln!(f, "renamed {kind} '{old}' to '{new}'")?;
+        }
+        for (old, new) in &self.rename_candidates {
+            writeln!(f, "{kind} '{old}' might have been renamed to '{new}'")?;
+        }
+        Ok(())
+    }
+}
+
+/**
+ * Differences between two versions of a ruleset that matter for existing maps.
+ * Created by Config::reload_from and Map::migrate_config.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigDiff {
+    pub units: TypeChanges,
+    pub terrains: TypeChanges,
+    pub tokens: TypeChanges,
+    pub flags: TypeChanges,
+    pub tags: TypeChanges,
+    pub heroes: TypeChanges,
+    pub movement_types: TypeChanges,
+    /// tags whose TagType changed, e.g. from Int to Point. their values can't be migrated
+    pub retyped_tags: Vec<String>,
+}
+
+impl ConfigDiff {
+    /// types that aren't found by name count as removed
+    pub fn new(old: &Config, new: &Config) -> Self {
+        Self::with_renames(old, new, &ConfigRenames::default())
+    }
+
+    pub fn with_renames(old: &Config, new: &Config, renames: &ConfigRenames) -> Self {
+        let tags = TypeChanges::new(
+            old.tags.iter().map(|t| t.name.as_str()),
+            new.tags.iter().map(|t| t.name.as_str()),
+            &renames.tags,
+        );
+        let retyped_tags = old
+            .tags
+            .iter()
+            .enumerate()
+            .filter(|(i, old_tag)| {
+                tags.new_index(*i).is_some_and(|new_index| {
+                    discriminant(&old_tag.tag_type) != discriminant(&new.tags[new_index].tag_type)
+                })
+            })
+            .map(|(_, tag)| tag.name.clone())
+            .collect();
+        Self {
+            units: TypeChanges::new(
+                old.units.iter().map(|u| u.name.as_str()),
+                new.units.iter().map(|u| u.name.as_str()),
+                &renames.units,
+            ),
+            terrains: TypeChanges::new(
+                old.terrains.iter().map(|t| t.name.as_str()),
+                new.terrains.iter().map(|t| t.name.as_str()),
+                &renames.terrains,
+            ),
+            tokens: TypeChanges::new(
+                old.tokens.iter().map(|t| t.name.as_str()),
+                new.tokens.iter().map(|t| t.name.as_str()),
+                &renames.tokens,
+            ),
+            flags: TypeChanges::new(
+                old.flags.iter().map(|f| f.name.as_str()),
+                new.flags.iter().map(|f| f.name.as_str()),
+                &renames.flags,
+            ),
+            tags,
+            heroes: TypeChanges::new(
+                old.heroes.iter().map(|h| h.name.as_str()),
+                new.heroes.iter().map(|h| h.name.as_str()),
+                &renames.heroes,
+            ),
+            movement_types: TypeChanges::new(
+                old.movement_types.iter().map(|m| m.name.as_str()),
+                new.movement_types.iter().map(|m| m.name.as_str()),
+                &renames.movement_types,
+            ),
+            retyped_tags,
+        }
+    }
+
+    fn all_changes(&self) -> [(&'static str, &TypeChanges); 7] {
+        [
+            ("unit", &self.units),
+            ("terrain", &self.terrains),
+            ("token", &self.tokens),
+            ("flag", &self.flags),
+            ("tag", &self.tags),
+            ("hero", &self.heroes),
+            ("movement type", &self.movement_types),
+        ]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.retyped_tags.is_empty() && self.all_changes().iter().all(|(_, c)| c.is_empty())
+    }
+
+    /**
+     * true if every map of the old config can be migrated.
+     * Otherwise Map::migrate_config only fails for maps that use a removed type or retyped tag.
+     * Rename candidates count as removed
+     */
+    pub fn can_migrate_maps(&self) -> bool {
+        self.retyped_tags.is_empty() && self.all_changes().iter().all(|(_, c)| c.removed.is_empty())
+    }
+
+    pub(crate) fn unit_type(&self, typ: UnitType) -> Result<UnitType, ConfigMigrationError> {
+        self.units.migrate("unit", typ.0).map(UnitType)
+    }
+
+    pub(crate) fn terrain_type(
+        &self,
+        typ: TerrainType,
+    ) -> Result<TerrainType, ConfigMigrationError> {
+        self.terrains.migrate("terrain", typ.0).map(TerrainType)
+    }
+
+    pub(crate) fn token_type(&self, typ: TokenType) -> Result<TokenType, ConfigMigrationError> {
+        self.tokens.migrate("token", typ.0).map(TokenType)
+    }
+
+    pub(crate) fn hero_type(&self, typ: HeroType) -> Result<HeroType, ConfigMigrationError> {
+        self.heroes.migrate("hero", typ.0).map(HeroType)
+    }
+
+    pub(crate) fn movement_type(
+        &self,
+        typ: MovementType,
+    ) -> Result<MovementType, ConfigMigrationError> {
+        self.movement_types
+            .migrate("movement type", typ.0)
+            .map(MovementType)
+    }
+
+    pub(crate) fn flag(&self, flag: usize) -> Result<usize, ConfigMigrationError> {
+        self.flags.migrate("flag", flag)
+    }
+
+    pub(crate) fn tag(&self, tag: usize) -> Result<usize, ConfigMigrationError> {
+        let new_tag = self.tags.migrate("tag", tag)?;
+        let name = &self.tags.old_names[tag];
+        if self.retyped_tags.contains(name) {
+            return Err(ConfigMigrationError::TagRetyped(name.clone()));
+        }
+        Ok(new_tag)
+    }
+}
+
+impl Display for ConfigDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "no changes");
+        }
+        for (kind, changes) in self.all_changes() {
+            changes.fmt(f, kind)?;
+        }
+        for name in &self.retyped_tags {
+            writeln!(f, "changed type of tag '{name}'")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigMigrationError {
+    /// kind of type (e.g. "unit") and its name in the old config
+    TypeRemoved(&'static str, String),
+    TagRetyped(String),
+}
+
+impl Display for ConfigMigrationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TypeRemoved(kind, name) => {
+                write!(
+                    f,
+                    "the map uses {kind} '{name}', which doesn't exist anymore"
+                )
+            }
+            Self::TagRetyped(name) => {
+                write!(f, "the map uses tag '{name}', whose type changed")
+            }
+        }
+    }
+}
+
+impl Error for ConfigMigrationError {}
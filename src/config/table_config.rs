@@ -11,7 +11,7 @@ use crate::units::movement::MovementType;
 use crate::units::unit_types::UnitType;
 
 use super::ConfigParseError;
-use super::file_loader::{FileLoader, TableLine};
+use super::file_loader::{FileLoader, TableLine, csv_error};
 
 pub struct CustomTable {
     pub default_value: TableValue,
@@ -40,7 +40,9 @@ impl TableLine for TableConfig {
         use TableConfigHeader as H;
         let get = |key| data.get(&key).ok_or(E::MissingColumn(format!("{key:?}")));
         let typ = parse(data, H::Type, loader)?;
-        let default_value = TableValue::from_conf(typ, get(H::DefaultValue)?, loader)?;
+        let default_value = get(H::DefaultValue)?;
+        let default_value = TableValue::from_conf(typ, default_value, loader)
+            .map_err(|e| e.in_cell("DefaultValue", default_value))?;
         let result = Self {
             id: get(H::Id)?.trim().to_string(),
             path: get(H::Path)?.trim().to_string(),
@@ -65,40 +67,60 @@ impl TableConfig {
         &self,
         loader: &mut FileLoader,
     ) -> Result<CustomTable, Box<dyn Error>> {
-        let data = loader.load_config(&format!("tables/{}", self.path))?;
+        let filename = format!("tables/{}", self.path);
+        let data = loader.load_config(&filename)?;
         let mut reader = csv::ReaderBuilder::new()
             .delimiter(b';')
             .from_reader(data.as_bytes());
-        // TODO: ensure uniqueness of column and row IDs
+        let header_record = reader.headers().map_err(csv_error(&filename))?.clone();
         let mut headers: Vec<TableAxisKey> = Vec::new();
-        for h in reader.headers()?.into_iter().skip(1) {
-            let header = TableAxisKey::from_conf(self.top, h, loader)?;
+        for h in header_record.iter().skip(1) {
+            let header = TableAxisKey::from_conf(self.top, h, loader)
+                .map_err(|e| e.in_file(&filename, Some(1)))?;
             if headers.contains(&header) {
-                return Err(Box::new(ConfigParseError::DuplicateHeader(h.to_string())));
+                return Err(ConfigParseError::DuplicateHeader(h.to_string())
+                    .in_file(&filename, Some(1))
+                    .into());
             }
             headers.push(header);
         }
         let mut row_keys: Vec<TableAxisKey> = Vec::new();
         let mut values = HashMap::default();
         for line in reader.records() {
-            let line = line?;
+            let line = line.map_err(csv_error(&filename))?;
+            let line_number = line.position().map(|p| p.line());
             let mut line = line.into_iter();
             let left: TableAxisKey = match line.next() {
-                Some(t) => TableAxisKey::from_conf(self.left, t, loader)?,
+                Some(t) => match TableAxisKey::from_conf(self.left, t, loader) {
+                    Ok(left) => left,
+                    Err(e) => {
+                        loader.report(e.in_file(&filename, line_number))?;
+                        continue;
+                    }
+                },
                 _ => continue,
             };
             if row_keys.contains(&left) {
-                return Err(Box::new(ConfigParseError::DuplicateHeader(format!(
-                    "{left:?}"
-                ))));
+                let error = ConfigParseError::DuplicateHeader(format!("{left:?}"));
+                loader.report(error.in_file(&filename, line_number))?;
+                continue;
             }
             row_keys.push(left.clone());
-            for (i, value) in line.enumerate() {
+            for (i, value) in line.enumerate().take(headers.len()) {
                 let value = value.trim();
                 if value.len() == 0 {
                     continue;
                 }
-                let value = TableValue::from_conf(self.typ, value, loader)?;
+                let value = match TableValue::from_conf(self.typ, value, loader) {
+                    Ok(value) => value,
+                    Err(e) => {
+                        loader.report(
+                            e.in_cell(&header_record[i + 1], value)
+                                .in_file(&filename, line_number),
+                        )?;
+                        continue;
+                    }
+                };
                 if value != self.default_value {
                     values.insert((headers[i].clone(), left.clone()), value);
                 }
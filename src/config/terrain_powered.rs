@@ -9,6 +9,7 @@ use crate::map::board::{Board, BoardView};
 use crate::map::direction::Direction;
 use crate::map::point::Point;
 use crate::script::executor::Executor;
+use crate::script::profiler::ScriptHook;
 use crate::tags::*;
 use crate::terrain::TerrainType;
 use crate::terrain::terrain::Terrain;
@@ -94,10 +95,15 @@ impl TerrainFilter {
     ) -> bool {
         match self {
             Self::Rhai(function_index) => {
-                match executor.run::<D, bool>(*function_index, ()) {
+                match executor.run::<D, bool>(ScriptHook::TerrainFilter, *function_index, ()) {
                     Ok(result) => result,
-                    Err(_e) => {
-                        // TODO: log error
+                    Err(e) => {
+                        let environment = executor.environment();
+                        environment.log_rhai_error(
+                            "TerrainFilter::Rhai",
+                            environment.get_rhai_function_name(*function_index),
+                            &e,
+                        );
                         false
                     }
                 }
@@ -151,14 +157,11 @@ impl TableLine for TerrainPoweredConfig {
             affects: power.into_iter().chain(affects.into_iter()).collect(),
             vision: parse_def(data, H::Vision, NumberMod::Keep, loader)?,
             income_factor: parse_def(data, H::IncomeFactor, NumberMod::Keep, loader)?,
-            action_script: match data.get(&H::ActionScript) {
-                Some(s) if s.len() > 0 => {
-                    let exe = loader.rhai_function(s, 2..=2)?;
-                    let input = loader.rhai_function(&format!("{s}_input"), 1..=1)?.index;
-                    Some((input, exe.index))
-                }
-                _ => None,
-            },
+            action_script: map_cell(data, H::ActionScript, |s| {
+                let exe = loader.rhai_function(s, 2..=2)?;
+                let input = loader.rhai_function(&format!("{s}_input"), 1..=1)?.index;
+                Ok((input, exe.index))
+            })?,
         };
         Ok(result)
     }
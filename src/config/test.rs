@@ -0,0 +1,565 @@
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use interfaces::MapInterface;
+use rhai::FuncArgs;
+use rustc_hash::FxHashMap as HashMap;
+use semver::Version;
+use uniform_smart_pointer::Urc;
+
+use crate::VERSION;
+use crate::map::board::BoardView;
+use crate::map::direction::Direction4;
+use crate::map::map::{Map, MapType, import_map};
+use crate::map::point::Point;
+use crate::map::point_map::{DEFAULT_MAX_SIZE, MapSize, PointMap};
+use crate::map::wrapping_map::WMBuilder;
+use crate::script::diagnostics::MAX_SCRIPT_FAILURES;
+use crate::script::executor::{Executor, ScriptError, ScriptErrorCause};
+use crate::script::limits::ScriptLimit;
+use crate::script::profiler::ScriptHook;
+
+use super::config::Config;
+use super::environment::Environment;
+use super::lint;
+use super::reload::{ConfigMigrationError, ConfigRenames, TypeChanges};
+use super::{ConfigDiagnostic, ConfigParseError};
+
+// helper functions
+
+fn test_folder() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("configs/default_test")
+}
+
+/// loads files from configs/default_test, but replaces the content of one file
+fn patched_loader(
+    filename: &'static str,
+    patch: impl Fn(String) -> String + 'static,
+) -> Box<dyn Fn(&str) -> Result<String, Box<dyn Error>>> {
+    let folder = test_folder();
+    Box::new(move |name: &str| {
+        let content = std::fs::read_to_string(folder.join(name))?;
+        if name == filename {
+            Ok(patch(content))
+        } else {
+            Ok(content)
+        }
+    })
+}
+
+pub(crate) fn parse_patched(
+    filename: &'static str,
+    patch: impl Fn(String) -> String + 'static,
+) -> Result<Config, Box<dyn Error>> {
+    Config::parse("Test".to_string(), patched_loader(filename, patch))
+}
+
+fn validate_patched(
+    filename: &'static str,
+    patch: impl Fn(String) -> String + 'static,
+) -> Vec<ConfigDiagnostic> {
+    Config::validate(patched_loader(filename, patch))
+}
+
+fn parse_error(
+    filename: &'static str,
+    patch: impl Fn(String) -> String + 'static,
+) -> ConfigParseError {
+    let error = parse_patched(filename, patch)
+        .err()
+        .expect("config should be invalid");
+    ConfigParseError::from(error)
+}
+
+// tests
+
+#[test]
+fn error_in_table_cell() {
+    let error = parse_error("units.csv", |s| {
+        s.replacen("Straight(2,2)", "Stright(2,2)", 1)
+    });
+    let location = error.location().expect("error should have a location");
+    assert_eq!(location.file.as_deref(), Some("units.csv"));
+    assert_eq!(location.line, Some(5));
+    assert_eq!(location.column.as_deref(), Some("AttackPattern"));
+    assert_eq!(location.cell.as_deref(), Some("Stright(2,2)"));
+    assert!(
+        error.to_string().starts_with("units.csv:5:AttackPattern: "),
+        "{error}"
+    );
+}
+
+#[test]
+fn error_in_key_value_table() {
+    let error = parse_error("ruleset.csv", |s| {
+        s.replace("ruleset > unit_death_test", "unit_death_test")
+    });
+    assert!(matches!(
+        error.inner(),
+        ConfigParseError::ScriptNeedsFileAndFunctionName(_)
+    ));
+    assert_eq!(
+        error.to_string(),
+        "ruleset.csv:5:UnitDeathTest: expected 'file > function', found 'unit_death_test'"
+    );
+}
+
+#[test]
+fn error_in_grid_table() {
+    let error = parse_error("movement.csv", |s| {
+        s.replacen("Airport;1;", "Airport;x;", 1)
+    });
+    assert_eq!(
+        error.to_string(),
+        "movement.csv:2:Foot: expected a number, found 'x'"
+    );
+}
+
+#[test]
+fn error_in_header() {
+    let error = parse_error("units.csv", |s| s.replacen("Visibility", "Id", 1));
+    let location = error.location().unwrap();
+    assert_eq!(location.file.as_deref(), Some("units.csv"));
+    assert_eq!(location.line, Some(1));
+    assert!(matches!(
+        error.inner(),
+        ConfigParseError::DuplicateHeader(_)
+    ));
+}
+
+#[test]
+fn default_config_is_valid() {
+    let errors: Vec<ConfigDiagnostic> = Config::validate_folder(test_folder())
+        .into_iter()
+        .filter(|diagnostic| diagnostic.is_error())
+        .collect();
+    assert!(errors.is_empty(), "{errors:?}");
+}
+
+#[test]
+fn validation_continues_after_errors() {
+    let diagnostics = validate_patched("units.csv", |s| {
+        s.replacen("Straight(2,2)", "Stright(2,2)", 1).replacen(
+            "TriangleDiagonal(3,5)",
+            "TriangleDiagonal(3,x)",
+            1,
+        )
+    });
+    let lines: Vec<(Option<&str>, Option<u64>)> = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.is_error())
+        .filter_map(|diagnostic| diagnostic.error.location())
+        .map(|location| (location.file.as_deref(), location.line))
+        .collect();
+    assert!(
+        lines.contains(&(Some("units.csv"), Some(5))),
+        "{diagnostics:?}"
+    );
+    assert!(
+        lines.contains(&(Some("units.csv"), Some(12))),
+        "{diagnostics:?}"
+    );
+}
+
+#[test]
+fn duplicate_flag() {
+    let error = ConfigParseError::from(
+        parse_patched("tags.csv", |s| format!("{s}Zombified;;Flag;;;;;;\n"))
+            .err()
+            .unwrap(),
+    );
+    assert!(matches!(
+        error.inner(),
+        ConfigParseError::DuplicateEntry(name) if name == "Flag::Zombified"
+    ));
+}
+
+#[test]
+fn attack_range_min_exceeds_max() {
+    let diagnostics = validate_patched("units.csv", |s| {
+        s.replacen("Straight(1,2)", "Straight(2,1)", 1)
+    });
+    assert!(diagnostics.iter().any(|diagnostic| matches!(
+        diagnostic.error.inner(),
+        ConfigParseError::InvalidAttackRange(_)
+    )));
+}
+
+#[test]
+fn next_power_out_of_range() {
+    let diagnostics = validate_patched("commander_powers.csv", |s| {
+        s.replacen("Simo;Order;0;2;0;", "Simo;Order;0;7;0;", 1)
+    });
+    assert!(diagnostics.iter().any(|diagnostic| matches!(
+        diagnostic.error.inner(),
+        ConfigParseError::InvalidNextPower(_, 7)
+    )));
+}
+
+#[test]
+fn hero_without_powers() {
+    let diagnostics = validate_patched("hero_powers.csv", |s| {
+        s.lines()
+            .filter(|line| !line.starts_with("Reed;"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    });
+    assert!(diagnostics.iter().any(|diagnostic| matches!(
+        diagnostic.error.inner(),
+        ConfigParseError::MissingPowers(name) if name == "HeroType::Reed"
+    )));
+}
+
+#[test]
+fn unused_table_warning() {
+    let diagnostics = validate_patched("tables.csv", |s| {
+        format!("{s}UNUSED_TABLE;unit_build.csv;Boolean;false;Unit;Unit\n")
+    });
+    let warning = diagnostics
+        .iter()
+        .find(|diagnostic| {
+            matches!(
+                diagnostic.error.inner(),
+                ConfigParseError::UnusedTable(id) if id == "UNUSED_TABLE"
+            )
+        })
+        .expect("unused table should be reported");
+    assert!(!warning.is_error());
+}
+
+#[test]
+fn lint_default_config() {
+    let diagnostics = lint::lint_folder(test_folder());
+    assert!(
+        !diagnostics.iter().any(ConfigDiagnostic::is_error),
+        "{diagnostics:#?}"
+    );
+}
+
+#[test]
+fn lint_terrain_without_movement_costs() {
+    let config = parse_patched("movement.csv", |s| {
+        s.lines()
+            .filter(|line| !line.starts_with("Forest;"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    })
+    .unwrap();
+    let mut errors = Vec::new();
+    lint::check_movement_costs(&config, &mut errors);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        errors[0].to_string(),
+        "movement.csv: no movement costs for terrain 'Forest'"
+    );
+}
+
+#[test]
+fn lint_unknown_attack_type() {
+    let config = Config::default();
+    let mut errors = Vec::new();
+    lint::check_unit_attacks(
+        &config,
+        ";Adjacent;SplashRange\nMarine;1;\nGhost;;1\n",
+        &mut errors,
+    );
+    let errors: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+    assert_eq!(
+        errors,
+        vec![
+            "unit_attacks.csv:1:SplashRange: unknown AttackType 'SplashRange'",
+            "unit_attacks.csv:3: unknown unit 'Ghost'",
+        ]
+    );
+}
+
+fn run_script(
+    environment: &Environment,
+    script: &str,
+    arguments: impl FuncArgs,
+) -> Result<(), ScriptError> {
+    let ast = environment
+        .config
+        .engine::<Direction4>()
+        .compile(script)
+        .unwrap();
+    let executor = Executor::new(rhai::Map::new(), environment.clone());
+    executor.run_ast::<Direction4, ()>(ScriptHook::CustomAction, &ast, "f", arguments)
+}
+
+fn run_with_limits(limits: &'static str, script: &str) -> Result<(), ScriptError> {
+    let config = parse_patched("ruleset.csv", move |s| format!("{s}\n{limits}\n")).unwrap();
+    let environment = Environment::new_map(Urc::new(config), MapSize::new(5, 5));
+    run_script(&environment, script, ())
+}
+
+#[test]
+fn script_operation_limit() {
+    let result = run_with_limits("ScriptMaxOperations;1000", "fn f(context) { loop {} }");
+    assert!(matches!(
+        result.map_err(|e| e.cause),
+        Err(ScriptErrorCause::LimitExceeded(ScriptLimit::Operations))
+    ));
+}
+
+#[test]
+fn script_array_size_limit() {
+    let result = run_with_limits(
+        "ScriptMaxArraySize;10",
+        "fn f(context) { let a = []; loop { a.push(1); } }",
+    );
+    assert!(matches!(
+        result.map_err(|e| e.cause),
+        Err(ScriptErrorCause::LimitExceeded(ScriptLimit::ArraySize))
+    ));
+}
+
+#[test]
+fn script_time_limit() {
+    // 0 disables the operation limit
+    let config = parse_patched("ruleset.csv", |s| format!("{s}\nScriptMaxOperations;0\n")).unwrap();
+    let environment = Environment::new_map(Urc::new(config), MapSize::new(5, 5));
+    environment.set_script_time_limit(Some(Duration::from_millis(10)));
+    let result = run_script(&environment, "fn f(context) { loop {} }", ());
+    assert!(matches!(
+        result.map_err(|e| e.cause),
+        Err(ScriptErrorCause::LimitExceeded(ScriptLimit::Time))
+    ));
+    // every call gets its own budget
+    assert!(run_script(&environment, "fn f(context) { }", ()).is_ok());
+}
+
+#[test]
+fn script_failure_is_recorded() {
+    let environment = Environment::new_map(Urc::new(Config::default()), MapSize::new(5, 5));
+    let error = run_script(
+        &environment,
+        "fn f(context, x) {\n    throw \"broken\";\n}",
+        (5,),
+    )
+    .unwrap_err();
+    environment.log_rhai_error("test", "f", &error);
+    let (failures, dropped) = environment.take_script_failures();
+    assert_eq!(failures.len(), 1);
+    assert_eq!(dropped, 0);
+    let failure = &failures[0];
+    assert_eq!(failure.location, "test");
+    assert_eq!(failure.function, "f");
+    assert_eq!(failure.arguments.len(), 2);
+    assert!(failure.arguments[0].starts_with("config: "));
+    assert_eq!(failure.arguments[1], "5");
+    assert_eq!(failure.line, Some(2));
+    assert!(failure.error.contains("broken"), "{}", failure.error);
+    assert!(environment.take_script_failures().0.is_empty());
+
+    for _ in 0..=MAX_SCRIPT_FAILURES {
+        environment.log_rhai_error("test", "f", &error);
+    }
+    assert_eq!(environment.dropped_script_failures(), 1);
+    let (failures, dropped) = environment.take_script_failures();
+    assert_eq!(failures.len(), MAX_SCRIPT_FAILURES);
+    assert_eq!(dropped, 1);
+    assert_eq!(environment.dropped_script_failures(), 0);
+}
+
+#[test]
+fn script_profiling() {
+    let environment = Environment::new_map(Urc::new(Config::default()), MapSize::new(5, 5));
+    run_script(&environment, "fn f(context) {}", ()).unwrap();
+    assert!(environment.script_profile().is_none());
+    environment.enable_script_profiling();
+    run_script(&environment, "fn f(context) {}", ()).unwrap();
+    environment.set_script_profiling_turn(1);
+    run_script(&environment, "fn f(context) {}", ()).unwrap();
+    run_script(&environment, "fn f(context) { throw 1; }", ()).unwrap_err();
+    let profile = environment.disable_script_profiling().unwrap();
+    assert_eq!(profile.total().calls, 3);
+    let per_turn: Vec<(usize, usize)> = profile
+        .per_turn()
+        .into_iter()
+        .map(|(turn, stats)| (turn, stats.calls))
+        .collect();
+    assert_eq!(per_turn, vec![(0, 1), (1, 2)]);
+    assert_eq!(profile.per_hook().len(), 1);
+    assert_eq!(profile.per_hook()[0].0, ScriptHook::CustomAction);
+    let csv = profile.to_csv();
+    assert!(
+        csv.starts_with("Turn;Hook;Function;Calls;Microseconds\n"),
+        "{csv}"
+    );
+    assert!(csv.contains("\n1;CustomAction;f;2;"), "{csv}");
+    assert!(!environment.is_script_profiling());
+}
+
+#[test]
+fn type_changes_dont_guess_renames() {
+    let no_renames = HashMap::default();
+    let changes = TypeChanges::new(["A", "B", "C"], ["A", "D", "C", "E"], &no_renames);
+    assert!(changes.renamed.is_empty());
+    assert_eq!(changes.removed, vec!["B".to_string()]);
+    assert_eq!(changes.added, vec!["D".to_string(), "E".to_string()]);
+    assert_eq!(
+        changes.rename_candidates,
+        vec![("B".to_string(), "D".to_string())]
+    );
+    assert_eq!(changes.new_index(1), None);
+
+    let mut renames = HashMap::default();
+    renames.insert("B".to_string(), "D".to_string());
+    let changes = TypeChanges::new(["A", "B", "C"], ["A", "D", "C", "E"], &renames);
+    assert_eq!(changes.renamed, vec![("B".to_string(), "D".to_string())]);
+    assert_eq!(changes.added, vec!["E".to_string()]);
+    assert!(changes.removed.is_empty());
+    assert!(changes.rename_candidates.is_empty());
+    assert_eq!(changes.new_index(1), Some(1));
+
+    let changes = TypeChanges::new(["A", "B"], ["B", "C"], &no_renames);
+    assert_eq!(changes.removed, vec!["A".to_string()]);
+    assert_eq!(changes.added, vec!["C".to_string()]);
+    assert_eq!(changes.new_index(1), Some(0));
+
+    // a rename can't merge a type into one that already existed
+    renames.insert("A".to_string(), "B".to_string());
+    let changes = TypeChanges::new(["A", "B"], ["B", "C"], &renames);
+    assert_eq!(changes.removed, vec!["A".to_string()]);
+    assert!(changes.renamed.is_empty());
+}
+
+#[test]
+fn reload_and_migrate_map() {
+    let old = Urc::new(Config::default());
+    let (new, diff) = old
+        .reload_from(patched_loader("units.csv", |s| {
+            let marine = s.lines().nth(1).unwrap().replacen("Marine", "Recruit", 1);
+            let (header, rest) = s.split_once('\n').unwrap();
+            format!("{header}\n{marine}\n{rest}")
+        }))
+        .unwrap();
+    assert_eq!(diff.units.added, vec!["Recruit".to_string()]);
+    assert!(diff.units.removed.is_empty());
+    assert!(diff.terrains.is_empty());
+    assert!(diff.can_migrate_maps());
+    let new = Urc::new(new);
+
+    let wrapping = WMBuilder::<Direction4>::new(PointMap::new(4, 4, false)).build();
+    let mut map = Map::new(wrapping, &old);
+    let environment = map.environment().clone();
+    let sniper = old.find_unit_by_name("Sniper").unwrap();
+    map.set_unit(
+        Point::new(1, 1),
+        Some(sniper.instance(&environment).set_owner_id(0).build()),
+    );
+    map.migrate_config(&new, &ConfigRenames::default()).unwrap();
+    let unit = map.get_unit(Point::new(1, 1)).unwrap();
+    assert_eq!(unit.name(), "Sniper");
+    assert_eq!(unit.typ(), new.find_unit_by_name("Sniper").unwrap());
+    assert_ne!(unit.typ(), sniper);
+
+    // the other way around, Recruit is removed
+    let recruit = new.find_unit_by_name("Recruit").unwrap();
+    let environment = map.environment().clone();
+    let mut with_recruit = map.clone();
+    with_recruit.set_unit(
+        Point::new(2, 2),
+        Some(recruit.instance(&environment).set_owner_id(0).build()),
+    );
+    assert_eq!(
+        with_recruit
+            .migrate_config(&old, &ConfigRenames::default())
+            .unwrap_err(),
+        ConfigMigrationError::TypeRemoved("unit", "Recruit".to_string())
+    );
+    assert_eq!(
+        with_recruit.environment().config.unit_count(),
+        new.unit_count()
+    );
+    let diff = map.migrate_config(&old, &ConfigRenames::default()).unwrap();
+    assert_eq!(diff.units.removed, vec!["Recruit".to_string()]);
+    assert!(!diff.can_migrate_maps());
+    assert_eq!(map.get_unit(Point::new(1, 1)).unwrap().typ(), sniper);
+}
+
+#[test]
+fn migrate_map_with_renames() {
+    let old = Urc::new(Config::default());
+    let folder = test_folder();
+    let (new, diff) = old
+        .reload_from(Box::new(move |name: &str| {
+            // tables etc. refer to units by name too
+            Ok(std::fs::read_to_string(folder.join(name))?.replace("Sniper", "Marksman"))
+        }))
+        .unwrap();
+    // renames aren't guessed
+    assert_eq!(diff.units.removed, vec!["Sniper".to_string()]);
+    assert_eq!(diff.units.added, vec!["Marksman".to_string()]);
+    assert_eq!(
+        diff.units.rename_candidates,
+        vec![("Sniper".to_string(), "Marksman".to_string())]
+    );
+    assert!(!diff.can_migrate_maps());
+    let new = Urc::new(new);
+
+    let wrapping = WMBuilder::<Direction4>::new(PointMap::new(4, 4, false)).build();
+    let mut map = Map::new(wrapping, &old);
+    let environment = map.environment().clone();
+    let sniper = old.find_unit_by_name("Sniper").unwrap();
+    map.set_unit(
+        Point::new(1, 1),
+        Some(sniper.instance(&environment).set_owner_id(0).build()),
+    );
+    assert_eq!(
+        map.clone()
+            .migrate_config(&new, &ConfigRenames::default())
+            .unwrap_err(),
+        ConfigMigrationError::TypeRemoved("unit", "Sniper".to_string())
+    );
+    let mut renames = ConfigRenames::default();
+    renames
+        .units
+        .insert("Sniper".to_string(), "Marksman".to_string());
+    let diff = map.migrate_config(&new, &renames).unwrap();
+    assert!(diff.can_migrate_maps());
+    assert_eq!(
+        diff.units.renamed,
+        vec![("Sniper".to_string(), "Marksman".to_string())]
+    );
+    assert_eq!(map.get_unit(Point::new(1, 1)).unwrap().name(), "Marksman");
+}
+
+#[test]
+fn max_map_size_per_ruleset() {
+    let default = Urc::new(Config::default());
+    assert_eq!(default.max_map_size(), DEFAULT_MAX_SIZE);
+    let large =
+        Urc::new(parse_patched("ruleset.csv", |s| format!("{s}\nMaxMapSize;128\n")).unwrap());
+    assert_eq!(large.max_map_size(), 128);
+    let error = parse_error("ruleset.csv", |s| format!("{s}\nMaxMapSize;200\n"));
+    assert!(matches!(
+        error.inner(),
+        ConfigParseError::InvalidColumnValue(_, _)
+    ));
+
+    let map = WMBuilder::<Direction4>::new(PointMap::new(100, 70, false));
+    let mut map = Map::new(map.build(), &large);
+    map.set_unit(
+        Point::new(99, 69),
+        Some(
+            large
+                .find_unit_by_name("Sniper")
+                .unwrap()
+                .instance(map.environment())
+                .set_owner_id(0)
+                .build(),
+        ),
+    );
+    let version = Version::parse(VERSION).unwrap();
+    let bytes = map.export();
+    let Ok(MapType::Square(imported)) = import_map(&large, bytes.clone(), version.clone()) else {
+        panic!("map should be importable with a ruleset that allows its size");
+    };
+    assert_eq!(imported.width(), 100);
+    assert_eq!(imported.height(), 70);
+    assert!(imported.get_unit(Point::new(99, 69)).is_some());
+    assert!(import_map(&default, bytes, version).is_err());
+}
@@ -6,6 +6,7 @@ use crate::map::board::{Board, BoardView};
 use crate::map::direction::Direction;
 use crate::map::point::Point;
 use crate::script::executor::Executor;
+use crate::script::profiler::ScriptHook;
 use crate::tags::*;
 use crate::tokens::token::Token;
 use crate::tokens::token_types::TokenType;
@@ -87,10 +88,15 @@ impl TokenFilter {
     ) -> bool {
         match self {
             Self::Rhai(function_index) => {
-                match executor.run::<D, bool>(*function_index, ()) {
+                match executor.run::<D, bool>(ScriptHook::TokenFilter, *function_index, ()) {
                     Ok(result) => result,
-                    Err(_e) => {
-                        // TODO: log error
+                    Err(e) => {
+                        let environment = executor.environment();
+                        environment.log_rhai_error(
+                            "TokenFilter::Rhai",
+                            environment.get_rhai_function_name(*function_index),
+                            &e,
+                        );
                         false
                     }
                 }
@@ -32,23 +32,16 @@ impl TableLine for TokenTypeConfig {
             name: get(H::Id)?.to_string(),
             owned: parse_def(data, H::Owned, OwnershipPredicate::Either, loader)?,
             owner_is_playable: parse_def(data, H::OwnerPlayable, false, loader)?,
-            visibility: match data.get(&H::Visibility) {
-                Some(s) => UnitVisibility::from_conf(s, loader)?.0,
-                None => UnitVisibility::Normal,
-            },
+            visibility: parse_def(data, H::Visibility, UnitVisibility::Normal, loader)?,
             vision_range: parse_def(data, H::VisionRange, -1, loader)?,
-            action_script: match data.get(&H::ActionScript) {
-                Some(s) if s.len() > 0 => {
-                    let exe = loader.rhai_function(s, 2..=2)?;
-                    let input = loader.rhai_function(&format!("{s}_input"), 1..=1)?.index;
-                    Some((input, exe.index))
-                }
-                _ => None,
-            },
-            on_unit_path: match data.get(&H::OnUnitPath) {
-                Some(s) if s.len() > 0 => Some(loader.rhai_function(s, 1..=1)?.index),
-                _ => None,
-            },
+            action_script: map_cell(data, H::ActionScript, |s| {
+                let exe = loader.rhai_function(s, 2..=2)?;
+                let input = loader.rhai_function(&format!("{s}_input"), 1..=1)?.index;
+                Ok((input, exe.index))
+            })?,
+            on_unit_path: map_cell(data, H::OnUnitPath, |s| {
+                Ok(loader.rhai_function(s, 1..=1)?.index)
+            })?,
         };
         Ok(result)
     }
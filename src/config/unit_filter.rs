@@ -8,6 +8,7 @@ use crate::commander::commander_type::CommanderType;
 use crate::game::fog::FogIntensity;
 use crate::map::board::{Board, BoardView};
 use crate::map::direction::Direction;
+use crate::script::profiler::ScriptHook;
 use crate::tags::{FlagKey, TagKey};
 use crate::terrain::TerrainType;
 use crate::tokens::token_types::TokenType;
@@ -208,7 +209,7 @@ impl UnitFilter {
                     heroes,
                     is_counter,
                 ));
-                match executor.run::<D, bool>(*function_index, ()) {
+                match executor.run::<D, bool>(ScriptHook::UnitFilter, *function_index, ()) {
                     Ok(result) => result,
                     Err(e) => {
                         let environment = game.environment();
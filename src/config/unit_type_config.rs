@@ -8,6 +8,7 @@ use crate::config::OwnershipPredicate;
 use crate::game::fog::VisionMode;
 use crate::units::UnitVisibility;
 use crate::units::movement::MovementType;
+use crate::units::zone_of_control::ZoneOfControl;
 
 use super::ConfigParseError;
 use super::file_loader::FileLoader;
@@ -27,6 +28,7 @@ pub struct UnitTypeConfig {
     pub(super) true_vision: usize,
     pub(super) owned: OwnershipPredicate,
     pub(super) pass_enemy_units: bool,
+    pub(super) zone_of_control: ZoneOfControl,
     pub(super) can_be_moved_through: bool,
     pub(super) can_be_taken: bool,
     pub(super) can_attack_after_moving: bool,
@@ -57,10 +59,7 @@ impl TableLine for UnitTypeConfig {
         }
         let result = Self {
             name: get(H::Id)?.trim().to_string(),
-            visibility: match data.get(&H::Visibility) {
-                Some(s) => UnitVisibility::from_conf(s, loader)?.0,
-                None => UnitVisibility::Normal,
-            },
+            visibility: parse_def(data, H::Visibility, UnitVisibility::Normal, loader)?,
             movement_pattern: parse_def(
                 data,
                 H::MovementPattern,
@@ -79,13 +78,11 @@ impl TableLine for UnitTypeConfig {
             true_vision: parse_def(data, H::TrueVision, 0 as u8, loader)? as usize,
             owned: parse_def(data, H::Owned, OwnershipPredicate::Either, loader)?,
             pass_enemy_units: parse_def(data, H::PassEnemyUnits, false, loader)?,
+            zone_of_control: parse_def(data, H::ZoneOfControl, ZoneOfControl::None, loader)?,
             can_be_moved_through: parse_def(data, H::CanBeMovedThrough, false, loader)?,
             can_be_taken: parse_def(data, H::CanBeTaken, false, loader)?,
             can_attack_after_moving: parse_def(data, H::CanAttackAfterMoving, false, loader)?,
-            attack_pattern: match data.get(&H::AttackPattern) {
-                Some(s) if s.len() > 0 => AttackPattern::from_conf(s, loader)?.0,
-                _ => AttackPattern::None,
-            },
+            attack_pattern: parse_def(data, H::AttackPattern, AttackPattern::None, loader)?,
             attack_direction: parse_def(
                 data,
                 H::AttackDirection,
@@ -93,10 +90,7 @@ impl TableLine for UnitTypeConfig {
                 loader,
             )?,
             attack_targets: parse_def(data, H::AttackTargets, ValidAttackTargets::Enemy, loader)?,
-            attack_type: match data.get(&H::AttackType) {
-                Some(s) if s.len() > 0 => AttackType::from_conf(s, loader)?.0,
-                _ => AttackType(None),
-            },
+            attack_type: parse_def(data, H::AttackType, AttackType(None), loader)?,
             can_be_displaced: parse_def(data, H::CanBeDisplaced, false, loader)?,
             transport_capacity: parse_def(data, H::TransportCapacity, 0 as u8, loader)? as usize,
             custom_columns,
@@ -128,6 +122,7 @@ crate::enum_with_custom! {
         TrueVision,
         Owned,
         PassEnemyUnits,
+        ZoneOfControl,
         CanBeMovedThrough,
         CanBeTaken,
         CanAttackAfterMoving,
@@ -0,0 +1,127 @@
+use semver::Version;
+use zipper::*;
+use zipper_derive::Zippable;
+
+/// the first version that exports time controls and player clocks
+pub const TIME_CONTROL_VERSION: Version = Version::new(0, 2, 0);
+
+/// data exported by older versions doesn't contain the time control or clock
+pub(crate) fn unzip_clock<T: Zippable>(
+    unzipper: &mut Unzipper,
+    version: &Version,
+) -> Result<Option<T>, ZipperError> {
+    if *version < TIME_CONTROL_VERSION {
+        return Ok(None);
+    }
+    Option::<T>::unzip(unzipper)
+}
+
+/**
+ * a duration in milliseconds.
+ * the game never reads the system clock, every ClockTime it consumes is supplied
+ * by the server through commands, so replays and clients arrive at the same state
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ClockTime(pub u32);
+
+impl ClockTime {
+    pub const ZERO: Self = Self(0);
+
+    pub fn from_secs(secs: u32) -> Self {
+        Self(secs.saturating_mul(1000))
+    }
+}
+
+impl Zippable for ClockTime {
+    fn zip(&self, zipper: &mut Zipper) {
+        zipper.write_u32(self.0, 32);
+    }
+    fn unzip(unzipper: &mut Unzipper) -> Result<Self, ZipperError> {
+        Ok(Self(unzipper.read_u32(32)?))
+    }
+}
+
+/// what happens to a player who runs out of time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Zippable)]
+#[zippable(bits = 1)]
+pub enum TimeoutPolicy {
+    EndTurn,
+    Defeat,
+}
+
+/**
+ * Chess-clock rules of a game. Combining the fields allows e.g.
+ * a fixed time per turn (only per_turn), a bank for the whole game (only bank)
+ * or Fischer timing (bank and increment).
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Zippable)]
+pub struct TimeControl {
+    /// time every player starts the game with
+    pub bank: ClockTime,
+    /// added to a player's bank after each of their turns
+    pub increment: ClockTime,
+    /// used up before the bank during every turn. unused time is lost
+    pub per_turn: ClockTime,
+    pub on_timeout: TimeoutPolicy,
+}
+
+/// the time a player has left, only exists if the game has a TimeControl
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Zippable)]
+pub struct PlayerClock {
+    /// carries over from turn to turn
+    pub bank: ClockTime,
+    /// what's left of TimeControl::per_turn during the player's current turn
+    pub turn: ClockTime,
+}
+
+impl PlayerClock {
+    pub const OUT_OF_TIME: Self = Self {
+        bank: ClockTime::ZERO,
+        turn: ClockTime::ZERO,
+    };
+
+    pub fn new(time_control: &TimeControl) -> Self {
+        Self {
+            bank: time_control.bank,
+            turn: time_control.per_turn,
+        }
+    }
+
+    pub fn remaining(&self) -> ClockTime {
+        ClockTime(self.bank.0.saturating_add(self.turn.0))
+    }
+
+    /**
+     * the turn's time is used up first, then the bank.
+     * None if that's all the time the player has left
+     */
+    pub fn spend(&self, time: ClockTime) -> Option<Self> {
+        if time >= self.remaining() {
+            return None;
+        }
+        if time <= self.turn {
+            return Some(Self {
+                bank: self.bank,
+                turn: ClockTime(self.turn.0 - time.0),
+            });
+        }
+        Some(Self {
+            bank: ClockTime(self.bank.0 - (time.0 - self.turn.0)),
+            turn: ClockTime::ZERO,
+        })
+    }
+
+    pub fn end_turn(&self, time_control: &TimeControl) -> Self {
+        Self {
+            bank: ClockTime(self.bank.0.saturating_add(time_control.increment.0)),
+            turn: ClockTime::ZERO,
+        }
+    }
+
+    pub fn start_turn(&self, time_control: &TimeControl) -> Self {
+        Self {
+            bank: self.bank,
+            turn: time_control.per_turn,
+        }
+    }
+}
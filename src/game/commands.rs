@@ -7,16 +7,19 @@ use rustc_hash::FxHashSet;
 use zipper::*;
 use zipper_derive::Zippable;
 
+use super::clock::ClockTime;
 use super::event_handler::EventHandler;
 use super::fog::FogIntensity;
 use crate::config::environment::Environment;
-use crate::map::board::{Board, BoardView};
+use crate::map::board::{Board, BoardView, current_team};
 use crate::map::direction::Direction;
 use crate::map::map::valid_points;
 use crate::map::point::Point;
 use crate::script::custom_action::*;
+use crate::script::profiler::ScriptHook;
 use crate::script::*;
 use crate::units::commands::{MAX_CUSTOM_ACTION_STEPS, UnitCommand};
+use crate::units::group_move::{GroupMoveEntry, MAX_GROUP_SIZE, execute_group_move};
 use crate::units::hero::Hero;
 
 #[derive(Debug, Clone, PartialEq, Zippable)]
@@ -36,9 +39,22 @@ pub enum Command<D: Direction> {
         CommanderPowerIndex,
         LVec<CustomActionInput<D>, { MAX_CUSTOM_ACTION_STEPS }>,
     ),
+    GroupMove(LVec<GroupMoveEntry, { MAX_GROUP_SIZE }>),
+    /**
+     * only sent by the server: time the current player has spent since the last SpendTime.
+     * runs out the player's time if that's all they had left
+     */
+    SpendTime(ClockTime),
+    /// only sent by the server: the current player is out of time
+    Timeout,
 }
 
 impl<D: Direction> Command<D> {
+    /// rejected by Game::handle_player_command
+    pub fn is_server_only(&self) -> bool {
+        matches!(self, Self::SpendTime(_) | Self::Timeout)
+    }
+
     pub fn commander_power(index: usize, custom_action_data: Vec<CustomActionInput<D>>) -> Self {
         Self::CommanderPower(
             CommanderPowerIndex(index),
@@ -46,6 +62,92 @@ impl<D: Direction> Command<D> {
         )
     }
 
+    /// panics if there are more than MAX_GROUP_SIZE entries
+    pub fn group_move(entries: Vec<GroupMoveEntry>) -> Self {
+        Self::GroupMove(entries.try_into().unwrap())
+    }
+
+    /**
+     * lists every command the current player could send.
+     * board should be the current player's perspective (see Game::legal_commands)
+     */
+    pub fn legal_commands(board: &Board<D>) -> Vec<Self> {
+        let mut result = vec![Self::EndTurn];
+        let environment = board.environment();
+        let owner_id = board.current_owner();
+        let team = current_team(board);
+        for pos in valid_points(board) {
+            if let Some(unit) = board.get_unit(pos) {
+                for command in UnitCommand::legal_commands(board, pos, None) {
+                    result.push(Self::UnitCommand(command));
+                }
+                for i in 0..unit.get_transported().len() {
+                    for command in UnitCommand::legal_commands(board, pos, Some(i.into())) {
+                        result.push(Self::UnitCommand(command));
+                    }
+                }
+                continue;
+            }
+            if board.get_fog_at(team, pos) != FogIntensity::TrueSight {
+                // without TrueSight, a stealthed unit could block this field
+                continue;
+            }
+            // token action, looking from top to bottom
+            let mut has_token_action = false;
+            for token in board.get_tokens(pos).iter().rev() {
+                let Some((input_script, _)) = environment.config.token_action_script(token.typ())
+                else {
+                    continue;
+                };
+                has_token_action = true;
+                if token.get_owner_id() == owner_id {
+                    for data in all_script_inputs(|data| {
+                        run_token_input_script(input_script, board, pos, token.clone(), data)
+                    }) {
+                        result.push(Self::TokenAction(pos, data.try_into().unwrap()));
+                    }
+                }
+                break;
+            }
+            // terrain action, blocked by tokens that have their own action
+            let terrain = board.get_terrain(pos).unwrap();
+            if has_token_action || terrain.get_owner_id() != owner_id {
+                continue;
+            }
+            let heroes = Hero::hero_influence_at(board, pos, Some(owner_id));
+            if let Some((input_script, _)) = environment
+                .config
+                .terrain_action_script(board, pos, terrain, &heroes)
+            {
+                for data in all_script_inputs(|data| {
+                    run_terrain_input_script(input_script, board, pos, terrain.clone(), data)
+                }) {
+                    result.push(Self::TerrainAction(pos, data.try_into().unwrap()));
+                }
+            }
+        }
+        // commander powers
+        if let Some(player) = board.get_owning_player(owner_id) {
+            let commander = &player.commander;
+            for index in 0..environment.config.commander_powers(commander.typ()).len() {
+                if !commander.can_activate_power(index, false) {
+                    continue;
+                }
+                match commander.power_activation_script(index) {
+                    Some((Some(input_script), _)) => {
+                        for data in all_script_inputs(|data| {
+                            run_commander_input_script(input_script, board, data)
+                        }) {
+                            result.push(Self::commander_power(index, data));
+                        }
+                    }
+                    _ => result.push(Self::commander_power(index, Vec::new())),
+                }
+            }
+        }
+        result
+    }
+
     pub fn execute(self, handler: &mut EventHandler<D>) -> Result<(), CommandError> {
         match self {
             Self::EndTurn => {
@@ -53,6 +155,26 @@ impl<D: Direction> Command<D> {
                 Ok(())
             }
             Self::UnitCommand(command) => command.execute(handler),
+            Self::GroupMove(entries) => execute_group_move(&entries, handler),
+            Self::SpendTime(time) => {
+                if handler.get_game().time_control().is_none() {
+                    return Err(CommandError::NoTimeControl);
+                }
+                handler.spend_time(time);
+                Ok(())
+            }
+            Self::Timeout => {
+                if handler.get_game().time_control().is_none() {
+                    return Err(CommandError::NoTimeControl);
+                }
+                if let Some(clock) = handler.get_game().current_player().clock {
+                    if clock.remaining() > ClockTime::ZERO {
+                        return Err(CommandError::TimeLeft);
+                    }
+                }
+                handler.time_out();
+                Ok(())
+            }
             Self::TerrainAction(pos, data) => {
                 let team = handler.get_game().current_team();
                 if !handler.get_game().get_map().is_point_valid(pos) {
@@ -60,37 +182,28 @@ impl<D: Direction> Command<D> {
                 }
                 if handler.get_game().get_fog_at(team, pos) != FogIntensity::TrueSight {
                     // without TrueSight, a stealthed unit could block this field
-                    return Err(CommandError::NoVision);
+                    return Err(CommandError::NoVision(pos));
                 }
                 if handler.get_game().get_map().get_unit(pos).is_some() {
                     return Err(CommandError::Blocked(pos));
                 }
                 let terrain = handler.get_game().get_terrain(pos).unwrap().clone();
                 if terrain.get_owner_id() != handler.get_game().current_owner() {
-                    return Err(CommandError::NotYourProperty);
+                    return Err(CommandError::NotYourProperty(pos));
                 }
                 let borrowed_game = handler.get_game();
-                let client_game;
-                let client = if handler.get_game().has_secrets() {
-                    client_game = handler
-                        .get_game()
-                        .reimport_as_client(ClientPerspective::Team(
-                            handler.get_game().current_owner() as u8,
-                        ));
-                    &client_game
-                } else {
-                    &*borrowed_game
-                };
-                let board = Board::from(client);
+                let perspective = ClientPerspective::Team(borrowed_game.current_owner() as u8);
                 // check whether the player should even be able to send this command
                 let (script, data) = {
                     // making sure i don't accidently change anything while testing move validity
                     #[allow(unused_variables)]
                     let handler = ();
-                    let heroes = Hero::hero_influence_at(&board, pos, Some(client.current_owner()));
+                    let game_board = Board::from(&*borrowed_game);
+                    let board = game_board.fog_view(perspective);
+                    let heroes = Hero::hero_influence_at(&board, pos, Some(board.current_owner()));
                     let (input_script, script) = {
-                        for token in client.get_tokens(pos) {
-                            if client
+                        for token in board.get_tokens(pos) {
+                            if board
                                 .environment()
                                 .config
                                 .token_action_script(token.typ())
@@ -99,11 +212,13 @@ impl<D: Direction> Command<D> {
                                 return Err(CommandError::Blocked(pos));
                             }
                         }
-                        client
+                        board
                             .environment()
                             .config
                             .terrain_action_script(&board, pos, &terrain, &heroes)
-                            .ok_or(CommandError::InvalidAction)
+                            .ok_or(CommandError::InvalidAction(
+                                ActionRejection::NoTerrainAction(pos),
+                            ))
                     }?;
                     let Some(data) = is_terrain_script_input_valid(
                         input_script,
@@ -112,7 +227,9 @@ impl<D: Direction> Command<D> {
                         terrain.clone(),
                         &data,
                     ) else {
-                        return Err(CommandError::InvalidAction);
+                        return Err(CommandError::InvalidAction(
+                            ActionRejection::input_rejected(board.environment(), input_script),
+                        ));
                     };
                     (script, data)
                 };
@@ -126,38 +243,29 @@ impl<D: Direction> Command<D> {
                 }
                 if handler.get_game().get_fog_at(team, pos) != FogIntensity::TrueSight {
                     // without TrueSight, a stealthed unit could block this field
-                    return Err(CommandError::NoVision);
+                    return Err(CommandError::NoVision(pos));
                 }
                 if handler.get_game().get_unit(pos).is_some() {
                     return Err(CommandError::Blocked(pos));
                 }
                 let borrowed_game = handler.get_game();
-                let client_game;
-                let client = if handler.get_game().has_secrets() {
-                    client_game = handler
-                        .get_game()
-                        .reimport_as_client(ClientPerspective::Team(
-                            handler.get_game().current_owner() as u8,
-                        ));
-                    &client_game
-                } else {
-                    &*borrowed_game
-                };
+                let perspective = ClientPerspective::Team(borrowed_game.current_owner() as u8);
                 // check whether the player should even be able to send this command
                 let (script, token, data) = {
                     // making sure i don't accidently change anything while testing move validity
                     #[allow(unused_variables)]
                     let handler = ();
-                    let mut script_data = None;
+                    let game_board = Board::from(&*borrowed_game);
+                    let board = game_board.fog_view(perspective);
+                    let mut script_data = Err(ActionRejection::NoTokenAction(pos));
                     // look from top to bottom
-                    for token in client.get_tokens(pos).iter().rev() {
+                    for token in board.get_tokens(pos).iter().rev() {
                         if let Some((input_script, script)) =
-                            client.environment().config.token_action_script(token.typ())
+                            board.environment().config.token_action_script(token.typ())
                         {
-                            if token.get_owner_id() != client.current_player().get_owner_id() {
+                            if token.get_owner_id() != board.current_owner() {
                                 return Err(CommandError::Blocked(pos));
                             }
-                            let board = Board::from(client);
                             script_data = is_token_script_input_valid(
                                 input_script,
                                 &board,
@@ -165,11 +273,14 @@ impl<D: Direction> Command<D> {
                                 token.clone(),
                                 &data,
                             )
-                            .map(|data| (script, token.clone(), data));
+                            .map(|data| (script, token.clone(), data))
+                            .ok_or_else(|| {
+                                ActionRejection::input_rejected(board.environment(), input_script)
+                            });
                             break;
                         }
                     }
-                    script_data.ok_or(CommandError::InvalidAction)?
+                    script_data.map_err(CommandError::InvalidAction)?
                 };
                 execute_token_script(script, handler, pos, token, data);
                 Ok(())
@@ -179,16 +290,38 @@ impl<D: Direction> Command<D> {
                 let player = handler.get_game().get_owning_player(owner_id).unwrap();
                 let commander = &player.commander;
                 if !commander.can_activate_power(index.0, false) {
-                    return Err(CommandError::PowerNotUsable);
+                    let power_count = handler
+                        .environment()
+                        .config
+                        .commander_powers(commander.typ())
+                        .len();
+                    let required = commander.power_cost(index.0);
+                    return Err(if index.0 >= power_count {
+                        CommandError::InvalidCommanderPower(index.0)
+                    } else if required > commander.get_charge() {
+                        CommandError::NotEnoughCharge {
+                            required,
+                            available: commander.get_charge(),
+                        }
+                    } else {
+                        CommandError::PowerNotUsable(index.0)
+                    });
                 }
                 let script = commander.power_activation_script(index.0);
                 let data = if let Some((Some(input_script), _)) = script {
                     is_commander_script_input_valid(input_script, handler.get_board(), &data)
-                        .ok_or(CommandError::PowerNotUsable)?
+                        .ok_or_else(|| {
+                            CommandError::InvalidAction(ActionRejection::input_rejected(
+                                handler.environment(),
+                                input_script,
+                            ))
+                        })?
                 } else if data.len() == 0 {
                     Vec::new()
                 } else {
-                    return Err(CommandError::PowerNotUsable);
+                    return Err(CommandError::InvalidAction(
+                        ActionRejection::UnexpectedInput,
+                    ));
                 };
                 handler.add_commander_charge(owner_id, -(commander.power_cost(index.0) as i32));
                 handler.commander_power(owner_id, index.0);
@@ -223,7 +356,7 @@ pub fn cleanup_dead_material<D: Direction>(handler: &mut EventHandler<D>, execut
                         first_argument.insert(CONST_NAME_POSITION.into(), Dynamic::from(*p));
                         first_argument.insert(CONST_NAME_UNIT.into(), Dynamic::from(u));
                         let executor = handler.get_board().executor(first_argument);
-                        match executor.run::<D, bool>(is_unit_dead_rhai, ()) {
+                        match executor.run::<D, bool>(ScriptHook::Ruleset, is_unit_dead_rhai, ()) {
                             Ok(result) => result,
                             Err(e) => {
                                 environment.log_rhai_error(
@@ -258,7 +391,7 @@ pub fn cleanup_dead_material<D: Direction>(handler: &mut EventHandler<D>, execut
                         let environment = handler.environment().clone();
                         let executor = handler.executor(first_argument);
                         for function_index in scripts {
-                            match executor.run::<D, ()>(function_index, ()) {
+                            match executor.run::<D, ()>(ScriptHook::UnitEvent, function_index, ()) {
                                 Ok(()) => (),
                                 Err(e) => {
                                     environment.log_rhai_error(
@@ -324,38 +457,242 @@ impl SupportedZippable<&Environment> for CommanderPowerIndex {
     }
 }
 
+/**
+ * why the server rejected a command.
+ * Display is meant for logs and support staff,
+ * clients should use code() and the variant's fields to show a localized message.
+ */
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum CommandError {
-    NoVision,
-    MissingUnit,
-    MissingBoardedUnit,
-    NotYourUnit,
-    UnitCannotMove,
+    NoVision(Point),
+    MissingUnit(Point),
+    /// position of the transporter, index of the transported unit
+    MissingBoardedUnit(Point, usize),
+    NotYourUnit(Point),
+    UnitCannotMove(Point),
     UnitCannotCapture,
     UnitCannotBeBoarded,
     UnitCannotPull,
     UnitTypeWrong,
-    InvalidPath,
+    InvalidPath(PathError),
     InvalidPoint(Point),
     InvalidTarget,
     InvalidUnitType,
-    InvalidAction,
-    PowerNotUsable,
+    InvalidAction(ActionRejection),
+    /// the power with this index can't be activated right now
+    PowerNotUsable(usize),
     Blocked(Point),
-    NotEnoughMoney,
-    NotYourProperty,
+    NotEnoughMoney {
+        required: i32,
+        available: i32,
+    },
+    NotYourProperty(Point),
     BuildLimitReached,
     CannotCaptureHere,
-    InvalidCommanderPower,
-    NotEnoughCharge,
+    /// the commander doesn't have a power with this index
+    InvalidCommanderPower(usize),
+    NotEnoughCharge {
+        required: u32,
+        available: u32,
+    },
     CannotRepairHere,
     CannotBuildHere,
+    /// the game doesn't have a TimeControl
+    NoTimeControl,
+    /// a GroupMove would move the unit at this point more than once
+    DuplicateGroupMember(Point),
+    /// SpendTime and Timeout can't be sent by players
+    ServerOnlyCommand,
+    /// Timeout while the current player's clock isn't exhausted
+    TimeLeft,
+}
+
+impl CommandError {
+    /**
+     * stable identifier of the error, doesn't change when the Display text does.
+     * can be used as key to look up translations
+     */
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::NoVision(_) => "no_vision",
+            Self::MissingUnit(_) => "missing_unit",
+            Self::MissingBoardedUnit(..) => "missing_boarded_unit",
+            Self::NotYourUnit(_) => "not_your_unit",
+            Self::UnitCannotMove(_) => "unit_cannot_move",
+            Self::UnitCannotCapture => "unit_cannot_capture",
+            Self::UnitCannotBeBoarded => "unit_cannot_be_boarded",
+            Self::UnitCannotPull => "unit_cannot_pull",
+            Self::UnitTypeWrong => "unit_type_wrong",
+            Self::InvalidPath(error) => error.code(),
+            Self::InvalidPoint(_) => "invalid_point",
+            Self::InvalidTarget => "invalid_target",
+            Self::InvalidUnitType => "invalid_unit_type",
+            Self::InvalidAction(rejection) => rejection.code(),
+            Self::PowerNotUsable(_) => "power_not_usable",
+            Self::Blocked(_) => "blocked",
+            Self::NotEnoughMoney { .. } => "not_enough_money",
+            Self::NotYourProperty(_) => "not_your_property",
+            Self::BuildLimitReached => "build_limit_reached",
+            Self::CannotCaptureHere => "cannot_capture_here",
+            Self::InvalidCommanderPower(_) => "invalid_commander_power",
+            Self::NotEnoughCharge { .. } => "not_enough_charge",
+            Self::CannotRepairHere => "cannot_repair_here",
+            Self::CannotBuildHere => "cannot_build_here",
+            Self::NoTimeControl => "no_time_control",
+            Self::DuplicateGroupMember(_) => "duplicate_group_member",
+            Self::ServerOnlyCommand => "server_only_command",
+            Self::TimeLeft => "time_left",
+        }
+    }
 }
 
 impl Display for CommandError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{self:?}")
+        match self {
+            Self::NoVision(p) => write!(f, "No vision on {}", display_point(*p)),
+            Self::MissingUnit(p) => write!(f, "There's no unit at {}", display_point(*p)),
+            Self::MissingBoardedUnit(p, index) => write!(
+                f,
+                "The unit at {} doesn't transport a unit with index {index}",
+                display_point(*p)
+            ),
+            Self::NotYourUnit(p) => {
+                write!(
+                    f,
+                    "The unit at {} belongs to another player",
+                    display_point(*p)
+                )
+            }
+            Self::UnitCannotMove(p) => {
+                write!(f, "The unit at {} can't move right now", display_point(*p))
+            }
+            Self::UnitCannotCapture => write!(f, "The unit can't capture"),
+            Self::UnitCannotBeBoarded => write!(f, "The unit can't be boarded"),
+            Self::UnitCannotPull => write!(f, "The unit can't pull"),
+            Self::UnitTypeWrong => write!(f, "Wrong unit type"),
+            Self::InvalidPath(error) => write!(f, "Invalid path: {error}"),
+            Self::InvalidPoint(p) => write!(f, "{} is not on the map", display_point(*p)),
+            Self::InvalidTarget => write!(f, "Invalid target"),
+            Self::InvalidUnitType => write!(f, "Invalid unit type"),
+            Self::InvalidAction(rejection) => write!(f, "Invalid action: {rejection}"),
+            Self::PowerNotUsable(index) => write!(f, "Power {index} can't be activated right now"),
+            Self::Blocked(p) => write!(f, "{} is blocked", display_point(*p)),
+            Self::NotEnoughMoney {
+                required,
+                available,
+            } => write!(
+                f,
+                "Requires {required} funds, but only {available} are available"
+            ),
+            Self::NotYourProperty(p) => {
+                write!(
+                    f,
+                    "The terrain at {} belongs to another player",
+                    display_point(*p)
+                )
+            }
+            Self::BuildLimitReached => write!(f, "Build limit reached"),
+            Self::CannotCaptureHere => write!(f, "Can't capture here"),
+            Self::InvalidCommanderPower(index) => {
+                write!(f, "The commander doesn't have a power with index {index}")
+            }
+            Self::NotEnoughCharge {
+                required,
+                available,
+            } => write!(f, "Requires {required} charge, but only has {available}"),
+            Self::CannotRepairHere => write!(f, "Can't repair here"),
+            Self::CannotBuildHere => write!(f, "Can't build here"),
+            Self::NoTimeControl => write!(f, "This game isn't timed"),
+            Self::DuplicateGroupMember(p) => write!(
+                f,
+                "The unit at {} can only be moved once per group",
+                display_point(*p)
+            ),
+            Self::ServerOnlyCommand => write!(f, "Only the server can send this command"),
+            Self::TimeLeft => write!(f, "The player still has time left"),
+        }
     }
 }
 
 impl Error for CommandError {}
+
+fn display_point(p: Point) -> String {
+    format!("({}, {})", p.x, p.y)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathError {
+    /// the step with this index leads off the map
+    LeavesMap(usize),
+    /// the unit can't take the step with this index, e.g. because it's blocked or lacks movement points
+    Unreachable(usize),
+    /// the unit can take every step, but can't end its movement at the end of the path
+    CannotStop(Point),
+}
+
+impl PathError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::LeavesMap(_) => "path_leaves_map",
+            Self::Unreachable(_) => "path_unreachable",
+            Self::CannotStop(_) => "path_cannot_stop",
+        }
+    }
+}
+
+impl Display for PathError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::LeavesMap(step) => write!(f, "step {step} leads off the map"),
+            Self::Unreachable(step) => write!(f, "the unit can't take step {step}"),
+            Self::CannotStop(p) => write!(f, "the unit can't stop at {}", display_point(*p)),
+        }
+    }
+}
+
+/**
+ * why an action, terrain action, token action or commander power was rejected
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ActionRejection {
+    /// the unit doesn't have this option at the end of its path
+    NotAnOption,
+    /// the terrain at this position doesn't offer an action to the current player
+    NoTerrainAction(Point),
+    /// none of the tokens at this position offer an action
+    NoTokenAction(Point),
+    /// input was sent for an action that doesn't take any
+    UnexpectedInput,
+    /// the input script with this name didn't accept the input
+    InputRejected(String),
+}
+
+impl ActionRejection {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::NotAnOption => "action_not_an_option",
+            Self::NoTerrainAction(_) => "action_no_terrain_action",
+            Self::NoTokenAction(_) => "action_no_token_action",
+            Self::UnexpectedInput => "action_unexpected_input",
+            Self::InputRejected(_) => "action_input_rejected",
+        }
+    }
+
+    pub(crate) fn input_rejected(environment: &Environment, script: usize) -> Self {
+        Self::InputRejected(environment.get_rhai_function_name(script).clone())
+    }
+}
+
+impl Display for ActionRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotAnOption => write!(f, "the unit can't do that after this path"),
+            Self::NoTerrainAction(p) => {
+                write!(f, "the terrain at {} has no action", display_point(*p))
+            }
+            Self::NoTokenAction(p) => write!(f, "no token at {} has an action", display_point(*p)),
+            Self::UnexpectedInput => write!(f, "the action doesn't take any input"),
+            Self::InputRejected(script) => write!(f, "input was rejected by {script}"),
+        }
+    }
+}
@@ -352,7 +352,7 @@ impl<D: Direction> Effect<D> {
             }) => {
                 let start = steps.first()?.get_start();
                 let end = steps.last()?;
-                let end = end.get_step().progress(game, end.get_start()).ok()?.0;
+                let end = end.get_step().progress(game, end.get_start())?.0;
                 let mut points = Vec::with_capacity(steps.len() + 1);
                 for step in steps {
                     points.push(step.get_start());
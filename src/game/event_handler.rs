@@ -2,12 +2,14 @@ use std::marker::PhantomData;
 use std::ptr::with_exposed_provenance_mut;
 
 use interfaces::GameInterface;
-use interfaces::{ClientPerspective, Perspective as IPerspective, RandomFn};
+use interfaces::{ClientPerspective, Perspective as IPerspective};
 use rhai::{Dynamic, Map};
 use rustc_hash::{FxHashMap as HashMap, FxHashSet as HashSet};
 
+use super::clock::{ClockTime, PlayerClock, TimeoutPolicy};
 use super::event_fx::*;
 use super::events::Event;
+use super::rng::GameRng;
 use crate::config::environment::Environment;
 use crate::config::global_events::GlobalEventConfig;
 use crate::game::fog::*;
@@ -16,10 +18,12 @@ use crate::map::board::{Board, BoardView};
 use crate::map::direction::Direction;
 use crate::map::map::*;
 use crate::map::point::Point;
+use crate::map::point_map::AreaVec;
 use crate::map::wrapping_map::Distortion;
 use crate::player::*;
 use crate::script::custom_action::execute_commander_script;
 use crate::script::executor::Executor;
+use crate::script::profiler::ScriptHook;
 use crate::script::*;
 use crate::tags::*;
 use crate::terrain::terrain::*;
@@ -40,13 +44,16 @@ pub struct EventHandler<'a, D: Direction> {
     // so there can't be an immutable referance to Self::board at the same time
     board: Board<'a, D>,
     events: HashMap<IPerspective, Vec<Event<D>>>,
-    random: RandomFn,
+    // restored if the command is cancelled, so a rejected command doesn't influence later results
+    rng_backup: GameRng,
     observed_units: HashMap<usize, (Point, Option<usize>, Distortion<D>)>,
     next_observed_unit_id: usize,
+    // see start_effect_batch
+    effect_batch: Option<Vec<Effect<D>>>,
 }
 
 impl<'a, D: Direction> EventHandler<'a, D> {
-    pub fn new(game: &'a mut Game<D>, random: RandomFn) -> Self {
+    pub fn new(game: &'a mut Game<D>) -> Self {
         let mut events = HashMap::default();
         events.insert(IPerspective::Server, Vec::new());
         events.insert(IPerspective::Neutral, Vec::new());
@@ -63,12 +70,13 @@ impl<'a, D: Direction> EventHandler<'a, D> {
             }
         }
         Self {
+            rng_backup: game.get_rng().clone(),
             game,
             board,
             events,
-            random,
             next_observed_unit_id: 0,
             observed_units: HashMap::default(),
+            effect_batch: None,
         }
     }
 
@@ -84,8 +92,8 @@ impl<'a, D: Direction> EventHandler<'a, D> {
         &self.board
     }
 
-    pub fn rng(&self) -> f32 {
-        (*self.random)()
+    pub fn rng(&mut self) -> &mut GameRng {
+        self.game.get_rng_mut()
     }
 
     pub fn observe_unit(&mut self, position: Point, unload_index: Option<usize>) -> UnitId<D> {
@@ -192,9 +200,23 @@ impl<'a, D: Direction> EventHandler<'a, D> {
             None
         };
 
+        if let Some(time_control) = self.get_game().time_control() {
+            let player = self.get_game().current_player();
+            if let Some(clock) = player.clock.filter(|_| !player.dead) {
+                self.set_player_clock(player.get_owner_id(), clock.end_turn(&time_control));
+            }
+        }
+
         self.next_turn();
         let owner_id = self.get_game().current_owner();
 
+        if let Some(time_control) = self.get_game().time_control() {
+            let player = self.get_game().current_player();
+            if let Some(clock) = player.clock.filter(|_| !player.dead) {
+                self.set_player_clock(owner_id, clock.start_turn(&time_control));
+            }
+        }
+
         if let Some((power_index, function_index, charge_cost)) = {
             let commander = &self.get_game().current_player().commander;
             Some(commander.get_next_power())
@@ -249,6 +271,9 @@ impl<'a, D: Direction> EventHandler<'a, D> {
 
     pub fn next_turn(&mut self) {
         self.add_event(Event::NextTurn);
+        // scripts of the new turn shouldn't be counted for the previous one
+        self.environment()
+            .set_script_profiling_turn(self.get_game().current_turn());
     }
 
     pub fn start_turn(&mut self, fog_before: Option<HashMap<Point, FogIntensity>>) {
@@ -398,19 +423,26 @@ impl<'a, D: Direction> EventHandler<'a, D> {
     }
 
     pub fn effect(&mut self, effect: Effect<D>) {
-        self.add_event(Event::Effect(effect));
+        if let Some(batch) = &mut self.effect_batch {
+            batch.push(effect);
+        } else {
+            self.add_event(Event::Effect(effect));
+        }
     }
 
     pub fn effects(&mut self, mut effects: Vec<Effect<D>>) {
+        if let Some(batch) = &mut self.effect_batch {
+            batch.append(&mut effects);
+            return;
+        }
         match effects.len() {
             0 => return,
             1 => self.effect(effects.pop().unwrap()),
-            _ => {
-                match effects.try_into() {
-                    Ok(effects) => self.add_event(Event::Effects(effects)),
-                    Err(_) => self.effect_glitch(),
-                };
+            // too many to export
+            len if len > AreaVec::<Effect<D>>::max_len(self.environment().map_size) => {
+                self.effect_glitch()
             }
+            _ => self.add_event(Event::Effects(effects.try_into().unwrap())),
         }
     }
 
@@ -419,7 +451,24 @@ impl<'a, D: Direction> EventHandler<'a, D> {
     }
 
     pub fn effect_fog_surprise(&mut self, position: Point) {
-        self.add_event(Event::Effect(Effect::new_fog_surprise(position)));
+        self.effect(Effect::new_fog_surprise(position));
+    }
+
+    /**
+     * effects are collected instead of sent, until end_effect_batch sends them as one event.
+     * effects don't change the game, so delaying them is safe.
+     * lets clients animate e.g. several units moving at the same time
+     */
+    pub fn start_effect_batch(&mut self) {
+        if self.effect_batch.is_none() {
+            self.effect_batch = Some(Vec::new());
+        }
+    }
+
+    pub fn end_effect_batch(&mut self) {
+        if let Some(effects) = self.effect_batch.take() {
+            self.effects(effects);
+        }
     }
 
     pub fn unit_set_hero(&mut self, position: Point, hero: Hero) {
@@ -522,6 +571,47 @@ impl<'a, D: Direction> EventHandler<'a, D> {
         }
     }
 
+    pub fn set_player_clock(&mut self, owner_id: i8, clock: PlayerClock) {
+        if let Some(before) = self
+            .get_game()
+            .get_owning_player(owner_id)
+            .and_then(|player| player.clock)
+            .filter(|before| *before != clock)
+        {
+            self.add_event(Event::PlayerClock(Owner(owner_id), before, clock));
+        }
+    }
+
+    /**
+     * time the server measured for the current player.
+     * if that's all the time the player had left, the TimeControl's TimeoutPolicy is applied
+     */
+    pub fn spend_time(&mut self, time: ClockTime) {
+        let player = self.get_game().current_player();
+        let owner_id = player.get_owner_id();
+        let Some(clock) = player.clock else {
+            return;
+        };
+        match clock.spend(time) {
+            Some(clock) => self.set_player_clock(owner_id, clock),
+            None => self.time_out(),
+        }
+    }
+
+    /// the current player ran out of time
+    pub fn time_out(&mut self) {
+        let Some(time_control) = self.get_game().time_control() else {
+            return;
+        };
+        let owner_id = self.get_game().current_owner();
+        self.set_player_clock(owner_id, PlayerClock::OUT_OF_TIME);
+        match time_control.on_timeout {
+            TimeoutPolicy::EndTurn => self.end_turn(),
+            // also ends the turn
+            TimeoutPolicy::Defeat => self.player_dies(owner_id),
+        }
+    }
+
     pub fn set_player_flag(&mut self, owner_id: i8, flag: usize) {
         if self
             .get_game()
@@ -807,7 +897,7 @@ impl<'a, D: Direction> EventHandler<'a, D> {
                 first_argument.insert(CONST_NAME_POSITION.into(), Dynamic::from(p));
                 first_argument.insert(CONST_NAME_TOKEN.into(), Dynamic::from(token));
                 let executor = self.executor(first_argument);
-                match executor.run::<D, ()>(function_index, ()) {
+                match executor.run::<D, ()>(ScriptHook::TokenEvent, function_index, ()) {
                     Ok(()) => (),
                     Err(e) => {
                         environment.log_rhai_error(
@@ -954,7 +1044,7 @@ impl<'a, D: Direction> EventHandler<'a, D> {
         first_argument.insert(CONST_NAME_INTERRUPTED.into(), Dynamic::from(interrupted));
         let executor = self.executor(first_argument);
         for function_index in scripts {
-            match executor.run::<D, ()>(function_index, ()) {
+            match executor.run::<D, ()>(ScriptHook::UnitEvent, function_index, ()) {
                 Ok(()) => (),
                 Err(e) => {
                     environment.log_rhai_error(
@@ -1234,7 +1324,7 @@ impl<'a, D: Direction> EventHandler<'a, D> {
             }
             for (function_index, first_argument) in scripts {
                 let executor = self.executor(first_argument);
-                match executor.run::<D, ()>(function_index, ()) {
+                match executor.run::<D, ()>(ScriptHook::GlobalEvent, function_index, ()) {
                     Ok(()) => (),
                     Err(e) => {
                         let environment = self.environment();
@@ -1243,6 +1333,11 @@ impl<'a, D: Direction> EventHandler<'a, D> {
                             environment.get_rhai_function_name(function_index),
                             &e,
                         );
+                        if e.is_limit_exceeded() {
+                            // the script would most likely exceed its limits for the other targets too
+                            self.effect_glitch();
+                            break;
+                        }
                     }
                 }
             }
@@ -1282,6 +1377,7 @@ impl<'a, D: Direction> EventHandler<'a, D> {
         while let Some(event) = self.events.get_mut(&IPerspective::Server).unwrap().pop() {
             event.undo(self.game);
         }
+        *self.game.get_rng_mut() = self.rng_backup.clone();
     }
 
     pub fn executor<'b>(&'b mut self, mut first_argument: rhai::Map) -> Executor<'b> {
@@ -5,13 +5,14 @@ use zipper::*;
 
 use crate::commander::commander_type::CommanderChargeChange;
 use crate::config::environment::Environment;
+use crate::game::clock::PlayerClock;
 use crate::game::fog::*;
 use crate::game::game::*;
 use crate::map::board::{Board, BoardView};
 use crate::map::direction::Direction;
 use crate::map::map::FieldData;
 use crate::map::point::Point;
-use crate::map::point_map;
+use crate::map::point_map::AreaVec;
 use crate::tags::*;
 use crate::terrain::terrain::*;
 use crate::tokens::token::Token;
@@ -75,22 +76,16 @@ pub enum Event<D: Direction> {
     NextTurn,
     GameEnds,
     // fog events
-    PureFogChange(
-        Perspective,
-        LVec<(Point, FogIntensity, FogIntensity), { point_map::MAX_AREA }>,
-    ),
+    PureFogChange(Perspective, AreaVec<(Point, FogIntensity, FogIntensity)>),
     FogChange(
         Perspective,
-        LVec<
-            (
-                Point,
-                FogIntensity,
-                FieldData<D>,
-                FogIntensity,
-                FieldData<D>,
-            ),
-            { point_map::MAX_AREA },
-        >,
+        AreaVec<(
+            Point,
+            FogIntensity,
+            FieldData<D>,
+            FogIntensity,
+            FieldData<D>,
+        )>,
     ),
     // player events
     PlayerDies(Owner),
@@ -98,6 +93,8 @@ pub enum Event<D: Direction> {
     PlayerSetTag(Owner, TagKeyValues<1, D>),
     PlayerRemoveTag(Owner, TagKeyValues<1, D>),
     PlayerReplaceTag(Owner, TagKeyValues<2, D>),
+    /// clock before and after the change
+    PlayerClock(Owner, PlayerClock, PlayerClock),
     // commander events
     CommanderCharge(Owner, CommanderChargeChange),
     CommanderPowerIndex(Owner, U<31>, U<31>),
@@ -134,7 +131,7 @@ pub enum Event<D: Direction> {
     ),
     // visual
     Effect(Effect<D>),
-    Effects(LVec<Effect<D>, { point_map::MAX_AREA }>),
+    Effects(AreaVec<Effect<D>>),
 }
 
 impl<D: Direction> Event<D> {
@@ -206,6 +203,9 @@ impl<D: Direction> Event<D> {
                     .unwrap()
                     .remove_tag(key.0);
             }
+            Self::PlayerClock(owner, _, clock) => {
+                game.get_owning_player_mut(owner.0).unwrap().clock = Some(*clock);
+            }
             // commander
             Self::CommanderCharge(owner, delta) => {
                 game.get_owning_player_mut(owner.0)
@@ -404,6 +404,9 @@ impl<D: Direction> Event<D> {
                     value.clone(),
                 );
             }
+            Self::PlayerClock(owner, clock, _) => {
+                game.get_owning_player_mut(owner.0).unwrap().clock = Some(*clock);
+            }
             // commander
             Self::CommanderCharge(owner, delta) => {
                 game.get_owning_player_mut(owner.0)
@@ -569,7 +572,7 @@ impl<D: Direction> Event<D> {
             // fog
             Self::PureFogChange(t, points) => {
                 if to_client_perspective(t) == team {
-                    let mut changes = LVec::new();
+                    let mut changes = AreaVec::new();
                     for (p, intensity_before, intensity) in points.iter() {
                         let fd = FieldData::game_field(game, *p);
                         changes.push((
@@ -589,7 +592,7 @@ impl<D: Direction> Event<D> {
                 );
             }
             // player
-            Self::PlayerDies(_) => result.push(self.clone()),
+            Self::PlayerDies(_) | Self::PlayerClock(_, _, _) => result.push(self.clone()),
             Self::PlayerFlag(owner, FlagKey(key)) => {
                 if team == game.get_team(owner.0)
                     || game.get_fog_setting().intensity() <= FogIntensity::NormalVision
@@ -1,11 +1,13 @@
 use rustc_hash::{FxHashMap as HashMap, FxHashSet as HashSet};
 use std::error::Error;
+use std::time::Duration;
 use uniform_smart_pointer::Urc;
 
 use interfaces::*;
 use semver::Version;
 use zipper::*;
 
+use crate::combat::{AttackPreview, Threat, expected_damage};
 use crate::config::config::Config;
 use crate::config::environment::Environment;
 use crate::game::events;
@@ -18,14 +20,19 @@ use crate::map::pipe::PipeState;
 use crate::map::point::Point;
 use crate::map::point_map::MapSize;
 use crate::map::wrapping_map::WrappingMap;
+use crate::script::diagnostics::ScriptFailure;
+use crate::script::profiler::ScriptProfile;
 use crate::terrain::terrain::Terrain;
 use crate::tokens::token::Token;
+use crate::units::commands::UnitCommand;
 use crate::units::unit::*;
 use crate::{VERSION, player::*};
 
+use super::clock::TimeControl;
 use super::commands::{Command, CommandError};
 use super::event_handler;
 use super::events::Event;
+use super::rng::GameRng;
 use super::settings::{GameConfig, GameSettings};
 
 #[derive(Debug, Clone, PartialEq)]
@@ -37,10 +44,16 @@ pub struct Game<D: Direction> {
     pub players: LVec<Player<D>, 16>,
     fog_mode: FogMode,
     fog: HashMap<ClientPerspective, HashMap<Point, FogIntensity>>,
+    // only meaningful on the server, clients never receive the real state
+    rng: GameRng,
+    // false for saves from before the rng state was exported, see execute_command
+    rng_seeded: bool,
+    // the rng's state before the GameSettings were built, see rng_seed
+    seed: Option<u64>,
 }
 
 impl<D: Direction> Game<D> {
-    fn new(mut map: Map<D>, config: &GameConfig<D>, settings: GameSettings) -> Self {
+    fn new(mut map: Map<D>, config: &GameConfig<D>, settings: GameSettings, rng: GameRng) -> Self {
         *map.get_tag_bag_mut() = config.tags.clone();
         map.start_game(&Urc::new(settings));
         let settings = map.environment().settings.as_ref().unwrap();
@@ -59,17 +72,24 @@ impl<D: Direction> Game<D> {
             players: players.try_into().unwrap(),
             map,
             fog_mode,
+            rng,
+            rng_seeded: true,
+            seed: None,
         }
     }
 
+    /**
+     * settings should be built with the same rng before it's passed here
+     * so that the whole game can be recreated from the rng's initial seed
+     */
     pub fn new_server(
         map: Map<D>,
         config: &GameConfig<D>,
         settings: GameSettings,
-        random: RandomFn,
+        rng: GameRng,
     ) -> (Self, EventsMap<D>) {
-        let mut this = Self::new(map, config, settings);
-        let events = this.start_server(random);
+        let mut this = Self::new(map, config, settings, rng);
+        let events = this.start_server();
         (this, events)
     }
 
@@ -79,7 +99,7 @@ impl<D: Direction> Game<D> {
         settings: GameSettings,
         events: &[events::Event<D>],
     ) -> Self {
-        let mut this = Self::new(map, config, settings);
+        let mut this = Self::new(map, config, settings, GameRng::default());
         for e in events {
             e.apply(&mut this);
         }
@@ -99,8 +119,8 @@ impl<D: Direction> Game<D> {
         Self::import_client(exported.public, secret, &self.environment().config, version).unwrap()
     }
 
-    fn start_server(&mut self, random: RandomFn) -> EventsMap<D> {
-        let mut handler = event_handler::EventHandler::new(self, random);
+    fn start_server(&mut self) -> EventsMap<D> {
+        let mut handler = event_handler::EventHandler::new(self);
         handler.start_turn(None);
         handler.accept()
     }
@@ -112,28 +132,34 @@ impl<D: Direction> Game<D> {
     ) -> Result<Self, ZipperError> {
         if let Some(mut hidden_data) = data.hidden {
             let mut unzipper = Unzipper::new(hidden_data.server, version.clone());
-            let mut game = import_game_base(&mut unzipper, config)?;
-
-            let points = game.map.all_points();
-            game.fog.insert(
-                ClientPerspective::Neutral,
-                import_fog(&mut unzipper, &points)?,
-            );
-
-            for team in game.get_living_teams() {
-                if let Some(data) = hidden_data.teams.remove(&(team)) {
-                    let mut unzipper = Unzipper::new(data, version.clone());
-                    game.fog.insert(
-                        ClientPerspective::Team(team),
-                        import_fog(&mut unzipper, &points)?,
-                    );
+            let mut game = import_game_base(&mut unzipper, config, &version)?;
+
+            // without fog, the hidden data only contains the rng
+            if game.has_secrets() {
+                let points = game.map.all_points();
+                game.fog.insert(
+                    ClientPerspective::Neutral,
+                    import_fog(&mut unzipper, &points)?,
+                );
+
+                for team in game.get_living_teams() {
+                    if let Some(data) = hidden_data.teams.remove(&(team)) {
+                        let mut unzipper = Unzipper::new(data, version.clone());
+                        game.fog.insert(
+                            ClientPerspective::Team(team),
+                            import_fog(&mut unzipper, &points)?,
+                        );
+                    }
                 }
             }
 
+            import_rng(&mut game, &mut unzipper);
             Ok(game)
         } else {
-            let mut unzipper = Unzipper::new(data.public, version);
-            let game = import_game_base(&mut unzipper, config)?;
+            // older saves without fog have the rng in the public data
+            let mut unzipper = Unzipper::new(data.public, version.clone());
+            let mut game = import_game_base(&mut unzipper, config, &version)?;
+            import_rng(&mut game, &mut unzipper);
             Ok(game)
         }
     }
@@ -145,7 +171,7 @@ impl<D: Direction> Game<D> {
         version: Version,
     ) -> Result<Game<D>, ZipperError> {
         let mut unzipper = Unzipper::new(public, version.clone());
-        let mut game = import_game_base(&mut unzipper, config)?;
+        let mut game = import_game_base(&mut unzipper, config, &version)?;
         let points = game.map.all_points();
         let neutral_fog = if game.has_secrets() {
             import_fog(&mut unzipper, &points)?
@@ -153,7 +179,7 @@ impl<D: Direction> Game<D> {
             HashMap::default()
         };
         if let Some((team, team_view)) = team_view {
-            let mut unzipper = Unzipper::new(team_view, version);
+            let mut unzipper = Unzipper::new(team_view, version.clone());
             let fog = import_fog(&mut unzipper, &points)?;
             for p in &points {
                 if fog.get(p).cloned().unwrap_or(FogIntensity::TrueSight)
@@ -172,7 +198,7 @@ impl<D: Direction> Game<D> {
             let mut players: Vec<Player<D>> = vec![];
             for player in game.players.iter() {
                 players.push(if player.get_team() == ClientPerspective::Team(team) {
-                    Player::import(&mut unzipper, &game.environment)?
+                    Player::import(&mut unzipper, &game.environment, &version)?
                 } else {
                     player.clone()
                 });
@@ -213,6 +239,14 @@ impl<D: Direction> Game<D> {
         &self.players[self.current_turn as usize % self.players.len()]
     }
 
+    /// None if players can take as much time as they want
+    pub fn time_control(&self) -> Option<TimeControl> {
+        self.environment
+            .settings
+            .as_ref()
+            .and_then(|settings| settings.time_control)
+    }
+
     pub fn current_team(&self) -> ClientPerspective {
         self.current_player().get_team()
     }
@@ -281,12 +315,44 @@ impl<D: Direction> Game<D> {
         }
     }
 
-    pub fn handle_command(
+    pub fn get_rng(&self) -> &GameRng {
+        &self.rng
+    }
+
+    pub(crate) fn get_rng_mut(&mut self) -> &mut GameRng {
+        &mut self.rng
+    }
+
+    /**
+     * the seed GameCreation::replay needs to recreate this game.
+     * None if the game wasn't started through GameCreation or was imported from an older save
+     */
+    pub fn rng_seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    pub(crate) fn set_rng_seed(&mut self, seed: u64) {
+        self.seed = Some(seed);
+    }
+
+    /**
+     * commands received from players.
+     * unlike handle_command, this rejects the commands only the server may send
+     */
+    pub fn handle_player_command(
         &mut self,
         command: Command<D>,
-        random: RandomFn,
     ) -> Result<EventsMap<D>, CommandError> {
-        let mut handler = event_handler::EventHandler::new(self, random);
+        if command.is_server_only() {
+            return Err(CommandError::ServerOnlyCommand);
+        }
+        self.handle_command(command)
+    }
+
+    pub fn handle_command(&mut self, command: Command<D>) -> Result<EventsMap<D>, CommandError> {
+        self.environment
+            .set_script_profiling_turn(self.current_turn());
+        let mut handler = event_handler::EventHandler::new(self);
         match command.execute(&mut handler) {
             Ok(()) => Ok(handler.accept()),
             Err(err) => {
@@ -296,12 +362,117 @@ impl<D: Direction> Game<D> {
         }
     }
 
+    /**
+     * every command the current player could send right now.
+     * uses the same fog-redacted view that Command::execute validates against
+     */
+    pub fn legal_commands(&self) -> impl Iterator<Item = Command<D>> {
+        if self.has_ended() {
+            return Vec::new().into_iter();
+        }
+        let board = Board::from(self);
+        let team = ClientPerspective::Team(self.current_owner() as u8);
+        Command::legal_commands(&board.fog_view(team)).into_iter()
+    }
+
+    /**
+     * executes the command on a copy of the game as seen by the current player
+     * and reports what happens to every affected unit. units hidden by fog aren't included
+     */
+    pub fn preview_attack(
+        &self,
+        unit_command: &UnitCommand<D>,
+    ) -> Result<AttackPreview<D>, CommandError> {
+        let mut scratch = if self.has_secrets() {
+            self.reimport_as_client(ClientPerspective::Team(self.current_owner() as u8))
+        } else {
+            self.clone()
+        };
+        AttackPreview::new(&mut scratch, unit_command)
+    }
+
+    /**
+     * every field that enemies of `team` could attack during their next turn.
+     * expected damage is found by executing every direct attack on a unit of `team`
+     * on a copy of the game as seen by `team`, so it includes what scripts add on top of the base damage
+     */
+    pub fn threat_map(&self, team: ClientPerspective) -> HashMap<Point, Vec<Threat<D>>> {
+        let mut threats = Board::from(self).threat_map(team);
+        let mut scratch = if self.has_secrets() {
+            self.reimport_as_client(team)
+        } else {
+            self.clone()
+        };
+        for (target, threats) in threats.iter_mut() {
+            for threat in threats {
+                threat.expected_damage = expected_damage(&mut scratch, team, *target, threat);
+            }
+        }
+        threats
+    }
+
+    /**
+     * Script calls that failed since the last call of this function,
+     * so the server can report broken rulesets to their authors.
+     * Only the first MAX_SCRIPT_FAILURES are kept, the rest are only counted.
+     * Returns the kept failures and how many were dropped
+     */
+    pub fn take_script_failures(&self) -> (Vec<ScriptFailure>, usize) {
+        self.environment.take_script_failures()
+    }
+
+    pub fn dropped_script_failures(&self) -> usize {
+        self.environment.dropped_script_failures()
+    }
+
+    /**
+     * Counts and times script calls per turn, hook and function until
+     * disable_script_profiling is called. Clones of this game share the profiler
+     */
+    pub fn enable_script_profiling(&self) {
+        self.environment.enable_script_profiling();
+        self.environment
+            .set_script_profiling_turn(self.current_turn());
+    }
+
+    pub fn disable_script_profiling(&self) -> Option<ScriptProfile> {
+        self.environment.disable_script_profiling()
+    }
+
+    /// what has been recorded so far, None if profiling isn't enabled
+    pub fn script_profile(&self) -> Option<ScriptProfile> {
+        self.environment.script_profile()
+    }
+
+    /**
+     * Script calls that take longer than this are stopped and treated like calls that
+     * exceeded one of the ruleset's ScriptLimits, so the command continues with the fallback value.
+     * Only meant for servers, since the outcome depends on the speed of the host.
+     * Clients apply the server's events and never check the time. None by default.
+     * Clones of this game share the limit, imported games don't
+     */
+    pub fn set_script_time_limit(&self, time_limit: Option<Duration>) {
+        self.environment.set_script_time_limit(time_limit);
+    }
+
     pub fn undo(&mut self, events: &[events::Event<D>]) {
         for event in events.iter().rev() {
             event.undo(self);
         }
     }
 
+    // without fog, this is also what clients receive
+    fn zip_server_perspective(&self, zipper: &mut Zipper) {
+        self.zip(zipper, None);
+        zipper.write_u32(self.current_turn, 32);
+        zipper.write_bool(self.ended);
+        self.fog_mode.zip(zipper);
+        zipper.write_u8(self.players.len() as u8 - 1, 4);
+        for player in self.players.iter() {
+            player.export(zipper, &self.environment);
+        }
+    }
+
     fn zip(&self, zipper: &mut Zipper, fog: Option<&HashMap<Point, FogIntensity>>) {
         zipper.write_bool(D::is_hex());
         let environment = self.environment();
@@ -378,13 +549,14 @@ fn create_base_fog<D: Direction>(
 fn import_game_base<D: Direction>(
     unzipper: &mut Unzipper,
     config: &Urc<Config>,
+    version: &Version,
 ) -> Result<Game<D>, ZipperError> {
     // is_hex: skip because at this point we already know
     unzipper.read_bool()?;
     let mut environment = Environment::new_game(
         config.clone(),
         MapSize::new(0, 0),
-        GameSettings::import(unzipper, config.clone())?,
+        GameSettings::import(unzipper, config.clone(), version)?,
     );
     let map = Map::<D>::import_from_unzipper(unzipper, &mut environment)?;
     let current_turn = unzipper.read_u32(32)?;
@@ -393,7 +565,7 @@ fn import_game_base<D: Direction>(
     let player_len = unzipper.read_u8(4)? + 1;
     let mut players = vec![];
     for _ in 0..player_len {
-        players.push(Player::import(unzipper, &environment)?);
+        players.push(Player::import(unzipper, &environment, version)?);
     }
     Ok(Game {
         fog: create_base_fog(&map, &players),
@@ -403,9 +575,25 @@ fn import_game_base<D: Direction>(
         fog_mode,
         players: players.try_into().unwrap(),
         environment,
+        rng: GameRng::default(),
+        rng_seeded: true,
+        seed: None,
     })
 }
 
+fn import_rng<D: Direction>(game: &mut Game<D>, unzipper: &mut Unzipper) {
+    match GameRng::unzip(unzipper) {
+        Ok(rng) => game.rng = rng,
+        // saves from before the rng state was exported don't contain it
+        Err(_) => game.rng_seeded = false,
+    }
+    // neither do older saves contain the seed
+    game.seed = Option::<GameRng>::unzip(unzipper)
+        .ok()
+        .flatten()
+        .map(|seed| seed.state());
+}
+
 impl<D: Direction> BoardView<D> for Game<D> {
     fn environment(&self) -> &Environment {
         &self.environment
@@ -462,15 +650,24 @@ impl<D: Direction> GameInterface for Game<D> {
         self.map.height() as usize
     }
 
+    /**
+     * the server sends SpendTime and Timeout through Game::handle_command instead.
+     * random is ignored and the game's own rng is used instead, otherwise the result couldn't be reproduced.
+     * only saves from before the rng state was exported use random once, to seed the game's rng
+     */
     fn execute_command(
         &mut self,
         command: Vec<u8>,
         random: RandomFn,
     ) -> Result<Events, Box<dyn Error>> {
+        if !self.rng_seeded {
+            self.rng = GameRng::from_random(&random);
+            self.rng_seeded = true;
+        }
         let environment = self.environment.clone();
         let mut unzipper = Unzipper::new(command, Version::parse(VERSION).unwrap());
         let command = Command::import(&mut unzipper, &environment)?;
-        match self.handle_command(command, random) {
+        match self.handle_player_command(command) {
             Ok(events) => Ok(events.export(&environment)),
             Err(e) => Err(Box::new(e)),
         }
@@ -501,19 +698,14 @@ impl<D: Direction> GameInterface for Game<D> {
     fn export(&self) -> ExportedGame {
         // server perspective
         let mut zipper = Zipper::new();
-        self.zip(&mut zipper, None);
-        zipper.write_u32(self.current_turn, 32);
-        zipper.write_bool(self.ended);
-        self.fog_mode.zip(&mut zipper);
-        zipper.write_u8(self.players.len() as u8 - 1, 4);
-        for player in self.players.iter() {
-            player.export(&mut zipper, &self.environment);
-        }
+        self.zip_server_perspective(&mut zipper);
         if self.has_secrets() {
             let points = self.map.all_points();
             // Server-perspective. only needs neutral fog, the teams' vision is exported later
             let neutral_fog = self.fog.get(&ClientPerspective::Neutral).unwrap();
             export_fog(&mut zipper, &points, neutral_fog);
+            self.rng.zip(&mut zipper);
+            self.seed.map(GameRng::new).zip(&mut zipper);
             let server = zipper.finish();
             // "None" perspective, visible to all
             let mut zipper = Zipper::new();
@@ -563,10 +755,19 @@ impl<D: Direction> GameInterface for Game<D> {
             }
         } else {
             // no need to add fog info to the export
+            // but clients could predict random outcomes with the rng state, so only the server gets it
+            self.rng.zip(&mut zipper);
+            self.seed.map(GameRng::new).zip(&mut zipper);
+            let server = zipper.finish();
+            let mut zipper = Zipper::new();
+            self.zip_server_perspective(&mut zipper);
             let public = zipper.finish();
             ExportedGame {
                 public,
-                hidden: None,
+                hidden: Some(ExportedGameHidden {
+                    server,
+                    teams: std::collections::HashMap::new(),
+                }),
             }
         }
     }
@@ -1,10 +1,13 @@
+pub mod clock;
 pub mod commands;
 pub mod event_fx;
 pub mod event_handler;
 pub mod events;
 pub mod fog;
 pub mod game;
+pub mod replay;
 pub mod rhai_event_handler;
+pub mod rng;
 pub mod settings;
 #[cfg(test)]
 mod test;
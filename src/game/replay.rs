@@ -0,0 +1,300 @@
+use std::fmt::Display;
+
+use interfaces::MapInterface;
+use semver::Version;
+use uniform_smart_pointer::Urc;
+use zipper::*;
+
+use crate::VERSION;
+use crate::config::config::Config;
+use crate::config::environment::Environment;
+use crate::map::board::BoardView;
+use crate::map::direction::Direction;
+use crate::map::map::{GameCreation, Map};
+use crate::map::point_map::MapSize;
+
+use super::commands::{Command, CommandError};
+use super::game::{EventsMap, Game};
+use super::rng::GameRng;
+use super::settings::{GameConfig, PlayerSelectedOptions};
+
+/**
+ * everything needed to recreate a server game:
+ * the GameCreation it was started from, the seed of its rng
+ * and every command it accepted, in order.
+ * unlike an ExportedGame, this shows how the current state was reached.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Replay {
+    /// crate version the replay was recorded with, needed to unzip map and commands
+    pub version: Version,
+    /// Map::export
+    pub map: Vec<u8>,
+    /// GameConfig::export
+    pub settings: Vec<u8>,
+    /// PlayerSelectedOptions::pack, one per player
+    pub player_selection: Vec<Vec<u8>>,
+    /// initial state of the game's rng
+    pub seed: u64,
+    /// zipped Command<D>
+    pub commands: Vec<Vec<u8>>,
+}
+
+impl Replay {
+    pub fn new<D: Direction>(
+        map: &Map<D>,
+        settings: &GameConfig<D>,
+        player_selection: &[PlayerSelectedOptions],
+        seed: u64,
+    ) -> Self {
+        let config = &map.environment().config;
+        Self {
+            version: Version::parse(VERSION).unwrap(),
+            map: map.export(),
+            settings: settings.export(map),
+            player_selection: player_selection
+                .iter()
+                .map(|selection| selection.pack(config))
+                .collect(),
+            seed,
+            commands: Vec::new(),
+        }
+    }
+
+    /**
+     * should only be called for commands the server accepted
+     */
+    pub fn record<D: Direction>(&mut self, command: &Command<D>, environment: &Environment) {
+        let mut zipper = Zipper::new();
+        command.export(&mut zipper, environment);
+        self.commands.push(zipper.finish());
+    }
+
+    pub fn export(&self) -> Vec<u8> {
+        let mut zipper = Zipper::new();
+        write_bytes(&mut zipper, self.version.to_string().as_bytes());
+        GameRng::new(self.seed).zip(&mut zipper);
+        write_bytes(&mut zipper, &self.map);
+        write_bytes(&mut zipper, &self.settings);
+        zipper.write_u8(self.player_selection.len() as u8, 8);
+        for selection in &self.player_selection {
+            write_bytes(&mut zipper, selection);
+        }
+        zipper.write_u32(self.commands.len() as u32, 32);
+        for command in &self.commands {
+            write_bytes(&mut zipper, command);
+        }
+        zipper.finish()
+    }
+
+    pub fn import(bytes: Vec<u8>) -> Result<Self, ReplayError> {
+        // the outer layer doesn't depend on the crate version
+        let mut unzipper = Unzipper::new(bytes, Version::parse(VERSION).unwrap());
+        let version = String::from_utf8(read_bytes(&mut unzipper)?)
+            .ok()
+            .and_then(|version| Version::parse(&version).ok())
+            .ok_or(ReplayError::Version)?;
+        let seed = GameRng::unzip(&mut unzipper)?.state();
+        let map = read_bytes(&mut unzipper)?;
+        let settings = read_bytes(&mut unzipper)?;
+        let mut player_selection = Vec::new();
+        for _ in 0..unzipper.read_u8(8)? {
+            player_selection.push(read_bytes(&mut unzipper)?);
+        }
+        let mut commands = Vec::new();
+        for _ in 0..unzipper.read_u32(32)? {
+            commands.push(read_bytes(&mut unzipper)?);
+        }
+        Ok(Self {
+            version,
+            map,
+            settings,
+            player_selection,
+            seed,
+            commands,
+        })
+    }
+
+    /**
+     * creates the server game like it was at the start of the replay.
+     * also returns the events created when the game started.
+     */
+    pub fn start<D: Direction>(
+        &self,
+        config: &Urc<Config>,
+    ) -> Result<(ReplayPlayer<D>, EventsMap<D>), ReplayError> {
+        let mut unzipper = Unzipper::new(self.map.clone(), self.version.clone());
+        if unzipper.read_bool()? != D::is_hex() {
+            return Err(ReplayError::MapType);
+        }
+        let mut environment = Environment::new_map(config.clone(), MapSize::new(0, 0));
+        let map = Map::<D>::import_from_unzipper(&mut unzipper, &mut environment)?;
+        let settings =
+            GameConfig::import_version(&map, self.settings.clone(), self.version.clone())?;
+        if settings.players.len() != self.player_selection.len() {
+            return Err(ReplayError::PlayerCount(
+                settings.players.len(),
+                self.player_selection.len(),
+            ));
+        }
+        let mut player_selection = Vec::new();
+        for bytes in &self.player_selection {
+            let mut unzipper = Unzipper::new(bytes.clone(), self.version.clone());
+            player_selection.push(PlayerSelectedOptions::import(&mut unzipper, config)?);
+        }
+        let creation = GameCreation {
+            map,
+            settings,
+            player_selection,
+        };
+        let (game, events) = creation.start_server(GameRng::new(self.seed));
+        let player = ReplayPlayer {
+            game,
+            version: self.version.clone(),
+            commands: self.commands.clone(),
+            next_command: 0,
+        };
+        Ok((player, events))
+    }
+
+    /**
+     * the server game after all commands of the replay have been executed
+     */
+    pub fn play<D: Direction>(&self, config: &Urc<Config>) -> Result<Game<D>, ReplayError> {
+        let (mut player, _) = self.start(config)?;
+        while let Some(events) = player.step() {
+            events?;
+        }
+        Ok(player.into_game())
+    }
+}
+
+fn write_bytes(zipper: &mut Zipper, bytes: &[u8]) {
+    zipper.write_u32(bytes.len() as u32, 32);
+    for byte in bytes {
+        zipper.write_u8(*byte, 8);
+    }
+}
+
+fn read_bytes(unzipper: &mut Unzipper) -> Result<Vec<u8>, ZipperError> {
+    let len = unzipper.read_u32(32)?;
+    let mut result = Vec::new();
+    for _ in 0..len {
+        result.push(unzipper.read_u8(8)?);
+    }
+    Ok(result)
+}
+
+/**
+ * executes the commands of a replay one by one
+ */
+pub struct ReplayPlayer<D: Direction> {
+    game: Game<D>,
+    version: Version,
+    commands: Vec<Vec<u8>>,
+    next_command: usize,
+}
+
+impl<D: Direction> ReplayPlayer<D> {
+    pub fn game(&self) -> &Game<D> {
+        &self.game
+    }
+
+    pub fn into_game(self) -> Game<D> {
+        self.game
+    }
+
+    /**
+     * how many commands have been executed so far
+     */
+    pub fn position(&self) -> usize {
+        self.next_command
+    }
+
+    pub fn command_count(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next_command >= self.commands.len()
+    }
+
+    /**
+     * the command that the next call to step will execute
+     */
+    pub fn next_command(&self) -> Option<Result<Command<D>, ReplayError>> {
+        let bytes = self.commands.get(self.next_command)?;
+        let mut unzipper = Unzipper::new(bytes.clone(), self.version.clone());
+        Some(Command::import(&mut unzipper, self.game.environment()).map_err(ReplayError::Zipper))
+    }
+
+    /**
+     * executes the next command and returns the resulting events.
+     * None if there are no commands left.
+     * after an error, the replay can't continue and None is returned from then on.
+     */
+    pub fn step(&mut self) -> Option<Result<EventsMap<D>, ReplayError>> {
+        let command = match self.next_command()? {
+            Ok(command) => command,
+            Err(error) => {
+                self.next_command = self.commands.len();
+                return Some(Err(error));
+            }
+        };
+        let index = self.next_command;
+        match self.game.handle_command(command) {
+            Ok(events) => {
+                self.next_command += 1;
+                Some(Ok(events))
+            }
+            Err(error) => {
+                self.next_command = self.commands.len();
+                Some(Err(ReplayError::Command(index, error)))
+            }
+        }
+    }
+}
+
+impl<D: Direction> Iterator for ReplayPlayer<D> {
+    type Item = Result<EventsMap<D>, ReplayError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.step()
+    }
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Zipper(ZipperError),
+    Version,
+    MapType,
+    PlayerCount(usize, usize),
+    Command(usize, CommandError),
+}
+
+impl From<ZipperError> for ReplayError {
+    fn from(value: ZipperError) -> Self {
+        Self::Zipper(value)
+    }
+}
+
+impl Display for ReplayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Zipper(error) => write!(f, "Replay data is corrupted: {error:?}"),
+            Self::Version => write!(f, "Replay doesn't contain a valid version"),
+            Self::MapType => write!(f, "Replay was recorded on a different map type"),
+            Self::PlayerCount(slots, selections) => {
+                write!(
+                    f,
+                    "{slots} player slots exist, but the replay contains {selections} players"
+                )
+            }
+            Self::Command(index, error) => {
+                write!(f, "Command {index} of the replay was rejected: {error}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
@@ -14,6 +14,7 @@ use crate::map::direction::*;
 use crate::map::map::get_neighbor;
 use crate::map::point::*;
 use crate::map::wrapping_map::OrientedPoint;
+use crate::script::diagnostics::ScriptFailure;
 use crate::tags::*;
 use crate::terrain::terrain::Terrain;
 use crate::tokens::token::Token;
@@ -96,7 +97,7 @@ macro_rules! event_handler_module {
 
             pub fn generate_unique_id(mut handler: Handler, tag: TagKey) -> Dynamic {
                 let handler = handler.as_mut();
-                let environment = handler.environment();
+                let environment = handler.environment().clone();
                 super::UniqueId::new(&environment, tag.0, handler.rng())
                 .map(Dynamic::from)
                 .unwrap_or(().into())
@@ -377,8 +378,9 @@ macro_rules! event_handler_module {
             pub fn effect(mut handler: Handler, effect: Effect<$d>) {
                 handler.as_mut().effect(effect);
             }
-            pub fn effects(mut handler: Handler, effects: Array) {
+            pub fn effects(context: NativeCallContext, mut handler: Handler, effects: Array) {
                 let mut list = Vec::with_capacity(effects.len());
+                let mut invalid = Vec::new();
                 for effect in effects {
                     let effect = match effect.try_cast_result::<Effect<$d>>() {
                         Ok(effect) => {
@@ -387,16 +389,32 @@ macro_rules! event_handler_module {
                         }
                         Err(effect) => effect,
                     };
-                    let _effect = match effect.try_cast_result::<EffectWithoutPosition<$d>>() {
+                    let effect = match effect.try_cast_result::<EffectWithoutPosition<$d>>() {
                         Ok(effect) => {
                             list.push(Effect::Global(effect));
                             continue;
                         }
                         Err(effect) => effect,
                     };
-                    // TODO: log error, add glitch effect (at most one)
+                    invalid.push(effect.type_name().to_string());
+                }
+                let handler = handler.as_mut();
+                handler.effects(list);
+                if invalid.len() > 0 {
+                    let error = format!("expected effects, found {}", invalid.join(", "));
+                    crate::warn!("RHAI error in event_handler.effects: {error}");
+                    let position = context.position();
+                    handler.environment().record_script_failure(ScriptFailure {
+                        location: "event_handler.effects".to_string(),
+                        function: context.fn_name().to_string(),
+                        arguments: invalid,
+                        line: position.line(),
+                        column: position.position(),
+                        error,
+                    });
+                    // at most one glitch, no matter how many effects were invalid
+                    handler.effect_glitch();
                 }
-                handler.as_mut().effects(list);
             }
 
             #[rhai_fn(name = "attack")]
@@ -0,0 +1,60 @@
+use interfaces::RandomFn;
+use zipper::*;
+
+/**
+ * seedable pseudo random number generator (SplitMix64).
+ * its state is part of the server's save data, so a saved game
+ * combined with a list of commands always leads to the same results.
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct GameRng {
+    state: u64,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /**
+     * for callers that only have a RandomFn, e.g. through GameCreationInterface.
+     * the closure is only called to create the seed
+     */
+    pub fn from_random(random: &RandomFn) -> Self {
+        let high = (random() as f64 * u32::MAX as f64) as u64;
+        let low = (random() as f64 * u32::MAX as f64) as u64;
+        Self::new((high << 32) | low)
+    }
+
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /**
+     * uniformly distributed in [0, 1)
+     */
+    pub fn next_f32(&mut self) -> f32 {
+        // f32 has 24 bits of precision
+        (self.next_u64() >> 40) as f32 / (1u32 << 24) as f32
+    }
+}
+
+impl Zippable for GameRng {
+    fn zip(&self, zipper: &mut Zipper) {
+        zipper.write_u32((self.state >> 32) as u32, 32);
+        zipper.write_u32(self.state as u32, 32);
+    }
+    fn unzip(unzipper: &mut Unzipper) -> Result<Self, ZipperError> {
+        let high = unzipper.read_u32(32)? as u64;
+        let low = unzipper.read_u32(32)? as u64;
+        Ok(Self::new((high << 32) | low))
+    }
+}
@@ -12,9 +12,11 @@ use crate::tags::{TagBag, TagValue};
 use crate::units::hero::HeroType;
 use crate::{VERSION, player::*};
 
+use super::clock::{PlayerClock, TimeControl, unzip_clock};
 use super::fog::FogMode;
+use super::rng::GameRng;
+use interfaces::PlayerMeta;
 use interfaces::map_interface::GameSettingsInterface;
-use interfaces::{PlayerMeta, RandomFn};
 use rustc_hash::FxHashSet;
 use semver::Version;
 use uniform_smart_pointer::Urc;
@@ -24,6 +26,8 @@ use zipper_derive::Zippable;
 #[derive(Debug, Clone)]
 pub struct GameConfig<D: Direction> {
     pub fog_mode: FogMode,
+    /// None if players can take as much time as they want
+    pub time_control: Option<TimeControl>,
     pub tags: TagBag<D>,
     pub players: Vec<PlayerConfig<D>>,
 }
@@ -32,28 +36,31 @@ impl<D: Direction> GameConfig<D> {
     pub fn build(
         &self,
         player_selections: &[PlayerSelectedOptions],
-        random: &RandomFn,
+        rng: &mut GameRng,
     ) -> GameSettings {
         GameSettings {
             fog_mode: self.fog_mode.clone(),
+            time_control: self.time_control,
             players: self
                 .players
                 .iter()
                 .enumerate()
-                .map(|(i, p)| p.build(&player_selections[i], random))
+                .map(|(i, p)| p.build(&player_selections[i], rng))
                 .collect(),
         }
     }
 
     pub fn build_default(&self) -> GameSettings {
-        let player_selections: Vec<_> = (0..self.players.len())
-            .map(|_| PlayerSelectedOptions {
-                commander: None,
-                hero: None,
+        // always the first option, so the rng isn't needed
+        let player_selections: Vec<_> = self
+            .players
+            .iter()
+            .map(|player| PlayerSelectedOptions {
+                commander: player.options.commanders.first().cloned(),
+                hero: player.options.heroes.first().cloned(),
             })
             .collect();
-        let random: RandomFn = Urc::new(|| 0.);
-        Self::build(&self, &player_selections, &random)
+        Self::build(&self, &player_selections, &mut GameRng::default())
     }
 
     pub(crate) fn check_player_setting(
@@ -82,7 +89,16 @@ impl<D: Direction> GameConfig<D> {
     }
 
     pub fn import(map: &Map<D>, bytes: Vec<u8>) -> Result<Self, ZipperError> {
-        let mut unzipper = Unzipper::new(bytes, Version::parse(VERSION).unwrap());
+        Self::import_version(map, bytes, Version::parse(VERSION).unwrap())
+    }
+
+    /// for data exported by an older version, e.g. in a replay
+    pub fn import_version(
+        map: &Map<D>,
+        bytes: Vec<u8>,
+        version: Version,
+    ) -> Result<Self, ZipperError> {
+        let mut unzipper = Unzipper::new(bytes, version.clone());
         let fog_mode = FogMode::unzip(&mut unzipper)?;
         let tags = TagBag::import(&mut unzipper, map.environment())?;
         let mut players = Vec::new();
@@ -92,9 +108,11 @@ impl<D: Direction> GameConfig<D> {
         {
             players.push(PlayerConfig::import(&mut unzipper, map.environment())?);
         }
+        let time_control = unzip_clock(&mut unzipper, &version)?;
         unzipper.finish()?;
         Ok(Self {
             fog_mode,
+            time_control,
             tags,
             players,
         })
@@ -111,30 +129,41 @@ impl<D: Direction> GameConfig<D> {
         for p in &self.players {
             p.export(&mut zipper, map.environment());
         }
+        self.time_control.zip(&mut zipper);
         zipper.finish()
     }
 }
 
 impl<D: Direction> PartialEq for GameConfig<D> {
     fn eq(&self, other: &Self) -> bool {
-        self.fog_mode == other.fog_mode && self.tags == other.tags && self.players == other.players
+        self.fog_mode == other.fog_mode
+            && self.time_control == other.time_control
+            && self.tags == other.tags
+            && self.players == other.players
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct GameSettings {
     pub fog_mode: FogMode,
+    pub time_control: Option<TimeControl>,
     pub players: Vec<PlayerSettings>,
 }
 
 impl PartialEq for GameSettings {
     fn eq(&self, other: &Self) -> bool {
-        self.fog_mode == other.fog_mode && self.players == other.players
+        self.fog_mode == other.fog_mode
+            && self.time_control == other.time_control
+            && self.players == other.players
     }
 }
 
 impl GameSettings {
-    pub fn import(unzipper: &mut Unzipper, config: Urc<Config>) -> Result<Self, ZipperError> {
+    pub fn import(
+        unzipper: &mut Unzipper,
+        config: Urc<Config>,
+        version: &Version,
+    ) -> Result<Self, ZipperError> {
         let fog_mode = FogMode::unzip(unzipper)?;
         let mut players = Vec::new();
         for _ in 0..unzipper.read_u8(bits_needed_for_max_value(
@@ -143,7 +172,12 @@ impl GameSettings {
         {
             players.push(PlayerSettings::import(unzipper, &config)?);
         }
-        Ok(Self { fog_mode, players })
+        let time_control = unzip_clock(unzipper, version)?;
+        Ok(Self {
+            fog_mode,
+            time_control,
+            players,
+        })
     }
 
     pub fn export(&self, zipper: &mut Zipper, config: &Config) {
@@ -155,6 +189,7 @@ impl GameSettings {
         for p in &self.players {
             p.export(zipper, config);
         }
+        self.time_control.zip(zipper);
     }
 }
 
@@ -239,7 +274,7 @@ pub struct PlayerConfig<D: Direction> {
 }
 
 impl<D: Direction> PlayerConfig<D> {
-    pub fn new(owner_id: u8, map: &Map<D>, random: &RandomFn) -> Self {
+    pub fn new(owner_id: u8, map: &Map<D>, rng: &mut GameRng) -> Self {
         let mut tags = TagBag::new();
         let config = map.environment().config.clone();
         for tag in 0..config.tag_count() {
@@ -247,7 +282,7 @@ impl<D: Direction> PlayerConfig<D> {
                 tags.set_tag(
                     map.environment(),
                     tag,
-                    TagValue::default_value(map, tag, random()),
+                    TagValue::default_value(map, tag, rng),
                 );
             }
         }
@@ -306,13 +341,14 @@ impl<D: Direction> PlayerConfig<D> {
     pub fn build(
         &self,
         player_selection: &PlayerSelectedOptions,
-        random: &RandomFn,
+        rng: &mut GameRng,
     ) -> PlayerSettings {
         let commander = player_selection.commander.unwrap_or_else(|| {
             if self.options.commanders.len() == 0 {
                 CommanderType(0)
             } else {
-                let index = (self.options.commanders.len() as f32 * random()).floor() as usize;
+                let index =
+                    (self.options.commanders.len() as f32 * rng.next_f32()).floor() as usize;
                 self.options.commanders[index]
             }
         });
@@ -320,7 +356,7 @@ impl<D: Direction> PlayerConfig<D> {
             if self.options.heroes.len() == 0 {
                 HeroType(0)
             } else {
-                let index = (self.options.heroes.len() as f32 * random()).floor() as usize;
+                let index = (self.options.heroes.len() as f32 * rng.next_f32()).floor() as usize;
                 self.options.heroes[index]
             }
         });
@@ -333,11 +369,17 @@ impl<D: Direction> PlayerConfig<D> {
     }
 
     pub fn build_player(&self, environment: &Environment, settings: &PlayerSettings) -> Player<D> {
-        Player::new(
+        let mut player = Player::new(
             self.owner_id.0 as u8,
             self.tags.clone(),
             Commander::new(environment, settings.commander),
-        )
+        );
+        player.clock = environment
+            .settings
+            .as_ref()
+            .and_then(|settings| settings.time_control)
+            .map(|time_control| PlayerClock::new(&time_control));
+        player
     }
 }
 
@@ -414,16 +456,17 @@ impl PlayerSettings {
 
 #[cfg(test)]
 mod tests {
-    use interfaces::RandomFn;
     use semver::Version;
     use uniform_smart_pointer::Urc;
-    use zipper::{SupportedZippable, Unzipper, Zipper};
+    use zipper::{SupportedZippable, Unzipper, Zippable, Zipper, bits_needed_for_max_value};
 
     use crate::VERSION;
     use crate::commander::commander_type::CommanderType;
     use crate::config::config::Config;
     use crate::config::environment::Environment;
+    use crate::game::clock::{ClockTime, TimeControl, TimeoutPolicy};
     use crate::game::fog::{FogMode, FogSetting};
+    use crate::game::rng::GameRng;
     use crate::map::board::BoardView;
     use crate::map::direction::Direction4;
     use crate::map::map::Map;
@@ -467,12 +510,18 @@ mod tests {
         let environment = map.environment().clone();
         let mut tags = TagBag::new();
         tags.set_tag(&environment, 12, TagValue::Direction(Direction4::D90));
-        let random: RandomFn = Urc::new(|| 0.5);
+        let mut rng = GameRng::new(0);
         let setting = GameConfig {
             fog_mode: FogMode::Constant(FogSetting::Sharp(2)),
+            time_control: Some(TimeControl {
+                bank: ClockTime::from_secs(600),
+                increment: ClockTime::from_secs(10),
+                per_turn: ClockTime::ZERO,
+                on_timeout: TimeoutPolicy::Defeat,
+            }),
             tags: TagBag::new(),
             players: vec![
-                PlayerConfig::new(0, &map, &random),
+                PlayerConfig::new(0, &map, &mut rng),
                 PlayerConfig {
                     options: PlayerOptions {
                         commanders: Vec::new(),
@@ -493,6 +542,12 @@ mod tests {
         let config = Urc::new(Config::default());
         let setting = GameSettings {
             fog_mode: FogMode::Constant(FogSetting::Sharp(2)),
+            time_control: Some(TimeControl {
+                bank: ClockTime::ZERO,
+                increment: ClockTime::ZERO,
+                per_turn: ClockTime(90_500),
+                on_timeout: TimeoutPolicy::EndTurn,
+            }),
             players: vec![
                 PlayerSettings::new(0, CommanderType::Celerity, HeroType::CRYSTAL),
                 PlayerSettings::new(3, CommanderType(0), HeroType(0)),
@@ -502,10 +557,65 @@ mod tests {
         setting.export(&mut zipper, &config);
         zipper.write_u8(8, 4);
         let data = zipper.finish();
-        let mut unzipper = Unzipper::new(data, Version::parse(VERSION).unwrap());
+        let version = Version::parse(VERSION).unwrap();
+        let mut unzipper = Unzipper::new(data, version.clone());
         assert_eq!(
             setting,
-            GameSettings::import(&mut unzipper, config.clone()).unwrap()
+            GameSettings::import(&mut unzipper, config.clone(), &version).unwrap()
+        );
+        assert_eq!(8, unzipper.read_u8(4).unwrap())
+    }
+
+    #[test]
+    fn import_settings_without_time_control() {
+        let config = Urc::new(Config::default());
+        let map = PointMap::new(8, 8, false);
+        let map = WMBuilder::<Direction4>::new(map);
+        let map = Map::new(map.build(), &config);
+        let mut rng = GameRng::new(0);
+        let setting = GameConfig {
+            fog_mode: FogMode::Constant(FogSetting::Sharp(2)),
+            time_control: None,
+            tags: TagBag::new(),
+            players: vec![
+                PlayerConfig::new(0, &map, &mut rng),
+                PlayerConfig::new(1, &map, &mut rng),
+            ],
+        };
+        let old_version = Version::new(0, 1, 0);
+        // GameConfig as exported before time controls existed
+        let mut zipper = Zipper::new();
+        setting.fog_mode.zip(&mut zipper);
+        setting.tags.export(&mut zipper, map.environment());
+        zipper.write_u8(
+            (setting.players.len() - 1) as u8,
+            bits_needed_for_max_value(config.max_player_count() as u32 - 1),
+        );
+        for p in &setting.players {
+            p.export(&mut zipper, map.environment());
+        }
+        let bytes = zipper.finish();
+        assert_eq!(
+            Ok(setting.clone()),
+            GameConfig::import_version(&map, bytes, old_version.clone())
+        );
+
+        // GameSettings as exported before time controls existed
+        let settings = setting.build_default();
+        let mut zipper = Zipper::new();
+        settings.fog_mode.zip(&mut zipper);
+        zipper.write_u8(
+            (settings.players.len() - 1) as u8,
+            bits_needed_for_max_value(config.max_player_count() as u32 - 1),
+        );
+        for p in &settings.players {
+            p.export(&mut zipper, &config);
+        }
+        zipper.write_u8(8, 4);
+        let mut unzipper = Unzipper::new(zipper.finish(), old_version.clone());
+        assert_eq!(
+            settings,
+            GameSettings::import(&mut unzipper, config.clone(), &old_version).unwrap()
         );
         assert_eq!(8, unzipper.read_u8(4).unwrap())
     }
@@ -1,22 +1,32 @@
 use crate::VERSION;
 use crate::commander::commander_type::CommanderType;
 use crate::config::config::Config;
-use crate::game::commands::Command;
+use crate::game::clock::{ClockTime, PlayerClock, TimeControl, TimeoutPolicy};
+use crate::game::commands::{Command, CommandError, PathError};
 use crate::game::fog::*;
 use crate::game::game::*;
-use crate::map::board::BoardView;
+use crate::game::replay::Replay;
+use crate::game::rng::GameRng;
+use crate::game::settings::PlayerSelectedOptions;
+use crate::map::board::{Board, BoardView};
 use crate::map::direction::*;
-use crate::map::map::Map;
+use crate::map::map::{GameCreation, Map, valid_points};
 use crate::map::point::*;
 use crate::map::point_map::PointMap;
 use crate::map::wrapping_map::WMBuilder;
 use crate::script::custom_action::CustomActionInput;
+use crate::units::commands::{UnitAction, UnitCommand};
+use crate::units::movement::{Path, PathStep};
 use crate::units::unit_types::UnitType;
 use interfaces::ClientPerspective;
+use interfaces::GameEventsMap;
 use interfaces::Perspective;
+use interfaces::RandomFn;
 use interfaces::game_interface::*;
 use semver::Version;
+use std::time::Duration;
 use uniform_smart_pointer::Urc;
+use zipper::*;
 
 #[test]
 fn export_import_chess() {
@@ -34,7 +44,7 @@ fn export_import_chess() {
             map.clone(),
             &settings,
             settings.build_default(),
-            Urc::new(|| 0.),
+            GameRng::new(42),
         );
         let client = Game::new_client(
             map.clone(),
@@ -47,6 +57,7 @@ fn export_import_chess() {
         let imported_server = Game::import_server(data.clone(), &config, version.clone()).unwrap();
         assert_eq!(server.get_fog(), imported_server.get_fog());
         assert_eq!(server.environment(), imported_server.environment());
+        assert_eq!(server.get_rng(), imported_server.get_rng());
         assert_eq!(server, imported_server);
         assert_eq!(
             client,
@@ -61,6 +72,106 @@ fn export_import_chess() {
     }
 }
 
+#[test]
+fn client_import_has_no_rng() {
+    let version = Version::parse(VERSION).unwrap();
+    let map = crate::map::test::chess_board();
+    let config = map.environment().config.clone();
+    let mut settings = map.settings().unwrap();
+    settings.fog_mode = FogMode::Constant(FogSetting::None);
+    let (server, _) = Game::new_server(
+        map.clone(),
+        &settings,
+        settings.build_default(),
+        GameRng::new(42),
+    );
+    assert_ne!(server.get_rng(), &GameRng::default());
+    let data = server.export();
+    // even without fog, the rng is only in the server's hidden data
+    assert!(data.hidden.is_some());
+    let client = Game::import_client(data.public.clone(), None, &config, version.clone()).unwrap();
+    assert_eq!(client.get_rng(), &GameRng::default());
+    let client = server.reimport_as_client(ClientPerspective::Team(0));
+    assert_eq!(client.get_rng(), &GameRng::default());
+    let imported_server = Game::import_server(data, &config, version).unwrap();
+    assert_eq!(server.get_rng(), imported_server.get_rng());
+}
+
+#[test]
+fn legacy_save_seeds_rng_from_random_fn() {
+    let version = Version::parse(VERSION).unwrap();
+    let map = crate::map::test::chess_board();
+    let config = map.environment().config.clone();
+    let mut settings = map.settings().unwrap();
+    settings.fog_mode = FogMode::Constant(FogSetting::None);
+    let (server, _) = Game::new_server(
+        map.clone(),
+        &settings,
+        settings.build_default(),
+        GameRng::new(42),
+    );
+    let mut data = server.export();
+    // saves from before the rng state was exported don't contain it
+    data.hidden = None;
+    let end_turn = |game: &mut Game<Direction4>, random: f32| {
+        let mut zipper = Zipper::new();
+        Command::<Direction4>::EndTurn.export(&mut zipper, game.environment());
+        let random: RandomFn = Urc::new(move || random);
+        game.execute_command(zipper.finish(), random).unwrap();
+        game.get_rng().clone()
+    };
+    let mut first = Game::import_server(data.clone(), &config, version.clone()).unwrap();
+    let mut second = Game::import_server(data.clone(), &config, version.clone()).unwrap();
+    let rng = end_turn(&mut first, 0.25);
+    assert_ne!(rng, GameRng::default());
+    assert_eq!(end_turn(&mut second, 0.25), rng);
+    // later commands don't use the RandomFn anymore
+    assert_eq!(end_turn(&mut first, 0.25), end_turn(&mut second, 0.75));
+    let mut third = Game::import_server(data, &config, version).unwrap();
+    assert_ne!(end_turn(&mut third, 0.75), rng);
+}
+
+#[test]
+fn fog_view_matches_reimport() {
+    let map = crate::map::test::chess_board();
+    let mut settings = map.settings().unwrap();
+    settings.fog_mode = FogMode::Constant(FogSetting::Sharp(0));
+    let (server, _) = Game::new_server(
+        map.clone(),
+        &settings,
+        settings.build_default(),
+        GameRng::default(),
+    );
+    let board = Board::from(&server);
+    for team in [0, 1] {
+        let perspective = ClientPerspective::Team(team);
+        let client = server.reimport_as_client(perspective);
+        let view = board.fog_view(perspective);
+        for p in valid_points(&server) {
+            assert_eq!(
+                client.get_terrain(p),
+                view.get_terrain(p),
+                "terrain at {p:?}"
+            );
+            assert_eq!(client.get_tokens(p), view.get_tokens(p), "tokens at {p:?}");
+            assert_eq!(client.get_unit(p), view.get_unit(p), "unit at {p:?}");
+            for fog_team in [
+                ClientPerspective::Neutral,
+                ClientPerspective::Team(0),
+                ClientPerspective::Team(1),
+            ] {
+                assert_eq!(client.get_fog_at(fog_team, p), view.get_fog_at(fog_team, p));
+            }
+        }
+        for owner in 0..2 {
+            assert_eq!(
+                client.get_owning_player(owner),
+                view.get_owning_player(owner)
+            );
+        }
+    }
+}
+
 #[test]
 fn changing_visibility() {
     let config = Urc::new(Config::default());
@@ -92,7 +203,7 @@ fn changing_visibility() {
     let mut settings = game_config.build_default();
     settings.players[0].set_commander(CommanderType::Tapio);
     let perspective = Perspective::Team(1);
-    let (mut server, _) = Game::new_server(map.clone(), &game_config, settings, Urc::new(|| 0.));
+    let (mut server, _) = Game::new_server(map.clone(), &game_config, settings, GameRng::default());
     let commander = &mut server.players.get_mut(0).unwrap().commander;
     commander.add_charge(commander.get_max_charge() as i32);
     let mut client: Game<Direction6> = server.reimport_as_client(ClientPerspective::Team(1));
@@ -102,10 +213,10 @@ fn changing_visibility() {
 
     // now create a forest at origin, which changes the unit's visibility
     let events = server
-        .handle_command(
-            Command::commander_power(1, vec![CustomActionInput::Point(origin)]),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::commander_power(
+            1,
+            vec![CustomActionInput::Point(origin)],
+        ))
         .unwrap();
     for ev in events.get(&perspective).unwrap() {
         ev.apply(&mut client);
@@ -115,3 +226,324 @@ fn changing_visibility() {
     let client2: Game<Direction6> = server.reimport_as_client(ClientPerspective::Team(1));
     assert_eq!(client, client2);
 }
+
+#[test]
+fn legal_commands_are_accepted() {
+    let config = Urc::new(Config::default());
+    let map = PointMap::new(5, 5, false);
+    let map = WMBuilder::<Direction4>::new(map);
+    let mut map = Map::new(map.build(), &config);
+    let environment = map.environment().clone();
+    map.set_unit(
+        Point::new(0, 0),
+        Some(
+            UnitType::BAZOOKA
+                .instance(&environment)
+                .set_owner_id(0)
+                .build(),
+        ),
+    );
+    map.set_unit(
+        Point::new(1, 1),
+        Some(
+            UnitType::SMALL_TANK
+                .instance(&environment)
+                .set_owner_id(1)
+                .build(),
+        ),
+    );
+    let game_config = map.settings().unwrap();
+    let mut settings = game_config.build_default();
+    settings.players[0].set_commander(CommanderType::Tapio);
+    let (mut server, _) = Game::new_server(map, &game_config, settings, GameRng::default());
+    let commander = &mut server.players.get_mut(0).unwrap().commander;
+    commander.add_charge(commander.get_max_charge() as i32);
+
+    let commands: Vec<Command<Direction4>> = server.legal_commands().collect();
+    assert!(commands.contains(&Command::EndTurn));
+    assert!(
+        commands
+            .iter()
+            .any(|command| matches!(command, Command::UnitCommand(_)))
+    );
+    assert!(
+        commands
+            .iter()
+            .any(|command| matches!(command, Command::CommanderPower(_, _)))
+    );
+    for command in commands {
+        let mut game = server.clone();
+        game.handle_command(command.clone())
+            .expect(&format!("legal command was rejected: {command:?}"));
+    }
+}
+
+#[test]
+fn command_errors_have_context() {
+    let config = Urc::new(Config::default());
+    let map = PointMap::new(5, 5, false);
+    let map = WMBuilder::<Direction4>::new(map);
+    let mut map = Map::new(map.build(), &config);
+    let environment = map.environment().clone();
+    map.set_unit(
+        Point::new(0, 0),
+        Some(
+            UnitType::BAZOOKA
+                .instance(&environment)
+                .set_owner_id(0)
+                .build(),
+        ),
+    );
+    map.set_unit(
+        Point::new(1, 1),
+        Some(
+            UnitType::SMALL_TANK
+                .instance(&environment)
+                .set_owner_id(1)
+                .build(),
+        ),
+    );
+    let game_config = map.settings().unwrap();
+    let mut settings = game_config.build_default();
+    settings.players[0].set_commander(CommanderType::Tapio);
+    let (mut server, _) = Game::new_server(map, &game_config, settings, GameRng::default());
+
+    let unit_command = |path: Path<Direction4>| {
+        Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path,
+            action: UnitAction::Wait,
+        })
+    };
+    let error = server
+        .handle_command(unit_command(Path::new(Point::new(3, 3))))
+        .unwrap_err();
+    assert_eq!(error, CommandError::MissingUnit(Point::new(3, 3)));
+    assert_eq!(error.code(), "missing_unit");
+    let error = server
+        .handle_command(unit_command(Path::new(Point::new(1, 1))))
+        .unwrap_err();
+    assert_eq!(error, CommandError::NotYourUnit(Point::new(1, 1)));
+    let mut path = Path::new(Point::new(0, 0));
+    path.steps.push(PathStep::Dir(Direction4::D180));
+    let error = server.handle_command(unit_command(path)).unwrap_err();
+    assert_eq!(error, CommandError::InvalidPath(PathError::LeavesMap(0)));
+    assert_eq!(error.code(), "path_leaves_map");
+    let error = server
+        .handle_command(Command::commander_power(1, Vec::new()))
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        CommandError::NotEnoughCharge { available: 0, .. }
+    ));
+    assert_eq!(error.code(), "not_enough_charge");
+}
+
+#[test]
+fn replay_reproduces_game() {
+    let map = crate::map::test::chess_board();
+    let config = map.environment().config.clone();
+    let settings = map.settings().unwrap();
+    let player_selection = vec![
+        PlayerSelectedOptions {
+            commander: None,
+            hero: None,
+        };
+        settings.players.len()
+    ];
+    let creation = GameCreation {
+        map,
+        settings,
+        player_selection,
+    };
+    let mut replay = creation.replay(1234);
+    let (mut server, _) = creation.start_server(GameRng::new(1234));
+    let mut recorded_events = Vec::new();
+    for _ in 0..6 {
+        let command = server
+            .legal_commands()
+            .find(|command| matches!(command, Command::UnitCommand(_)))
+            .unwrap_or(Command::EndTurn);
+        recorded_events.push(server.handle_command(command.clone()).unwrap());
+        replay.record(&command, server.environment());
+        recorded_events.push(server.handle_command(Command::EndTurn).unwrap());
+        replay.record(&Command::EndTurn, server.environment());
+    }
+
+    let replay = Replay::import(replay.export()).unwrap();
+    let (player, _) = replay.start::<Direction4>(&config).unwrap();
+    let replayed_events: Vec<_> = player.map(|events| events.unwrap()).collect();
+    assert_eq!(replayed_events, recorded_events);
+    assert_eq!(replay.play::<Direction4>(&config).unwrap(), server);
+}
+
+#[test]
+fn interface_game_can_be_replayed() {
+    let version = Version::parse(VERSION).unwrap();
+    let map = crate::map::test::chess_board();
+    let config = map.environment().config.clone();
+    let settings = map.settings().unwrap();
+    let creation = || GameCreation {
+        map: map.clone(),
+        settings: settings.clone(),
+        player_selection: vec![
+            PlayerSelectedOptions {
+                commander: None,
+                hero: None,
+            };
+            settings.players.len()
+        ],
+    };
+    let random: RandomFn = Urc::new(|| 0.3);
+    let (mut server, _) = Box::new(creation()).server(random.clone());
+    let mut zipper = Zipper::new();
+    Command::<Direction4>::EndTurn.export(&mut zipper, map.environment());
+    server.execute_command(zipper.finish(), random).unwrap();
+
+    let server: Game<Direction4> = Game::import_server(server.export(), &config, version).unwrap();
+    let mut replay = creation().replay(server.rng_seed().unwrap());
+    replay.record(&Command::EndTurn, server.environment());
+    assert_eq!(replay.play::<Direction4>(&config).unwrap(), server);
+}
+
+fn timed_game(on_timeout: Option<TimeoutPolicy>) -> Game<Direction4> {
+    let config = Urc::new(Config::default());
+    let map = PointMap::new(5, 5, false);
+    let map = WMBuilder::<Direction4>::new(map);
+    let mut map = Map::new(map.build(), &config);
+    let environment = map.environment().clone();
+    for (p, owner) in [(Point::new(0, 0), 0), (Point::new(4, 4), 1)] {
+        map.set_unit(
+            p,
+            Some(
+                UnitType::SMALL_TANK
+                    .instance(&environment)
+                    .set_owner_id(owner)
+                    .build(),
+            ),
+        );
+    }
+    let mut game_config = map.settings().unwrap();
+    game_config.time_control = on_timeout.map(|on_timeout| TimeControl {
+        bank: ClockTime::from_secs(60),
+        increment: ClockTime::from_secs(5),
+        per_turn: ClockTime::from_secs(10),
+        on_timeout,
+    });
+    let settings = game_config.build_default();
+    Game::new_server(map, &game_config, settings, GameRng::default()).0
+}
+
+fn clock(game: &Game<Direction4>, owner: i8) -> PlayerClock {
+    game.get_owning_player(owner).unwrap().clock.unwrap()
+}
+
+#[test]
+fn chess_clock() {
+    let mut server = timed_game(Some(TimeoutPolicy::EndTurn));
+    let start = PlayerClock {
+        bank: ClockTime::from_secs(60),
+        turn: ClockTime::from_secs(10),
+    };
+    assert_eq!(clock(&server, 0), start);
+    assert_eq!(clock(&server, 1), start);
+
+    // the time per turn is used before the bank
+    server
+        .handle_command(Command::SpendTime(ClockTime::from_secs(4)))
+        .unwrap();
+    assert_eq!(clock(&server, 0).turn, ClockTime::from_secs(6));
+    let events = server
+        .handle_command(Command::SpendTime(ClockTime::from_secs(20)))
+        .unwrap();
+    let spent = PlayerClock {
+        bank: ClockTime::from_secs(46),
+        turn: ClockTime::ZERO,
+    };
+    assert_eq!(clock(&server, 0), spent);
+    server.undo(events.get(&Perspective::Server).unwrap());
+    assert_eq!(clock(&server, 0).turn, ClockTime::from_secs(6));
+    server
+        .handle_command(Command::SpendTime(ClockTime::from_secs(20)))
+        .unwrap();
+
+    // the increment is added after the turn
+    server.handle_command(Command::EndTurn).unwrap();
+    assert_eq!(clock(&server, 0).bank, ClockTime::from_secs(51));
+    assert_eq!(clock(&server, 1), start);
+    let client = server.reimport_as_client(ClientPerspective::Team(0));
+    assert_eq!(clock(&client, 0), clock(&server, 0));
+    assert_eq!(clock(&client, 1), clock(&server, 1));
+
+    // only the server can run out a player's time, and only if it's used up
+    assert_eq!(
+        server.handle_player_command(Command::SpendTime(ClockTime::from_secs(70))),
+        Err(CommandError::ServerOnlyCommand)
+    );
+    assert_eq!(
+        server.handle_command(Command::Timeout),
+        Err(CommandError::TimeLeft)
+    );
+    server
+        .handle_command(Command::SpendTime(ClockTime::from_secs(70)))
+        .unwrap();
+    assert_eq!(server.current_turn(), 2);
+    assert!(!server.get_owning_player(1).unwrap().dead);
+    assert_eq!(
+        clock(&server, 1),
+        PlayerClock {
+            bank: ClockTime::from_secs(5),
+            turn: ClockTime::ZERO,
+        }
+    );
+    assert_eq!(clock(&server, 0).turn, ClockTime::from_secs(10));
+
+    // running out of time while spending it works like a timeout
+    let mut server = timed_game(Some(TimeoutPolicy::Defeat));
+    server
+        .handle_command(Command::SpendTime(ClockTime::from_secs(70)))
+        .unwrap();
+    assert!(server.get_owning_player(0).unwrap().dead);
+    assert!(server.has_ended());
+
+    let mut untimed = timed_game(None);
+    assert_eq!(untimed.get_owning_player(0).unwrap().clock, None);
+    assert_eq!(
+        untimed.handle_command(Command::Timeout),
+        Err(CommandError::NoTimeControl)
+    );
+}
+
+#[test]
+fn script_time_limit_doesnt_fail_command() {
+    let mut server = timed_game(None);
+    // every script call that runs long enough to check the clock is stopped
+    server.set_script_time_limit(Some(Duration::ZERO));
+    server.handle_command(Command::EndTurn).unwrap();
+    assert_eq!(server.current_turn(), 1);
+    // clients don't inherit the limit
+    let mut client = server.reimport_as_client(ClientPerspective::Team(1));
+    assert_eq!(client.environment().script_time_limit(), None);
+    assert!(client.handle_command(Command::EndTurn).is_ok());
+
+    server.set_script_time_limit(None);
+    server.handle_command(Command::EndTurn).unwrap();
+    assert_eq!(server.current_turn(), 2);
+}
+
+#[test]
+fn script_profiling_follows_turns() {
+    let mut server = timed_game(None);
+    server.enable_script_profiling();
+    server.handle_command(Command::EndTurn).unwrap();
+    let profile = server.disable_script_profiling().unwrap();
+    // income is paid at the start of the new turn, during the same command
+    let income: Vec<_> = profile
+        .entries
+        .iter()
+        .filter(|entry| entry.function.ends_with("> income"))
+        .collect();
+    assert!(!income.is_empty(), "{profile}");
+    assert!(income.iter().all(|entry| entry.turn == 1), "{profile}");
+    assert!(!server.environment().is_script_profiling());
+}
@@ -1,4 +1,5 @@
 #![feature(mapped_lock_guards)]
+pub mod ai;
 pub mod combat;
 pub mod commander;
 pub mod config;
@@ -86,7 +87,7 @@ macro_rules! listable_enum {(
                 let (base, s) = crate::config::parse::string_base(s);
                 match base {
                     $(stringify!($member) => Ok((Self::$member, s)),)*
-                    _ => Err(crate::config::ConfigParseError::UnknownEnumMember(format!("{}::{base} - {s}", stringify!($name))))
+                    _ => Err(crate::config::ConfigParseError::UnknownEnumMember(format!("{}::{base}", stringify!($name))))
                 }
             }
         }
@@ -1,4 +1,4 @@
-use std::cell::RefCell;
+use std::cell::{OnceCell, RefCell};
 use std::marker::PhantomData;
 use std::ptr::with_exposed_provenance_mut;
 
@@ -9,7 +9,7 @@ use crate::config::environment::Environment;
 use crate::game::fog::{FogIntensity, FogSetting};
 use crate::game::game::Game;
 use crate::map::direction::*;
-use crate::map::map::{Map, get_unit};
+use crate::map::map::{FieldData, Map, get_unit, valid_points};
 use crate::map::pipe::PipeState;
 use crate::map::point::*;
 use crate::map::wrapping_map::WrappingMap;
@@ -55,6 +55,16 @@ pub enum Board<'a, D: Direction> {
     IgnoreUnits {
         base: &'a Self,
     },
+    Fog {
+        base: &'a Self,
+        team: ClientPerspective,
+        /**
+         * redacted lazily, indexed by y * width + x.
+         * empty if the fog doesn't hide anything or base is a client's game, which is already redacted
+         */
+        fields: Vec<OnceCell<FieldData<D>>>,
+        players: Vec<Player<D>>,
+    },
 }
 
 #[derive(Default)]
@@ -116,12 +126,69 @@ impl<'a, D: Direction> Board<'a, D> {
         Self::IgnoreUnits { base: self }
     }
 
+    /**
+     * the board as the given team sees it, like Game::reimport_as_client.
+     * fields are only redacted once they're looked at, so this is a lot cheaper
+     * than exporting and importing the whole game.
+     */
+    pub fn fog_view(&'a self, team: ClientPerspective) -> Self {
+        let hidden = self.get_fog_setting().intensity() != FogIntensity::TrueSight;
+        // clients don't receive the neutral fog, so it's TrueSight everywhere for them.
+        // on the server, fog covers at least some fields that neutral can't see
+        let redact_fields = hidden
+            && valid_points(self).iter().any(|p| {
+                self.get_fog_at(ClientPerspective::Neutral, *p) != FogIntensity::TrueSight
+            });
+        let pointmap = self.wrapping_logic().pointmap();
+        let fields = if redact_fields {
+            let area = pointmap.width() as usize * pointmap.height() as usize;
+            (0..area).map(|_| OnceCell::new()).collect()
+        } else {
+            Vec::new()
+        };
+        let players = (0..self.environment().config.max_player_count())
+            .filter_map(|owner| self.get_owning_player(owner))
+            .map(|player| {
+                if hidden && player.get_team() != team {
+                    player.fog_replacement()
+                } else {
+                    player.clone()
+                }
+            })
+            .collect();
+        Self::Fog {
+            base: self,
+            team,
+            fields,
+            players,
+        }
+    }
+
+    fn fog_field(&self, p: Point) -> Option<&FieldData<D>> {
+        let Self::Fog {
+            base, team, fields, ..
+        } = self
+        else {
+            return None;
+        };
+        let pointmap = base.wrapping_logic().pointmap();
+        if fields.is_empty() || !pointmap.is_point_valid(p) {
+            return None;
+        }
+        let index = p.y as usize * pointmap.width() as usize + p.x as usize;
+        Some(fields[index].get_or_init(|| {
+            let intensity = self.get_fog_at(*team, p);
+            FieldData::game_field(*base, p).fog_replacement(base, p, intensity)
+        }))
+    }
+
     fn parent(&self) -> &dyn BoardView<D> {
         match self {
             Self::Base { base, .. } => *base,
             Self::UnitPath { base, .. } => *base,
             Self::PutUnit { base, .. } => *base,
             Self::IgnoreUnits { base } => *base,
+            Self::Fog { base, .. } => *base,
         }
     }
 
@@ -131,6 +198,7 @@ impl<'a, D: Direction> Board<'a, D> {
             Self::UnitPath { base, .. } => base.limits(),
             Self::PutUnit { base, .. } => base.limits(),
             Self::IgnoreUnits { base } => base.limits(),
+            Self::Fog { base, .. } => base.limits(),
         }
     }
     pub fn get_attack_config_limit(&self) -> Option<usize> {
@@ -177,12 +245,21 @@ impl<'a, D: Direction> BoardView<D> for Board<'a, D> {
         self.parent().get_pipes(p)
     }
     fn get_terrain(&self, p: Point) -> Option<&Terrain<D>> {
+        if let Some(field) = self.fog_field(p) {
+            return Some(&field.terrain);
+        }
         self.parent().get_terrain(p)
     }
     fn get_tokens(&self, p: Point) -> &[Token<D>] {
+        if let Some(field) = self.fog_field(p) {
+            return &field.tokens;
+        }
         self.parent().get_tokens(p)
     }
     fn get_unit(&self, p: Point) -> Option<&Unit<D>> {
+        if let Some(field) = self.fog_field(p) {
+            return field.unit.as_ref();
+        }
         match self {
             Self::UnitPath { start, .. } => {
                 if p == *start {
@@ -204,6 +281,9 @@ impl<'a, D: Direction> BoardView<D> for Board<'a, D> {
         self.parent().current_owner()
     }
     fn get_owning_player(&self, owner: i8) -> Option<&Player<D>> {
+        if let Self::Fog { players, .. } = self {
+            return players.iter().find(|player| player.get_owner_id() == owner);
+        }
         self.parent().get_owning_player(owner)
     }
     fn get_team(&self, owner: i8) -> ClientPerspective {
@@ -214,7 +294,25 @@ impl<'a, D: Direction> BoardView<D> for Board<'a, D> {
         self.parent().get_fog_setting()
     }
     fn get_fog_at(&self, team: ClientPerspective, position: Point) -> FogIntensity {
-        self.parent().get_fog_at(team, position)
+        match self {
+            Self::Fog {
+                base,
+                team: own_team,
+                fields,
+                ..
+            } if !fields.is_empty() => {
+                if team != *own_team {
+                    // a client only knows the vision of its own team
+                    FogIntensity::TrueSight
+                } else {
+                    // like Game::import_client, team vision only counts where it's better than neutral.
+                    // fields are redacted with this intensity too
+                    base.get_fog_at(team, position)
+                        .min(base.get_fog_at(ClientPerspective::Neutral, position))
+                }
+            }
+            _ => self.parent().get_fog_at(team, position),
+        }
     }
 }
 
@@ -0,0 +1,373 @@
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt::Display;
+
+use rustc_hash::FxHashSet as HashSet;
+use zipper::LVec;
+
+use crate::terrain::terrain::Terrain;
+use crate::tokens::token::Token;
+use crate::units::unit::Unit;
+
+use super::board::BoardView;
+use super::direction::Direction;
+use super::map::{FieldData, Map, ResizeAnchor, ResizeError};
+use super::pipe::PipeState;
+use super::point::{GlobalPoint, Point, Position};
+use super::point_map::{MIN_SIZE, MapSize};
+use super::wrapping_map::{Distortion, Transformation, TransformationError, WMBuilder};
+
+/// older steps are forgotten
+pub const MAX_UNDO_STEPS: usize = 200;
+
+/// One editing operation that can be undone as a whole.
+#[derive(Debug, Clone)]
+pub enum EditorCommand<D: Direction> {
+    PaintTerrain(Vec<Point>, Terrain<D>),
+    /// None removes the unit
+    PlaceUnit(Point, Option<Unit<D>>),
+    SetPipes(Point, Vec<PipeState<D>>),
+    AddToken(Point, Token<D>),
+    InsertToken(Point, usize, Token<D>),
+    RemoveToken(Point, usize),
+    /// paints the connected area that has the same terrain type as the given point
+    FillTerrain(Point, Terrain<D>),
+    /**
+     * moves the content of the selected points to their copies under the distortion
+     * around `center`. with `keep_original`, the selection stays as it is (useful for
+     * building symmetric maps), otherwise selected points that don't receive a copy are cleared
+     */
+    TransformSelection {
+        selection: Vec<Point>,
+        center: Point,
+        distortion: Distortion<D>,
+        keep_original: bool,
+    },
+    Resize(u8, u8, ResizeAnchor),
+    TranslateContent(D::T),
+    SetWrapping(Vec<Transformation<D>>),
+}
+
+#[derive(Debug, Clone)]
+enum EditorChange<D: Direction> {
+    // previous content of the changed fields
+    Fields(Vec<(Point, FieldData<D>)>),
+    // changes to size and wrapping replace the whole map
+    Map(Box<Map<D>>),
+}
+
+/**
+ * Applies EditorCommands to a map with undo and redo.
+ * Token stacks are corrected with Map::fix_errors_tokens after every command.
+ */
+#[derive(Debug, Clone)]
+pub struct MapEditor<D: Direction> {
+    map: Map<D>,
+    // the oldest step is dropped from the front once MAX_UNDO_STEPS is exceeded
+    undo: VecDeque<EditorChange<D>>,
+    redo: Vec<EditorChange<D>>,
+}
+
+impl<D: Direction> MapEditor<D> {
+    pub fn new(map: Map<D>) -> Self {
+        Self {
+            map,
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+        }
+    }
+
+    pub fn map(&self) -> &Map<D> {
+        &self.map
+    }
+
+    pub fn into_map(self) -> Map<D> {
+        self.map
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.undo.len() > 0
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.redo.len() > 0
+    }
+
+    /// the map stays unchanged if the command returns an error
+    pub fn apply(&mut self, command: EditorCommand<D>) -> Result<(), EditorError<D>> {
+        let change = match command {
+            EditorCommand::PaintTerrain(points, terrain) => {
+                self.check_points(&points)?;
+                self.change_fields(points, |map, p| map.set_terrain(p, terrain.clone()))
+            }
+            EditorCommand::PlaceUnit(p, unit) => {
+                self.check_points(&[p])?;
+                self.change_fields(vec![p], |map, p| {
+                    map.set_unit(p, unit.clone());
+                })
+            }
+            EditorCommand::SetPipes(p, pipes) => {
+                self.check_points(&[p])?;
+                self.change_fields(vec![p], |map, p| map.set_pipes(p, pipes.clone()))
+            }
+            EditorCommand::AddToken(p, token) => {
+                self.check_points(&[p])?;
+                self.change_fields(vec![p], |map, p| map.add_token(p, token.clone()))
+            }
+            EditorCommand::InsertToken(p, index, token) => {
+                self.check_points(&[p])?;
+                if index > self.map.get_tokens(p).len() {
+                    return Err(EditorError::InvalidTokenIndex(p, index));
+                }
+                self.change_fields(vec![p], |map, p| map.insert_token(p, index, token.clone()))
+            }
+            EditorCommand::RemoveToken(p, index) => {
+                self.check_points(&[p])?;
+                if index >= self.map.get_tokens(p).len() {
+                    return Err(EditorError::InvalidTokenIndex(p, index));
+                }
+                self.change_fields(vec![p], |map, p| {
+                    map.remove_token(p, index);
+                })
+            }
+            EditorCommand::FillTerrain(start, terrain) => {
+                self.check_points(&[start])?;
+                let typ = self.map.get_terrain(start).unwrap().typ();
+                let map = &self.map;
+                let mut same_type = |p: Point| map.get_terrain(p).unwrap().typ() == typ;
+                let region: Vec<Point> = map
+                    .width_search(
+                        start,
+                        Box::new(&mut same_type as &mut dyn FnMut(Point) -> bool),
+                    )
+                    .into_iter()
+                    .collect();
+                self.change_fields(region, |map, p| map.set_terrain(p, terrain.clone()))
+            }
+            EditorCommand::TransformSelection {
+                selection,
+                center,
+                distortion,
+                keep_original,
+            } => {
+                self.check_points(&selection)?;
+                self.transform_selection(selection, center, distortion, keep_original)
+            }
+            EditorCommand::Resize(width, height, anchor) => {
+                let previous = self.map.clone();
+                self.map
+                    .resize(MapSize::new(width, height), anchor)
+                    .map_err(|error| match error {
+                        ResizeError::InvalidSize(_) => EditorError::InvalidSize(width, height),
+                        ResizeError::Transformation(error) => EditorError::Transformation(error),
+                    })?;
+                EditorChange::Map(Box::new(previous))
+            }
+            EditorCommand::TranslateContent(translation) => {
+                self.change_map(|map| map.translate_content(translation))?
+            }
+            EditorCommand::SetWrapping(transformations) => {
+                let point_map = self.map.wrapping_logic().pointmap().clone();
+                let builder = WMBuilder::with_transformations(point_map, transformations)
+                    .map_err(EditorError::Transformation)?;
+                self.change_map(|map| {
+                    map.set_wrapping_logic(builder.build());
+                    Ok(())
+                })?
+            }
+        };
+        let change = self.fix_errors(change);
+        self.undo.push_back(change);
+        if self.undo.len() > MAX_UNDO_STEPS {
+            self.undo.pop_front();
+        }
+        self.redo.clear();
+        Ok(())
+    }
+
+    /// returns false if there's nothing to undo
+    pub fn undo(&mut self) -> bool {
+        let Some(change) = self.undo.pop_back() else {
+            return false;
+        };
+        let change = self.revert(change);
+        self.redo.push(change);
+        true
+    }
+
+    /// returns false if there's nothing to redo
+    pub fn redo(&mut self) -> bool {
+        let Some(change) = self.redo.pop() else {
+            return false;
+        };
+        let change = self.revert(change);
+        self.undo.push_back(change);
+        true
+    }
+
+    // restores the given state and returns the change that restores the current state
+    fn revert(&mut self, change: EditorChange<D>) -> EditorChange<D> {
+        match change {
+            EditorChange::Fields(fields) => {
+                let mut current = Vec::with_capacity(fields.len());
+                for (p, data) in fields.into_iter().rev() {
+                    current.push((p, self.map.get_field_data(p)));
+                    self.map.set_field_data(p, data);
+                }
+                EditorChange::Fields(current)
+            }
+            EditorChange::Map(map) => {
+                EditorChange::Map(Box::new(std::mem::replace(&mut self.map, *map)))
+            }
+        }
+    }
+
+    fn check_points(&self, points: &[Point]) -> Result<(), EditorError<D>> {
+        for p in points {
+            if !self.map.is_point_valid(*p) {
+                return Err(EditorError::InvalidPoint(*p));
+            }
+        }
+        Ok(())
+    }
+
+    fn change_fields(
+        &mut self,
+        points: Vec<Point>,
+        mut f: impl FnMut(&mut Map<D>, Point),
+    ) -> EditorChange<D> {
+        let mut previous = Vec::with_capacity(points.len());
+        let mut changed = HashSet::default();
+        for p in points {
+            if changed.insert(p) {
+                previous.push((p, self.map.get_field_data(p)));
+            }
+            f(&mut self.map, p);
+        }
+        EditorChange::Fields(previous)
+    }
+
+    fn transform_selection(
+        &mut self,
+        selection: Vec<Point>,
+        center: Point,
+        distortion: Distortion<D>,
+        keep_original: bool,
+    ) -> EditorChange<D> {
+        let transformation = Transformation::new(distortion, D::angle_0().translation(0));
+        let center_global = GlobalPoint::new(center.x as i16, center.y as i16);
+        // same as WMBuilder::odd, since the transformation happens around center
+        let odd = self.map.odd_if_hex() == (center.y % 2 == 0);
+        let mut copies = Vec::new();
+        for p in &selection {
+            let image = transformation.transform_point(
+                &GlobalPoint::new(p.x as i16, p.y as i16),
+                &center_global,
+                odd,
+            );
+            if image.x < 0 || image.y < 0 || image.x > u8::MAX as i16 || image.y > u8::MAX as i16 {
+                continue;
+            }
+            let image = Point::new(image.x as u8, image.y as u8);
+            if !self.map.is_point_valid(image) {
+                continue;
+            }
+            let data = self.map.get_field_data(*p);
+            let mut pipes = data.pipes.into_inner();
+            for pipe in &mut pipes {
+                pipe.distort(distortion);
+            }
+            let mut terrain = data.terrain;
+            terrain.distort(distortion);
+            let mut tokens = data.tokens.into_inner();
+            for token in &mut tokens {
+                token.distort(distortion);
+            }
+            let mut unit = data.unit;
+            if let Some(unit) = &mut unit {
+                unit.distort(distortion);
+            }
+            let data = FieldData {
+                pipes: pipes.try_into().unwrap(),
+                terrain,
+                tokens: tokens.try_into().unwrap(),
+                unit,
+            };
+            copies.push((image, data));
+        }
+        let mut points = Vec::new();
+        if !keep_original {
+            let images: HashSet<Point> = copies.iter().map(|(p, _)| *p).collect();
+            points.extend(selection.into_iter().filter(|p| !images.contains(p)));
+        }
+        let cleared = points.len();
+        points.extend(copies.iter().map(|(p, _)| *p));
+        let environment = self.map.environment().clone();
+        let mut copies = copies.into_iter();
+        let mut index = 0;
+        self.change_fields(points, |map, p| {
+            if index < cleared {
+                map.set_field_data(
+                    p,
+                    FieldData {
+                        pipes: LVec::new(),
+                        terrain: environment.default_terrain(),
+                        tokens: LVec::new(),
+                        unit: None,
+                    },
+                );
+            } else if let Some((_, data)) = copies.next() {
+                map.set_field_data(p, data);
+            }
+            index += 1;
+        })
+    }
+
+    fn change_map(
+        &mut self,
+        f: impl FnOnce(&mut Map<D>) -> Result<(), TransformationError<D>>,
+    ) -> Result<EditorChange<D>, EditorError<D>> {
+        let previous = self.map.clone();
+        f(&mut self.map).map_err(EditorError::Transformation)?;
+        Ok(EditorChange::Map(Box::new(previous)))
+    }
+
+    // corrections become part of the change, so they're undone together with the command
+    fn fix_errors(&mut self, mut change: EditorChange<D>) -> EditorChange<D> {
+        for (p, tokens) in self.map.fix_errors_tokens() {
+            if let EditorChange::Fields(fields) = &mut change {
+                if !fields.iter().any(|(changed, _)| *changed == p) {
+                    fields.push((p, self.map.get_field_data(p)));
+                }
+            }
+            self.map.set_tokens(p, tokens);
+        }
+        change
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EditorError<D: Direction> {
+    InvalidPoint(Point),
+    InvalidTokenIndex(Point, usize),
+    InvalidSize(u8, u8),
+    Transformation(TransformationError<D>),
+}
+
+impl<D: Direction> Display for EditorError<D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidPoint(p) => write!(f, "{},{} isn't part of the map", p.x, p.y),
+            Self::InvalidTokenIndex(p, index) => {
+                write!(f, "no token with index {index} at {},{}", p.x, p.y)
+            }
+            Self::InvalidSize(width, height) => write!(
+                f,
+                "can't resize to {width}x{height}, width and height have to be between {MIN_SIZE} and the ruleset's maximum map size"
+            ),
+            Self::Transformation(error) => write!(f, "invalid wrapping: {error:?}"),
+        }
+    }
+}
+
+impl<D: Direction> Error for EditorError<D> {}
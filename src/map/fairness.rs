@@ -0,0 +1,303 @@
+use std::fmt::Display;
+
+use num_rational::Rational32;
+use rustc_hash::{FxHashMap as HashMap, FxHashSet as HashSet};
+
+use crate::config::movement_type_config::MovementPattern;
+use crate::units::movement::{MovementType, Path, movement_area_map};
+use crate::units::unit::Unit;
+
+use super::board::{Board, BoardView};
+use super::direction::Direction;
+use super::generator::centered_orbits;
+use super::map::Map;
+use super::point::Point;
+use super::wrapping_map::Distortion;
+
+/// How quickly one player can reach the map's income-bearing properties.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerReach {
+    pub owner_id: u8,
+    /**
+     * for each movement type, how many turns a unit needs to reach each point,
+     * starting from any of the player's properties or units
+     */
+    pub distances: Vec<(MovementType, HashMap<Point, usize>)>,
+    /// income-bearing properties this player reaches before everyone else
+    pub first: Vec<Point>,
+}
+
+impl PlayerReach {
+    /// turns needed by the fastest movement type
+    pub fn distance(&self, p: Point) -> Option<usize> {
+        self.distances
+            .iter()
+            .filter_map(|(_, distances)| distances.get(&p).cloned())
+            .min()
+    }
+}
+
+/// Compares each field of the map with its copy under the distortion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymmetryCheck<D: Direction> {
+    pub distortion: Distortion<D>,
+    /// valid points whose copy is outside the map
+    pub outline_mismatches: Vec<Point>,
+    pub terrain_mismatches: Vec<Point>,
+    /// starting units without a matching unit at their copy
+    pub unit_mismatches: Vec<Point>,
+    /// (owner, owner of the copy)
+    pub owners: Vec<(i8, i8)>,
+}
+
+impl<D: Direction> SymmetryCheck<D> {
+    fn new(map: &Map<D>, distortion: Distortion<D>) -> Self {
+        let len = D::list().len();
+        let steps = if distortion.is_mirrored() {
+            2
+        } else {
+            let rotation = distortion.get_rotation().list_index();
+            len / gcd(rotation, len)
+        };
+        let orbits = centered_orbits(map.wrapping_logic().pointmap(), distortion, steps);
+        let mut result = Self {
+            distortion,
+            outline_mismatches: Vec::new(),
+            terrain_mismatches: Vec::new(),
+            unit_mismatches: Vec::new(),
+            owners: vec![(-1, -1)],
+        };
+        let covered: HashSet<Point> = orbits.iter().flatten().cloned().collect();
+        for p in map.all_points() {
+            if !covered.contains(&p) {
+                result.outline_mismatches.push(p);
+            }
+        }
+        for orbit in &orbits {
+            for (i, p) in orbit.iter().enumerate() {
+                let copy = orbit[(i + 1) % orbit.len()];
+                let terrain = map.get_terrain(*p).unwrap();
+                let terrain_copy = map.get_terrain(copy).unwrap();
+                if terrain.typ() != terrain_copy.typ()
+                    || !result.map_owner(terrain.get_owner_id(), terrain_copy.get_owner_id())
+                {
+                    result.terrain_mismatches.push(*p);
+                }
+                let units_match = match (map.get_unit(*p), map.get_unit(copy)) {
+                    (None, None) => true,
+                    (Some(unit), Some(unit_copy)) => {
+                        unit.typ() == unit_copy.typ()
+                            && result.map_owner(unit.get_owner_id(), unit_copy.get_owner_id())
+                    }
+                    _ => false,
+                };
+                if !units_match {
+                    result.unit_mismatches.push(*p);
+                }
+            }
+        }
+        result.owners.sort();
+        result
+    }
+
+    // false if the owner was already mapped to someone else or someone else maps to the same owner
+    fn map_owner(&mut self, owner: i8, copy: i8) -> bool {
+        for (o, c) in &self.owners {
+            if (*o == owner) != (*c == copy) {
+                return false;
+            }
+            if *o == owner {
+                return true;
+            }
+        }
+        self.owners.push((owner, copy));
+        true
+    }
+
+    pub fn is_symmetric(&self) -> bool {
+        self.mismatch_count() == 0
+    }
+
+    pub fn mismatch_count(&self) -> usize {
+        self.outline_mismatches.len() + self.terrain_mismatches.len() + self.unit_mismatches.len()
+    }
+
+    /// a symmetry that maps every player onto themselves doesn't say anything about fairness
+    pub fn swaps_players(&self) -> bool {
+        self.owners.iter().any(|(owner, copy)| owner != copy)
+    }
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+/// See Map::analyze_fairness
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FairnessReport<D: Direction> {
+    pub players: Vec<PlayerReach>,
+    /// terrain with a positive base income factor
+    pub income_properties: Vec<Point>,
+    /// income-bearing properties that several players reach equally fast
+    pub contested: Vec<Point>,
+    /// one check for every rotation and mirror except the neutral distortion
+    pub symmetries: Vec<SymmetryCheck<D>>,
+}
+
+impl<D: Direction> FairnessReport<D> {
+    /// the symmetry with the fewest mismatches that maps players onto each other
+    pub fn best_symmetry(&self) -> Option<&SymmetryCheck<D>> {
+        self.symmetries
+            .iter()
+            .filter(|check| check.swaps_players())
+            .min_by_key(|check| check.mismatch_count())
+    }
+
+    pub fn asymmetric_units(&self) -> &[Point] {
+        self.best_symmetry()
+            .map(|check| &check.unit_mismatches[..])
+            .unwrap_or(&[])
+    }
+
+    /// every player reaches the same number of income-bearing properties first
+    pub fn is_reach_balanced(&self) -> bool {
+        self.players
+            .windows(2)
+            .all(|w| w[0].first.len() == w[1].first.len())
+    }
+
+    pub fn is_fair(&self) -> bool {
+        self.is_reach_balanced() && self.best_symmetry().is_some_and(|s| s.is_symmetric())
+    }
+}
+
+impl<D: Direction> Display for FairnessReport<D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for player in &self.players {
+            writeln!(
+                f,
+                "player {} reaches {} of {} income properties first",
+                player.owner_id,
+                player.first.len(),
+                self.income_properties.len()
+            )?;
+        }
+        writeln!(f, "{} contested income properties", self.contested.len())?;
+        match self.best_symmetry() {
+            Some(check) => {
+                writeln!(
+                    f,
+                    "closest symmetry: {:?}, {} mismatches",
+                    check.distortion,
+                    check.mismatch_count()
+                )?;
+                for p in &check.unit_mismatches {
+                    writeln!(f, "asymmetric unit at {},{}", p.x, p.y)?;
+                }
+            }
+            None => writeln!(f, "no symmetry between players")?,
+        }
+        Ok(())
+    }
+}
+
+impl<D: Direction> Map<D> {
+    /**
+     * Checks whether the map favors some players.
+     * Distances are measured in turns, with one unit with standard movement pattern
+     * per movement type. `rounds` limits how many turns ahead the search looks.
+     */
+    pub fn analyze_fairness(&self, rounds: usize) -> FairnessReport<D> {
+        let config = &self.environment().config;
+        let income_properties: Vec<Point> = self
+            .all_points()
+            .into_iter()
+            .filter(|p| {
+                let terrain = self.get_terrain(*p).unwrap();
+                config.terrain_base_income_factor(terrain.typ()) > Rational32::from_integer(0)
+            })
+            .collect();
+        let mut players: Vec<PlayerReach> = self
+            .get_viable_player_ids()
+            .into_iter()
+            .map(|owner_id| PlayerReach {
+                owner_id,
+                distances: self.player_distances(owner_id as i8, rounds),
+                first: Vec::new(),
+            })
+            .collect();
+        let mut contested = Vec::new();
+        for p in &income_properties {
+            let distances: Vec<Option<usize>> = players.iter().map(|pl| pl.distance(*p)).collect();
+            let Some(best) = distances.iter().filter_map(|d| *d).min() else {
+                continue;
+            };
+            let closest: Vec<usize> = (0..players.len())
+                .filter(|i| distances[*i] == Some(best))
+                .collect();
+            if closest.len() == 1 {
+                players[closest[0]].first.push(*p);
+            } else {
+                contested.push(*p);
+            }
+        }
+        let mut symmetries = Vec::new();
+        for mirrored in [false, true] {
+            for rotation in D::list() {
+                if mirrored || rotation != D::angle_0() {
+                    symmetries.push(SymmetryCheck::new(
+                        self,
+                        Distortion::new(mirrored, rotation),
+                    ));
+                }
+            }
+        }
+        FairnessReport {
+            players,
+            income_properties,
+            contested,
+            symmetries,
+        }
+    }
+
+    fn player_distances(
+        &self,
+        owner_id: i8,
+        rounds: usize,
+    ) -> Vec<(MovementType, HashMap<Point, usize>)> {
+        let config = &self.environment().config;
+        let starts: Vec<Point> = self
+            .all_points()
+            .into_iter()
+            .filter(|p| {
+                self.get_terrain(*p).unwrap().get_owner_id() == owner_id
+                    || self
+                        .get_unit(*p)
+                        .is_some_and(|u| u.get_owner_id() == owner_id)
+            })
+            .collect();
+        let board = Board::from(self);
+        let mut result = Vec::new();
+        for movement_type in config.movement_types() {
+            let Some(unit_type) = config.unit_types().into_iter().find(|typ| {
+                config.base_movement_type(*typ) == movement_type
+                    && config.movement_pattern(*typ) == MovementPattern::Standard
+            }) else {
+                continue;
+            };
+            let unit: Unit<D> = unit_type
+                .instance(self.environment())
+                .set_owner_id(owner_id)
+                .build();
+            let mut distances: HashMap<Point, usize> = HashMap::default();
+            for start in &starts {
+                for (p, turns) in movement_area_map(&board, &unit, &Path::new(*start), rounds) {
+                    let distance = distances.entry(p).or_insert(turns);
+                    *distance = (*distance).min(turns);
+                }
+            }
+            result.push((movement_type, distances));
+        }
+        result
+    }
+}
@@ -0,0 +1,483 @@
+use std::error::Error;
+use std::fmt::Display;
+
+use rustc_hash::{FxHashMap as HashMap, FxHashSet as HashSet};
+use uniform_smart_pointer::Urc;
+
+use crate::config::OwnershipPredicate;
+use crate::config::config::Config;
+use crate::game::rng::GameRng;
+use crate::terrain::TerrainType;
+
+use super::direction::{Direction, Translation};
+use super::map::{Map, NeighborMode, NotPlayable};
+use super::point::{GlobalPoint, Point, Position};
+use super::point_map::{MIN_SIZE, PointMap};
+use super::wrapping_map::{Distortion, Transformation, TransformationError, WMBuilder};
+
+/// if a property can't be placed fairly, the generator starts over with new terrain this often
+const MAX_ATTEMPTS: usize = 20;
+
+/**
+ * How the parts of the generated map belonging to different players relate to each other.
+ * Every player's part is a copy of the first player's part, so no player has an advantage.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapSymmetry<D: Direction> {
+    /// only for 2 players, left and right half are mirror images of each other
+    Mirror,
+    /// the map looks the same after rotating it by 360° / player count.
+    /// points whose rotated copies would be outside the map become invalid
+    Rotational,
+    /**
+     * the map wraps around after moving player count times by this translation,
+     * i.e. the WMBuilder gets a transformation that translates by translation * player count.
+     * each player's part is the previous player's part moved by this translation
+     */
+    Wrapping(D::T),
+}
+
+/// a type of property that should be placed on the generated map
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PropertyPlacement {
+    pub terrain: TerrainType,
+    /// how many each player owns at the start of the game
+    pub owned_per_player: usize,
+    /// neutral properties per player. each is closer to its player's home than to any other player's home
+    pub neutral_per_player: usize,
+}
+
+/**
+ * Creates random maps for ladder seasons.
+ * The first owned property (e.g. HQ) is each player's home. All other properties
+ * are placed closer to their player's home than to any other home.
+ */
+#[derive(Debug, Clone)]
+pub struct MapGenerator<D: Direction> {
+    config: Urc<Config>,
+    width: u8,
+    height: u8,
+    player_count: u8,
+    symmetry: MapSymmetry<D>,
+    terrain_weights: Vec<(TerrainType, u32)>,
+    properties: Vec<PropertyPlacement>,
+    min_property_distance: usize,
+}
+
+impl<D: Direction> MapGenerator<D> {
+    pub fn new(config: &Urc<Config>, width: u8, height: u8, player_count: u8) -> Self {
+        Self {
+            config: config.clone(),
+            width,
+            height,
+            player_count,
+            symmetry: MapSymmetry::Rotational,
+            terrain_weights: Vec::new(),
+            properties: Vec::new(),
+            min_property_distance: 2,
+        }
+    }
+
+    pub fn set_symmetry(mut self, symmetry: MapSymmetry<D>) -> Self {
+        self.symmetry = symmetry;
+        self
+    }
+
+    /// unowned terrain is picked randomly with these relative weights
+    pub fn set_terrain_weights(mut self, weights: Vec<(TerrainType, u32)>) -> Self {
+        self.terrain_weights = weights;
+        self
+    }
+
+    /// properties are placed in the order they were added
+    pub fn add_property(mut self, property: PropertyPlacement) -> Self {
+        self.properties.push(property);
+        self
+    }
+
+    /// properties are placed at least this many steps apart from each other
+    pub fn set_min_property_distance(mut self, distance: usize) -> Self {
+        self.min_property_distance = distance.max(1);
+        self
+    }
+
+    fn validate(&self) -> Result<(), GeneratorError<D>> {
+        for size in [self.width, self.height] {
+            if size < MIN_SIZE || size > self.config.max_map_size() {
+                return Err(GeneratorError::InvalidSize(self.config.max_map_size()));
+            }
+        }
+        if self.player_count < 2 || self.player_count as i16 > self.config.max_player_count() as i16
+        {
+            return Err(GeneratorError::InvalidPlayerCount(self.player_count));
+        }
+        if self.terrain_weights.iter().all(|(_, weight)| *weight == 0) {
+            return Err(GeneratorError::NoTerrain);
+        }
+        for property in &self.properties {
+            let ownership = self.config.terrain_ownership(property.terrain);
+            if property.owned_per_player > 0 && ownership == OwnershipPredicate::Never
+                || property.neutral_per_player > 0 && ownership == OwnershipPredicate::Always
+            {
+                let name = self.config.terrain_name(property.terrain).to_string();
+                return Err(GeneratorError::OwnershipMismatch(name));
+            }
+        }
+        if self.properties.iter().all(|p| p.owned_per_player == 0) {
+            return Err(GeneratorError::NotPlayable(NotPlayable::TooFewPlayers));
+        }
+        Ok(())
+    }
+
+    /// the result always passes Map::settings
+    pub fn generate(&self, rng: &mut GameRng) -> Result<Map<D>, GeneratorError<D>> {
+        self.validate()?;
+        let (map, orbits) = self.layout()?;
+        let player_count = self.player_count as usize;
+        // how far each full orbit's first point is from its closest copy
+        let spreads: Vec<Option<usize>> = orbits
+            .iter()
+            .map(|orbit| {
+                if orbit.len() < player_count {
+                    return None;
+                }
+                let distances = distances(&map, orbit[0], usize::MAX);
+                // unreachable copies would be on different islands
+                let spread: Option<Vec<usize>> = orbit[1..]
+                    .iter()
+                    .map(|p| distances.get(p).cloned())
+                    .collect();
+                spread?.into_iter().min()
+            })
+            .collect();
+        for _ in 0..MAX_ATTEMPTS {
+            if let Some(map) = self.attempt(&map, &orbits, &spreads, rng) {
+                map.settings().map_err(GeneratorError::NotPlayable)?;
+                return Ok(map);
+            }
+        }
+        let name = self.config.terrain_name(self.properties[0].terrain);
+        Err(GeneratorError::NoSpace(name.to_string()))
+    }
+
+    /**
+     * the empty map and its points grouped by symmetry.
+     * the n-th point of a full orbit belongs to the n-th player
+     */
+    fn layout(&self) -> Result<(Map<D>, Vec<Vec<Point>>), GeneratorError<D>> {
+        let player_count = self.player_count as usize;
+        let mut point_map = PointMap::new(self.width, self.height, false);
+        let odd = point_map.odd_if_hex();
+        let (wrapping, orbits) = match self.symmetry {
+            MapSymmetry::Wrapping(translation) => {
+                let transformation = Transformation::new(
+                    Distortion::neutral(),
+                    translation.multiply(self.player_count as i16),
+                );
+                let builder = WMBuilder::with_transformations(point_map, vec![transformation])
+                    .map_err(GeneratorError::Wrapping)?;
+                let points = builder.pointmap().get_valid_points();
+                let orbits = find_orbits(&points, player_count, |p| {
+                    let p = translation.translate_point(&global(p), odd);
+                    builder.localize_point(p).map(|(p, _)| p)
+                });
+                if orbits.iter().map(Vec::len).sum::<usize>() < points.len() {
+                    return Err(GeneratorError::UnsupportedSymmetry);
+                }
+                (builder.build(), orbits)
+            }
+            MapSymmetry::Mirror | MapSymmetry::Rotational => {
+                let distortion = match self.symmetry {
+                    MapSymmetry::Mirror if player_count == 2 => Distortion::new(true, D::angle_0()),
+                    MapSymmetry::Rotational if D::list().len() % player_count == 0 => {
+                        Distortion::new(false, D::list()[D::list().len() / player_count])
+                    }
+                    _ => return Err(GeneratorError::UnsupportedSymmetry),
+                };
+                let orbits = centered_orbits(&point_map, distortion, player_count);
+                let valid: HashSet<Point> = orbits.iter().flatten().cloned().collect();
+                for p in point_map.get_valid_points() {
+                    point_map.set_valid(p, valid.contains(&p));
+                }
+                (WMBuilder::new(point_map).build(), orbits)
+            }
+        };
+        Ok((Map::new(wrapping, &self.config), orbits))
+    }
+
+    /// returns None if a property didn't fit
+    fn attempt(
+        &self,
+        empty: &Map<D>,
+        orbits: &[Vec<Point>],
+        spreads: &[Option<usize>],
+        rng: &mut GameRng,
+    ) -> Option<Map<D>> {
+        let mut map = empty.clone();
+        let environment = map.environment().clone();
+        for orbit in orbits {
+            let terrain = pick_weighted(&self.terrain_weights, rng);
+            for p in orbit {
+                map.set_terrain(*p, terrain.instance(&environment).build());
+            }
+        }
+        let player_count = self.player_count as usize;
+        let mut used = vec![false; orbits.len()];
+        let mut blocked: HashSet<Point> = HashSet::default();
+        // distances from each player's home
+        let mut homes: Vec<HashMap<Point, usize>> = Vec::new();
+        for property in &self.properties {
+            for i in 0..property.owned_per_player + property.neutral_per_player {
+                let mut candidates: Vec<(usize, usize)> = Vec::new();
+                if homes.len() == 0 {
+                    let max_spread = (0..orbits.len())
+                        .filter(|index| !used[*index])
+                        .filter_map(|index| spreads[index])
+                        .max()
+                        .unwrap_or(0);
+                    for (index, spread) in spreads.iter().enumerate() {
+                        if let Some(spread) = spread {
+                            if !used[index]
+                                && *spread >= self.min_property_distance
+                                && spread * 4 >= max_spread * 3
+                            {
+                                candidates.push((index, 0));
+                            }
+                        }
+                    }
+                } else {
+                    for (index, orbit) in orbits.iter().enumerate() {
+                        if used[index] || orbit.len() < player_count {
+                            continue;
+                        }
+                        let distance = |player: usize, p: &Point| {
+                            homes[player].get(p).cloned().unwrap_or(usize::MAX)
+                        };
+                        let k = (0..player_count)
+                            .min_by_key(|k| distance(0, &orbit[*k]))
+                            .unwrap();
+                        let closest = distance(0, &orbit[k]);
+                        if closest < usize::MAX
+                            && (1..player_count).all(|j| closest < distance(j, &orbit[k]))
+                        {
+                            candidates.push((index, k));
+                        }
+                    }
+                }
+                candidates.retain(|(index, _)| orbits[*index].iter().all(|p| !blocked.contains(p)));
+                let (index, k) = loop {
+                    if candidates.len() == 0 {
+                        return None;
+                    }
+                    let (index, k) =
+                        candidates.swap_remove(rng.next_u64() as usize % candidates.len());
+                    // copies of the property mustn't be too close to each other either
+                    let mut new_blocked = blocked.clone();
+                    let mut fits = true;
+                    for p in &orbits[index] {
+                        if new_blocked.contains(p) {
+                            fits = false;
+                            break;
+                        }
+                        new_blocked.extend(
+                            distances(&map, *p, self.min_property_distance - 1).into_keys(),
+                        );
+                    }
+                    if fits {
+                        blocked = new_blocked;
+                        break (index, k);
+                    }
+                };
+                used[index] = true;
+                let owned = i < property.owned_per_player;
+                for player in 0..player_count {
+                    let p = orbits[index][(k + player) % player_count];
+                    let mut terrain = property.terrain.instance(&environment);
+                    if owned {
+                        terrain = terrain.set_owner_id(player as i8);
+                    }
+                    map.set_terrain(p, terrain.build());
+                    if homes.len() < player_count && owned {
+                        homes.push(distances(&map, p, usize::MAX));
+                    }
+                }
+            }
+        }
+        Some(map)
+    }
+}
+
+fn global(p: Point) -> GlobalPoint {
+    GlobalPoint::new(p.x as i16, p.y as i16)
+}
+
+/**
+ * follows f from each point until it returns to the point.
+ * points where that takes more than `steps` steps or leaves the map aren't part of any orbit
+ */
+fn find_orbits(
+    points: &[Point],
+    steps: usize,
+    f: impl Fn(Point) -> Option<Point>,
+) -> Vec<Vec<Point>> {
+    let mut visited: HashSet<Point> = HashSet::default();
+    let mut result = Vec::new();
+    for start in points {
+        if visited.contains(start) {
+            continue;
+        }
+        let mut orbit = vec![*start];
+        let mut p = *start;
+        let mut closed = false;
+        for _ in 0..steps {
+            match f(p) {
+                Some(next) if next == *start => {
+                    closed = true;
+                    break;
+                }
+                Some(next) if !orbit.contains(&next) => {
+                    orbit.push(next);
+                    p = next;
+                }
+                _ => break,
+            }
+        }
+        visited.extend(orbit.iter().cloned());
+        if closed && steps % orbit.len() == 0 {
+            result.push(orbit);
+        }
+    }
+    result
+}
+
+/**
+ * orbits of the distortion around the center of the map.
+ * the distortion is applied around (0, 0) and then moved back into the map,
+ * trying a few offsets to find the one that keeps the most points valid
+ */
+pub(crate) fn centered_orbits<D: Direction>(
+    point_map: &PointMap,
+    distortion: Distortion<D>,
+    steps: usize,
+) -> Vec<Vec<Point>> {
+    let odd = point_map.odd_if_hex();
+    let points = point_map.get_valid_points();
+    let transformation = Transformation::new(distortion, D::angle_0().translation(0));
+    let distort = |p: Point| transformation.transform_point(&global(p), &GlobalPoint::ZERO, odd);
+    let min_x = points.iter().map(|p| distort(*p).x).min().unwrap_or(0);
+    let min_y = points.iter().map(|p| distort(*p).y).min().unwrap_or(0);
+    let mut best: Vec<Vec<Point>> = Vec::new();
+    for dy in -1..=1 {
+        for dx in -1..=1 {
+            let corner = GlobalPoint::new(min_x + dx, min_y + dy);
+            let translation = D::T::between(&corner, &GlobalPoint::ZERO, odd);
+            let orbits = find_orbits(&points, steps, |p| {
+                let p = translation.translate_point(&distort(p), odd);
+                if p.x < 0 || p.y < 0 || p.x > u8::MAX as i16 || p.y > u8::MAX as i16 {
+                    return None;
+                }
+                let p = Point::new(p.x as u8, p.y as u8);
+                if point_map.is_point_valid(p) {
+                    Some(p)
+                } else {
+                    None
+                }
+            });
+            let count = |orbits: &Vec<Vec<Point>>| orbits.iter().map(Vec::len).sum::<usize>();
+            if count(&orbits) > count(&best) {
+                best = orbits;
+            }
+        }
+    }
+    best
+}
+
+/// distance in steps to each point that's at most max_distance steps away
+fn distances<D: Direction>(
+    map: &Map<D>,
+    start: Point,
+    max_distance: usize,
+) -> HashMap<Point, usize> {
+    let mut result = HashMap::default();
+    result.insert(start, 0);
+    let mut layer = vec![start];
+    let mut distance = 0;
+    while layer.len() > 0 && distance < max_distance {
+        distance += 1;
+        let mut next = Vec::new();
+        for p in layer {
+            for neighbor in map.get_neighbors(p, NeighborMode::Direct) {
+                if !result.contains_key(&neighbor.point) {
+                    result.insert(neighbor.point, distance);
+                    next.push(neighbor.point);
+                }
+            }
+        }
+        layer = next;
+    }
+    result
+}
+
+fn pick_weighted(weights: &[(TerrainType, u32)], rng: &mut GameRng) -> TerrainType {
+    let total: u64 = weights.iter().map(|(_, weight)| *weight as u64).sum();
+    let mut roll = rng.next_u64() % total;
+    for (terrain, weight) in weights {
+        if roll < *weight as u64 {
+            return *terrain;
+        }
+        roll -= *weight as u64;
+    }
+    unreachable!()
+}
+
+#[derive(Debug)]
+pub enum GeneratorError<D: Direction> {
+    /// contains the ruleset's maximum map size
+    InvalidSize(u8),
+    InvalidPlayerCount(u8),
+    /// e.g. mirror symmetry for more than 2 players or a wrapping translation that doesn't fit the map
+    UnsupportedSymmetry,
+    Wrapping(TransformationError<D>),
+    NoTerrain,
+    /// owned properties of a terrain that can't be owned or neutral properties that have to be owned
+    OwnershipMismatch(String),
+    /// not enough space to place the properties fairly
+    NoSpace(String),
+    NotPlayable(NotPlayable),
+}
+
+impl<D: Direction> Display for GeneratorError<D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidSize(max) => {
+                write!(
+                    f,
+                    "width and height have to be between {MIN_SIZE} and {max}"
+                )
+            }
+            Self::InvalidPlayerCount(count) => {
+                write!(f, "can't generate a map for {count} players")
+            }
+            Self::UnsupportedSymmetry => {
+                write!(f, "the symmetry doesn't fit the player count or map size")
+            }
+            Self::Wrapping(error) => write!(f, "invalid wrapping transformation: {error:?}"),
+            Self::NoTerrain => write!(f, "no terrain to fill the map with"),
+            Self::OwnershipMismatch(name) => {
+                write!(
+                    f,
+                    "ownership of terrain '{name}' doesn't match the property settings"
+                )
+            }
+            Self::NoSpace(name) => {
+                write!(
+                    f,
+                    "not enough space to place '{name}' and the other properties fairly"
+                )
+            }
+            Self::NotPlayable(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl<D: Direction> Error for GeneratorError<D> {}
@@ -11,8 +11,11 @@ use zipper_derive::Zippable;
 use crate::VERSION;
 use crate::config::config::Config;
 use crate::config::environment::Environment;
+use crate::config::reload::{ConfigDiff, ConfigMigrationError, ConfigRenames};
 use crate::game::fog::*;
 use crate::game::game::*;
+use crate::game::replay::Replay;
+use crate::game::rng::GameRng;
 use crate::game::settings::{
     self, GameConfig, GameSettings, PlayerConfig, PlayerSelectedOptions, PlayerSettingError,
 };
@@ -31,7 +34,7 @@ use crate::units::unit::Unit;
 use uniform_smart_pointer::Urc;
 
 use super::pipe::PipeState;
-use super::point_map::MapSize;
+use super::point_map::{MIN_SIZE, MapSize, PointMap};
 
 #[derive(Clone, PartialEq)]
 pub struct Map<D>
@@ -483,11 +486,188 @@ impl<D: Direction> Map<D> {
         }
     }
 
+    pub fn set_field_data(&mut self, p: Point, data: FieldData<D>) {
+        if !self.is_point_valid(p) {
+            return;
+        }
+        self.set_pipes(p, data.pipes.into_inner());
+        self.set_terrain(p, data.terrain);
+        self.set_tokens(p, data.tokens.into_inner());
+        self.set_unit(p, data.unit);
+    }
+
+    /**
+     * e.g. after changing the size or wrapping transformations of the map.
+     * content on points that aren't valid anymore is removed, new points get the default terrain
+     */
+    pub fn set_wrapping_logic(&mut self, wrapping_logic: WrappingMap<D>) {
+        let point_map = wrapping_logic.pointmap().clone();
+        self.environment.map_size = point_map.size();
+        self.wrapping_logic = wrapping_logic;
+        self.pipes.retain(|p, _| point_map.is_point_valid(*p));
+        self.terrain.retain(|p, _| point_map.is_point_valid(*p));
+        self.units.retain(|p, _| point_map.is_point_valid(*p));
+        self.tokens.retain(|p, _| point_map.is_point_valid(*p));
+        for p in point_map.get_valid_points() {
+            if !self.terrain.contains_key(&p) {
+                self.terrain.insert(p, self.environment.default_terrain());
+            }
+        }
+    }
+
+    /**
+     * Adds or removes rows and columns. The content stays at the anchor, e.g. with
+     * ResizeAnchor::BottomRight rows and columns are added to or removed from the top and left.
+     * width and height have to be within point_map::MIN_SIZE and Config::max_map_size
+     */
+    pub fn resize(
+        &mut self,
+        new_size: MapSize,
+        anchor: ResizeAnchor,
+    ) -> Result<(), ResizeError<D>> {
+        let max_size = self.environment.config.max_map_size();
+        if [new_size.width(), new_size.height()]
+            .iter()
+            .any(|size| *size < MIN_SIZE || *size > max_size)
+        {
+            return Err(ResizeError::InvalidSize(new_size));
+        }
+        let (x, y) = anchor.offset(self.wrapping_logic.pointmap().size(), new_size);
+        let mut odd_if_hex = self.odd_if_hex();
+        if D::is_hex() && y % 2 != 0 {
+            // the rows keep their horizontal position
+            odd_if_hex = !odd_if_hex;
+        }
+        let move_point = |p: GlobalPoint| GlobalPoint::new(p.x + x, p.y + y);
+        let translations = [0, 1].map(|row| {
+            D::T::between(
+                &GlobalPoint::new(0, row),
+                &GlobalPoint::new(x, row + y),
+                self.odd_if_hex(),
+            )
+        });
+        let point_map = PointMap::new(new_size.width(), new_size.height(), odd_if_hex);
+        self.move_content(point_map, move_point, translations, self.odd_if_hex())
+            .map_err(ResizeError::Transformation)
+    }
+
+    /**
+     * Moves all content and invalid points by the translation, keeping the size of the map.
+     * Content that's moved outside the map is lost.
+     */
+    pub fn translate_content(&mut self, translation: D::T) -> Result<(), TransformationError<D>> {
+        let odd_if_hex = self.odd_if_hex();
+        let point_map = PointMap::new(self.width(), self.height(), odd_if_hex);
+        self.move_content(
+            point_map,
+            |p| translation.translate_point(&p, odd_if_hex),
+            [translation; 2],
+            odd_if_hex,
+        )
+    }
+
+    /**
+     * `point_map` decides the size of the map, points that aren't covered by the old map stay valid.
+     * the wrapping transformations are adjusted so they keep connecting the same content
+     */
+    fn move_content(
+        &mut self,
+        mut point_map: PointMap,
+        move_point: impl Fn(GlobalPoint) -> GlobalPoint,
+        tag_translations: [D::T; 2],
+        tag_odd_if_hex: bool,
+    ) -> Result<(), TransformationError<D>> {
+        let old = self.wrapping_logic.pointmap();
+        let inside = |p: GlobalPoint| {
+            if p.x < 0
+                || p.y < 0
+                || p.x >= point_map.width() as i16
+                || p.y >= point_map.height() as i16
+            {
+                None
+            } else {
+                Some(Point::new(p.x as u8, p.y as u8))
+            }
+        };
+        let mut moved = HashMap::default();
+        for y in 0..old.height() {
+            for x in 0..old.width() {
+                let p = Point::new(x, y);
+                if let Some(new_p) = inside(move_point(GlobalPoint::new(x as i16, y as i16))) {
+                    moved.insert(p, new_p);
+                }
+            }
+        }
+        for (p, new_p) in &moved {
+            point_map.set_valid(*new_p, old.is_point_valid(*p));
+        }
+        // T(p) = R(p - c) + c + t, so moving content by u relative to the center changes t by u - R(u)
+        let old_center = GlobalPoint::new(old.width() as i16 / 2, old.height() as i16 / 2);
+        let new_center =
+            GlobalPoint::new(point_map.width() as i16 / 2, point_map.height() as i16 / 2);
+        let u = D::T::between(&new_center, &move_point(old_center), point_map.odd_if_hex());
+        let transformations = self
+            .wrapping_logic
+            .seed_transformations()
+            .iter()
+            .map(|tr| {
+                Transformation::new(
+                    tr.distortion,
+                    tr.translate_by + u - u.distort(tr.distortion),
+                )
+            })
+            .collect();
+        let wrapping_logic = WMBuilder::with_transformations(point_map, transformations)?.build();
+        let new_point = |p: &Point| moved.get(p).cloned();
+        let mut pipes = HashMap::default();
+        for (p, value) in self.pipes.drain() {
+            if let Some(p) = new_point(&p) {
+                pipes.insert(p, value);
+            }
+        }
+        let mut terrain = HashMap::default();
+        for (p, mut value) in self.terrain.drain() {
+            if let Some(p) = new_point(&p) {
+                value.translate(tag_translations, tag_odd_if_hex);
+                terrain.insert(p, value);
+            }
+        }
+        let mut units = HashMap::default();
+        for (p, mut value) in self.units.drain() {
+            if let Some(p) = new_point(&p) {
+                value.translate(tag_translations, tag_odd_if_hex);
+                units.insert(p, value);
+            }
+        }
+        let mut tokens = HashMap::default();
+        for (p, mut stack) in self.tokens.drain() {
+            if let Some(p) = new_point(&p) {
+                for token in &mut stack {
+                    token.translate(tag_translations, tag_odd_if_hex);
+                }
+                tokens.insert(p, stack);
+            }
+        }
+        self.tags.translate(tag_translations, tag_odd_if_hex);
+        self.pipes = pipes;
+        self.terrain = terrain;
+        self.units = units;
+        self.tokens = tokens;
+        self.set_wrapping_logic(wrapping_logic);
+        Ok(())
+    }
+
     pub fn import_from_unzipper(
         unzipper: &mut Unzipper,
         environment: &mut Environment,
     ) -> Result<Self, ZipperError> {
         let wrapping_logic = WrappingMap::unzip(unzipper)?;
+        let max_size = environment.config.max_map_size();
+        if wrapping_logic.pointmap().width() > max_size
+            || wrapping_logic.pointmap().height() > max_size
+        {
+            return Err(ZipperError::InconsistentData);
+        }
         environment.map_size = wrapping_logic.pointmap().size();
         let tags = TagBag::import(unzipper, environment)?;
         let mut pipes = HashMap::default();
@@ -518,6 +698,43 @@ impl<D: Direction> Map<D> {
         })
     }
 
+    /**
+     * Switches the map to a new version of its config, e.g. after Config::reload_from.
+     * Types, flags and tags are matched by name or by the given renames, see ConfigDiff.
+     * Leaves the map unchanged if it uses something that was removed from the config
+     */
+    pub fn migrate_config(
+        &mut self,
+        new: &Urc<Config>,
+        renames: &ConfigRenames,
+    ) -> Result<ConfigDiff, ConfigMigrationError> {
+        let diff = ConfigDiff::with_renames(&self.environment.config, new, renames);
+        let environment = Environment::new_map(new.clone(), self.environment.map_size);
+        let tags = self.tags.migrate_config(&diff, &environment)?;
+        let mut terrain = HashMap::default();
+        for (p, t) in &self.terrain {
+            terrain.insert(*p, t.migrate_config(&diff, &environment)?);
+        }
+        let mut units = HashMap::default();
+        for (p, unit) in &self.units {
+            units.insert(*p, unit.migrate_config(&diff, &environment)?);
+        }
+        let mut tokens = HashMap::default();
+        for (p, stack) in &self.tokens {
+            let mut migrated = Vec::with_capacity(stack.len());
+            for token in stack {
+                migrated.push(token.migrate_config(&diff, &environment)?);
+            }
+            tokens.insert(*p, migrated);
+        }
+        self.environment = environment;
+        self.tags = tags;
+        self.terrain = terrain;
+        self.units = units;
+        self.tokens = tokens;
+        Ok(diff)
+    }
+
     pub(crate) fn start_game(&mut self, settings: &Urc<GameSettings>) {
         self.environment.start_game(settings);
         for p in self.all_points() {
@@ -538,13 +755,14 @@ impl<D: Direction> Map<D> {
         if owners.len() < 2 {
             return Err(NotPlayable::TooFewPlayers);
         }
-        let random: RandomFn = Urc::new(|| 0.);
+        let mut rng = GameRng::default();
         let players: Vec<PlayerConfig<D>> = owners
             .into_iter()
-            .map(|owner| PlayerConfig::new(owner, self, &random))
+            .map(|owner| PlayerConfig::new(owner, self, &mut rng))
             .collect();
         Ok(settings::GameConfig {
             fog_mode: FogMode::Constant(FogSetting::None),
+            time_control: None,
             tags: self.tags.clone(),
             players: players.try_into().unwrap(),
         })
@@ -779,6 +997,29 @@ impl<D: Direction> MapInterface for Map<D> {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResizeError<D: Direction> {
+    /// width or height is outside of point_map::MIN_SIZE and Config::max_map_size
+    InvalidSize(MapSize),
+    Transformation(TransformationError<D>),
+}
+
+impl<D: Direction> Display for ResizeError<D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidSize(size) => write!(
+                f,
+                "can't resize to {}x{}, width and height have to be between {MIN_SIZE} and the ruleset's maximum map size",
+                size.width(),
+                size.height()
+            ),
+            Self::Transformation(error) => write!(f, "invalid wrapping: {error:?}"),
+        }
+    }
+}
+
+impl<D: Direction> Error for ResizeError<D> {}
+
 #[derive(Debug)]
 pub enum NotPlayable {
     TooFewPlayers,
@@ -800,10 +1041,27 @@ pub struct GameCreation<D: Direction> {
     pub player_selection: Vec<settings::PlayerSelectedOptions>,
 }
 
+impl<D: Direction> GameCreation<D> {
+    /**
+     * the state of the rng before it's passed here is the seed needed for a Replay.
+     * the game remembers it, see Game::rng_seed
+     */
+    pub fn start_server(self, mut rng: GameRng) -> (Game<D>, EventsMap<D>) {
+        let seed = rng.state();
+        let settings = self.settings.build(&self.player_selection, &mut rng);
+        let (mut game, events) = Game::new_server(self.map, &self.settings, settings, rng);
+        game.set_rng_seed(seed);
+        (game, events)
+    }
+
+    pub fn replay(&self, seed: u64) -> Replay {
+        Replay::new(&self.map, &self.settings, &self.player_selection, seed)
+    }
+}
+
 impl<D: Direction> GameCreationInterface for GameCreation<D> {
     fn server(self: Box<Self>, random: RandomFn) -> (Box<dyn GameInterface>, Events) {
-        let settings = self.settings.build(&self.player_selection, &random);
-        let (server, events) = Game::new_server(self.map, &self.settings, settings, random);
+        let (server, events) = self.start_server(GameRng::from_random(&random));
         let events = events.export(server.environment());
         (Box::new(server), events)
     }
@@ -813,9 +1071,12 @@ impl<D: Direction> GameCreationInterface for GameCreation<D> {
         client_perspective: ClientPerspective,
         random: RandomFn,
     ) -> (Box<dyn GameInterface>, Box<dyn GameInterface>, Events) {
-        let settings = self.settings.build(&self.player_selection, &random);
-        let (server, events) =
-            Game::new_server(self.map.clone(), &self.settings, settings.clone(), random);
+        let mut rng = GameRng::from_random(&random);
+        let seed = rng.state();
+        let settings = self.settings.build(&self.player_selection, &mut rng);
+        let (mut server, events) =
+            Game::new_server(self.map.clone(), &self.settings, settings.clone(), rng);
+        server.set_rng_seed(seed);
         let client = Game::new_client(
             self.map,
             &self.settings,
@@ -833,6 +1094,39 @@ pub enum NeighborMode {
     FollowPipes,
 }
 
+/// which part of the map keeps its content in Map::resize
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResizeAnchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl ResizeAnchor {
+    // how far the content moves
+    fn offset(&self, old: MapSize, new: MapSize) -> (i16, i16) {
+        let dx = new.width() as i16 - old.width() as i16;
+        let dy = new.height() as i16 - old.height() as i16;
+        let x = match self {
+            Self::TopLeft | Self::Left | Self::BottomLeft => 0,
+            Self::Top | Self::Center | Self::Bottom => dx / 2,
+            Self::TopRight | Self::Right | Self::BottomRight => dx,
+        };
+        let y = match self {
+            Self::TopLeft | Self::Top | Self::TopRight => 0,
+            Self::Left | Self::Center | Self::Right => dy / 2,
+            Self::BottomLeft | Self::Bottom | Self::BottomRight => dy,
+        };
+        (x, y)
+    }
+}
+
 pub fn valid_points<D: Direction>(board: &impl BoardView<D>) -> Vec<Point> {
     board.wrapping_logic().pointmap().get_valid_points()
 }
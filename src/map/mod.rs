@@ -1,5 +1,8 @@
 pub mod board;
 pub mod direction;
+pub mod editor;
+pub mod fairness;
+pub mod generator;
 pub mod map;
 pub mod pipe;
 pub mod point;
@@ -7,6 +10,7 @@ pub mod point_map;
 pub(crate) mod rhai_board;
 pub mod rhai_direction;
 pub mod rhai_point;
+pub mod text_format;
 pub mod wrapping_map;
 
 #[cfg(test)]
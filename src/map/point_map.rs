@@ -1,3 +1,6 @@
+use std::ops::Deref;
+
+use crate::config::environment::Environment;
 use crate::map::point::*;
 
 use zipper::zipper_derive::*;
@@ -6,10 +9,14 @@ use zipper::*;
 use super::direction::*;
 
 pub const MIN_SIZE: u8 = 3;
-pub const MAX_SIZE: u32 = 50;
+/// no ruleset can allow larger maps, see Config::max_map_size
+pub const MAX_SIZE: u32 = 128;
 pub const MAX_AREA: u32 = MAX_SIZE * MAX_SIZE;
+/// used by rulesets that don't set MaxMapSize. maps were limited to this size before it was configurable
+pub const DEFAULT_MAX_SIZE: u8 = 50;
+pub const DEFAULT_MAX_AREA: u32 = DEFAULT_MAX_SIZE as u32 * DEFAULT_MAX_SIZE as u32;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Zippable)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct MapSize {
     pub width: U<{ MAX_SIZE as i32 }>,
     pub height: U<{ MAX_SIZE as i32 }>,
@@ -28,6 +35,49 @@ impl MapSize {
     pub fn height(&self) -> u8 {
         *self.height as u8
     }
+    pub fn area(&self) -> u32 {
+        self.width() as u32 * self.height() as u32
+    }
+
+    /**
+     * sizes that fit into the bits needed for DEFAULT_MAX_SIZE are stored like in older saves,
+     * larger sizes follow after the highest value of those bits
+     */
+    fn zip_length(zipper: &mut Zipper, length: u8) {
+        let bits = bits_needed_for_max_value(DEFAULT_MAX_SIZE as u32);
+        let escape = (1u32 << bits) - 1;
+        if (length as u32) < escape {
+            zipper.write_u32(length as u32, bits);
+        } else {
+            zipper.write_u32(escape, bits);
+            zipper.write_u32(length as u32, bits_needed_for_max_value(MAX_SIZE));
+        }
+    }
+
+    fn unzip_length(unzipper: &mut Unzipper) -> Result<u8, ZipperError> {
+        let bits = bits_needed_for_max_value(DEFAULT_MAX_SIZE as u32);
+        let escape = (1u32 << bits) - 1;
+        let mut length = unzipper.read_u32(bits)?;
+        if length == escape {
+            length = unzipper.read_u32(bits_needed_for_max_value(MAX_SIZE))?;
+        }
+        if length > MAX_SIZE {
+            return Err(ZipperError::EnumOutOfBounds(format!("map size {length}")));
+        }
+        Ok(length as u8)
+    }
+}
+
+impl Zippable for MapSize {
+    fn zip(&self, zipper: &mut Zipper) {
+        Self::zip_length(zipper, self.width());
+        Self::zip_length(zipper, self.height());
+    }
+    fn unzip(unzipper: &mut Unzipper) -> Result<Self, ZipperError> {
+        let width = Self::unzip_length(unzipper)?;
+        let height = Self::unzip_length(unzipper)?;
+        Ok(Self::new(width, height))
+    }
 }
 
 impl<Z: Zippable> SupportedZippable<MapSize> for Vec<Vec<Z>> {
@@ -207,12 +257,89 @@ impl PointMap {
     }
 }
 
+/**
+ * A list with up to one entry per point of the map, e.g. the fog changes of one event.
+ * The number of bits for the length depends on the map's area,
+ * but is never lower than for DEFAULT_MAX_AREA so older saves stay compatible.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AreaVec<T>(Vec<T>);
+
+impl<T> AreaVec<T> {
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// the longest list that can be exported for a map of the given size
+    pub fn max_len(map_size: MapSize) -> usize {
+        map_size.area().max(DEFAULT_MAX_AREA) as usize
+    }
+
+    pub fn push(&mut self, value: T) {
+        self.0.push(value);
+    }
+
+    pub fn into_inner(self) -> Vec<T> {
+        self.0
+    }
+}
+
+impl<T> Deref for AreaVec<T> {
+    type Target = [T];
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<'a, T> IntoIterator for &'a AreaVec<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<T> TryFrom<Vec<T>> for AreaVec<T> {
+    type Error = Vec<T>;
+    fn try_from(value: Vec<T>) -> Result<Self, Self::Error> {
+        if value.len() > MAX_AREA as usize {
+            Err(value)
+        } else {
+            Ok(Self(value))
+        }
+    }
+}
+
+impl<'a, T: SupportedZippable<&'a Environment>> SupportedZippable<&'a Environment> for AreaVec<T> {
+    fn export(&self, zipper: &mut Zipper, support: &'a Environment) {
+        let bits = bits_needed_for_max_value(Self::max_len(support.map_size) as u32);
+        zipper.write_u32(self.0.len() as u32, bits);
+        for value in &self.0 {
+            value.export(zipper, support);
+        }
+    }
+    fn import(unzipper: &mut Unzipper, support: &'a Environment) -> Result<Self, ZipperError> {
+        let max_len = Self::max_len(support.map_size);
+        let len = unzipper.read_u32(bits_needed_for_max_value(max_len as u32))? as usize;
+        if len > max_len {
+            return Err(ZipperError::InconsistentData);
+        }
+        let mut result = Vec::with_capacity(len);
+        for _ in 0..len {
+            result.push(T::import(unzipper, support)?);
+        }
+        Ok(Self(result))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use semver::Version;
 
     use super::*;
+    use crate::config::config::Config;
     use crate::{VERSION, map::point::Point};
+    use uniform_smart_pointer::Urc;
 
     #[test]
     fn filled_point_map() {
@@ -440,4 +567,41 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn export_import_large_map_size() {
+        // sizes that older saves could contain keep their encoding
+        let mut zipper = Zipper::new();
+        MapSize::new(50, 7).zip(&mut zipper);
+        let mut legacy = Zipper::new();
+        legacy.write_u32(50, 6);
+        legacy.write_u32(7, 6);
+        assert_eq!(zipper.finish(), legacy.finish());
+
+        for size in [MapSize::new(62, 63), MapSize::new(128, 100)] {
+            let mut zipper = Zipper::new();
+            size.zip(&mut zipper);
+            let mut unzipper = Unzipper::new(zipper.finish(), Version::parse(VERSION).unwrap());
+            assert_eq!(Ok(size), MapSize::unzip(&mut unzipper));
+        }
+    }
+
+    #[test]
+    fn export_import_area_vec() {
+        let config = Urc::new(Config::default());
+        for map_size in [MapSize::new(5, 5), MapSize::new(120, 90)] {
+            let environment = Environment::new_map(config.clone(), map_size);
+            let points: AreaVec<Point> =
+                vec![Point::new(0, 1), Point::new(4, 3)].try_into().unwrap();
+            let mut zipper = Zipper::new();
+            points.export(&mut zipper, &environment);
+            let mut unzipper = Unzipper::new(zipper.finish(), Version::parse(VERSION).unwrap());
+            assert_eq!(Ok(points), AreaVec::import(&mut unzipper, &environment));
+        }
+        assert_eq!(
+            AreaVec::<Point>::max_len(MapSize::new(5, 5)),
+            DEFAULT_MAX_AREA as usize
+        );
+        assert_eq!(AreaVec::<Point>::max_len(MapSize::new(120, 90)), 120 * 90);
+    }
 }
@@ -1,16 +1,22 @@
 use uniform_smart_pointer::Urc;
 
 use crate::config::config::Config;
+use crate::game::rng::GameRng;
 use crate::map::board::BoardView;
 use crate::tags::tests::*;
+use crate::tags::{TagValue, UniqueId};
 use crate::terrain::TerrainType;
+use crate::units::hero::{Hero, HeroType};
 use crate::units::unit_types::UnitType;
 
 use super::direction::*;
-use super::map::Map;
+use super::editor::{EditorCommand, EditorError, MapEditor};
+use super::generator::{MapGenerator, MapSymmetry, PropertyPlacement};
+use super::map::{Map, MapType, ResizeAnchor, ResizeError};
 use super::point::*;
-use super::point_map::PointMap;
-use super::wrapping_map::{Distortion, WMBuilder};
+use super::point_map::{MIN_SIZE, MapSize, PointMap};
+use super::text_format::{MapTextErrorKind, import_map_text};
+use super::wrapping_map::{Distortion, Transformation, WMBuilder};
 
 pub fn chess_board() -> Map<Direction4> {
     let config = Urc::new(Config::default());
@@ -263,3 +269,462 @@ fn simple_distortions() {
         Direction6::D240
     );
 }
+
+#[test]
+fn text_format_round_trip() {
+    let map = chess_board();
+    let config = map.environment().config.clone();
+    let text = map.export_text();
+    match import_map_text(&config, &text) {
+        Ok(MapType::Square(imported)) => assert_eq!(imported, map),
+        Ok(MapType::Hex(_)) => panic!("imported square map as hex map"),
+        Err(errors) => panic!("{errors:?}"),
+    }
+}
+
+#[test]
+fn text_format_unknown_names() {
+    let map = chess_board();
+    let config = map.environment().config.clone();
+    let text = map.export_text();
+    let (line, terrain) = text
+        .lines()
+        .enumerate()
+        .find(|(_, line)| line.starts_with("Terrain;"))
+        .unwrap();
+    let broken = text.replacen(terrain, "Terrain;0,0;Nonexistent", 1);
+    let errors = import_map_text(&config, &broken).unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].line, line + 1);
+    assert_eq!(
+        errors[0].kind,
+        MapTextErrorKind::UnknownTerrain("Nonexistent".to_string())
+    );
+}
+
+#[test]
+fn text_format_round_trip_hex() {
+    let config = Urc::new(Config::default());
+    let mut pointmap = PointMap::new(5, 4, true);
+    pointmap.set_valid(Point::new(4, 3), false);
+    let mut map = Map::new(WMBuilder::<Direction6>::new(pointmap).build(), &config);
+    let environment = map.environment().clone();
+    map.set_terrain(
+        Point::new(2, 2),
+        TerrainType::Forest.instance(&environment).build(),
+    );
+    let mut hero = Hero::new(HeroType::CRYSTAL);
+    hero.set_charge(&environment, 1);
+    map.set_unit(
+        Point::new(0, 1),
+        Some(
+            UnitType::DRAGON_HEAD
+                .instance(&environment)
+                .set_owner_id(0)
+                .set_hero(hero)
+                .build(),
+        ),
+    );
+    map.set_unit(
+        Point::new(3, 2),
+        Some(
+            UnitType::TRANSPORT_HELI
+                .instance(&environment)
+                .set_owner_id(1)
+                .set_transported(vec![
+                    UnitType::MARINE.instance(&environment).set_hp(34).build(),
+                    UnitType::SNIPER.instance(&environment).build(),
+                ])
+                .build(),
+        ),
+    );
+    map.set_unit(
+        Point::new(1, 3),
+        Some(
+            UnitType::PAWN
+                .instance(&environment)
+                .set_owner_id(1)
+                .set_tag(TAG_PAWN_DIRECTION, TagValue::Direction(Direction6::D120))
+                .build(),
+        ),
+    );
+    let text = map.export_text();
+    match import_map_text(&config, &text) {
+        Ok(MapType::Hex(imported)) => assert_eq!(imported, map),
+        Ok(MapType::Square(_)) => panic!("imported hex map as square map"),
+        Err(errors) => panic!("{errors:?}"),
+    }
+}
+
+#[test]
+fn text_format_round_trip_wrapping() {
+    let wrapping = WMBuilder::<Direction4>::with_transformations(
+        PointMap::new(8, 8, false),
+        vec![Transformation::new(
+            Distortion::neutral(),
+            Direction4::D0.translation(8),
+        )],
+    )
+    .unwrap();
+    let mut map = chess_board();
+    map.set_wrapping_logic(wrapping.build());
+    let config = map.environment().config.clone();
+    let text = map.export_text();
+    match import_map_text(&config, &text) {
+        Ok(MapType::Square(imported)) => {
+            assert_eq!(imported.wrapping_logic().seed_transformations().len(), 1);
+            assert_eq!(imported, map);
+        }
+        Ok(MapType::Hex(_)) => panic!("imported square map as hex map"),
+        Err(errors) => panic!("{errors:?}"),
+    }
+}
+
+#[test]
+fn text_format_invalid_point() {
+    let map = chess_board();
+    let config = map.environment().config.clone();
+    let text = format!("{}Unit;8,3;Marine\n", map.export_text());
+    let errors = import_map_text(&config, &text).unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].line, text.lines().count());
+    assert_eq!(
+        errors[0].kind,
+        MapTextErrorKind::InvalidPoint("8,3".to_string())
+    );
+}
+
+#[test]
+fn text_format_unique_id_out_of_range() {
+    let map = chess_board();
+    let config = map.environment().config.clone();
+    let id = (UniqueId::MAX_VALUE + 1).to_string();
+    let text = format!("{}Unit;3,3;Marine\n  Tag;DroneId;{id}\n", map.export_text());
+    let errors = import_map_text(&config, &text).unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].line, text.lines().count());
+    assert_eq!(errors[0].kind, MapTextErrorKind::InvalidValue(id));
+}
+
+fn generator<D: Direction>(player_count: u8) -> MapGenerator<D> {
+    let config = Urc::new(Config::default());
+    MapGenerator::new(&config, 16, 16, player_count)
+        .set_terrain_weights(vec![
+            (TerrainType::Grass, 5),
+            (TerrainType::Forest, 2),
+            (TerrainType::Mountain, 1),
+        ])
+        .add_property(PropertyPlacement {
+            terrain: TerrainType::Hq,
+            owned_per_player: 1,
+            neutral_per_player: 0,
+        })
+        .add_property(PropertyPlacement {
+            terrain: TerrainType::Factory,
+            owned_per_player: 1,
+            neutral_per_player: 1,
+        })
+        .add_property(PropertyPlacement {
+            terrain: TerrainType::City,
+            owned_per_player: 1,
+            neutral_per_player: 2,
+        })
+}
+
+fn assert_fair<D: Direction>(map: &Map<D>, player_count: u8) {
+    assert!(map.settings().is_ok());
+    assert_eq!(
+        map.get_viable_player_ids(),
+        (0..player_count).collect::<Vec<_>>()
+    );
+    let properties = |owner: i8| {
+        let mut result: Vec<TerrainType> = map
+            .all_points()
+            .into_iter()
+            .map(|p| map.get_terrain(p).unwrap())
+            .filter(|t| t.get_owner_id() == owner)
+            .map(|t| t.typ())
+            .collect();
+        result.sort();
+        result
+    };
+    for owner in 0..player_count as i8 {
+        assert_eq!(
+            properties(owner),
+            vec![TerrainType::City, TerrainType::Factory, TerrainType::Hq],
+            "{map:?}"
+        );
+    }
+    let neutral = properties(-1);
+    let count = |typ: TerrainType| neutral.iter().filter(|t| **t == typ).count();
+    assert_eq!(count(TerrainType::Factory), player_count as usize);
+    assert_eq!(count(TerrainType::City), 2 * player_count as usize);
+}
+
+#[test]
+fn generate_rotational_maps() {
+    let map = generator::<Direction4>(4)
+        .generate(&mut GameRng::new(1))
+        .unwrap();
+    assert_fair(&map, 4);
+    let map = generator::<Direction6>(3)
+        .generate(&mut GameRng::new(2))
+        .unwrap();
+    assert_fair(&map, 3);
+}
+
+#[test]
+fn generate_mirrored_and_wrapping_maps() {
+    let map = generator::<Direction4>(2)
+        .set_symmetry(MapSymmetry::Mirror)
+        .generate(&mut GameRng::new(3))
+        .unwrap();
+    assert_fair(&map, 2);
+    let map = generator::<Direction6>(2)
+        .set_symmetry(MapSymmetry::Mirror)
+        .generate(&mut GameRng::new(4))
+        .unwrap();
+    assert_fair(&map, 2);
+    let map = generator::<Direction4>(2)
+        .set_symmetry(MapSymmetry::Wrapping(Direction4::D0.translation(8)))
+        .generate(&mut GameRng::new(5))
+        .unwrap();
+    assert_fair(&map, 2);
+    assert!(
+        generator::<Direction4>(3)
+            .set_symmetry(MapSymmetry::Mirror)
+            .generate(&mut GameRng::new(6))
+            .is_err()
+    );
+}
+
+#[test]
+fn generator_is_deterministic() {
+    let generator = generator::<Direction4>(2);
+    assert_eq!(
+        generator.generate(&mut GameRng::new(7)).unwrap(),
+        generator.generate(&mut GameRng::new(7)).unwrap()
+    );
+}
+
+#[test]
+fn generated_maps_are_fair() {
+    let map = generator::<Direction4>(4)
+        .generate(&mut GameRng::new(8))
+        .unwrap();
+    let report = map.analyze_fairness(3);
+    assert!(report.is_fair(), "{report}");
+    assert_eq!(report.players.len(), 4);
+    assert_eq!(report.income_properties.len(), 4 * 3);
+
+    let mut map = generator::<Direction4>(2)
+        .set_symmetry(MapSymmetry::Mirror)
+        .generate(&mut GameRng::new(9))
+        .unwrap();
+    let report = map.analyze_fairness(3);
+    assert!(report.is_fair(), "{report}");
+    let environment = map.environment().clone();
+    map.set_unit(
+        Point::new(0, 0),
+        Some(
+            UnitType::ROOK
+                .instance(&environment)
+                .set_owner_id(0)
+                .build(),
+        ),
+    );
+    let report = map.analyze_fairness(3);
+    assert!(!report.is_fair());
+    assert!(report.asymmetric_units().contains(&Point::new(0, 0)));
+}
+
+#[test]
+fn editor_undo_redo() {
+    let map = chess_board();
+    let environment = map.environment().clone();
+    let mut editor = MapEditor::new(map.clone());
+    let grass = TerrainType::Grass.instance(&environment).build();
+    editor
+        .apply(EditorCommand::PaintTerrain(
+            vec![Point::new(3, 3)],
+            grass.clone(),
+        ))
+        .unwrap();
+    assert_eq!(
+        editor.map().get_terrain(Point::new(3, 3)).unwrap().typ(),
+        TerrainType::Grass
+    );
+    // rows 1 and 6 separate the chess tiles into 3 regions
+    editor
+        .apply(EditorCommand::FillTerrain(Point::new(0, 4), grass))
+        .unwrap();
+    for p in editor.map().all_points() {
+        let expected = if p.y >= 2 && p.y <= 5 {
+            TerrainType::Grass
+        } else {
+            map.get_terrain(p).unwrap().typ()
+        };
+        assert_eq!(editor.map().get_terrain(p).unwrap().typ(), expected);
+    }
+    assert!(editor.undo());
+    assert_eq!(
+        editor.map().get_terrain(Point::new(3, 4)).unwrap().typ(),
+        TerrainType::ChessTile
+    );
+    assert!(editor.undo());
+    assert_eq!(editor.map(), &map);
+    assert!(!editor.undo());
+    assert!(editor.redo());
+    assert!(editor.redo());
+    assert!(!editor.redo());
+    assert_eq!(
+        editor.map().get_terrain(Point::new(3, 4)).unwrap().typ(),
+        TerrainType::Grass
+    );
+    assert_eq!(
+        editor.apply(EditorCommand::PlaceUnit(Point::new(20, 3), None)),
+        Err(EditorError::InvalidPoint(Point::new(20, 3)))
+    );
+}
+
+#[test]
+fn editor_transform_and_resize() {
+    let map = chess_board();
+    let mut editor = MapEditor::new(map.clone());
+    editor
+        .apply(EditorCommand::PlaceUnit(Point::new(6, 0), None))
+        .unwrap();
+    editor
+        .apply(EditorCommand::TransformSelection {
+            selection: vec![Point::new(0, 0)],
+            center: Point::new(3, 0),
+            distortion: Distortion::new(true, Direction4::D0),
+            keep_original: true,
+        })
+        .unwrap();
+    assert_eq!(
+        editor.map().get_unit(Point::new(6, 0)).map(|u| u.typ()),
+        Some(UnitType::ROOK)
+    );
+    assert!(editor.map().get_unit(Point::new(0, 0)).is_some());
+    editor
+        .apply(EditorCommand::Resize(10, 7, ResizeAnchor::TopLeft))
+        .unwrap();
+    assert_eq!(editor.map().width(), 10);
+    assert_eq!(editor.map().height(), 7);
+    assert!(editor.map().get_unit(Point::new(0, 7)).is_none());
+    assert!(editor.map().get_unit(Point::new(0, 0)).is_some());
+    assert!(editor.map().get_terrain(Point::new(9, 0)).is_some());
+    assert!(editor.undo());
+    assert_eq!(editor.map().width(), 8);
+    assert!(editor.map().get_unit(Point::new(0, 7)).is_some());
+    assert!(editor.undo());
+    assert!(editor.undo());
+    assert_eq!(editor.map(), &map);
+}
+
+#[test]
+fn resize_keeps_content_at_anchor() {
+    let mut map = chess_board();
+    let mut rook = map.get_unit(Point::new(0, 0)).unwrap().clone();
+    rook.set_tag(TAG_EN_PASSANT, TagValue::Point(Point::new(0, 1)));
+    map.set_unit(Point::new(0, 0), Some(rook));
+    let original = map.clone();
+    map.resize(MapSize::new(10, 9), ResizeAnchor::BottomRight)
+        .unwrap();
+    assert_eq!(map.width(), 10);
+    assert_eq!(map.height(), 9);
+    assert!(map.get_unit(Point::new(0, 0)).is_none());
+    assert_eq!(
+        map.get_unit(Point::new(2, 1))
+            .unwrap()
+            .get_tag(TAG_EN_PASSANT),
+        Some(TagValue::Point(Point::new(2, 2)))
+    );
+    assert_eq!(
+        map.get_unit(Point::new(9, 8)).map(|u| u.typ()),
+        Some(UnitType::ROOK)
+    );
+    assert_eq!(
+        map.get_terrain(Point::new(2, 2)).unwrap().typ(),
+        TerrainType::ChessPawnTile
+    );
+    assert_eq!(
+        map.get_terrain(Point::new(0, 0)).unwrap().typ(),
+        map.environment().default_terrain::<Direction4>().typ()
+    );
+    map.resize(MapSize::new(8, 8), ResizeAnchor::BottomRight)
+        .unwrap();
+    assert_eq!(map, original);
+    // cropping loses the content of removed columns
+    map.resize(MapSize::new(6, 8), ResizeAnchor::Center)
+        .unwrap();
+    assert_eq!(map.width(), 6);
+    // both columns with rooks are gone
+    assert!(
+        map.all_points()
+            .into_iter()
+            .filter_map(|p| map.get_unit(p))
+            .all(|u| u.typ() != UnitType::ROOK)
+    );
+}
+
+#[test]
+fn resize_rejects_invalid_size() {
+    let mut map = chess_board();
+    let original = map.clone();
+    let max_size = map.environment().config.max_map_size();
+    for size in [
+        MapSize::new(0, 0),
+        MapSize::new(8, MIN_SIZE - 1),
+        MapSize::new(max_size + 1, 8),
+    ] {
+        assert_eq!(
+            map.resize(size, ResizeAnchor::Center),
+            Err(ResizeError::InvalidSize(size))
+        );
+    }
+    assert_eq!(map, original);
+    map.resize(MapSize::new(MIN_SIZE, max_size), ResizeAnchor::Center)
+        .unwrap();
+}
+
+#[test]
+fn translate_content_and_wrapping() {
+    let mut map = chess_board();
+    let mut rook = map.get_unit(Point::new(0, 0)).unwrap().clone();
+    rook.set_tag(TAG_EN_PASSANT, TagValue::Point(Point::new(0, 1)));
+    map.set_unit(Point::new(0, 0), Some(rook));
+    map.translate_content(Direction4::D0.translation(1))
+        .unwrap();
+    assert_eq!(map.width(), 8);
+    assert_eq!(
+        map.get_unit(Point::new(1, 0))
+            .unwrap()
+            .get_tag(TAG_EN_PASSANT),
+        Some(TagValue::Point(Point::new(1, 1)))
+    );
+    assert!(map.get_unit(Point::new(0, 0)).is_none());
+    assert!(map.get_unit(Point::new(7, 0)).is_some());
+    // wrapping around the x-axis
+    let wrapping = WMBuilder::<Direction4>::with_transformations(
+        PointMap::new(8, 8, false),
+        vec![Transformation::new(
+            Distortion::neutral(),
+            Direction4::D0.translation(8),
+        )],
+    )
+    .unwrap();
+    let mut map = chess_board();
+    map.set_wrapping_logic(wrapping.build());
+    map.translate_content(Direction4::D90.translation(2))
+        .unwrap();
+    assert_eq!(map.wrapping_logic().seed_transformations().len(), 1);
+    // the wrapped copies would overlap
+    let unchanged = map.clone();
+    assert!(
+        map.resize(MapSize::new(9, 8), ResizeAnchor::TopLeft)
+            .is_err()
+    );
+    assert_eq!(map, unchanged);
+}
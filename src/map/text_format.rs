@@ -0,0 +1,703 @@
+use std::error::Error;
+use std::fmt::Display;
+
+use interfaces::ConfigInterface;
+use rustc_hash::FxHashMap as HashMap;
+use uniform_smart_pointer::Urc;
+
+use crate::VERSION;
+use crate::config::config::Config;
+use crate::config::environment::Environment;
+use crate::config::tag_config::TagType;
+use crate::map::board::BoardView;
+use crate::map::direction::*;
+use crate::map::map::{Map, MapType};
+use crate::map::pipe::PipeState;
+use crate::map::point::*;
+use crate::map::point_map::{MIN_SIZE, PointMap};
+use crate::map::wrapping_map::{Distortion, Transformation, WMBuilder, WrappingMap};
+use crate::tags::{Int32, TagBag, TagValue, UniqueId};
+use crate::tokens::token::Token;
+use crate::units::hero::Hero;
+use crate::units::movement::MovementType;
+use crate::units::unit::Unit;
+
+/**
+ * Text serialization of maps for archiving, diffing and sharing maps between ruleset versions.
+ * Unlike MapInterface::export, types, flags and tags are stored by name, so reordering rows
+ * in the config files doesn't break saved maps.
+ *
+ * Every line consists of ';'-separated fields, e.g. "Unit;3,4;Marine".
+ * Properties of a line (owner, tags, transported units, ...) follow on the lines below,
+ * indented by two more spaces.
+ */
+pub const MAP_TEXT_HEADER: &'static str = "TankticsMap";
+pub const MAP_TEXT_VERSION: u32 = 1;
+const INDENT: &'static str = "  ";
+
+impl<D: Direction> Map<D> {
+    pub fn export_text(&self) -> String {
+        let config = &self.environment().config;
+        let mut writer = TextWriter::default();
+        writer.line(
+            0,
+            &[MAP_TEXT_HEADER, &MAP_TEXT_VERSION.to_string(), VERSION],
+        );
+        writer.line(0, &["Ruleset", config.get_name()]);
+        let pointmap = self.wrapping_logic().pointmap();
+        writer.line(
+            0,
+            &[
+                "Grid",
+                if D::is_hex() { "Hex" } else { "Square" },
+                &pointmap.width().to_string(),
+                &pointmap.height().to_string(),
+                &pointmap.odd_if_hex().to_string(),
+            ],
+        );
+        for y in 0..pointmap.height() {
+            for x in 0..pointmap.width() {
+                let p = Point::new(x, y);
+                if !pointmap.is_point_valid(p) {
+                    writer.line(0, &["Invalid", &write_point(p)]);
+                }
+            }
+        }
+        for transformation in self.wrapping_logic().seed_transformations() {
+            let translation = transformation
+                .translate_by
+                .translate_point(&GlobalPoint::ZERO, false);
+            writer.line(
+                0,
+                &[
+                    "Transformation",
+                    &transformation.distortion.is_mirrored().to_string(),
+                    &write_direction(transformation.distortion.get_rotation()),
+                    &format!("{},{}", translation.x, translation.y),
+                ],
+            );
+        }
+        writer.tags(0, self.get_tag_bag(), self.environment());
+        for p in sorted_points(self) {
+            let position = write_point(p);
+            for pipe in self.get_pipes(p) {
+                let [d1, d2] = pipe.directions();
+                writer.line(
+                    0,
+                    &[
+                        "Pipe",
+                        &position,
+                        &write_direction(d1),
+                        &write_direction(d2),
+                    ],
+                );
+            }
+            let terrain = self.get_terrain(p).unwrap();
+            writer.line(
+                0,
+                &["Terrain", &position, config.terrain_name(terrain.typ())],
+            );
+            writer.line(1, &["Owner", &terrain.get_owner_id().to_string()]);
+            writer.tags(1, terrain.get_tag_bag(), self.environment());
+            for token in self.get_tokens(p) {
+                writer.line(0, &["Token", &position, config.token_name(token.typ())]);
+                writer.line(1, &["Owner", &token.get_owner_id().to_string()]);
+                writer.tags(1, token.get_tag_bag(), self.environment());
+            }
+            if let Some(unit) = self.get_unit(p) {
+                writer.unit(0, &["Unit", &position], unit);
+            }
+        }
+        writer.result
+    }
+}
+
+fn sorted_points<D: Direction>(map: &Map<D>) -> Vec<Point> {
+    let mut points = map.all_points();
+    points.sort_by_key(|p| (p.y, p.x));
+    points
+}
+
+#[derive(Default)]
+struct TextWriter {
+    result: String,
+}
+
+impl TextWriter {
+    fn line(&mut self, depth: usize, fields: &[&str]) {
+        for _ in 0..depth {
+            self.result.push_str(INDENT);
+        }
+        self.result.push_str(&fields.join(";"));
+        self.result.push('\n');
+    }
+
+    // sorted by name so the output doesn't depend on the order tags were added in
+    fn tags<D: Direction>(&mut self, depth: usize, bag: &TagBag<D>, environment: &Environment) {
+        let config = &environment.config;
+        let mut flags: Vec<&str> = bag.flags().map(|flag| config.flag_name(*flag)).collect();
+        flags.sort();
+        for flag in flags {
+            self.line(depth, &["Flag", flag]);
+        }
+        let mut tags: Vec<(&str, String)> = bag
+            .tags()
+            .map(|(key, value)| (config.tag_name(*key), write_tag_value(value, environment)))
+            .collect();
+        tags.sort();
+        for (key, value) in tags {
+            self.line(depth, &["Tag", key, &value]);
+        }
+    }
+
+    fn unit<D: Direction>(&mut self, depth: usize, prefix: &[&str], unit: &Unit<D>) {
+        let config = &unit.environment().config;
+        let mut fields = prefix.to_vec();
+        fields.push(unit.name());
+        self.line(depth, &fields);
+        self.line(depth + 1, &["Owner", &unit.get_owner_id().to_string()]);
+        self.line(
+            depth + 1,
+            &[
+                "MovementType",
+                config.movement_type_name(unit.sub_movement_type()),
+            ],
+        );
+        if let Some(hero) = unit.get_hero() {
+            self.line(
+                depth + 1,
+                &[
+                    "Hero",
+                    config.hero_name(hero.typ()),
+                    &hero.get_active_power().to_string(),
+                    &hero.get_charge().to_string(),
+                ],
+            );
+        }
+        self.tags(depth + 1, unit.get_tag_bag(), unit.environment());
+        for transported in unit.get_transported() {
+            self.unit(depth + 1, &["Transport"], transported);
+        }
+    }
+}
+
+fn write_point(p: Point) -> String {
+    format!("{},{}", p.x, p.y)
+}
+
+fn write_direction<D: Direction>(d: D) -> String {
+    format!("{d:?}")
+}
+
+fn write_tag_value<D: Direction>(value: &TagValue<D>, environment: &Environment) -> String {
+    let config = &environment.config;
+    match value {
+        TagValue::Unique(id) => id.get_id().to_string(),
+        TagValue::Int(value) => value.0.to_string(),
+        TagValue::Point(p) => write_point(*p),
+        TagValue::Direction(d) => write_direction(*d),
+        TagValue::UnitType(typ) => config.unit_name(*typ).to_string(),
+        TagValue::TerrainType(typ) => config.terrain_name(*typ).to_string(),
+        TagValue::MovementType(typ) => config.movement_type_name(*typ).to_string(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MapTextErrorKind {
+    MissingHeader,
+    UnsupportedVersion(String),
+    MissingGrid,
+    InvalidIndentation,
+    UnexpectedLine(String),
+    InvalidValue(String),
+    /// the point is outside the map or marked as Invalid
+    InvalidPoint(String),
+    InvalidTransformation,
+    UnknownUnit(String),
+    UnknownTerrain(String),
+    UnknownToken(String),
+    UnknownHero(String),
+    UnknownMovementType(String),
+    UnknownFlag(String),
+    UnknownTag(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MapTextError {
+    /// 1-based, 0 if the error isn't about a specific line
+    pub line: usize,
+    pub kind: MapTextErrorKind,
+}
+
+impl Display for MapTextError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.line > 0 {
+            write!(f, "line {}: ", self.line)?;
+        }
+        match &self.kind {
+            MapTextErrorKind::MissingHeader => {
+                write!(f, "missing '{MAP_TEXT_HEADER}' header")
+            }
+            MapTextErrorKind::UnsupportedVersion(version) => {
+                write!(f, "unsupported map text version '{version}'")
+            }
+            MapTextErrorKind::MissingGrid => write!(f, "missing 'Grid' line"),
+            MapTextErrorKind::InvalidIndentation => write!(f, "invalid indentation"),
+            MapTextErrorKind::UnexpectedLine(line) => write!(f, "unexpected line '{line}'"),
+            MapTextErrorKind::InvalidValue(value) => write!(f, "invalid value '{value}'"),
+            MapTextErrorKind::InvalidPoint(p) => write!(f, "'{p}' isn't a valid point on this map"),
+            MapTextErrorKind::InvalidTransformation => {
+                write!(f, "the wrapping transformations don't fit together")
+            }
+            MapTextErrorKind::UnknownUnit(name) => write!(f, "unknown unit '{name}'"),
+            MapTextErrorKind::UnknownTerrain(name) => write!(f, "unknown terrain '{name}'"),
+            MapTextErrorKind::UnknownToken(name) => write!(f, "unknown token '{name}'"),
+            MapTextErrorKind::UnknownHero(name) => write!(f, "unknown hero '{name}'"),
+            MapTextErrorKind::UnknownMovementType(name) => {
+                write!(f, "unknown movement type '{name}'")
+            }
+            MapTextErrorKind::UnknownFlag(name) => write!(f, "unknown flag '{name}'"),
+            MapTextErrorKind::UnknownTag(name) => write!(f, "unknown tag '{name}'"),
+        }
+    }
+}
+
+impl Error for MapTextError {}
+
+// one line of the text format, with the indented lines below it
+#[derive(Debug)]
+struct Node<'a> {
+    line: usize,
+    fields: Vec<&'a str>,
+    children: Vec<Node<'a>>,
+}
+
+impl<'a> Node<'a> {
+    fn error(&self, kind: MapTextErrorKind) -> MapTextError {
+        MapTextError {
+            line: self.line,
+            kind,
+        }
+    }
+
+    fn unexpected(&self) -> MapTextError {
+        self.error(MapTextErrorKind::UnexpectedLine(self.fields.join(";")))
+    }
+
+    fn field(&self, index: usize) -> Result<&'a str, MapTextError> {
+        self.fields
+            .get(index)
+            .cloned()
+            .ok_or_else(|| self.unexpected())
+    }
+
+    fn parse<T: std::str::FromStr>(&self, index: usize) -> Result<T, MapTextError> {
+        let field = self.field(index)?;
+        field
+            .parse()
+            .map_err(|_| self.error(MapTextErrorKind::InvalidValue(field.to_string())))
+    }
+
+    fn point(&self, index: usize) -> Result<Point, MapTextError> {
+        let field = self.field(index)?;
+        parse_pair(field)
+            .map(|(x, y)| Point::new(x, y))
+            .ok_or_else(|| self.error(MapTextErrorKind::InvalidValue(field.to_string())))
+    }
+
+    fn direction<D: Direction>(&self, index: usize) -> Result<D, MapTextError> {
+        let field = self.field(index)?;
+        D::list()
+            .into_iter()
+            .find(|d| write_direction(*d) == field)
+            .ok_or_else(|| self.error(MapTextErrorKind::InvalidValue(field.to_string())))
+    }
+}
+
+fn parse_pair<T: std::str::FromStr>(s: &str) -> Option<(T, T)> {
+    let (x, y) = s.split_once(',')?;
+    Some((x.trim().parse().ok()?, y.trim().parse().ok()?))
+}
+
+fn parse_nodes(text: &str) -> Result<Vec<Node<'_>>, MapTextError> {
+    // stack of unfinished nodes, one per indentation level
+    let mut stack: Vec<Node> = Vec::new();
+    let mut result = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let content = line.trim_start_matches(' ');
+        if content.trim().is_empty() {
+            continue;
+        }
+        let spaces = line.len() - content.len();
+        let depth = spaces / INDENT.len();
+        if spaces % INDENT.len() != 0 || depth > stack.len() {
+            return Err(MapTextError {
+                line: i + 1,
+                kind: MapTextErrorKind::InvalidIndentation,
+            });
+        }
+        while stack.len() > depth {
+            let node = stack.pop().unwrap();
+            match stack.last_mut() {
+                Some(parent) => parent.children.push(node),
+                None => result.push(node),
+            }
+        }
+        stack.push(Node {
+            line: i + 1,
+            fields: content.trim_end().split(';').collect(),
+            children: Vec::new(),
+        });
+    }
+    while let Some(node) = stack.pop() {
+        match stack.last_mut() {
+            Some(parent) => parent.children.push(node),
+            None => result.push(node),
+        }
+    }
+    Ok(result)
+}
+
+/**
+ * Reads a map written by Map::export_text.
+ * Names that don't exist in the config are reported as errors, but reading continues
+ * so all of them are reported at once.
+ */
+pub fn import_map_text(config: &Urc<Config>, text: &str) -> Result<MapType, Vec<MapTextError>> {
+    let nodes = parse_nodes(text).map_err(|e| vec![e])?;
+    let Some(header) = nodes.first() else {
+        return Err(vec![MapTextError {
+            line: 0,
+            kind: MapTextErrorKind::MissingHeader,
+        }]);
+    };
+    if header.fields[0] != MAP_TEXT_HEADER {
+        return Err(vec![header.error(MapTextErrorKind::MissingHeader)]);
+    }
+    let version = header.fields.get(1).cloned().unwrap_or("");
+    if version.parse::<u32>().ok() != Some(MAP_TEXT_VERSION) {
+        return Err(vec![
+            header.error(MapTextErrorKind::UnsupportedVersion(version.to_string())),
+        ]);
+    }
+    let Some(grid) = nodes.iter().find(|n| n.fields[0] == "Grid") else {
+        return Err(vec![MapTextError {
+            line: 0,
+            kind: MapTextErrorKind::MissingGrid,
+        }]);
+    };
+    match grid.field(1).map_err(|e| vec![e])? {
+        "Square" => Ok(MapType::Square(import_nodes(config, &nodes[1..])?)),
+        "Hex" => Ok(MapType::Hex(import_nodes(config, &nodes[1..])?)),
+        other => Err(vec![
+            grid.error(MapTextErrorKind::InvalidValue(other.to_string())),
+        ]),
+    }
+}
+
+fn import_nodes<D: Direction>(
+    config: &Urc<Config>,
+    nodes: &[Node],
+) -> Result<Map<D>, Vec<MapTextError>> {
+    let mut errors = Vec::new();
+    // the shape of the map has to be known before anything can be placed on it
+    let wrapping = match read_wrapping::<D>(nodes, config.max_map_size()) {
+        Ok(wrapping) => wrapping,
+        Err(e) => return Err(vec![e]),
+    };
+    let mut map = Map::new(wrapping, config);
+    let environment = map.environment().clone();
+    let mut pipes: HashMap<Point, Vec<PipeState<D>>> = HashMap::default();
+    let mut tokens: HashMap<Point, Vec<Token<D>>> = HashMap::default();
+    for node in nodes {
+        let result = match node.fields[0] {
+            "Ruleset" | "Grid" | "Invalid" | "Transformation" => Ok(()),
+            "Flag" | "Tag" => read_tag(node, map.get_tag_bag_mut(), &environment),
+            "Pipe" => read_pipe(node, &map).map(|(p, pipe)| pipes.entry(p).or_default().push(pipe)),
+            "Terrain" => read_terrain(node, &mut map, &environment, &mut errors),
+            "Token" => read_token(node, &map, &environment, &mut errors)
+                .map(|(p, token)| tokens.entry(p).or_default().push(token)),
+            "Unit" => read_map_point(node, &map).and_then(|p| {
+                let unit = read_unit(node, 2, &environment, &mut errors)?;
+                map.set_unit(p, Some(unit));
+                Ok(())
+            }),
+            _ => Err(node.unexpected()),
+        };
+        if let Err(e) = result {
+            errors.push(e);
+        }
+    }
+    for (p, pipes) in pipes {
+        map.set_pipes(p, pipes);
+    }
+    for (p, tokens) in tokens {
+        map.set_tokens(p, tokens);
+    }
+    if errors.is_empty() {
+        Ok(map)
+    } else {
+        errors.sort_by_key(|e| e.line);
+        Err(errors)
+    }
+}
+
+fn read_wrapping<D: Direction>(
+    nodes: &[Node],
+    max_size: u8,
+) -> Result<WrappingMap<D>, MapTextError> {
+    let Some(grid) = nodes.iter().find(|n| n.fields[0] == "Grid") else {
+        return Err(MapTextError {
+            line: 0,
+            kind: MapTextErrorKind::MissingGrid,
+        });
+    };
+    let width: u8 = grid.parse(2)?;
+    let height: u8 = grid.parse(3)?;
+    for (index, size) in [(2, width), (3, height)] {
+        if size < MIN_SIZE || size > max_size {
+            return Err(grid.error(MapTextErrorKind::InvalidValue(
+                grid.field(index)?.to_string(),
+            )));
+        }
+    }
+    let mut pointmap = PointMap::new(width, height, grid.parse(4)?);
+    let mut transformations = Vec::new();
+    for node in nodes {
+        match node.fields[0] {
+            "Invalid" => pointmap.set_valid(node.point(1)?, false),
+            "Transformation" => {
+                let translation = node.field(3)?;
+                let Some((x, y)) = parse_pair::<i16>(translation) else {
+                    return Err(node.error(MapTextErrorKind::InvalidValue(translation.to_string())));
+                };
+                let translate_by = <D::T as Translation<D>>::between(
+                    &GlobalPoint::ZERO,
+                    &GlobalPoint::new(x, y),
+                    false,
+                );
+                let distortion = Distortion::new(node.parse(1)?, node.direction(2)?);
+                transformations.push(Transformation::new(distortion, translate_by));
+            }
+            _ => (),
+        }
+    }
+    match WMBuilder::with_transformations(pointmap, transformations) {
+        Ok(builder) => Ok(builder.build()),
+        Err(_) => Err(MapTextError {
+            line: 0,
+            kind: MapTextErrorKind::InvalidTransformation,
+        }),
+    }
+}
+
+// the position of a Pipe, Terrain, Token or Unit line
+fn read_map_point<D: Direction>(node: &Node, map: &Map<D>) -> Result<Point, MapTextError> {
+    let p = node.point(1)?;
+    if map.wrapping_logic().pointmap().is_point_valid(p) {
+        Ok(p)
+    } else {
+        Err(node.error(MapTextErrorKind::InvalidPoint(write_point(p))))
+    }
+}
+
+fn read_pipe<D: Direction>(
+    node: &Node,
+    map: &Map<D>,
+) -> Result<(Point, PipeState<D>), MapTextError> {
+    let p = read_map_point(node, map)?;
+    match PipeState::new(node.direction(2)?, node.direction(3)?) {
+        Some(pipe) => Ok((p, pipe)),
+        None => Err(node.unexpected()),
+    }
+}
+
+fn read_terrain<D: Direction>(
+    node: &Node,
+    map: &mut Map<D>,
+    environment: &Environment,
+    errors: &mut Vec<MapTextError>,
+) -> Result<(), MapTextError> {
+    let p = read_map_point(node, map)?;
+    let name = node.field(2)?;
+    let Some(typ) = environment.config.find_terrain_by_name(name) else {
+        return Err(node.error(MapTextErrorKind::UnknownTerrain(name.to_string())));
+    };
+    let (owner, bag, _) = read_properties(node, environment, errors, &[]);
+    let mut builder = typ.instance(environment).set_tag_bag(bag);
+    if let Some(owner) = owner {
+        builder = builder.set_owner_id(owner);
+    }
+    map.set_terrain(p, builder.build());
+    Ok(())
+}
+
+fn read_token<D: Direction>(
+    node: &Node,
+    map: &Map<D>,
+    environment: &Environment,
+    errors: &mut Vec<MapTextError>,
+) -> Result<(Point, Token<D>), MapTextError> {
+    let p = read_map_point(node, map)?;
+    let name = node.field(2)?;
+    let Some(typ) = environment.config.find_token_by_name(name) else {
+        return Err(node.error(MapTextErrorKind::UnknownToken(name.to_string())));
+    };
+    let (owner, bag, _) = read_properties(node, environment, errors, &[]);
+    let mut token = typ.instance(environment);
+    if let Some(owner) = owner {
+        token.set_owner_id(owner);
+    }
+    *token.get_tag_bag_mut() = bag;
+    Ok((p, token))
+}
+
+/**
+ * reads the Owner, Flag and Tag lines below the node.
+ * lines starting with one of the other keywords are returned, everything else is an error
+ */
+fn read_properties<'a, 'b, D: Direction>(
+    node: &'b Node<'a>,
+    environment: &Environment,
+    errors: &mut Vec<MapTextError>,
+    other: &[&str],
+) -> (Option<i8>, TagBag<D>, Vec<&'b Node<'a>>) {
+    let mut owner = None;
+    let mut bag = TagBag::new();
+    let mut rest = Vec::new();
+    for child in &node.children {
+        let result = match child.fields[0] {
+            "Owner" => child.parse(1).map(|o| owner = Some(o)),
+            "Flag" | "Tag" => read_tag(child, &mut bag, environment),
+            keyword if other.contains(&keyword) => {
+                rest.push(child);
+                Ok(())
+            }
+            _ => Err(child.unexpected()),
+        };
+        if let Err(e) = result {
+            errors.push(e);
+        }
+    }
+    (owner, bag, rest)
+}
+
+fn read_unit<D: Direction>(
+    node: &Node,
+    name_index: usize,
+    environment: &Environment,
+    errors: &mut Vec<MapTextError>,
+) -> Result<Unit<D>, MapTextError> {
+    let config = &environment.config;
+    let name = node.field(name_index)?;
+    let Some(typ) = config.find_unit_by_name(name) else {
+        return Err(node.error(MapTextErrorKind::UnknownUnit(name.to_string())));
+    };
+    let (owner, bag, rest) = read_properties(
+        node,
+        environment,
+        errors,
+        &["MovementType", "Hero", "Transport"],
+    );
+    let mut movement_type = None;
+    let mut hero = None;
+    let mut transported = Vec::new();
+    for child in rest {
+        let result = match child.fields[0] {
+            "MovementType" => {
+                read_movement_type(child, environment).map(|m| movement_type = Some(m))
+            }
+            "Hero" => read_hero(child, environment).map(|h| hero = Some(h)),
+            _ => read_unit(child, 1, environment, errors).map(|unit| transported.push(unit)),
+        };
+        if let Err(e) = result {
+            errors.push(e);
+        }
+    }
+    let mut builder = typ.instance(environment).set_tag_bag(bag);
+    if let Some(owner) = owner {
+        builder = builder.set_owner_id(owner);
+    }
+    if let Some(movement_type) = movement_type {
+        builder = builder.set_movement_type(movement_type);
+    }
+    if let Some(hero) = hero {
+        builder = builder.set_hero(hero);
+    }
+    Ok(builder.set_transported(transported).build())
+}
+
+fn read_movement_type(
+    node: &Node,
+    environment: &Environment,
+) -> Result<MovementType, MapTextError> {
+    let name = node.field(1)?;
+    environment
+        .config
+        .find_movement_by_name(name)
+        .ok_or_else(|| node.error(MapTextErrorKind::UnknownMovementType(name.to_string())))
+}
+
+fn read_hero(node: &Node, environment: &Environment) -> Result<Hero, MapTextError> {
+    let name = node.field(1)?;
+    let Some(typ) = environment.config.find_hero_by_name(name) else {
+        return Err(node.error(MapTextErrorKind::UnknownHero(name.to_string())));
+    };
+    let mut hero = Hero::new(typ);
+    hero.set_active_power(node.parse(2)?);
+    hero.set_charge(environment, node.parse(3)?);
+    Ok(hero)
+}
+
+fn read_tag<D: Direction>(
+    node: &Node,
+    bag: &mut TagBag<D>,
+    environment: &Environment,
+) -> Result<(), MapTextError> {
+    let config = &environment.config;
+    let name = node.field(1)?;
+    if node.fields[0] == "Flag" {
+        let Some(flag) = config.flag_by_name(name) else {
+            return Err(node.error(MapTextErrorKind::UnknownFlag(name.to_string())));
+        };
+        bag.set_flag(environment, flag);
+        return Ok(());
+    }
+    let Some(key) = config.tag_by_name(name) else {
+        return Err(node.error(MapTextErrorKind::UnknownTag(name.to_string())));
+    };
+    let value = node.field(2)?;
+    let value = match config.tag_type(key) {
+        TagType::Flag => return Err(node.unexpected()),
+        TagType::Unique { .. } => {
+            let id: usize = node.parse(2)?;
+            // with_id would clamp the id and silently merge it with another one
+            if id > UniqueId::MAX_VALUE {
+                return Err(node.error(MapTextErrorKind::InvalidValue(value.to_string())));
+            }
+            TagValue::Unique(UniqueId::with_id(environment, key, id))
+        }
+        TagType::Int { .. } => TagValue::Int(Int32(node.parse(2)?)),
+        TagType::Point => TagValue::Point(node.point(2)?),
+        TagType::Direction => TagValue::Direction(node.direction(2)?),
+        TagType::UnitType => match config.find_unit_by_name(value) {
+            Some(typ) => TagValue::UnitType(typ),
+            None => return Err(node.error(MapTextErrorKind::UnknownUnit(value.to_string()))),
+        },
+        TagType::TerrainType => match config.find_terrain_by_name(value) {
+            Some(typ) => TagValue::TerrainType(typ),
+            None => return Err(node.error(MapTextErrorKind::UnknownTerrain(value.to_string()))),
+        },
+        TagType::MovementType => match config.find_movement_by_name(value) {
+            Some(typ) => TagValue::MovementType(typ),
+            None => {
+                return Err(node.error(MapTextErrorKind::UnknownMovementType(value.to_string())));
+            }
+        },
+    };
+    bag.set_tag(environment, key, value.clone());
+    if bag.get_tag(key) != Some(value) {
+        return Err(node.error(MapTextErrorKind::InvalidValue(node.fields[2..].join(";"))));
+    }
+    Ok(())
+}
@@ -787,6 +787,7 @@ mod tests {
     use super::*;
     use crate::config::config::Config;
     use crate::game::game::Game;
+    use crate::game::rng::GameRng;
     use crate::map::board::{Board, BoardView};
     use crate::map::map::{Map, NeighborMode};
     use crate::map::point_map::PointMap;
@@ -1247,7 +1248,8 @@ mod tests {
             ]
         );
         let settings = map.settings().unwrap();
-        let (game, _) = Game::new_server(map, &settings, settings.build_default(), Urc::new(|| 0.));
+        let (game, _) =
+            Game::new_server(map, &settings, settings.build_default(), GameRng::default());
         let game = Board::from(&game);
         let environment = game.environment();
         let rook = UnitType::ROOK
@@ -1,10 +1,11 @@
 use interfaces::ClientPerspective;
+use semver::Version;
 use zipper::*;
-use zipper_derive::Zippable;
 
 use crate::commander::Commander;
 use crate::config::config::Config;
 use crate::config::environment::Environment;
+use crate::game::clock::{PlayerClock, unzip_clock};
 use crate::map::direction::Direction;
 use crate::tags::{TagBag, TagValue};
 use crate::units::UnitVisibility;
@@ -88,13 +89,14 @@ pub fn to_client_perspective(value: &Perspective) -> ClientPerspective {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Zippable)]
-#[zippable(support_ref = Environment)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Player<D: Direction> {
     owner_id: Owner,
     pub dead: bool,
     pub commander: Commander,
     tags: TagBag<D>,
+    /// None if the game doesn't have a TimeControl
+    pub clock: Option<PlayerClock>,
 }
 
 impl<D: Direction> Player<D> {
@@ -104,9 +106,32 @@ impl<D: Direction> Player<D> {
             dead: false,
             commander,
             tags,
+            clock: None,
         }
     }
 
+    pub fn export(&self, zipper: &mut Zipper, environment: &Environment) {
+        self.owner_id.export(zipper, environment);
+        zipper.write_bool(self.dead);
+        self.commander.export(zipper, environment);
+        self.tags.export(zipper, environment);
+        self.clock.zip(zipper);
+    }
+
+    pub fn import(
+        unzipper: &mut Unzipper,
+        environment: &Environment,
+        version: &Version,
+    ) -> Result<Self, ZipperError> {
+        Ok(Self {
+            owner_id: Owner::import(unzipper, environment)?,
+            dead: unzipper.read_bool()?,
+            commander: Commander::import(unzipper, environment)?,
+            tags: TagBag::import(unzipper, environment)?,
+            clock: unzip_clock(unzipper, version)?,
+        })
+    }
+
     pub fn get_owner_id(&self) -> i8 {
         self.owner_id.0
     }
@@ -154,6 +179,8 @@ impl<D: Direction> Player<D> {
             tags: self
                 .tags
                 .fog_replacement(self.commander.environment(), UnitVisibility::Normal),
+            // everyone can see the other players' clocks
+            clock: self.clock,
         }
     }
 }
@@ -12,12 +12,15 @@ use crate::game::event_handler::EventHandler;
 use crate::map::board::{Board, BoardView, current_team};
 use crate::map::direction::Direction;
 use crate::map::point::Point;
+use crate::script::profiler::ScriptHook;
 use crate::terrain::terrain::Terrain;
 use crate::tokens::token::Token;
+use crate::units::commands::MAX_CUSTOM_ACTION_STEPS;
 use crate::units::hero::{HeroInfluence, HeroType};
 use crate::units::movement::{Path, TBallast};
 use crate::units::unit::Unit;
 
+use super::executor::ScriptError;
 use super::*;
 
 // Shop windows can have at most this many entries.
@@ -130,6 +133,31 @@ pub enum CustomActionDataOptions<D: Direction> {
 }
 
 impl<D: Direction> CustomActionDataOptions<D> {
+    /**
+     * every input the player could choose from these options,
+     * in a stable order so enumerations are reproducible
+     */
+    pub fn inputs(&self) -> Vec<CustomActionInput<D>> {
+        match self {
+            Self::Point(options) => {
+                let mut points: Vec<Point> = options.iter().cloned().collect();
+                points.sort_by_key(|p| (p.y, p.x));
+                points.into_iter().map(CustomActionInput::Point).collect()
+            }
+            Self::Direction(_visual_center, options) => D::list()
+                .into_iter()
+                .filter(|d| options.contains(d))
+                .map(CustomActionInput::Direction)
+                .collect(),
+            Self::Shop(_, options) => options
+                .iter()
+                .enumerate()
+                .filter(|(_, item)| item.enabled)
+                .map(|(i, _)| CustomActionInput::ShopItem(i.into()))
+                .collect(),
+        }
+    }
+
     pub fn contains(&self, data: &CustomActionInput<D>) -> Option<CustomActionData<D>> {
         match (self, data) {
             (Self::Point(options), CustomActionInput::Point(option)) => {
@@ -251,7 +279,7 @@ fn run_input_script<D: Direction>(
     )));
     first_argument.insert(CONST_NAME_PLAYER.into(), Dynamic::from(controller.clone()));
     let executor = game.executor(first_argument);
-    match executor.run::<D, bool>(script, ()) {
+    match executor.run::<D, bool>(ScriptHook::CustomAction, script, ()) {
         Ok(true) => CustomActionTestResult::Success,
         Ok(false) => CustomActionTestResult::Failure,
         Err(e) => {
@@ -272,6 +300,44 @@ fn run_input_script<D: Direction>(
     }
 }
 
+/**
+ * walks through the options an input script offers and collects every input sequence it accepts.
+ * test should run the input script with the given data, e.g. through run_unit_input_script
+ */
+pub fn all_script_inputs<D: Direction>(
+    test: impl Fn(&[CustomActionInput<D>]) -> CustomActionTestResult<D>,
+) -> Vec<Vec<CustomActionInput<D>>> {
+    fn collect<D: Direction>(
+        test: &impl Fn(&[CustomActionInput<D>]) -> CustomActionTestResult<D>,
+        data: Vec<CustomActionInput<D>>,
+        result: &mut Vec<Vec<CustomActionInput<D>>>,
+    ) {
+        let options = match test(&data) {
+            CustomActionTestResult::Success => {
+                result.push(data);
+                return;
+            }
+            CustomActionTestResult::Failure => return,
+            CustomActionTestResult::Next(options) => options,
+            CustomActionTestResult::NextOrSuccess(options) => {
+                result.push(data.clone());
+                options
+            }
+        };
+        if data.len() >= MAX_CUSTOM_ACTION_STEPS as usize {
+            return;
+        }
+        for input in options.inputs() {
+            let mut data = data.clone();
+            data.push(input);
+            collect(test, data, result);
+        }
+    }
+    let mut result = Vec::new();
+    collect(&test, Vec::new(), &mut result);
+    result
+}
+
 pub(super) struct InputScriptController<D: Direction> {
     script: usize,
     test_result: Option<CustomActionTestResult<D>>,
@@ -408,7 +474,7 @@ fn is_script_input_valid<D: Direction>(
     )));
     first_argument.insert(CONST_NAME_PLAYER.into(), Dynamic::from(controller.clone()));
     let executor = game.executor(first_argument);
-    match executor.run::<D, bool>(script, ()) {
+    match executor.run::<D, bool>(ScriptHook::CustomAction, script, ()) {
         Ok(b) => {
             let mut controller = controller.borrow_mut();
             if b && controller.data.len() == data.len() {
@@ -530,14 +596,14 @@ fn execute_script<D: Direction>(
     data: Option<Vec<CustomActionData<D>>>,
 ) {
     let executor = handler.executor(first_argument);
-    let result: Result<(), Box<EvalAltResult>> = if let Some(data) = data {
+    let result: Result<(), ScriptError> = if let Some(data) = data {
         let data = data
             .iter()
             .map(CustomActionData::into_dynamic)
             .collect::<Array>();
-        executor.run::<D, ()>(script, (data,))
+        executor.run::<D, ()>(ScriptHook::CustomAction, script, (data,))
     } else {
-        executor.run::<D, ()>(script, ())
+        executor.run::<D, ()>(ScriptHook::CustomAction, script, ())
     };
     match result {
         Ok(_) => (),
@@ -0,0 +1,152 @@
+use std::fmt::Display;
+
+use rhai::{Dynamic, EvalAltResult, Map, Position};
+
+use super::executor::{ScriptError, ScriptErrorCause};
+
+/// broken rulesets can fail the same script every turn, so only this many failures are kept
+pub const MAX_SCRIPT_FAILURES: usize = 100;
+/// Debug output of long arguments (e.g. strings) is cut off
+const MAX_ARGUMENT_LENGTH: usize = 200;
+
+/// One failed call into a ruleset script.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptFailure {
+    /// what the engine was doing when calling the script, e.g. "NumberMod::Rhai"
+    pub location: String,
+    pub function: String,
+    pub arguments: Vec<String>,
+    /// 1-based line and column within the script, if Rhai knows where the error happened
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub error: String,
+}
+
+impl ScriptFailure {
+    pub(crate) fn new(location: &str, function: &str, error: &ScriptError) -> Self {
+        let position = match &error.cause {
+            ScriptErrorCause::Rhai(error) => innermost_position(error),
+            ScriptErrorCause::LimitExceeded(_) => Position::NONE,
+        };
+        Self {
+            location: location.to_string(),
+            function: function.to_string(),
+            arguments: error.arguments.clone(),
+            line: position.line(),
+            column: position.position(),
+            error: error.to_string(),
+        }
+    }
+}
+
+/**
+ * what's kept of a script argument in case the call fails.
+ * the arguments are moved into the call, and copying units or arrays for every call would be expensive
+ */
+pub(crate) enum ArgumentSummary {
+    Value(Dynamic),
+    Type(&'static str),
+}
+
+impl ArgumentSummary {
+    pub(crate) fn new(argument: &Dynamic) -> Self {
+        if argument.is_array() || argument.is_map() || argument.is_blob() || argument.is_variant() {
+            Self::Type(argument.type_name())
+        } else {
+            Self::Value(argument.clone())
+        }
+    }
+}
+
+/**
+ * names and types of the values in the script's first argument,
+ * followed by the Debug output of the other arguments.
+ * arguments that are expensive to copy are only described by their type
+ */
+pub(crate) fn describe_arguments(context: &Map, arguments: &[ArgumentSummary]) -> Vec<String> {
+    let mut result: Vec<String> = context
+        .iter()
+        .map(|(key, value)| format!("{key}: {}", value.type_name()))
+        .collect();
+    result.sort();
+    for argument in arguments {
+        let argument = match argument {
+            ArgumentSummary::Value(value) => value,
+            ArgumentSummary::Type(type_name) => {
+                result.push(type_name.to_string());
+                continue;
+            }
+        };
+        let mut argument = format!("{argument:?}");
+        if argument.len() > MAX_ARGUMENT_LENGTH {
+            let mut end = MAX_ARGUMENT_LENGTH;
+            while !argument.is_char_boundary(end) {
+                end -= 1;
+            }
+            argument.truncate(end);
+            argument.push_str("...");
+        }
+        result.push(argument);
+    }
+    result
+}
+
+impl Display for ScriptFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.location, self.function)?;
+        if let Some(line) = self.line {
+            write!(f, ":{line}")?;
+            if let Some(column) = self.column {
+                write!(f, ":{column}")?;
+            }
+        }
+        write!(f, "({}): {}", self.arguments.join(", "), self.error)
+    }
+}
+
+/// errors in nested function calls are wrapped, the position of the wrapper isn't helpful
+fn innermost_position(error: &EvalAltResult) -> Position {
+    match error {
+        EvalAltResult::ErrorInFunctionCall(_, _, inner, position)
+        | EvalAltResult::ErrorInModule(_, inner, position) => {
+            let inner = innermost_position(inner);
+            if inner.is_none() { *position } else { inner }
+        }
+        error => error.position(),
+    }
+}
+
+/**
+ * Collects failed script calls so the server can report broken rulesets to their authors.
+ * Shared by all clones of an Environment.
+ */
+#[derive(Debug, Default)]
+pub struct ScriptDiagnostics {
+    failures: Vec<ScriptFailure>,
+    /// failures that didn't fit anymore
+    dropped: usize,
+}
+
+impl ScriptDiagnostics {
+    pub fn record(&mut self, failure: ScriptFailure) {
+        if self.failures.len() < MAX_SCRIPT_FAILURES {
+            self.failures.push(failure);
+        } else {
+            self.dropped += 1;
+        }
+    }
+
+    pub fn failures(&self) -> &[ScriptFailure] {
+        &self.failures
+    }
+
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    /// returns the recorded failures and how many were dropped, then starts over
+    pub fn take(&mut self) -> (Vec<ScriptFailure>, usize) {
+        let dropped = std::mem::take(&mut self.dropped);
+        (std::mem::take(&mut self.failures), dropped)
+    }
+}
@@ -1,4 +1,5 @@
 use std::any::{Any, type_name};
+use std::fmt::Display;
 use std::marker::PhantomData;
 
 use rhai::*;
@@ -6,6 +7,55 @@ use rhai::*;
 use crate::config::environment::Environment;
 use crate::map::direction::Direction;
 use crate::script::CONST_NAME_CONFIG;
+use crate::script::diagnostics::{ArgumentSummary, describe_arguments};
+use crate::script::limits::{ScriptLimit, start_deadline};
+use crate::script::profiler::{ProfileTimer, ScriptHook};
+
+#[derive(Debug)]
+pub enum ScriptErrorCause {
+    /// the script was stopped because it exceeded one of the ruleset's ScriptLimits
+    LimitExceeded(ScriptLimit),
+    Rhai(Box<EvalAltResult>),
+}
+
+impl From<Box<EvalAltResult>> for ScriptErrorCause {
+    fn from(error: Box<EvalAltResult>) -> Self {
+        match ScriptLimit::from_error(&error) {
+            Some(limit) => Self::LimitExceeded(limit),
+            None => Self::Rhai(error),
+        }
+    }
+}
+
+impl Display for ScriptErrorCause {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::LimitExceeded(limit) => write!(f, "script exceeded its {limit} limit"),
+            Self::Rhai(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ScriptError {
+    pub cause: ScriptErrorCause,
+    /// what the script was called with, see describe_arguments
+    pub arguments: Vec<String>,
+}
+
+impl ScriptError {
+    pub fn is_limit_exceeded(&self) -> bool {
+        matches!(self.cause, ScriptErrorCause::LimitExceeded(_))
+    }
+}
+
+impl Display for ScriptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.cause)
+    }
+}
+
+impl std::error::Error for ScriptError {}
 
 pub struct Executor<'a> {
     first_argument: Map,
@@ -29,38 +79,78 @@ impl<'a> Executor<'a> {
 
     pub fn run<D: Direction, T: Any>(
         &self,
+        hook: ScriptHook,
         function_index: usize,
         additional_arguments: impl FuncArgs,
-    ) -> Result<T, Box<EvalAltResult>> {
+    ) -> Result<T, ScriptError> {
         let (ast, name) = self.environment.get_rhai_function(function_index);
-        self.run_ast::<D, T>(ast, name, additional_arguments)
+        let timer = self
+            .environment
+            .is_script_profiling()
+            .then(ProfileTimer::start);
+        let result = self.call::<D, T>(ast, name, additional_arguments);
+        if let Some(timer) = timer {
+            let label = self.environment.get_rhai_function_label(function_index);
+            self.environment
+                .record_script_call(hook, &label, timer.elapsed());
+        }
+        result
     }
 
     pub fn run_ast<D: Direction, T: Any>(
         &self,
+        hook: ScriptHook,
         ast: &AST,
         function: impl AsRef<str>,
         additional_arguments: impl FuncArgs,
-    ) -> Result<T, Box<EvalAltResult>> {
+    ) -> Result<T, ScriptError> {
+        let timer = self
+            .environment
+            .is_script_profiling()
+            .then(ProfileTimer::start);
+        let result = self.call::<D, T>(ast, function.as_ref(), additional_arguments);
+        if let Some(timer) = timer {
+            self.environment
+                .record_script_call(hook, function.as_ref(), timer.elapsed());
+        }
+        result
+    }
+
+    fn call<D: Direction, T: Any>(
+        &self,
+        ast: &AST,
+        function: &str,
+        additional_arguments: impl FuncArgs,
+    ) -> Result<T, ScriptError> {
         let engine = self.environment.config.engine::<D>();
         let mut scope = Scope::new();
         let options = CallFnOptions::new().eval_ast(false).rewind_scope(true);
         let mut args = vec![Dynamic::from(self.first_argument.clone())];
         additional_arguments.parse(&mut args);
-        let result: Dynamic =
-            engine.call_fn_with_options(options, &mut scope, ast, function, args)?;
+        let summaries: Vec<ArgumentSummary> = args[1..].iter().map(ArgumentSummary::new).collect();
+        let result: Result<Dynamic, _> = {
+            let _deadline = start_deadline(self.environment.script_time_limit());
+            engine.call_fn_with_options(options, &mut scope, ast, function, args)
+        };
+        let error = |cause: Box<EvalAltResult>| ScriptError {
+            cause: cause.into(),
+            arguments: describe_arguments(&self.first_argument, &summaries),
+        };
+        let result = result.map_err(error)?;
         result.try_cast_result().map_err(|r| {
             let result_type = engine.map_type_name(r.type_name());
             let cast_type = match type_name::<T>() {
                 typ if typ.contains("::") => engine.map_type_name(typ),
                 typ => typ,
             };
-            EvalAltResult::ErrorMismatchOutputType(
-                cast_type.into(),
-                result_type.into(),
-                Position::NONE,
+            error(
+                EvalAltResult::ErrorMismatchOutputType(
+                    cast_type.into(),
+                    result_type.into(),
+                    Position::NONE,
+                )
+                .into(),
             )
-            .into()
         })
     }
 }
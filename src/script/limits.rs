@@ -0,0 +1,162 @@
+#[cfg(not(target_family = "wasm"))]
+use std::cell::Cell;
+use std::fmt::Display;
+use std::time::Duration;
+#[cfg(not(target_family = "wasm"))]
+use std::time::Instant;
+
+use rhai::{Dynamic, Engine, EvalAltResult};
+
+/// returned by the on_progress callback when a script runs past the deadline
+const TIME_LIMIT_TOKEN: &'static str = "time limit exceeded";
+/// checking the clock after every single operation would be too expensive
+const OPERATIONS_PER_CLOCK_CHECK: u64 = 1024;
+
+/**
+ * Upper bounds for a single call into a ruleset script, so a script with
+ * an accidental infinite loop can't hang the server.
+ * Can be changed per ruleset in ruleset.csv.
+ * These limits don't depend on the host, so every client stops a script at the same point.
+ * Wall time is limited separately by the server, see start_deadline
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptLimits {
+    pub max_operations: u64,
+    pub max_string_size: usize,
+    pub max_array_size: usize,
+    pub max_map_size: usize,
+}
+
+impl Default for ScriptLimits {
+    fn default() -> Self {
+        Self {
+            max_operations: 1_000_000,
+            max_string_size: 10_000,
+            max_array_size: 10_000,
+            max_map_size: 10_000,
+        }
+    }
+}
+
+impl ScriptLimits {
+    // https://rhai.rs/book/safety/index.html
+    pub(crate) fn apply(&self, engine: &mut Engine) {
+        engine.set_max_operations(self.max_operations);
+        engine.set_max_string_size(self.max_string_size);
+        engine.set_max_array_size(self.max_array_size);
+        engine.set_max_map_size(self.max_map_size);
+        #[cfg(not(target_family = "wasm"))]
+        engine.on_progress(|operations| {
+            if operations % OPERATIONS_PER_CLOCK_CHECK != 0 {
+                return None;
+            }
+            let deadline = DEADLINE.with(Cell::get)?;
+            if Instant::now() >= deadline {
+                Some(Dynamic::from(TIME_LIMIT_TOKEN.to_string()))
+            } else {
+                None
+            }
+        });
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScriptLimit {
+    Operations,
+    StringSize,
+    ArraySize,
+    MapSize,
+    Time,
+    CallDepth,
+}
+
+impl ScriptLimit {
+    /// returns which limit was exceeded, if the error was caused by one
+    pub fn from_error(error: &EvalAltResult) -> Option<Self> {
+        match error {
+            EvalAltResult::ErrorInFunctionCall(_, _, inner, _)
+            | EvalAltResult::ErrorInModule(_, inner, _) => Self::from_error(inner),
+            EvalAltResult::ErrorTooManyOperations(_) => Some(Self::Operations),
+            EvalAltResult::ErrorStackOverflow(_) => Some(Self::CallDepth),
+            EvalAltResult::ErrorDataTooLarge(what, _) => {
+                let what = what.to_lowercase();
+                if what.contains("string") {
+                    Some(Self::StringSize)
+                } else if what.contains("array") {
+                    Some(Self::ArraySize)
+                } else {
+                    Some(Self::MapSize)
+                }
+            }
+            EvalAltResult::ErrorTerminated(token, _)
+                if token
+                    .clone()
+                    .into_immutable_string()
+                    .is_ok_and(|token| token.as_str() == TIME_LIMIT_TOKEN) =>
+            {
+                Some(Self::Time)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Display for ScriptLimit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Operations => write!(f, "operation count"),
+            Self::StringSize => write!(f, "string size"),
+            Self::ArraySize => write!(f, "array size"),
+            Self::MapSize => write!(f, "map size"),
+            Self::Time => write!(f, "time"),
+            Self::CallDepth => write!(f, "call depth"),
+        }
+    }
+}
+
+thread_local! {
+    // the engine is shared by all games of a Config, so the deadline of the running call is stored here
+    #[cfg(not(target_family = "wasm"))]
+    static DEADLINE: Cell<Option<Instant>> = const { Cell::new(None) };
+}
+
+/**
+ * Starts the wall time budget of a script call, if the server set one.
+ * A script that is still running when the budget is used up is stopped
+ * and fails with ScriptLimit::Time, like it would for the other limits.
+ * Nested calls (a script calling rust code that runs another script) share the outer budget.
+ * The budget ends when the returned value is dropped.
+ * Ignored on wasm since there's no clock available
+ */
+#[cfg_attr(target_family = "wasm", allow(unused_variables))]
+pub(crate) fn start_deadline(time_limit: Option<Duration>) -> Deadline {
+    #[cfg(not(target_family = "wasm"))]
+    {
+        let deadline = time_limit.map(|limit| Instant::now() + limit);
+        let outermost = DEADLINE.with(|outer| {
+            if outer.get().is_some() || deadline.is_none() {
+                false
+            } else {
+                outer.set(deadline);
+                true
+            }
+        });
+        Deadline { outermost }
+    }
+    #[cfg(target_family = "wasm")]
+    Deadline {}
+}
+
+pub(crate) struct Deadline {
+    #[cfg(not(target_family = "wasm"))]
+    outermost: bool,
+}
+
+impl Drop for Deadline {
+    fn drop(&mut self) {
+        #[cfg(not(target_family = "wasm"))]
+        if self.outermost {
+            DEADLINE.with(|deadline| deadline.set(None));
+        }
+    }
+}
@@ -2,7 +2,10 @@ use rhai::packages::*;
 use rhai::*;
 
 pub mod custom_action;
+pub mod diagnostics;
 pub mod executor;
+pub mod limits;
+pub mod profiler;
 mod rhai_action_data;
 mod rhai_environment;
 mod rhai_fraction;
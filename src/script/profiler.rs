@@ -0,0 +1,199 @@
+use std::fmt::Display;
+use std::time::Duration;
+#[cfg(not(target_family = "wasm"))]
+use std::time::Instant;
+
+use rustc_hash::FxHashMap as HashMap;
+
+/// What the engine was doing when it called a script.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ScriptHook {
+    UnitFilter,
+    TerrainFilter,
+    TokenFilter,
+    AttackFilter,
+    GlobalFilter,
+    NumberModifier,
+    AttackPattern,
+    Attack,
+    Effect,
+    GlobalEvent,
+    UnitEvent,
+    TokenEvent,
+    CustomAction,
+    /// UnitDeathTest and UnitMovableTest from ruleset.csv
+    Ruleset,
+}
+
+impl Display for ScriptHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/**
+ * Measures the wall time of a script call. There's no clock on wasm,
+ * so only call counts are meaningful there.
+ */
+pub(crate) struct ProfileTimer {
+    #[cfg(not(target_family = "wasm"))]
+    start: Instant,
+}
+
+impl ProfileTimer {
+    pub(crate) fn start() -> Self {
+        Self {
+            #[cfg(not(target_family = "wasm"))]
+            start: Instant::now(),
+        }
+    }
+
+    pub(crate) fn elapsed(&self) -> Duration {
+        #[cfg(not(target_family = "wasm"))]
+        return self.start.elapsed();
+        #[cfg(target_family = "wasm")]
+        Duration::ZERO
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProfileStats {
+    pub calls: usize,
+    /// includes the time spent in nested script calls
+    pub time: Duration,
+}
+
+impl ProfileStats {
+    fn add(&mut self, other: Self) {
+        self.calls += other.calls;
+        self.time += other.time;
+    }
+}
+
+/**
+ * Collects call counts and cumulative time per script function while profiling is enabled,
+ * see Environment::enable_script_profiling.
+ */
+#[derive(Debug, Default)]
+pub struct ScriptProfiler {
+    turn: usize,
+    stats: HashMap<(usize, ScriptHook, String), ProfileStats>,
+}
+
+impl ScriptProfiler {
+    pub(crate) fn set_turn(&mut self, turn: usize) {
+        self.turn = turn;
+    }
+
+    pub(crate) fn record(&mut self, hook: ScriptHook, function: &str, time: Duration) {
+        let key = (self.turn, hook, function.to_string());
+        self.stats
+            .entry(key)
+            .or_default()
+            .add(ProfileStats { calls: 1, time });
+    }
+
+    pub fn report(&self) -> ScriptProfile {
+        let mut entries: Vec<ScriptProfileEntry> = self
+            .stats
+            .iter()
+            .map(|((turn, hook, function), stats)| ScriptProfileEntry {
+                turn: *turn,
+                hook: *hook,
+                function: function.clone(),
+                stats: *stats,
+            })
+            .collect();
+        entries.sort_by(|a, b| (a.turn, a.hook, &a.function).cmp(&(b.turn, b.hook, &b.function)));
+        ScriptProfile { entries }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptProfileEntry {
+    pub turn: usize,
+    pub hook: ScriptHook,
+    /// "file > function" for functions registered by the config
+    pub function: String,
+    pub stats: ProfileStats,
+}
+
+/// Snapshot of a ScriptProfiler, sorted by turn, hook and function.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScriptProfile {
+    pub entries: Vec<ScriptProfileEntry>,
+}
+
+impl ScriptProfile {
+    pub fn total(&self) -> ProfileStats {
+        let mut total = ProfileStats::default();
+        for entry in &self.entries {
+            total.add(entry.stats);
+        }
+        total
+    }
+
+    pub fn per_turn(&self) -> Vec<(usize, ProfileStats)> {
+        self.aggregate(|entry| entry.turn)
+    }
+
+    pub fn per_hook(&self) -> Vec<(ScriptHook, ProfileStats)> {
+        self.aggregate(|entry| entry.hook)
+    }
+
+    /// summed over all turns, most expensive first
+    pub fn per_function(&self) -> Vec<(String, ProfileStats)> {
+        let mut result = self.aggregate(|entry| entry.function.clone());
+        result.sort_by(|(_, a), (_, b)| b.time.cmp(&a.time).then(b.calls.cmp(&a.calls)));
+        result
+    }
+
+    fn aggregate<K: Ord + Clone + std::hash::Hash>(
+        &self,
+        key: impl Fn(&ScriptProfileEntry) -> K,
+    ) -> Vec<(K, ProfileStats)> {
+        let mut map: HashMap<K, ProfileStats> = HashMap::default();
+        for entry in &self.entries {
+            map.entry(key(entry)).or_default().add(entry.stats);
+        }
+        let mut result: Vec<(K, ProfileStats)> = map.into_iter().collect();
+        result.sort_by(|(a, _), (b, _)| a.cmp(b));
+        result
+    }
+
+    /// one line per turn, hook and function, separated by ';' like the config files
+    pub fn to_csv(&self) -> String {
+        let mut result = String::from("Turn;Hook;Function;Calls;Microseconds\n");
+        for entry in &self.entries {
+            result.push_str(&format!(
+                "{};{};{};{};{}\n",
+                entry.turn,
+                entry.hook,
+                entry.function,
+                entry.stats.calls,
+                entry.stats.time.as_micros()
+            ));
+        }
+        result
+    }
+}
+
+impl Display for ScriptProfile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let total = self.total();
+        writeln!(f, "{} script calls, {:?} total", total.calls, total.time)?;
+        writeln!(f, "per hook:")?;
+        for (hook, stats) in self.per_hook() {
+            writeln!(f, "  {hook}: {} calls, {:?}", stats.calls, stats.time)?;
+        }
+        writeln!(f, "per turn:")?;
+        for (turn, stats) in self.per_turn() {
+            writeln!(f, "  {turn}: {} calls, {:?}", stats.calls, stats.time)?;
+        }
+        writeln!(f, "per function:")?;
+        for (function, stats) in self.per_function() {
+            writeln!(f, "  {function}: {} calls, {:?}", stats.calls, stats.time)?;
+        }
+        Ok(())
+    }
+}
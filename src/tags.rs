@@ -6,12 +6,14 @@ use zipper::*;
 
 use crate::config::environment::Environment;
 use crate::config::parse::FromConfig;
+use crate::config::reload::{ConfigDiff, ConfigMigrationError};
 use crate::config::tag_config::TagType;
+use crate::game::rng::GameRng;
 use crate::map::board::BoardView;
 use crate::map::direction::Direction;
 use crate::map::map::Map;
 use crate::map::point::Point;
-use crate::map::point_map::MAX_AREA;
+use crate::map::point_map::DEFAULT_MAX_AREA;
 use crate::map::wrapping_map::Distortion;
 use crate::terrain::TerrainType;
 use crate::units::UnitVisibility;
@@ -134,6 +136,37 @@ impl<D: Direction> TagBag<D> {
         }
     }
 
+    /// the same flags and tags, but with indices of the new environment's config
+    pub(crate) fn migrate_config(
+        &self,
+        diff: &ConfigDiff,
+        environment: &Environment,
+    ) -> Result<Self, ConfigMigrationError> {
+        let mut result = Self::new();
+        for flag in &self.flags {
+            result.flags.push(diff.flag(*flag)?);
+        }
+        for (key, value) in &self.tags {
+            let key = diff.tag(*key)?;
+            let value = match value {
+                TagValue::Unique(id) => TagValue::Unique(id.migrate_config(environment, key)),
+                TagValue::Int(value) => match environment.config.tag_type(key) {
+                    TagType::Int { min, max, .. } => {
+                        TagValue::Int(Int32(value.0.max(*min).min(*max)))
+                    }
+                    _ => TagValue::Int(*value),
+                },
+                TagValue::Point(p) => TagValue::Point(*p),
+                TagValue::Direction(d) => TagValue::Direction(*d),
+                TagValue::UnitType(typ) => TagValue::UnitType(diff.unit_type(*typ)?),
+                TagValue::TerrainType(typ) => TagValue::TerrainType(diff.terrain_type(*typ)?),
+                TagValue::MovementType(typ) => TagValue::MovementType(diff.movement_type(*typ)?),
+            };
+            result.tags.insert(key, value);
+        }
+        Ok(result)
+    }
+
     pub fn remove_tag(&mut self, key: usize) -> Option<TagValue<D>> {
         self.tags.remove(&key)
     }
@@ -201,11 +234,11 @@ pub enum TagValue<D: Direction> {
 }
 
 impl<D: Direction> TagValue<D> {
-    pub fn default_value(map: &Map<D>, tag_key: usize, random: f32) -> Self {
+    pub fn default_value(map: &Map<D>, tag_key: usize, rng: &mut GameRng) -> Self {
         match map.environment().config.tag_type(tag_key) {
             TagType::Flag => panic!("Flag {tag_key} doesn't have a TagValue"),
             TagType::Unique { .. } => Self::Unique(
-                UniqueId::new(map.environment(), tag_key, random)
+                UniqueId::new(map.environment(), tag_key, rng)
                     .expect(&format!("Couldn't generate unique ID for {tag_key}")),
             ),
             TagType::Point => Self::Point(map.all_points()[0]),
@@ -390,16 +423,17 @@ impl<D: Direction> From<Urc<UniqueId>> for TagValue<D> {
 }
 
 impl UniqueId {
-    pub(crate) const MAX_VALUE: usize = MAX_AREA as usize * 100 - 1;
+    // doesn't grow with larger maps, so ids keep their bit width in older saves
+    pub(crate) const MAX_VALUE: usize = DEFAULT_MAX_AREA as usize * 100 - 1;
 
     pub fn add_to_pool(&self, pool: &mut FxHashSet<usize>) {
         pool.insert(self.id);
     }
 
-    pub fn new(environment: &Environment, tag_key: usize, random: f32) -> Option<Urc<Self>> {
+    pub fn new(environment: &Environment, tag_key: usize, rng: &mut GameRng) -> Option<Urc<Self>> {
         // "add_unique_id" isn't needed here
         // because "generate_unique_id" automatically adds the generated id to the pool
-        let id = environment.generate_unique_id(tag_key, random)?;
+        let id = environment.generate_unique_id(tag_key, rng.next_f32())?;
         Some(Urc::new(Self {
             environment: environment.clone(),
             tag: tag_key,
@@ -411,6 +445,22 @@ impl UniqueId {
         self.id
     }
 
+    /// keeps the id, but moves it into the pool of the new environment
+    fn migrate_config(&self, environment: &Environment, tag_key: usize) -> Urc<Self> {
+        Self::with_id(environment, tag_key, self.id)
+    }
+
+    /// for restoring a known id, e.g. from the text map format
+    pub(crate) fn with_id(environment: &Environment, tag_key: usize, id: usize) -> Urc<Self> {
+        let id = id.min(Self::MAX_VALUE);
+        environment.add_unique_id(tag_key, id);
+        Urc::new(Self {
+            environment: environment.clone(),
+            tag: tag_key,
+            id,
+        })
+    }
+
     fn export(&self, zipper: &mut Zipper) {
         let bits = bits_needed_for_max_value(Self::MAX_VALUE as u32);
         zipper.write_u32(self.id as u32, bits);
@@ -640,24 +690,32 @@ pub mod tests {
     fn unique_ids_are_unique() {
         let config = Urc::new(Config::default());
         let environment = Environment::new_map(config, MapSize::new(5, 5));
+        // the first f32 of GameRng::new(0) is 0.8833108, which maps to this id
+        const FIRST: usize = 220827;
+        let rng = GameRng::new(0);
         // ids get dropped and freed immediately, so all ids are the same (since the rng is fixed)
         for _ in 0..10 {
             assert_eq!(
-                UniqueId::new(&environment, TAG_DRONE_STATION_ID, 0.)
+                UniqueId::new(&environment, TAG_DRONE_STATION_ID, &mut rng.clone())
+                    .unwrap()
+                    .id,
+                FIRST
+            );
+            assert_eq!(
+                UniqueId::new(&environment, TAG_DRONE_ID, &mut rng.clone())
                     .unwrap()
                     .id,
-                0
+                FIRST
             );
-            assert_eq!(UniqueId::new(&environment, TAG_DRONE_ID, 0.).unwrap().id, 0);
         }
         // now ids don't get dropped, so all ids are sequential (since the rng is fixed)
         let mut ids = Vec::new();
         for i in 0..10 {
-            let uid = UniqueId::new(&environment, TAG_DRONE_STATION_ID, 0.).unwrap();
-            assert_eq!(uid.id, i * 2);
+            let uid = UniqueId::new(&environment, TAG_DRONE_STATION_ID, &mut rng.clone()).unwrap();
+            assert_eq!(uid.id, FIRST + i * 2);
             ids.push(uid);
-            let uid = UniqueId::new(&environment, TAG_DRONE_ID, 0.).unwrap();
-            assert_eq!(uid.id, i * 2 + 1);
+            let uid = UniqueId::new(&environment, TAG_DRONE_ID, &mut rng.clone()).unwrap();
+            assert_eq!(uid.id, FIRST + i * 2 + 1);
             ids.push(uid);
         }
     }
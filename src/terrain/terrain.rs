@@ -10,6 +10,7 @@ use crate::commander::Commander;
 use crate::commander::commander_type::CommanderType;
 use crate::config::OwnershipPredicate;
 use crate::config::environment::Environment;
+use crate::config::reload::{ConfigDiff, ConfigMigrationError};
 use crate::game::fog::{FogIntensity, FogSetting};
 use crate::game::settings::GameSettings;
 use crate::map::board::{Board, BoardView};
@@ -60,6 +61,19 @@ impl<D: Direction> Terrain<D> {
         self.environment.start_game(settings);
     }
 
+    pub(crate) fn migrate_config(
+        &self,
+        diff: &ConfigDiff,
+        environment: &Environment,
+    ) -> Result<Self, ConfigMigrationError> {
+        Ok(Self {
+            environment: environment.clone(),
+            typ: diff.terrain_type(self.typ)?,
+            owner: self.owner,
+            tags: self.tags.migrate_config(diff, environment)?,
+        })
+    }
+
     // getters that aren't influenced by attributes
     pub fn environment(&self) -> &Environment {
         &self.environment
@@ -266,6 +280,9 @@ impl<D: Direction> Terrain<D> {
     pub fn distort(&mut self, distortion: Distortion<D>) {
         self.tags.distort(distortion);
     }
+    pub fn translate(&mut self, translations: [D::T; 2], odd_if_hex: bool) {
+        self.tags.translate(translations, odd_if_hex);
+    }
 }
 
 impl<D: Direction> SupportedZippable<&Environment> for Terrain<D> {
@@ -8,6 +8,7 @@ use crate::config::environment::Environment;
 use crate::game::commands::Command;
 use crate::game::fog::*;
 use crate::game::game::Game;
+use crate::game::rng::GameRng;
 use crate::map::board::BoardView;
 use crate::map::direction::*;
 use crate::map::map::Map;
@@ -144,34 +145,28 @@ fn capture_city() {
         ),
     );
     let settings = map.settings().unwrap();
-    let (mut game, _) = Game::new_server(map, &settings, settings.build_default(), Urc::new(|| 0.));
+    let (mut game, _) =
+        Game::new_server(map, &settings, settings.build_default(), GameRng::default());
     // can't capture your own properties
-    game.handle_command(
-        Command::UnitCommand(UnitCommand {
-            unload_index: None,
-            path: Path::new(Point::new(0, 0)),
-            action: UnitAction::custom(CA_UNIT_CAPTURE, Vec::new()),
-        }),
-        Urc::new(|| 0.),
-    )
+    game.handle_command(Command::UnitCommand(UnitCommand {
+        unload_index: None,
+        path: Path::new(Point::new(0, 0)),
+        action: UnitAction::custom(CA_UNIT_CAPTURE, Vec::new()),
+    }))
     .unwrap_err();
-    game.handle_command(
-        Command::UnitCommand(UnitCommand {
-            unload_index: None,
-            path: Path::with_steps(
-                Point::new(0, 0),
-                vec![
-                    PathStep::Dir(Direction4::D0),
-                    PathStep::Dir(Direction4::D270),
-                ],
-            ),
-            action: UnitAction::custom(CA_UNIT_CAPTURE, Vec::new()),
-        }),
-        Urc::new(|| 0.),
-    )
+    game.handle_command(Command::UnitCommand(UnitCommand {
+        unload_index: None,
+        path: Path::with_steps(
+            Point::new(0, 0),
+            vec![
+                PathStep::Dir(Direction4::D0),
+                PathStep::Dir(Direction4::D270),
+            ],
+        ),
+        action: UnitAction::custom(CA_UNIT_CAPTURE, Vec::new()),
+    }))
     .unwrap();
-    game.handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
+    game.handle_command(Command::EndTurn).unwrap();
     assert_eq!(
         -1,
         game.get_terrain(Point::new(1, 1)).unwrap().get_owner_id()
@@ -181,8 +176,7 @@ fn capture_city() {
             .unwrap()
             .has_flag(FLAG_CAPTURING)
     );
-    game.handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
+    game.handle_command(Command::EndTurn).unwrap();
     assert_eq!(
         -1,
         game.get_terrain(Point::new(1, 1)).unwrap().get_owner_id()
@@ -205,19 +199,14 @@ fn capture_city() {
             .unwrap()
             .get_tag(TAG_CAPTURE_OWNER)
     );
-    game.handle_command(
-        Command::UnitCommand(UnitCommand {
-            unload_index: None,
-            path: Path::with_steps(Point::new(1, 1), Vec::new()),
-            action: UnitAction::custom(CA_UNIT_CAPTURE, Vec::new()),
-        }),
-        Urc::new(|| 0.),
-    )
+    game.handle_command(Command::UnitCommand(UnitCommand {
+        unload_index: None,
+        path: Path::with_steps(Point::new(1, 1), Vec::new()),
+        action: UnitAction::custom(CA_UNIT_CAPTURE, Vec::new()),
+    }))
     .unwrap();
-    game.handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
-    game.handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
+    game.handle_command(Command::EndTurn).unwrap();
+    game.handle_command(Command::EndTurn).unwrap();
     assert_eq!(
         0,
         game.get_terrain(Point::new(1, 1)).unwrap().get_owner_id()
@@ -256,25 +245,21 @@ fn capture_hq() {
         ),
     );
     let settings = map.settings().unwrap();
-    let (mut game, _) = Game::new_server(map, &settings, settings.build_default(), Urc::new(|| 0.));
-    game.handle_command(
-        Command::UnitCommand(UnitCommand {
-            unload_index: None,
-            path: Path::new(Point::new(0, 0)),
-            action: UnitAction::custom(CA_UNIT_CAPTURE, Vec::new()),
-        }),
-        Urc::new(|| 0.),
-    )
+    let (mut game, _) =
+        Game::new_server(map, &settings, settings.build_default(), GameRng::default());
+    game.handle_command(Command::UnitCommand(UnitCommand {
+        unload_index: None,
+        path: Path::new(Point::new(0, 0)),
+        action: UnitAction::custom(CA_UNIT_CAPTURE, Vec::new()),
+    }))
     .unwrap();
-    game.handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
+    game.handle_command(Command::EndTurn).unwrap();
     assert_eq!(
         1,
         game.get_terrain(Point::new(0, 0)).unwrap().get_owner_id()
     );
     assert!(!game.players()[1].dead);
-    game.handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
+    game.handle_command(Command::EndTurn).unwrap();
     assert_eq!(
         0,
         game.get_terrain(Point::new(0, 0)).unwrap().get_owner_id()
@@ -315,7 +300,8 @@ fn build_unit() {
     settings.players[0]
         .get_tag_bag_mut()
         .set_tag(&environment, TAG_INCOME, 1000.into());
-    let (mut game, _) = Game::new_server(map, &settings, settings.build_default(), Urc::new(|| 0.));
+    let (mut game, _) =
+        Game::new_server(map, &settings, settings.build_default(), GameRng::default());
     assert_eq!(
         1000,
         game.current_player()
@@ -324,15 +310,12 @@ fn build_unit() {
             .into_dynamic()
             .cast::<i32>()
     );
-    game.handle_command(
-        Command::TerrainAction(
-            Point::new(0, 0),
-            vec![CustomActionInput::ShopItem(0.into())]
-                .try_into()
-                .unwrap(),
-        ),
-        Urc::new(|| 0.),
-    )
+    game.handle_command(Command::TerrainAction(
+        Point::new(0, 0),
+        vec![CustomActionInput::ShopItem(0.into())]
+            .try_into()
+            .unwrap(),
+    ))
     .unwrap();
     assert!(
         game.current_player()
@@ -355,8 +338,7 @@ fn build_unit() {
             .has_flag(FLAG_EXHAUSTED)
     );
     // property should get unexhausted when the owner ends their turn
-    game.handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
+    game.handle_command(Command::EndTurn).unwrap();
     assert!(
         !game
             .get_terrain(Point::new(0, 0))
@@ -421,7 +403,8 @@ fn kraken() {
         ),
     );
     let settings = map.settings().unwrap();
-    let (mut game, _) = Game::new_server(map, &settings, settings.build_default(), Urc::new(|| 0.));
+    let (mut game, _) =
+        Game::new_server(map, &settings, settings.build_default(), GameRng::default());
     let environment = game.environment();
     assert_eq!(
         game.get_unit(Point::new(1, 0)),
@@ -438,17 +421,14 @@ fn kraken() {
             .get_tag(TAG_ANGER),
         Some(7.into())
     );
-    game.handle_command(
-        Command::UnitCommand(UnitCommand {
-            unload_index: None,
-            path: Path::new(Point::new(3, 2)),
-            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                Point::new(4, 2),
-                Direction4::D0,
-            ))),
-        }),
-        Urc::new(|| 0.),
-    )
+    game.handle_command(Command::UnitCommand(UnitCommand {
+        unload_index: None,
+        path: Path::new(Point::new(3, 2)),
+        action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+            Point::new(4, 2),
+            Direction4::D0,
+        ))),
+    }))
     .unwrap();
     assert_eq!(game.get_unit(Point::new(4, 2)), None);
     assert_eq!(
@@ -458,17 +438,14 @@ fn kraken() {
         Some(8.into())
     );
     assert_eq!(game.get_unit(Point::new(3, 2)).unwrap().get_hp(), 100);
-    game.handle_command(
-        Command::UnitCommand(UnitCommand {
-            unload_index: None,
-            path: Path::new(Point::new(1, 2)),
-            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                Point::new(0, 2),
-                Direction4::D180,
-            ))),
-        }),
-        Urc::new(|| 0.),
-    )
+    game.handle_command(Command::UnitCommand(UnitCommand {
+        unload_index: None,
+        path: Path::new(Point::new(1, 2)),
+        action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+            Point::new(0, 2),
+            Direction4::D180,
+        ))),
+    }))
     .unwrap();
     assert_eq!(
         game.get_terrain(Point::new(2, 2))
@@ -523,9 +500,8 @@ fn terrain_vision() {
     map_settings.fog_mode = FogMode::Constant(FogSetting::Sharp(0));
     let mut settings = map_settings.build_default();
     settings.players[0].set_commander(CommanderType::Lageos);
-    let (mut game, _) = Game::new_server(map, &map_settings, settings, Urc::new(|| 0.));
-    game.handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
+    let (mut game, _) = Game::new_server(map, &map_settings, settings, GameRng::default());
+    game.handle_command(Command::EndTurn).unwrap();
     // neutral city gives no vision to anybody
     assert_eq!(
         FogIntensity::Dark,
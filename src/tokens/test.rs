@@ -5,6 +5,7 @@ use crate::config::environment::Environment;
 use crate::game::commands::Command;
 use crate::game::fog::{FogMode, FogSetting};
 use crate::game::game::Game;
+use crate::game::rng::GameRng;
 use crate::map::board::BoardView;
 use crate::map::direction::*;
 use crate::map::map::Map;
@@ -99,7 +100,7 @@ fn collect_coin_tokens() {
         map.clone(),
         &settings,
         settings.build_default(),
-        Urc::new(|| 0.),
+        GameRng::default(),
     );
     for player in &server.players {
         assert_eq!(
@@ -112,17 +113,14 @@ fn collect_coin_tokens() {
         );
     }
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::with_steps(
-                    Point::new(0, 0),
-                    vec![PathStep::Dir(Direction4::D0), PathStep::Dir(Direction4::D0)],
-                ),
-                action: UnitAction::Wait,
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::with_steps(
+                Point::new(0, 0),
+                vec![PathStep::Dir(Direction4::D0), PathStep::Dir(Direction4::D0)],
+            ),
+            action: UnitAction::Wait,
+        }))
         .unwrap();
     assert_eq!(server.get_map().get_tokens(Point::new(0, 0)), &[]);
     assert_eq!(server.get_map().get_tokens(Point::new(2, 0)), &[]);
@@ -194,39 +192,33 @@ fn bubble_token() {
         map.clone(),
         &settings,
         settings.build_default(),
-        Urc::new(|| 0.),
+        GameRng::default(),
     );
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::with_steps(
-                    Point::new(0, 0),
-                    vec![
-                        PathStep::Dir(Direction4::D0),
-                        PathStep::Dir(Direction4::D0),
-                        PathStep::Dir(Direction4::D0),
-                        PathStep::Dir(Direction4::D0),
-                        PathStep::Dir(Direction4::D0),
-                    ],
-                ),
-                action: UnitAction::Wait,
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::with_steps(
+                Point::new(0, 0),
+                vec![
+                    PathStep::Dir(Direction4::D0),
+                    PathStep::Dir(Direction4::D0),
+                    PathStep::Dir(Direction4::D0),
+                    PathStep::Dir(Direction4::D0),
+                    PathStep::Dir(Direction4::D0),
+                ],
+            ),
+            action: UnitAction::Wait,
+        }))
         .unwrap();
     assert_eq!(server.get_tokens(Point::new(2, 0)), &[]);
     // factory bubble
     server
-        .handle_command(
-            Command::TokenAction(
-                Point::new(1, 0),
-                vec![CustomActionInput::ShopItem(UnitType::SMALL_TANK.0.into())]
-                    .try_into()
-                    .unwrap(),
-            ),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::TokenAction(
+            Point::new(1, 0),
+            vec![CustomActionInput::ShopItem(UnitType::SMALL_TANK.0.into())]
+                .try_into()
+                .unwrap(),
+        ))
         .unwrap();
     assert_eq!(server.get_tokens(Point::new(1, 0)), &[]);
     assert_eq!(
@@ -239,15 +231,12 @@ fn bubble_token() {
     );
     // airport bubble
     server
-        .handle_command(
-            Command::TokenAction(
-                Point::new(3, 0),
-                vec![CustomActionInput::ShopItem(1.into())]
-                    .try_into()
-                    .unwrap(),
-            ),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::TokenAction(
+            Point::new(3, 0),
+            vec![CustomActionInput::ShopItem(1.into())]
+                .try_into()
+                .unwrap(),
+        ))
         .unwrap();
     assert_eq!(server.get_tokens(Point::new(3, 0)), &[]);
     assert_eq!(
@@ -260,15 +249,12 @@ fn bubble_token() {
     );
     // port bubble
     server
-        .handle_command(
-            Command::TokenAction(
-                Point::new(4, 0),
-                vec![CustomActionInput::ShopItem(7.into())]
-                    .try_into()
-                    .unwrap(),
-            ),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::TokenAction(
+            Point::new(4, 0),
+            vec![CustomActionInput::ShopItem(7.into())]
+                .try_into()
+                .unwrap(),
+        ))
         .unwrap();
     assert_eq!(server.get_tokens(Point::new(4, 0)), &[]);
     assert_eq!(
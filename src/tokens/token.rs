@@ -10,6 +10,7 @@ use crate::commander::Commander;
 use crate::commander::commander_type::CommanderType;
 use crate::config::OwnershipPredicate;
 use crate::config::environment::Environment;
+use crate::config::reload::{ConfigDiff, ConfigMigrationError};
 use crate::game::fog::{FogIntensity, FogSetting};
 use crate::game::settings::GameSettings;
 use crate::map::board::{Board, BoardView};
@@ -72,6 +73,19 @@ impl<D: Direction> Token<D> {
         self.environment.start_game(settings);
     }
 
+    pub(crate) fn migrate_config(
+        &self,
+        diff: &ConfigDiff,
+        environment: &Environment,
+    ) -> Result<Self, ConfigMigrationError> {
+        Ok(Self {
+            environment: environment.clone(),
+            typ: diff.token_type(self.typ)?,
+            owner: self.owner,
+            tags: self.tags.migrate_config(diff, environment)?,
+        })
+    }
+
     pub fn environment(&self) -> &Environment {
         &self.environment
     }
@@ -1,5 +1,4 @@
 use interfaces::ClientPerspective;
-use interfaces::GameInterface;
 use rhai::{Dynamic, Map};
 use rustc_hash::FxHashSet as HashSet;
 use std::fmt;
@@ -18,6 +17,7 @@ use crate::map::direction::Direction;
 use crate::map::map::valid_points;
 use crate::map::point::Point;
 use crate::script::custom_action::*;
+use crate::script::profiler::ScriptHook;
 use crate::script::*;
 
 use super::UnitData;
@@ -25,6 +25,7 @@ use super::UnitId;
 use super::hero::*;
 use super::movement::*;
 use super::unit::Unit;
+use super::zone_of_control::{ZoneOfControl, ZoneOfControlMap};
 
 pub const UNIT_REPAIR: u32 = 30;
 pub const MAX_CUSTOM_ACTION_STEPS: u32 = 8;
@@ -74,6 +75,30 @@ impl<D: Direction> UnitAction<D> {
         )
     }
 
+    /**
+     * the index of the script that asks the player for additional input, if this action has one
+     */
+    fn input_script(&self, environment: &Environment, unit: &Unit<D>) -> Option<usize> {
+        match self {
+            Self::HeroPower(index, _) => {
+                let hero = unit.get_hero()?;
+                environment.config.hero_powers(hero.typ())[index.0]
+                    .script
+                    .and_then(|(input_script, _)| input_script)
+            }
+            Self::Custom(index, _) => environment.config.custom_actions()[index.0].script.0,
+            _ => None,
+        }
+    }
+
+    fn with_input(&self, custom_action_data: Vec<CustomActionInput<D>>) -> Self {
+        match self {
+            Self::HeroPower(index, _) => Self::hero_power(index.0, custom_action_data),
+            Self::Custom(index, _) => Self::custom(index.0, custom_action_data),
+            _ => self.clone(),
+        }
+    }
+
     pub fn build_action_data_if_valid(
         &self,
         game: &Board<D>,
@@ -82,15 +107,15 @@ impl<D: Direction> UnitAction<D> {
         _destination: Point,
         transporter: Option<(&Unit<D>, usize)>,
         ballast: &[TBallast<D>],
-    ) -> Option<Vec<CustomActionData<D>>> {
+    ) -> Result<Vec<CustomActionData<D>>, ActionRejection> {
         let options = unit.options_after_path(game, path, transporter, ballast);
         match self {
             Self::HeroPower(index, data) => {
                 if !options.contains(&Self::hero_power(index.0, Vec::new())) {
-                    return None;
+                    return Err(ActionRejection::NotAnOption);
                 }
                 let Some(hero) = unit.get_hero() else {
-                    return None;
+                    return Err(ActionRejection::NotAnOption);
                 };
                 let environment = game.environment();
                 let power = &environment.config.hero_powers(hero.typ())[index.0];
@@ -101,14 +126,16 @@ impl<D: Direction> UnitAction<D> {
                         path,
                         transporter.map(|(_, i)| i),
                         data,
-                    );
+                    )
+                    .ok_or_else(|| ActionRejection::input_rejected(environment, input_script));
                 } else if data.len() == 0 {
-                    return Some(Vec::new());
+                    return Ok(Vec::new());
                 }
+                Err(ActionRejection::UnexpectedInput)
             }
             Self::Custom(index, data) => {
                 if !options.contains(&Self::custom(index.0, Vec::new())) {
-                    return None;
+                    return Err(ActionRejection::NotAnOption);
                 }
                 let environment = game.environment();
                 let custom_action = &environment.config.custom_actions()[index.0];
@@ -119,19 +146,22 @@ impl<D: Direction> UnitAction<D> {
                         path,
                         transporter.map(|(_, i)| i),
                         data,
-                    );
+                    )
+                    .ok_or_else(|| ActionRejection::input_rejected(environment, input_script));
                 } else if data.len() == 0 {
-                    return Some(Vec::new());
+                    return Ok(Vec::new());
                 }
+                Err(ActionRejection::UnexpectedInput)
             }
             _ => {
                 if options.contains(self) {
-                    return Some(Vec::new());
+                    Ok(Vec::new())
+                } else {
+                    // invalid option chosen
+                    Err(ActionRejection::NotAnOption)
                 }
             }
         }
-        // invalid option chosen
-        None
     }
 
     pub fn execute(
@@ -199,7 +229,11 @@ impl<D: Direction> UnitAction<D> {
                                 let environment = handler.environment().clone();
                                 let executor = handler.executor(first_argument);
                                 for function_index in scripts {
-                                    match executor.run::<D, ()>(function_index, ()) {
+                                    match executor.run::<D, ()>(
+                                        ScriptHook::UnitEvent,
+                                        function_index,
+                                        (),
+                                    ) {
                                         Ok(()) => (),
                                         Err(e) => {
                                             environment.log_rhai_error(
@@ -320,26 +354,85 @@ pub struct UnitCommand<D: Direction> {
 }
 
 impl<D: Direction> UnitCommand<D> {
+    /**
+     * lists every UnitCommand that moves the unit at start (or the unit at unload_index inside it).
+     * board should be the owner's perspective, the same one UnitCommand::execute validates against
+     */
+    pub fn legal_commands(
+        board: &Board<D>,
+        start: Point,
+        unload_index: Option<UnloadIndex>,
+    ) -> Vec<Self> {
+        let mut result = Vec::new();
+        let Some(transporter) = board.get_unit(start) else {
+            return result;
+        };
+        let (unit, transporter) = match unload_index {
+            Some(index) => match transporter.get_transported().get(index.0) {
+                Some(unit) => (unit, Some((transporter, index.0))),
+                None => return result,
+            },
+            None => (transporter, None),
+        };
+        if board.current_owner() != unit.get_owner_id() || !unit.can_move(board, start) {
+            return result;
+        }
+        let environment = board.environment();
+        let movement_map = unit.movement_map(board, &Path::new(start), transporter);
+        let mut destinations: Vec<Point> = movement_map.reachable_positions().into_iter().collect();
+        destinations.sort_by_key(|p| (p.y, p.x));
+        for destination in destinations {
+            let entry = movement_map.get(destination).unwrap();
+            let path = movement_map.path_to(destination).unwrap().clone();
+            if unload_index.is_some() && path.len() == 0 {
+                // the unit would stay inside its transporter
+                continue;
+            }
+            let ballast = if path.len() == 0 {
+                &[]
+            } else {
+                entry.ballast.get_entries()
+            };
+            for action in unit.options_after_path(board, &path, transporter, ballast) {
+                let inputs = match action.input_script(environment, unit) {
+                    Some(input_script) => all_script_inputs(|data| {
+                        run_unit_input_script(
+                            input_script,
+                            board,
+                            &path,
+                            transporter.map(|(_, i)| i),
+                            data,
+                        )
+                    }),
+                    None => vec![Vec::new()],
+                };
+                for data in inputs {
+                    result.push(Self {
+                        unload_index,
+                        path: path.clone(),
+                        action: action.with_input(data),
+                    });
+                }
+            }
+        }
+        result
+    }
+
     pub fn execute(self, handler: &mut EventHandler<D>) -> Result<(), CommandError> {
         let borrowed_game = handler.get_game();
-        let client_game;
-        let client = if borrowed_game.has_secrets() {
-            client_game = borrowed_game
-                .reimport_as_client(ClientPerspective::Team(borrowed_game.current_owner() as u8));
-            &client_game
-        } else {
-            &*borrowed_game
-        };
+        let perspective = ClientPerspective::Team(borrowed_game.current_owner() as u8);
         let board_at_the_end = self.action == UnitAction::Enter;
         let start = self.path.start;
         // check whether the player should even be able to send this command
         let action_data = {
-            if !client.get_map().is_point_valid(start) {
+            let game_board = Board::from(&*borrowed_game);
+            let board = game_board.fog_view(perspective);
+            if !board.wrapping_logic().pointmap().is_point_valid(start) {
                 return Err(CommandError::InvalidPoint(start));
             }
-            let unit = client
+            let unit = board
                 .get_unit(start)
-                .ok_or(CommandError::MissingUnit)?
+                .ok_or(CommandError::MissingUnit(start))?
                 .clone();
             let mut transporter = None;
             let unit = if let Some(index) = self.unload_index {
@@ -347,17 +440,16 @@ impl<D: Direction> UnitCommand<D> {
                 let boarded = unit.get_transported();
                 boarded
                     .get(index.0)
-                    .ok_or(CommandError::MissingBoardedUnit)?
+                    .ok_or(CommandError::MissingBoardedUnit(start, index.0))?
                     .clone()
             } else {
                 unit
             };
-            if client.current_owner() != unit.get_owner_id() {
-                return Err(CommandError::NotYourUnit);
+            if board.current_owner() != unit.get_owner_id() {
+                return Err(CommandError::NotYourUnit(start));
             }
-            let board = Board::from(client);
             if !unit.can_move(&board, start) {
-                return Err(CommandError::UnitCannotMove);
+                return Err(CommandError::UnitCannotMove(start));
             }
             let ballast = search_path(
                 &board,
@@ -366,7 +458,7 @@ impl<D: Direction> UnitCommand<D> {
                 transporter,
                 |path, p, can_stop_here, _| {
                     if *path == self.path && board_at_the_end {
-                        if let Some(transporter) = client.get_unit(p) {
+                        if let Some(transporter) = board.get_unit(p) {
                             if p != path.start && transporter.can_transport(&unit) {
                                 return PathSearchFeedback::Found;
                             }
@@ -377,9 +469,20 @@ impl<D: Direction> UnitCommand<D> {
                     PathSearchFeedback::Rejected
                 },
             )
-            .ok_or(CommandError::InvalidPath)?
+            .ok_or_else(|| {
+                let destination = match self.path.end(&board) {
+                    Ok((destination, _)) => destination,
+                    Err(error) => return error,
+                };
+                let steps = reachable_path_steps(&board, &unit, &self.path, transporter);
+                CommandError::InvalidPath(if steps < self.path.len() {
+                    PathError::Unreachable(steps)
+                } else {
+                    PathError::CannotStop(destination)
+                })
+            })?
             .1;
-            let destination = self.path.end(client).unwrap().0;
+            let destination = self.path.end(&board).unwrap().0;
             let ballast = if self.path.len() == 0 {
                 &[]
             } else {
@@ -394,13 +497,28 @@ impl<D: Direction> UnitCommand<D> {
                     transporter,
                     ballast,
                 )
-                .ok_or(CommandError::InvalidAction)?
+                .map_err(CommandError::InvalidAction)?
         };
+        self.execute_validated(handler, action_data);
+        Ok(())
+    }
 
-        // now we know that the player entered a valid command
+    /**
+     * executes a command that is valid from the current player's perspective.
+     * the unit stops early if it runs into a fog trap.
+     * returns false in that case
+     */
+    pub(crate) fn execute_validated(
+        self,
+        handler: &mut EventHandler<D>,
+        action_data: Vec<CustomActionData<D>>,
+    ) -> bool {
+        let board_at_the_end = self.action == UnitAction::Enter;
+        let start = self.path.start;
         // check for fog trap
         let mut path_taken = self.path.clone();
         let mut fog_trap = None;
+        let mut failed_path = None;
         let unit = handler.get_game().get_unit(start).unwrap().clone();
         let mut transporter = None;
         let unit = if let Some(index) = self.unload_index {
@@ -437,12 +555,56 @@ impl<D: Direction> UnitCommand<D> {
                 break;
             } else {
                 fog_trap = Some(path_taken.end(handler.get_board()).unwrap().0);
+                failed_path = Some(path_taken.clone());
                 path_taken.steps.pop();
             }
         }
         let ballast = ballast
             .expect(&format!("couldn't handle unit command {:?}", self))
             .1;
+        if fog_trap.is_some_and(|p| handler.get_board().get_unit(p).is_none()) {
+            // interrupted by the zone of control of a hidden unit
+            let board = handler.get_board();
+            let zones = ZoneOfControlMap::new(board);
+            let stopped_at = path_taken.end(board).unwrap().0;
+            let team = unit.get_team();
+            let failed_path = failed_path.unwrap();
+            // a Stop zone where the unit ended its movement explains why it couldn't go on
+            let stop_source = zones
+                .get(stopped_at)
+                .iter()
+                .find(|(t, _, zone)| *t != team && zone.stops_movement());
+            // a Cost zone on the path is only to blame if the path works without its source
+            let points = failed_path.points(board).unwrap_or_default();
+            let cost_source = || {
+                points
+                    .iter()
+                    .rev()
+                    .flat_map(|p| zones.get(*p))
+                    .find(|(t, source, zone)| {
+                        *t != team
+                            && matches!(zone, ZoneOfControl::Cost(_))
+                            && search_path(
+                                &board.replace_unit(*source, None),
+                                &unit,
+                                &failed_path,
+                                transporter,
+                                |path, _, _, _| {
+                                    if *path == failed_path {
+                                        PathSearchFeedback::Found
+                                    } else {
+                                        PathSearchFeedback::Rejected
+                                    }
+                                },
+                            )
+                            .is_some()
+                    })
+            };
+            fog_trap = stop_source
+                .or_else(cost_source)
+                .map(|(_, source, _)| *source)
+                .or(fog_trap);
+        }
         // no event for the path is necessary if the unit is unable to move at all
         let ballast = if path_taken.steps.len() > 0 {
             handler.unit_path(
@@ -461,6 +623,7 @@ impl<D: Direction> UnitCommand<D> {
             handler.effect_fog_surprise(fog_trap);
             let heroes = HeroMap::new(handler.get_board(), None);
             handler.on_unit_normal_action(unit_id, path_taken.clone(), true, &heroes, ballast);
+            false
         } else {
             // TODO: need to check whether action can really be executed
             // so far the code mainly checks whether it looks correct from the user perspective
@@ -473,8 +636,8 @@ impl<D: Direction> UnitCommand<D> {
                 ballast,
                 action_data,
             );
+            true
         }
-        Ok(())
     }
 }
 
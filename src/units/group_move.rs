@@ -0,0 +1,142 @@
+use interfaces::ClientPerspective;
+use zipper::*;
+use zipper_derive::Zippable;
+
+use crate::config::environment::Environment;
+use crate::game::commands::{CommandError, PathError};
+use crate::game::event_handler::EventHandler;
+use crate::map::board::{Board, BoardView};
+use crate::map::direction::Direction;
+use crate::map::point::Point;
+use crate::script::custom_action::CustomActionData;
+
+use super::commands::{UnitAction, UnitCommand};
+use super::movement::Path;
+
+pub const MAX_GROUP_SIZE: usize = 64;
+
+/// one unit of a Command::GroupMove. the unit waits at its destination
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Zippable)]
+#[zippable(support_ref = Environment)]
+pub struct GroupMoveEntry {
+    pub start: Point,
+    pub destination: Point,
+}
+
+/**
+ * Moves several units of the current player in one command.
+ * Paths are planned together on the player's view of the board: a unit whose destination
+ * is still occupied by another member of the group moves after that member.
+ * Every unit can only be part of the group once, as the start of an entry has to be
+ * where a unit stood before the group moved.
+ * If any unit can't reach its destination, nothing happens.
+ * If a unit is stopped by a fog trap, the units after it don't move,
+ * since their paths were planned without knowing about the trap.
+ * The path effects of all units are sent as one batch.
+ */
+pub(crate) fn execute_group_move<D: Direction>(
+    entries: &[GroupMoveEntry],
+    handler: &mut EventHandler<D>,
+) -> Result<(), CommandError> {
+    for (i, entry) in entries.iter().enumerate() {
+        if entries[..i].iter().any(|e| e.start == entry.start) {
+            return Err(CommandError::DuplicateGroupMember(entry.start));
+        }
+    }
+    let planned = {
+        let borrowed_game = handler.get_game();
+        let perspective = ClientPerspective::Team(borrowed_game.current_owner() as u8);
+        let game_board = Board::from(&*borrowed_game);
+        let board = game_board.fog_view(perspective);
+        let mut planned = Vec::with_capacity(entries.len());
+        plan_group_move(&board, entries.to_vec(), &mut Vec::new(), &mut planned)?;
+        planned
+    };
+    handler.start_effect_batch();
+    for (path, action_data) in planned {
+        let arrived = UnitCommand {
+            unload_index: None,
+            path,
+            action: UnitAction::Wait,
+        }
+        .execute_validated(handler, action_data);
+        if !arrived {
+            break;
+        }
+    }
+    handler.end_effect_batch();
+    Ok(())
+}
+
+// each planned unit is moved on the board that the remaining units are planned on
+// arrived contains the destinations of planned units, which can't be moved again
+fn plan_group_move<D: Direction>(
+    board: &Board<D>,
+    mut pending: Vec<GroupMoveEntry>,
+    arrived: &mut Vec<Point>,
+    planned: &mut Vec<(Path<D>, Vec<CustomActionData<D>>)>,
+) -> Result<(), CommandError> {
+    if pending.len() == 0 {
+        return Ok(());
+    }
+    let mut first_error = None;
+    for i in 0..pending.len() {
+        if arrived.contains(&pending[i].start) {
+            first_error.get_or_insert(CommandError::DuplicateGroupMember(pending[i].start));
+            continue;
+        }
+        match plan_unit(board, pending[i]) {
+            Ok((path, action_data)) => {
+                let entry = pending.remove(i);
+                let mut unit = board.get_unit(entry.start).unwrap().clone();
+                unit.transformed_by_path(board, &path);
+                planned.push((path, action_data));
+                arrived.push(entry.destination);
+                let board = board.replace_unit(entry.start, None);
+                let board = board.replace_unit(entry.destination, Some(unit));
+                return plan_group_move(&board, pending, arrived, planned);
+            }
+            Err(error) => {
+                first_error.get_or_insert(error);
+            }
+        }
+    }
+    Err(first_error.unwrap())
+}
+
+fn plan_unit<D: Direction>(
+    board: &Board<D>,
+    entry: GroupMoveEntry,
+) -> Result<(Path<D>, Vec<CustomActionData<D>>), CommandError> {
+    let GroupMoveEntry { start, destination } = entry;
+    for p in [start, destination] {
+        if !board.wrapping_logic().pointmap().is_point_valid(p) {
+            return Err(CommandError::InvalidPoint(p));
+        }
+    }
+    let unit = board
+        .get_unit(start)
+        .ok_or(CommandError::MissingUnit(start))?;
+    if board.current_owner() != unit.get_owner_id() {
+        return Err(CommandError::NotYourUnit(start));
+    }
+    if !unit.can_move(board, start) {
+        return Err(CommandError::UnitCannotMove(start));
+    }
+    let movement_map = unit.movement_map(board, &Path::new(start), None);
+    let Some(entry) = movement_map.get(destination).filter(|entry| entry.can_stop) else {
+        return Err(CommandError::InvalidPath(PathError::CannotStop(
+            destination,
+        )));
+    };
+    let path = movement_map.path_to(destination).unwrap().clone();
+    let ballast = if path.len() == 0 {
+        &[]
+    } else {
+        entry.ballast.get_entries()
+    };
+    let action_data = UnitAction::Wait
+        .build_action_data_if_valid(board, unit, &path, destination, None, ballast)
+        .map_err(CommandError::InvalidAction)?;
+    Ok((path, action_data))
+}
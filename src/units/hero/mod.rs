@@ -12,6 +12,7 @@ use super::unit::Unit;
 use crate::config::config::Config;
 use crate::config::environment::Environment;
 use crate::config::parse::FromConfig;
+use crate::config::reload::{ConfigDiff, ConfigMigrationError};
 use crate::game::event_handler::EventHandler;
 use crate::map::board::{Board, BoardView};
 use crate::map::direction::Direction;
@@ -103,6 +104,21 @@ impl Hero {
         self.typ
     }
 
+    /// powers that don't exist anymore are deactivated
+    pub(crate) fn migrate_config(
+        &self,
+        diff: &ConfigDiff,
+        environment: &Environment,
+    ) -> Result<Self, ConfigMigrationError> {
+        let typ = diff.hero_type(self.typ)?;
+        let mut result = Self::new(typ);
+        if self.power < environment.config.hero_powers(typ).len() {
+            result.power = self.power;
+        }
+        result.set_charge(environment, self.charge);
+        Ok(result)
+    }
+
     pub fn max_charge(&self, environment: &Environment) -> u32 {
         self.typ.max_charge(environment)
     }
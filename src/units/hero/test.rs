@@ -6,6 +6,7 @@ use crate::config::config::Config;
 use crate::game::commands::*;
 use crate::game::fog::*;
 use crate::game::game::Game;
+use crate::game::rng::GameRng;
 use crate::map::direction::*;
 use crate::map::map::Map;
 use crate::map::map::get_neighbors;
@@ -98,7 +99,7 @@ fn buy_hero() {
     let mut settings = game_config.build_default();
     settings.players[0].set_hero(HeroType::JAX);
 
-    let (mut server, _) = Game::new_server(map.clone(), &game_config, settings, Urc::new(|| 0.));
+    let (mut server, _) = Game::new_server(map.clone(), &game_config, settings, GameRng::default());
     let board = Board::new(&server);
     assert_eq!(
         Hero::aura_range(
@@ -129,17 +130,14 @@ fn buy_hero() {
     }
     // summon Jax
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path,
-                action: UnitAction::custom(
-                    CA_UNIT_BUY_HERO,
-                    vec![CustomActionInput::ShopItem(0.into())],
-                ),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path,
+            action: UnitAction::custom(
+                CA_UNIT_BUY_HERO,
+                vec![CustomActionInput::ShopItem(0.into())],
+            ),
+        }))
         .unwrap();
     let board = Board::new(&server);
     assert_eq!(
@@ -163,17 +161,14 @@ fn buy_hero() {
     );
     // can't summon another Jax
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(Point::new(0, 0)),
-                action: UnitAction::custom(
-                    CA_UNIT_BUY_HERO,
-                    vec![CustomActionInput::ShopItem(0.into())],
-                ),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(Point::new(0, 0)),
+            action: UnitAction::custom(
+                CA_UNIT_BUY_HERO,
+                vec![CustomActionInput::ShopItem(0.into())],
+            ),
+        }))
         .unwrap_err();
 }
 
@@ -215,22 +210,19 @@ fn gain_charge() {
         map.clone(),
         &settings,
         settings.build_default(),
-        Urc::new(|| 0.),
+        GameRng::default(),
     );
     assert_eq!(server.get_unit(Point::new(0, 1)).unwrap().get_charge(), 0);
     let path = Path::new(Point::new(0, 1));
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path,
-                action: UnitAction::Attack(AttackInput::AttackPattern(
-                    Point::new(2, 1),
-                    Direction4::D0,
-                )),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path,
+            action: UnitAction::Attack(AttackInput::AttackPattern(
+                Point::new(2, 1),
+                Direction4::D0,
+            )),
+        }))
         .unwrap();
     assert!(server.get_unit(Point::new(0, 1)).unwrap().get_charge() > 0);
 }
@@ -296,7 +288,7 @@ fn crystal() {
         map.clone(),
         &settings,
         settings.build_default(),
-        Urc::new(|| 0.),
+        GameRng::default(),
     );
     let unchanged = server.clone();
     let environment: crate::config::environment::Environment = server.environment().clone();
@@ -328,14 +320,11 @@ fn crystal() {
     crate::debug!("options: {:?}", options);
     assert!(options.contains(&UnitAction::hero_power(1, Vec::new())));
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path,
-                action: UnitAction::hero_power(1, vec![CustomActionInput::Point(Point::new(0, 1))]),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path,
+            action: UnitAction::hero_power(1, vec![CustomActionInput::Point(Point::new(0, 1))]),
+        }))
         .unwrap();
     let board = Board::new(&server);
     assert_eq!(
@@ -368,22 +357,17 @@ fn crystal() {
         Some(2)
     );
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(Point::new(2, 1)),
-                action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                    Point::new(3, 1),
-                    Direction4::D0,
-                ))),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(Point::new(2, 1)),
+            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+                Point::new(3, 1),
+                Direction4::D0,
+            ))),
+        }))
         .unwrap();
     let power_aura_damage = 100 - server.get_unit(Point::new(3, 1)).unwrap().get_hp();
-    server
-        .handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
+    server.handle_command(Command::EndTurn).unwrap();
     let board = Board::new(&server);
     assert_eq!(
         Hero::aura_range(
@@ -403,9 +387,7 @@ fn crystal() {
         ),
         Some(2)
     );
-    server
-        .handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
+    server.handle_command(Command::EndTurn).unwrap();
     let board = Board::new(&server);
     assert_eq!(
         Hero::aura_range(
@@ -429,25 +411,18 @@ fn crystal() {
     // don't use power
     let mut server = unchanged.clone();
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(Point::new(2, 1)),
-                action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                    Point::new(3, 1),
-                    Direction4::D0,
-                ))),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(Point::new(2, 1)),
+            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+                Point::new(3, 1),
+                Direction4::D0,
+            ))),
+        }))
         .unwrap();
     let aura_damage = 100 - server.get_unit(Point::new(3, 1)).unwrap().get_hp();
-    server
-        .handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
-    server
-        .handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
+    server.handle_command(Command::EndTurn).unwrap();
+    server.handle_command(Command::EndTurn).unwrap();
     let board = Board::new(&server);
     assert_eq!(server.get_unit(Point::new(4, 4)).unwrap().get_hp(), 80);
     assert_eq!(
@@ -474,32 +449,25 @@ fn crystal() {
         map.clone(),
         &settings,
         settings.build_default(),
-        Urc::new(|| 0.),
+        GameRng::default(),
     );
     let crystal_damage = 100 - server.get_unit(Point::new(4, 4)).unwrap().get_hp();
     assert!(crystal_damage > 0);
-    server
-        .handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
-    server
-        .handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
+    server.handle_command(Command::EndTurn).unwrap();
+    server.handle_command(Command::EndTurn).unwrap();
     assert_eq!(
         server.get_unit(Point::new(4, 4)).unwrap().get_hp(),
         100 - 2 * crystal_damage
     );
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(Point::new(2, 1)),
-                action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                    Point::new(3, 1),
-                    Direction4::D0,
-                ))),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(Point::new(2, 1)),
+            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+                Point::new(3, 1),
+                Direction4::D0,
+            ))),
+        }))
         .unwrap();
     let normal_damage = 100 - server.get_unit(Point::new(3, 1)).unwrap().get_hp();
 
@@ -548,7 +516,7 @@ fn earl_grey() {
         map.clone(),
         &settings,
         settings.build_default(),
-        Urc::new(|| 0.),
+        GameRng::default(),
     );
     let board = Board::new(&server);
     let heroes = HeroMap::new(&board, None);
@@ -570,17 +538,17 @@ fn earl_grey() {
     let mut path = Path::new(Point::new(1, 1));
     path.steps.push(PathStep::Dir(Direction4::D90));
     let error = server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path,
-                action: UnitAction::hero_power(1, Vec::new()),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path,
+            action: UnitAction::hero_power(1, Vec::new()),
+        }))
         .unwrap_err();
     let board = Board::new(&server);
-    assert_eq!(error, CommandError::InvalidAction);
+    assert_eq!(
+        error,
+        CommandError::InvalidAction(ActionRejection::NotAnOption)
+    );
     // use power
     let path = Path::new(Point::new(1, 1));
     let options = server
@@ -590,14 +558,11 @@ fn earl_grey() {
     crate::debug!("options: {:?}", options);
     assert!(options.contains(&UnitAction::hero_power(1, Vec::new())));
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path,
-                action: UnitAction::hero_power(1, Vec::new()),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path,
+            action: UnitAction::hero_power(1, Vec::new()),
+        }))
         .unwrap();
     let board = Board::new(&server);
     assert!(
@@ -680,7 +645,7 @@ fn blue_berry() {
         map.clone(),
         &settings,
         settings.build_default(),
-        Urc::new(|| 0.),
+        GameRng::default(),
     );
     let board = Board::new(&server);
     assert_eq!(server.get_unit(Point::new(4, 1)).unwrap().get_hp(), hp);
@@ -694,14 +659,11 @@ fn blue_berry() {
     crate::debug!("options: {:?}", options);
     assert!(options.contains(&UnitAction::hero_power(1, Vec::new())));
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path,
-                action: UnitAction::hero_power(1, Vec::new()),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path,
+            action: UnitAction::hero_power(1, Vec::new()),
+        }))
         .unwrap();
     assert!(server.get_unit(Point::new(4, 1)).unwrap().get_hp() > hp);
     assert_eq!(server.get_unit(Point::new(3, 1)).unwrap().get_hp(), hp);
@@ -752,7 +714,7 @@ fn tess() {
         map.clone(),
         &settings,
         settings.build_default(),
-        Urc::new(|| 0.),
+        GameRng::default(),
     );
 
     // get money from attacking
@@ -762,17 +724,14 @@ fn tess() {
         e => panic!("funds are {e:?}"),
     };
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(Point::new(1, 1)),
-                action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                    Point::new(1, 2),
-                    Direction4::D270,
-                ))),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(Point::new(1, 1)),
+            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+                Point::new(1, 2),
+                Direction4::D270,
+            ))),
+        }))
         .unwrap();
     let funds_after = match server.players[0].get_tag(TAG_FUNDS).unwrap() {
         TagValue::Int(i) => i.0,
@@ -791,20 +750,17 @@ fn tess() {
     crate::debug!("options: {:?}", options);
     assert!(options.contains(&UnitAction::hero_power(1, Vec::new())));
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path,
-                action: UnitAction::hero_power(
-                    1,
-                    vec![
-                        CustomActionInput::ShopItem(3.into()),
-                        CustomActionInput::Direction(Direction4::D0),
-                    ],
-                ),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path,
+            action: UnitAction::hero_power(
+                1,
+                vec![
+                    CustomActionInput::ShopItem(3.into()),
+                    CustomActionInput::Direction(Direction4::D0),
+                ],
+            ),
+        }))
         .unwrap();
     assert!(
         !server
@@ -859,7 +815,7 @@ fn edwin() {
         map.clone(),
         &settings,
         settings.build_default(),
-        Urc::new(|| 0.),
+        GameRng::default(),
     );
     let mut server = server_backup.clone();
     // use power
@@ -872,37 +828,31 @@ fn edwin() {
     crate::debug!("options: {:?}", options);
     assert!(options.contains(&UnitAction::hero_power(1, Vec::new())));
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path,
-                action: UnitAction::hero_power(
-                    1,
-                    vec![
-                        CustomActionInput::Point(Point::new(2, 1)),
-                        CustomActionInput::Point(Point::new(0, 4)),
-                    ],
-                ),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path,
+            action: UnitAction::hero_power(
+                1,
+                vec![
+                    CustomActionInput::Point(Point::new(2, 1)),
+                    CustomActionInput::Point(Point::new(0, 4)),
+                ],
+            ),
+        }))
         .unwrap();
     assert_eq!(server.get_unit(Point::new(2, 1)), Some(&friend));
     assert_eq!(server.get_unit(Point::new(0, 4)), Some(&enemy));
 
     // knockback with power
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(Point::new(2, 1)),
-                action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                    Point::new(2, 2),
-                    Direction4::D270,
-                ))),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(Point::new(2, 1)),
+            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+                Point::new(2, 2),
+                Direction4::D270,
+            ))),
+        }))
         .unwrap();
     assert_eq!(server.get_unit(Point::new(2, 2)), None);
     assert!(server.get_unit(Point::new(2, 3)).is_some());
@@ -913,7 +863,7 @@ fn edwin() {
         unload_index: None,
         path: Path::new(Point::new(1, 1)),
         action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(Point::new(2, 1), Direction4::D0))),
-    }), Urc::new(|| 0.)).unwrap();
+    })).unwrap();
     assert_eq!(server.get_unit(Point::new(3, 1)), None);
     assert!(server.get_unit(Point::new(2, 1)).is_some());*/
 }
@@ -1091,56 +1041,43 @@ fn jax() {
         map.clone(),
         &settings,
         settings.build_default(),
-        Urc::new(|| 0.),
+        GameRng::default(),
     );
-    server_backup
-        .handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
-    server_backup
-        .handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
+    server_backup.handle_command(Command::EndTurn).unwrap();
+    server_backup.handle_command(Command::EndTurn).unwrap();
     let server_backup = server_backup;
 
     // test: ground units get bonus from Jax aura. Bonus if in forest
     let mut server = server_backup.clone();
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(attacker_pos_none),
-                action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                    Point::new(3, 3),
-                    Direction4::D90,
-                ))),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(attacker_pos_none),
+            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+                Point::new(3, 3),
+                Direction4::D90,
+            ))),
+        }))
         .unwrap();
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(attacker_pos_aura),
-                action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                    Point::new(3, 1),
-                    Direction4::D270,
-                ))),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(attacker_pos_aura),
+            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+                Point::new(3, 1),
+                Direction4::D270,
+            ))),
+        }))
         .unwrap();
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(attacker_pos_forest),
-                action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                    Point::new(1, 1),
-                    Direction4::D270,
-                ))),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(attacker_pos_forest),
+            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+                Point::new(1, 1),
+                Direction4::D270,
+            ))),
+        }))
         .unwrap();
     let damage_none = 100 - server.get_unit(Point::new(3, 3)).unwrap().get_hp();
     let damage_aura = 100 - server.get_unit(Point::new(3, 1)).unwrap().get_hp();
@@ -1187,20 +1124,17 @@ fn jax() {
         FogIntensity::TrueSight
     );
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: path.clone(),
-                action: UnitAction::hero_power(
-                    1,
-                    vec![
-                        CustomActionInput::Point(jump_blocked),
-                        CustomActionInput::Direction(Direction4::D270),
-                    ],
-                ),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: path.clone(),
+            action: UnitAction::hero_power(
+                1,
+                vec![
+                    CustomActionInput::Point(jump_blocked),
+                    CustomActionInput::Direction(Direction4::D270),
+                ],
+            ),
+        }))
         .unwrap();
     assert_eq!(
         server.get_fog_at(ClientPerspective::Team(0), jump_blocked),
@@ -1219,20 +1153,17 @@ fn jax() {
     // test: jump succeeds
     let mut server = server_backup.clone();
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: path.clone(),
-                action: UnitAction::hero_power(
-                    1,
-                    vec![
-                        CustomActionInput::Point(jump_possible),
-                        CustomActionInput::Direction(Direction4::D270),
-                    ],
-                ),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: path.clone(),
+            action: UnitAction::hero_power(
+                1,
+                vec![
+                    CustomActionInput::Point(jump_possible),
+                    CustomActionInput::Direction(Direction4::D270),
+                ],
+            ),
+        }))
         .unwrap();
     let board = Board::from(&server);
     assert!(server.get_unit(path.start).is_none());
@@ -1293,7 +1224,7 @@ fn kane() {
         map.clone(),
         &settings,
         settings.build_default(),
-        Urc::new(|| 0.),
+        GameRng::default(),
     );
 
     // test: kane should be invisible to the enemy
@@ -1308,17 +1239,11 @@ fn kane() {
 
     // test: kane's power summons a LaserShark
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(hero_pos),
-                action: UnitAction::hero_power(
-                    1,
-                    vec![CustomActionInput::Direction(Direction4::D0)],
-                ),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(hero_pos),
+            action: UnitAction::hero_power(1, vec![CustomActionInput::Direction(Direction4::D0)]),
+        }))
         .unwrap();
     assert!(server.get_unit(Point::new(1, 0)).is_some());
 }
@@ -1379,51 +1304,38 @@ fn julia() {
         map.clone(),
         &settings,
         settings.build_default(),
-        Urc::new(|| 0.),
+        GameRng::default(),
     );
 
     // test: units capture better if in aura
     let mut server = server_backup.clone();
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(capturer_pos),
-                action: UnitAction::custom(CA_UNIT_CAPTURE, Vec::new()),
-            }),
-            Urc::new(|| 0.),
-        )
-        .unwrap();
-    server
-        .handle_command(Command::EndTurn, Urc::new(|| 0.))
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(capturer_pos),
+            action: UnitAction::custom(CA_UNIT_CAPTURE, Vec::new()),
+        }))
         .unwrap();
+    server.handle_command(Command::EndTurn).unwrap();
     assert_eq!(server.get_terrain(capturer_pos).unwrap().get_owner_id(), -1);
-    server
-        .handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
+    server.handle_command(Command::EndTurn).unwrap();
     assert_eq!(server.get_terrain(capturer_pos).unwrap().get_owner_id(), 0);
 
     // test: units capture instantly during power
     let mut server = server_backup.clone();
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(hero_pos),
-                action: UnitAction::hero_power(1, Vec::new()),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(hero_pos),
+            action: UnitAction::hero_power(1, Vec::new()),
+        }))
         .unwrap();
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(capturer_pos),
-                action: UnitAction::custom(CA_UNIT_CAPTURE, Vec::new()),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(capturer_pos),
+            action: UnitAction::custom(CA_UNIT_CAPTURE, Vec::new()),
+        }))
         .unwrap();
     assert_eq!(server.get_terrain(capturer_pos).unwrap().get_owner_id(), 0);
 }
@@ -1513,34 +1425,28 @@ fn castor_pollux() {
         map.clone(),
         &settings,
         settings.build_default(),
-        Urc::new(|| 0.),
+        GameRng::default(),
     );
 
     // test: castor damages enemies
     let mut server = server_backup.clone();
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(pollux_pos),
-                action: UnitAction::hero_power(1, Vec::new()),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(pollux_pos),
+            action: UnitAction::hero_power(1, Vec::new()),
+        }))
         .unwrap();
     assert!(server.get_unit(enemy_pos).unwrap().get_hp() < enemy_hp);
 
     // test: castor heals friendlies (but not self)
     let mut server = server_backup.clone();
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(castor_pos),
-                action: UnitAction::hero_power(1, Vec::new()),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(castor_pos),
+            action: UnitAction::hero_power(1, Vec::new()),
+        }))
         .unwrap();
     assert!(server.get_unit(Point::new(4, 2)).unwrap().get_hp() > aura_both_hp);
     assert_eq!(server.get_unit(castor_pos).unwrap().get_hp(), hero_hp);
@@ -1606,19 +1512,16 @@ fn reed() {
         map.clone(),
         &settings,
         settings.build_default(),
-        Urc::new(|| 0.),
+        GameRng::default(),
     );
 
     // use power
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(Point::new(1, 1)),
-                action: UnitAction::hero_power(1, Vec::new()),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(Point::new(1, 1)),
+            action: UnitAction::hero_power(1, Vec::new()),
+        }))
         .unwrap();
     assert!(
         !server
@@ -1,12 +1,15 @@
 pub mod commands;
+pub mod group_move;
 pub mod hero;
 pub mod movement;
+pub mod movement_map;
 pub mod rhai_movement;
 pub mod rhai_unit;
 #[cfg(test)]
 pub(crate) mod test;
 pub mod unit;
 pub mod unit_types;
+pub mod zone_of_control;
 
 use movement::TBallast;
 use unit::Unit;
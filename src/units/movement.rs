@@ -10,7 +10,7 @@ use zipper_derive::*;
 use crate::config::environment::Environment;
 use crate::config::movement_type_config::MovementPattern;
 use crate::config::parse::FromConfig;
-use crate::game::commands::CommandError;
+use crate::game::commands::{CommandError, PathError};
 use crate::map::board::{Board, BoardView};
 use crate::map::direction::Direction;
 use crate::map::map::{get_neighbor, valid_points};
@@ -20,6 +20,7 @@ use crate::terrain::terrain::Terrain;
 
 use super::hero::HeroMap;
 use super::unit::Unit;
+use super::zone_of_control::ZoneOfControlMap;
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum PathSearchFeedback {
@@ -78,25 +79,18 @@ pub enum PathStep<D: Direction> {
     Knight(D, bool),
 }
 impl<D: Direction> PathStep<D> {
-    pub fn progress(
-        &self,
-        map: &impl BoardView<D>,
-        pos: Point,
-    ) -> Result<(Point, Distortion<D>), CommandError> {
+    /**
+     * None if the step would leave the map
+     */
+    pub fn progress(&self, map: &impl BoardView<D>, pos: Point) -> Option<(Point, Distortion<D>)> {
         match *self {
-            Self::Dir(d) => get_neighbor(map, pos, d).ok_or(CommandError::InvalidPath),
-            Self::Jump(d) => get_neighbor(map, pos, d)
-                .and_then(|(pos, distortion)| {
-                    get_neighbor(map, pos, distortion.update_direction(d))
-                        .map(|(pos, disto)| (pos, distortion + disto))
-                })
-                .ok_or(CommandError::InvalidPath),
-            Self::Diagonal(d) => {
-                get_diagonal_neighbor(map, pos, d).ok_or(CommandError::InvalidPath)
-            }
-            Self::Knight(d, turn_left) => {
-                get_knight_neighbor(map, pos, d, turn_left).ok_or(CommandError::InvalidPath)
-            }
+            Self::Dir(d) => get_neighbor(map, pos, d),
+            Self::Jump(d) => get_neighbor(map, pos, d).and_then(|(pos, distortion)| {
+                get_neighbor(map, pos, distortion.update_direction(d))
+                    .map(|(pos, disto)| (pos, distortion + disto))
+            }),
+            Self::Diagonal(d) => get_diagonal_neighbor(map, pos, d),
+            Self::Knight(d, turn_left) => get_knight_neighbor(map, pos, d, turn_left),
         }
     }
 
@@ -157,8 +151,10 @@ impl<D: Direction> Path<D> {
     pub fn end(&self, map: &impl BoardView<D>) -> Result<(Point, Distortion<D>), CommandError> {
         let mut current = self.start;
         let mut distortion = Distortion::neutral();
-        for step in &self.steps {
-            let c = step.progress(map, current)?;
+        for (i, step) in self.steps.iter().enumerate() {
+            let c = step
+                .progress(map, current)
+                .ok_or(CommandError::InvalidPath(PathError::LeavesMap(i)))?;
             current = c.0;
             distortion += c.1;
         }
@@ -168,8 +164,11 @@ impl<D: Direction> Path<D> {
     pub fn points(&self, game: &impl BoardView<D>) -> Result<Vec<Point>, CommandError> {
         let mut points = vec![self.start];
         let mut current = self.start;
-        for step in self.steps.iter() {
-            current = step.progress(game, current)?.0;
+        for (i, step) in self.steps.iter().enumerate() {
+            current = step
+                .progress(game, current)
+                .ok_or(CommandError::InvalidPath(PathError::LeavesMap(i)))?
+                .0;
             points.push(current);
         }
         Ok(points)
@@ -401,6 +400,14 @@ impl<D: Direction> TemporaryBallast<D> {
     pub fn get_entries(&self) -> &[TBallast<D>] {
         &self.entries
     }
+
+    /// None if the unit couldn't move at all
+    pub fn movement_points(&self) -> Option<Rational32> {
+        match self.entries.first() {
+            Some(TBallast::MovementPoints(mp)) => Some(*mp),
+            _ => None,
+        }
+    }
 }
 
 /**
@@ -796,6 +803,8 @@ fn movement_search_map<D: Direction>(
         &PermanentBallast<D>,
     ) -> PathSearchFeedback,
     movement_cost: impl Fn(Point, &PermanentBallast<D>, &[TBallast<D>], usize) -> Option<Rational32>,
+    // the unit can't take further steps this turn after entering such a point
+    stops_movement: impl Fn(Point) -> bool,
 ) {
     if rounds == 0 {
         return;
@@ -822,6 +831,9 @@ fn movement_search_map<D: Direction>(
         },
         base_movement,
         |point, step_id, permanent_ballast, temporary_ballast| {
+            if step_id > 0 && stops_movement(point) {
+                return Vec::new();
+            }
             movement_pattern.find_steps(
                 map,
                 point,
@@ -832,7 +844,7 @@ fn movement_search_map<D: Direction>(
             )
         },
         |point, step, permanent_ballast, temporary_ballast, round| {
-            if let Ok((point, distortion)) = step.progress(map, point) {
+            if let Some((point, distortion)) = step.progress(map, point) {
                 let terrain = map.get_terrain(point).unwrap();
                 if let Some(cost) = movement_cost(
                     point,
@@ -893,6 +905,8 @@ pub fn movement_search_game<D: Direction, F>(
     let transporter = transporter.map(|(u, _)| u);
     let board = board.replace_unit(path_so_far.start, unit_replacement);
     let heroes = HeroMap::new(&board, Some(owner_id));
+    let zones = ZoneOfControlMap::new(&board);
+    let team = unit.get_team();
     movement_search_map(
         &board,
         &unit,
@@ -991,8 +1005,11 @@ pub fn movement_search_game<D: Direction, F>(
             let mut unit = unit.clone();
             permanent.update_unit(&mut unit);
             let terrain = board.get_terrain(pos).unwrap();
-            permanent.movement_cost(&terrain, &unit)
+            permanent
+                .movement_cost(&terrain, &unit)
+                .map(|cost| cost + zones.effect_on(pos, team).extra_cost())
         },
+        |pos| zones.effect_on(pos, team).stops_movement(),
     )
 }
 
@@ -1035,6 +1052,7 @@ fn movement_search_map_without_game<D: Direction, F>(
             let terrain = board.get_terrain(pos).unwrap();
             permanent.movement_cost(&terrain, &unit)
         },
+        |_| false,
     )
 }
 
@@ -1135,3 +1153,36 @@ where
     );
     result
 }
+
+/**
+ * how many steps of the path the unit is able to take, ignoring whether it could stop there.
+ * used to explain why search_path rejected a path
+ */
+pub fn reachable_path_steps<D: Direction>(
+    game: &Board<D>,
+    unit: &Unit<D>,
+    path: &Path<D>,
+    transporter: Option<(&Unit<D>, usize)>,
+) -> usize {
+    let mut result = 0;
+    movement_search_game(
+        game,
+        unit,
+        path,
+        1,
+        transporter,
+        |previous_turns, p, _, can_continue, _, _| {
+            if !previous_turns.is_empty() || p.steps.len() > path.steps.len() {
+                return PathSearchFeedback::Rejected;
+            }
+            // paths that aren't a prefix of the path are rejected by movement_search_game
+            result = result.max(p.steps.len());
+            if can_continue && p.steps.len() < path.steps.len() {
+                PathSearchFeedback::Continue
+            } else {
+                PathSearchFeedback::Rejected
+            }
+        },
+    );
+    result
+}
@@ -0,0 +1,124 @@
+use num_rational::Rational32;
+use rustc_hash::{FxHashMap as HashMap, FxHashSet as HashSet};
+
+use crate::map::board::Board;
+use crate::map::direction::Direction;
+use crate::map::point::Point;
+
+use super::movement::{Path, PathSearchFeedback, PathStep, TemporaryBallast, movement_search_game};
+use super::unit::Unit;
+
+/// What a MovementMap knows about one point the unit can reach this turn.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MovementMapEntry<D: Direction> {
+    /// movement points left after arriving here
+    pub movement_points: Rational32,
+    /// the point before the last step and the last step. None at the start of the path
+    pub previous: Option<(Point, PathStep<D>)>,
+    /// false if the unit can only pass through, e.g. because a friendly unit stands here
+    pub can_stop: bool,
+    /// the TemporaryBallast at arrival, needed e.g. for UnitAction options
+    pub ballast: TemporaryBallast<D>,
+    path: Path<D>,
+}
+
+/**
+ * Every point a unit can reach this turn, found with a single path search.
+ * Each point keeps the path with the most movement points left,
+ * unless only a worse path allows the unit to stop there.
+ */
+#[derive(Debug, Clone)]
+pub struct MovementMap<D: Direction> {
+    start: Point,
+    entries: HashMap<Point, MovementMapEntry<D>>,
+}
+
+impl<D: Direction> MovementMap<D> {
+    pub fn new(
+        game: &Board<D>,
+        unit: &Unit<D>,
+        path_so_far: &Path<D>,
+        transporter: Option<(&Unit<D>, usize)>,
+    ) -> Self {
+        let mut entries: HashMap<Point, MovementMapEntry<D>> = HashMap::default();
+        movement_search_game(
+            game,
+            unit,
+            path_so_far,
+            1,
+            transporter,
+            |_, path, destination, can_continue, can_stop_here, ballast| {
+                // the search visits paths with more movement points left first
+                let replace = match entries.get(&destination) {
+                    None => true,
+                    Some(entry) => !entry.can_stop && can_stop_here,
+                };
+                if replace {
+                    let previous = path.steps.last().map(|step| {
+                        let mut before = path.clone();
+                        before.steps.pop();
+                        (before.end(game).unwrap().0, *step)
+                    });
+                    entries.insert(
+                        destination,
+                        MovementMapEntry {
+                            movement_points: ballast
+                                .movement_points()
+                                .unwrap_or(Rational32::from_integer(0)),
+                            previous,
+                            can_stop: can_stop_here,
+                            ballast: ballast.clone(),
+                            path: path.clone(),
+                        },
+                    );
+                }
+                if !can_continue {
+                    PathSearchFeedback::Rejected
+                } else if can_stop_here {
+                    PathSearchFeedback::Continue
+                } else {
+                    PathSearchFeedback::ContinueWithoutStopping
+                }
+            },
+        );
+        Self {
+            start: path_so_far.start,
+            entries,
+        }
+    }
+
+    pub fn start(&self) -> Point {
+        self.start
+    }
+
+    pub fn get(&self, p: Point) -> Option<&MovementMapEntry<D>> {
+        self.entries.get(&p)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Point, &MovementMapEntry<D>)> {
+        self.entries.iter()
+    }
+
+    /// same as Unit::movable_positions
+    pub fn reachable_positions(&self) -> HashSet<Point> {
+        self.entries.keys().cloned().collect()
+    }
+
+    /// points where the unit can end its movement
+    pub fn stop_positions(&self) -> HashSet<Point> {
+        self.entries
+            .iter()
+            .filter(|(_, entry)| entry.can_stop)
+            .map(|(p, _)| *p)
+            .collect()
+    }
+
+    pub fn can_stop_at(&self, p: Point) -> bool {
+        self.get(p).is_some_and(|entry| entry.can_stop)
+    }
+
+    /// the path that MovementMapEntry::previous describes the last step of
+    pub fn path_to(&self, p: Point) -> Option<&Path<D>> {
+        self.get(p).map(|entry| &entry.path)
+    }
+}
@@ -1,13 +1,16 @@
 use interfaces::{GameEventsMap, Perspective};
+use num_rational::Rational32;
 use uniform_smart_pointer::Urc;
 
 use crate::combat::AttackInput;
 use crate::config::config::Config;
-use crate::game::commands::Command;
+use crate::config::test::parse_patched;
+use crate::game::commands::{Command, CommandError, PathError};
 use crate::game::event_fx::{Effect, EffectWithoutPosition};
 use crate::game::events::Event;
 use crate::game::fog::*;
 use crate::game::game::Game;
+use crate::game::rng::GameRng;
 use crate::map::board::{Board, BoardView};
 use crate::map::direction::*;
 use crate::map::map::Map;
@@ -24,6 +27,7 @@ use crate::terrain::TerrainType;
 use crate::tokens::token::Token;
 use crate::tokens::token_types::TokenType;
 use crate::units::commands::*;
+use crate::units::group_move::GroupMoveEntry;
 use crate::units::movement::{Path, PathStep};
 use crate::units::unit::*;
 use crate::units::unit_types::UnitType;
@@ -52,6 +56,65 @@ impl MovementType {
     pub const HOVER: MovementType = MovementType(4);
 }
 
+/// small tanks stop enemies next to them, snipers make those fields more expensive
+fn zone_of_control_config() -> Urc<Config> {
+    Urc::new(
+        parse_patched("units.csv", |s| {
+            s.lines()
+                .enumerate()
+                .map(|(i, line)| match i {
+                    0 => format!("{line};ZoneOfControl"),
+                    _ if line.starts_with("SmallTank;") => format!("{line};Stop"),
+                    _ if line.starts_with("Sniper;") => format!("{line};Cost(2)"),
+                    _ => format!("{line};"),
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .unwrap(),
+    )
+}
+
+fn zone_of_control_game(enemy: UnitType, enemy_pos: Point, fog: bool) -> Game<Direction4> {
+    let config = zone_of_control_config();
+    let map = PointMap::new(8, 4, false);
+    let wmap: WrappingMap<Direction4> = WMBuilder::new(map).build();
+    let mut map = Map::new(wmap, &config);
+    let map_env = map.environment().clone();
+    map.set_unit(
+        Point::new(0, 0),
+        Some(
+            UnitType::SMALL_TANK
+                .instance(&map_env)
+                .set_owner_id(0)
+                .set_hp(100)
+                .build(),
+        ),
+    );
+    map.set_unit(
+        enemy_pos,
+        Some(enemy.instance(&map_env).set_owner_id(1).set_hp(100).build()),
+    );
+    let mut settings = map.settings().unwrap();
+    if fog {
+        settings.fog_mode = FogMode::Constant(FogSetting::ExtraDark(0));
+    }
+    let (mut game, _) =
+        Game::new_server(map, &settings, settings.build_default(), GameRng::default());
+    // no fog during the first turn
+    game.handle_command(Command::EndTurn).unwrap();
+    game.handle_command(Command::EndTurn).unwrap();
+    game
+}
+
+fn move_right(steps: usize) -> Command<Direction4> {
+    Command::UnitCommand(UnitCommand {
+        unload_index: None,
+        path: Path::with_steps(Point::new(0, 0), vec![PathStep::Dir(Direction4::D0); steps]),
+        action: UnitAction::Wait,
+    })
+}
+
 // actual tests
 
 #[test]
@@ -184,7 +247,7 @@ fn drone() {
         map.clone(),
         &settings,
         settings.build_default(),
-        Urc::new(|| 0.),
+        GameRng::default(),
     );
     let mut client = Game::new_client(
         map,
@@ -200,17 +263,14 @@ fn drone() {
             .is_none()
     );
     let events = server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(Point::new(3, 4)),
-                action: UnitAction::custom(
-                    CA_UNIT_BUILD_UNIT,
-                    vec![CustomActionInput::ShopItem(0.into())],
-                ),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(Point::new(3, 4)),
+            action: UnitAction::custom(
+                CA_UNIT_BUILD_UNIT,
+                vec![CustomActionInput::ShopItem(0.into())],
+            ),
+        }))
         .unwrap();
     for ev in events.get(&Perspective::Team(0)).unwrap() {
         ev.apply(&mut client);
@@ -261,12 +321,8 @@ fn drone() {
             .unwrap()
             .get_tag(TAG_DRONE_STATION_ID)
     );
-    server
-        .handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
-    server
-        .handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
+    server.handle_command(Command::EndTurn).unwrap();
+    server.handle_command(Command::EndTurn).unwrap();
     // drone boat is unexhausted next turn
     assert!(
         !server
@@ -279,32 +335,24 @@ fn drone() {
         !server.get_unit(Point::new(3, 4)).unwrap().get_transported()[0].has_flag(FLAG_EXHAUSTED)
     );
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: Some(0.into()),
-                path: Path::with_steps(Point::new(3, 4), vec![PathStep::Dir(Direction6::D0)]),
-                action: UnitAction::Wait,
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: Some(0.into()),
+            path: Path::with_steps(Point::new(3, 4), vec![PathStep::Dir(Direction6::D0)]),
+            action: UnitAction::Wait,
+        }))
         .unwrap();
     // can't build another drone
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(Point::new(3, 4)),
-                action: UnitAction::custom(
-                    CA_UNIT_BUILD_UNIT,
-                    vec![CustomActionInput::ShopItem(0.into())],
-                ),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(Point::new(3, 4)),
+            action: UnitAction::custom(
+                CA_UNIT_BUILD_UNIT,
+                vec![CustomActionInput::ShopItem(0.into())],
+            ),
+        }))
         .unwrap_err();
-    server
-        .handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
+    server.handle_command(Command::EndTurn).unwrap();
     let drone = server.get_unit(Point::new(4, 4)).unwrap().clone();
     server.get_map_mut().set_unit(Point::new(0, 0), Some(drone));
     assert_eq!(
@@ -315,9 +363,7 @@ fn drone() {
             .len(),
         0
     );
-    let events = server
-        .handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
+    let events = server.handle_command(Command::EndTurn).unwrap();
     // one drone has returned to the boat, the other died
     assert_eq!(
         server
@@ -362,7 +408,8 @@ fn cannot_buy_unit_without_money() {
         ),
     );
     let settings = map.settings().unwrap();
-    let (mut game, _) = Game::new_server(map, &settings, settings.build_default(), Urc::new(|| 0.));
+    let (mut game, _) =
+        Game::new_server(map, &settings, settings.build_default(), GameRng::default());
     assert_eq!(
         game.current_player()
             .get_tag(TAG_FUNDS)
@@ -372,17 +419,14 @@ fn cannot_buy_unit_without_money() {
         0
     );
     let path = Path::new(Point::new(0, 0));
-    game.handle_command(
-        Command::UnitCommand(UnitCommand {
-            unload_index: None,
-            path,
-            action: UnitAction::custom(
-                CA_UNIT_BUILD_UNIT,
-                vec![CustomActionInput::ShopItem(0.into())],
-            ),
-        }),
-        Urc::new(|| 0.),
-    )
+    game.handle_command(Command::UnitCommand(UnitCommand {
+        unload_index: None,
+        path,
+        action: UnitAction::custom(
+            CA_UNIT_BUILD_UNIT,
+            vec![CustomActionInput::ShopItem(0.into())],
+        ),
+    }))
     .unwrap_err();
 }
 
@@ -436,18 +480,15 @@ fn repair_unit() {
         map.clone(),
         &settings,
         settings.build_default(),
-        Urc::new(|| 0.),
+        GameRng::default(),
     );
     assert_eq!(server.get_unit(Point::new(3, 4)).unwrap().get_hp(), 1);
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::new(Point::new(3, 4)),
-                action: UnitAction::custom(CA_UNIT_REPAIR, Vec::new()),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::new(Point::new(3, 4)),
+            action: UnitAction::custom(CA_UNIT_REPAIR, Vec::new()),
+        }))
         .unwrap();
     assert!(
         server
@@ -472,12 +513,8 @@ fn repair_unit() {
             .unwrap()
             .has_flag(FLAG_EXHAUSTED)
     );
-    server
-        .handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
-    server
-        .handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
+    server.handle_command(Command::EndTurn).unwrap();
+    server.handle_command(Command::EndTurn).unwrap();
     assert!(
         !server
             .get_unit(Point::new(3, 4))
@@ -520,18 +557,16 @@ fn end_game() {
         ),
     );
     let settings = map.settings().unwrap();
-    let (mut game, _) = Game::new_server(map, &settings, settings.build_default(), Urc::new(|| 0.));
-    game.handle_command(
-        Command::UnitCommand(UnitCommand {
-            unload_index: None,
-            path: Path::new(Point::new(0, 0)),
-            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                Point::new(0, 1),
-                Direction4::D270,
-            ))),
-        }),
-        Urc::new(|| 0.),
-    )
+    let (mut game, _) =
+        Game::new_server(map, &settings, settings.build_default(), GameRng::default());
+    game.handle_command(Command::UnitCommand(UnitCommand {
+        unload_index: None,
+        path: Path::new(Point::new(0, 0)),
+        action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+            Point::new(0, 1),
+            Direction4::D270,
+        ))),
+    }))
     .unwrap();
     assert_eq!(game.get_map().get_unit(Point::new(0, 1)), None);
     assert!(game.has_ended());
@@ -576,29 +611,25 @@ fn defeat_player_of_3() {
         ),
     );
     let settings = map.settings().unwrap();
-    let (mut game, _) = Game::new_server(map, &settings, settings.build_default(), Urc::new(|| 0.));
-    game.handle_command(
-        Command::UnitCommand(UnitCommand {
-            unload_index: None,
-            path: Path::new(Point::new(0, 0)),
-            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                Point::new(0, 1),
-                Direction4::D270,
-            ))),
-        }),
-        Urc::new(|| 0.),
-    )
+    let (mut game, _) =
+        Game::new_server(map, &settings, settings.build_default(), GameRng::default());
+    game.handle_command(Command::UnitCommand(UnitCommand {
+        unload_index: None,
+        path: Path::new(Point::new(0, 0)),
+        action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+            Point::new(0, 1),
+            Direction4::D270,
+        ))),
+    }))
     .unwrap();
     assert!(!game.has_ended());
     for (i, player) in game.players.iter().enumerate() {
         assert_eq!(player.dead, i == 0);
     }
     assert_eq!(game.current_owner(), 1);
-    game.handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
+    game.handle_command(Command::EndTurn).unwrap();
     assert_eq!(game.current_owner(), 2);
-    game.handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
+    game.handle_command(Command::EndTurn).unwrap();
     assert_eq!(game.current_owner(), 1);
 }
 
@@ -647,18 +678,16 @@ fn on_death_lose_game() {
         ),
     );
     let settings = map.settings().unwrap();
-    let (mut game, _) = Game::new_server(map, &settings, settings.build_default(), Urc::new(|| 0.));
-    game.handle_command(
-        Command::UnitCommand(UnitCommand {
-            unload_index: None,
-            path: Path::new(Point::new(0, 0)),
-            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                Point::new(0, 1),
-                Direction4::D270,
-            ))),
-        }),
-        Urc::new(|| 0.),
-    )
+    let (mut game, _) =
+        Game::new_server(map, &settings, settings.build_default(), GameRng::default());
+    game.handle_command(Command::UnitCommand(UnitCommand {
+        unload_index: None,
+        path: Path::new(Point::new(0, 0)),
+        action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+            Point::new(0, 1),
+            Direction4::D270,
+        ))),
+    }))
     .unwrap();
     assert!(game.get_unit(Point::new(0, 1)).is_none());
     assert!(game.has_ended());
@@ -713,18 +742,16 @@ fn puffer_fish() {
         ),
     );
     let settings = map.settings().unwrap();
-    let (mut game, _) = Game::new_server(map, &settings, settings.build_default(), Urc::new(|| 0.));
-    game.handle_command(
-        Command::UnitCommand(UnitCommand {
-            unload_index: None,
-            path: Path::new(Point::new(0, 1)),
-            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                Point::new(1, 1),
-                Direction4::D0,
-            ))),
-        }),
-        Urc::new(|| 0.),
-    )
+    let (mut game, _) =
+        Game::new_server(map, &settings, settings.build_default(), GameRng::default());
+    game.handle_command(Command::UnitCommand(UnitCommand {
+        unload_index: None,
+        path: Path::new(Point::new(0, 1)),
+        action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+            Point::new(1, 1),
+            Direction4::D0,
+        ))),
+    }))
     .unwrap();
     assert_eq!(
         game.get_unit(Point::new(2, 1)).unwrap().typ(),
@@ -734,17 +761,14 @@ fn puffer_fish() {
     assert_eq!(game.get_unit(Point::new(2, 1)).unwrap().get_hp(), 100);
     let hp = game.get_unit(Point::new(2, 0)).unwrap().get_hp();
     assert!(hp < 100);
-    game.handle_command(
-        Command::UnitCommand(UnitCommand {
-            unload_index: None,
-            path: Path::new(Point::new(0, 2)),
-            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                Point::new(2, 1),
-                Direction4::D90,
-            ))),
-        }),
-        Urc::new(|| 0.),
-    )
+    game.handle_command(Command::UnitCommand(UnitCommand {
+        unload_index: None,
+        path: Path::new(Point::new(0, 2)),
+        action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+            Point::new(2, 1),
+            Direction4::D90,
+        ))),
+    }))
     .unwrap();
     assert_eq!(game.get_unit(Point::new(2, 1)).unwrap().get_hp(), 100);
     assert!(game.get_unit(Point::new(2, 0)).unwrap().get_hp() < hp);
@@ -795,31 +819,26 @@ fn capture_pyramid() {
         ),
     );
     let settings = map.settings().unwrap();
-    let (mut game, _) = Game::new_server(map, &settings, settings.build_default(), Urc::new(|| 0.));
-    game.handle_command(
-        Command::UnitCommand(UnitCommand {
-            unload_index: None,
-            path: Path::new(Point::new(0, 0)),
-            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                Point::new(0, 1),
-                Direction4::D270,
-            ))),
-        }),
-        Urc::new(|| 0.),
-    )
+    let (mut game, _) =
+        Game::new_server(map, &settings, settings.build_default(), GameRng::default());
+    game.handle_command(Command::UnitCommand(UnitCommand {
+        unload_index: None,
+        path: Path::new(Point::new(0, 0)),
+        action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+            Point::new(0, 1),
+            Direction4::D270,
+        ))),
+    }))
     .unwrap();
     assert_eq!(game.get_unit(Point::new(0, 1)).unwrap().get_owner_id(), -1);
-    game.handle_command(
-        Command::UnitCommand(UnitCommand {
-            unload_index: None,
-            path: Path::new(Point::new(0, 2)),
-            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                Point::new(0, 1),
-                Direction4::D90,
-            ))),
-        }),
-        Urc::new(|| 0.),
-    )
+    game.handle_command(Command::UnitCommand(UnitCommand {
+        unload_index: None,
+        path: Path::new(Point::new(0, 2)),
+        action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+            Point::new(0, 1),
+            Direction4::D90,
+        ))),
+    }))
     .unwrap();
     assert_eq!(game.get_unit(Point::new(0, 1)).unwrap().get_owner_id(), 0);
 }
@@ -861,7 +880,8 @@ fn s_factory() {
         ),
     );
     let settings = map.settings().unwrap();
-    let (mut game, _) = Game::new_server(map, &settings, settings.build_default(), Urc::new(|| 0.));
+    let (mut game, _) =
+        Game::new_server(map, &settings, settings.build_default(), GameRng::default());
     assert_eq!(
         game.current_player()
             .get_tag(TAG_FUNDS)
@@ -885,20 +905,17 @@ fn s_factory() {
         .instance(&game.environment())
         .set_owner_id(0)
         .set_hp(100);
-    game.handle_command(
-        Command::UnitCommand(UnitCommand {
-            unload_index: None,
-            path: Path::new(Point::new(1, 1)),
-            action: UnitAction::custom(
-                CA_UNIT_BUILD_UNIT,
-                vec![
-                    CustomActionInput::ShopItem(0.into()),
-                    CustomActionInput::Direction(Direction6::D180),
-                ],
-            ),
-        }),
-        Urc::new(|| 0.),
-    )
+    game.handle_command(Command::UnitCommand(UnitCommand {
+        unload_index: None,
+        path: Path::new(Point::new(1, 1)),
+        action: UnitAction::custom(
+            CA_UNIT_BUILD_UNIT,
+            vec![
+                CustomActionInput::ShopItem(0.into()),
+                CustomActionInput::Direction(Direction6::D180),
+            ],
+        ),
+    }))
     .unwrap();
     assert_eq!(
         *game.get_unit(Point::new(0, 1)).unwrap(),
@@ -932,17 +949,15 @@ fn marine_movement_types() {
     settings.players[0]
         .get_tag_bag_mut()
         .set_tag(&map_env, TAG_FUNDS, 1000.into());
-    let (mut game, _) = Game::new_server(map, &settings, settings.build_default(), Urc::new(|| 0.));
+    let (mut game, _) =
+        Game::new_server(map, &settings, settings.build_default(), GameRng::default());
     let environment = game.environment().clone();
-    game.handle_command(
-        Command::TokenAction(
-            Point::new(0, 0),
-            vec![CustomActionInput::ShopItem(0.into())]
-                .try_into()
-                .unwrap(),
-        ),
-        Urc::new(|| 0.),
-    )
+    game.handle_command(Command::TokenAction(
+        Point::new(0, 0),
+        vec![CustomActionInput::ShopItem(0.into())]
+            .try_into()
+            .unwrap(),
+    ))
     .unwrap();
     assert_eq!(
         game.get_unit(Point::new(0, 0)),
@@ -955,15 +970,12 @@ fn marine_movement_types() {
                 .build()
         )
     );
-    game.handle_command(
-        Command::TokenAction(
-            Point::new(1, 0),
-            vec![CustomActionInput::ShopItem(0.into())]
-                .try_into()
-                .unwrap(),
-        ),
-        Urc::new(|| 0.),
-    )
+    game.handle_command(Command::TokenAction(
+        Point::new(1, 0),
+        vec![CustomActionInput::ShopItem(0.into())]
+            .try_into()
+            .unwrap(),
+    ))
     .unwrap();
     assert_eq!(
         game.get_unit(Point::new(1, 0)),
@@ -1018,7 +1030,8 @@ fn enter_transporter() {
     );
     // create game
     let settings = map.settings().unwrap();
-    let (mut game, _) = Game::new_server(map, &settings, settings.build_default(), Urc::new(|| 0.));
+    let (mut game, _) =
+        Game::new_server(map, &settings, settings.build_default(), GameRng::default());
     // test
     let transporter = game.get_unit(Point::new(2, 0)).unwrap();
     assert_eq!(transporter.get_transported().len(), 0);
@@ -1033,14 +1046,11 @@ fn enter_transporter() {
             .options_after_path(&board, &path, None, &[])
             .contains(&UnitAction::Enter)
     );
-    game.handle_command(
-        Command::UnitCommand(UnitCommand {
-            unload_index: None,
-            path,
-            action: UnitAction::Enter,
-        }),
-        Urc::new(|| 0.),
-    )
+    game.handle_command(Command::UnitCommand(UnitCommand {
+        unload_index: None,
+        path,
+        action: UnitAction::Enter,
+    }))
     .unwrap();
     let transporter = game.get_unit(Point::new(2, 0)).unwrap();
     assert_eq!(transporter.typ(), UnitType::TRANSPORT_HELI);
@@ -1057,24 +1067,21 @@ fn chess_movement_exhausts_all() {
         map.clone(),
         &settings,
         settings.build_default(),
-        Urc::new(|| 0.),
+        GameRng::default(),
     )
     .0;
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::with_steps(
-                    Point::new(0, 6),
-                    vec![
-                        PathStep::Dir(Direction4::D90),
-                        PathStep::Dir(Direction4::D90),
-                    ],
-                ),
-                action: UnitAction::Wait,
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::with_steps(
+                Point::new(0, 6),
+                vec![
+                    PathStep::Dir(Direction4::D90),
+                    PathStep::Dir(Direction4::D90),
+                ],
+            ),
+            action: UnitAction::Wait,
+        }))
         .unwrap();
     assert!(
         server
@@ -1171,25 +1178,22 @@ fn chess_castling() {
         map.clone(),
         &settings,
         settings.build_default(),
-        Urc::new(|| 0.),
+        GameRng::default(),
     );
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::with_steps(
-                    Point::new(4, 4),
-                    vec![
-                        PathStep::Dir(Direction4::D180),
-                        PathStep::Dir(Direction4::D180),
-                        PathStep::Dir(Direction4::D180),
-                        PathStep::Dir(Direction4::D180),
-                    ],
-                ),
-                action: UnitAction::Wait,
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::with_steps(
+                Point::new(4, 4),
+                vec![
+                    PathStep::Dir(Direction4::D180),
+                    PathStep::Dir(Direction4::D180),
+                    PathStep::Dir(Direction4::D180),
+                    PathStep::Dir(Direction4::D180),
+                ],
+            ),
+            action: UnitAction::Wait,
+        }))
         .unwrap();
     assert!(
         server
@@ -1209,51 +1213,41 @@ fn chess_castling() {
             .unwrap()
             .has_flag(FLAG_UNMOVED)
     );
+    server.handle_command(Command::EndTurn).unwrap();
+    server.handle_command(Command::EndTurn).unwrap();
     server
-        .handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
-    server
-        .handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
-    server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::with_steps(
-                    Point::new(0, 4),
-                    vec![
-                        PathStep::Dir(Direction4::D90),
-                        PathStep::Dir(Direction4::D90),
-                        PathStep::Dir(Direction4::D90),
-                    ],
-                ),
-                action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                    Point::new(0, 3),
-                    Direction4::D90,
-                ))),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::with_steps(
+                Point::new(0, 4),
+                vec![
+                    PathStep::Dir(Direction4::D90),
+                    PathStep::Dir(Direction4::D90),
+                    PathStep::Dir(Direction4::D90),
+                ],
+            ),
+            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+                Point::new(0, 3),
+                Direction4::D90,
+            ))),
+        }))
         .unwrap_err();
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::with_steps(
-                    Point::new(4, 0),
-                    vec![
-                        PathStep::Dir(Direction4::D180),
-                        PathStep::Dir(Direction4::D180),
-                        PathStep::Dir(Direction4::D180),
-                    ],
-                ),
-                action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                    Point::new(0, 0),
-                    Direction4::D180,
-                ))),
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::with_steps(
+                Point::new(4, 0),
+                vec![
+                    PathStep::Dir(Direction4::D180),
+                    PathStep::Dir(Direction4::D180),
+                    PathStep::Dir(Direction4::D180),
+                ],
+            ),
+            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+                Point::new(0, 0),
+                Direction4::D180,
+            ))),
+        }))
         .unwrap();
     assert!(
         !server
@@ -1335,19 +1329,16 @@ fn chess_en_passant() {
         map.clone(),
         &settings,
         settings.build_default(),
-        Urc::new(|| 0.),
+        GameRng::default(),
     );
     let unchanged = server.clone();
     // take pawn normally
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::with_steps(Point::new(0, 0), vec![PathStep::Dir(Direction4::D270)]),
-                action: UnitAction::Wait,
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::with_steps(Point::new(0, 0), vec![PathStep::Dir(Direction4::D270)]),
+            action: UnitAction::Wait,
+        }))
         .unwrap();
     assert!(
         server
@@ -1356,65 +1347,47 @@ fn chess_en_passant() {
             .get_tag(TAG_EN_PASSANT)
             .is_none()
     );
+    server.handle_command(Command::EndTurn).unwrap();
     server
-        .handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
-    server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::with_steps(Point::new(1, 2), vec![PathStep::Diagonal(Direction4::D90)]),
-                action: UnitAction::Take,
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::with_steps(Point::new(1, 2), vec![PathStep::Diagonal(Direction4::D90)]),
+            action: UnitAction::Take,
+        }))
         .unwrap();
     // unable to take pawn that wasn't moved (out of range)
     let mut server = unchanged.clone();
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::with_steps(Point::new(4, 3), vec![PathStep::Dir(Direction4::D180)]),
-                action: UnitAction::Wait,
-            }),
-            Urc::new(|| 0.),
-        )
-        .unwrap();
-    server
-        .handle_command(Command::EndTurn, Urc::new(|| 0.))
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::with_steps(Point::new(4, 3), vec![PathStep::Dir(Direction4::D180)]),
+            action: UnitAction::Wait,
+        }))
         .unwrap();
+    server.handle_command(Command::EndTurn).unwrap();
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::with_steps(Point::new(1, 2), vec![PathStep::Diagonal(Direction4::D90)]),
-                action: UnitAction::Take,
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::with_steps(Point::new(1, 2), vec![PathStep::Diagonal(Direction4::D90)]),
+            action: UnitAction::Take,
+        }))
         .unwrap_err();
     // en passant
     let mut server = unchanged.clone();
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::with_steps(
-                    Point::new(0, 0),
-                    vec![
-                        PathStep::Dir(Direction4::D270),
-                        PathStep::Dir(Direction4::D270),
-                    ],
-                ),
-                action: UnitAction::Wait,
-            }),
-            Urc::new(|| 0.),
-        )
-        .unwrap();
-    server
-        .handle_command(Command::EndTurn, Urc::new(|| 0.))
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::with_steps(
+                Point::new(0, 0),
+                vec![
+                    PathStep::Dir(Direction4::D270),
+                    PathStep::Dir(Direction4::D270),
+                ],
+            ),
+            action: UnitAction::Wait,
+        }))
         .unwrap();
+    server.handle_command(Command::EndTurn).unwrap();
     assert_eq!(
         server
             .get_unit(Point::new(0, 2))
@@ -1427,96 +1400,67 @@ fn chess_en_passant() {
         Some(Point::new(0, 1))
     );
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::with_steps(Point::new(1, 2), vec![PathStep::Diagonal(Direction4::D90)]),
-                action: UnitAction::Wait,
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::with_steps(Point::new(1, 2), vec![PathStep::Diagonal(Direction4::D90)]),
+            action: UnitAction::Wait,
+        }))
         .unwrap();
     // pawn moved twice, no en passant possible
     let mut server = unchanged.clone();
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::with_steps(Point::new(0, 0), vec![PathStep::Dir(Direction4::D270)]),
-                action: UnitAction::Wait,
-            }),
-            Urc::new(|| 0.),
-        )
-        .unwrap();
-    server
-        .handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
-    server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::with_steps(Point::new(4, 4), vec![PathStep::Dir(Direction4::D180)]),
-                action: UnitAction::Wait,
-            }),
-            Urc::new(|| 0.),
-        )
-        .unwrap();
-    server
-        .handle_command(Command::EndTurn, Urc::new(|| 0.))
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::with_steps(Point::new(0, 0), vec![PathStep::Dir(Direction4::D270)]),
+            action: UnitAction::Wait,
+        }))
         .unwrap();
+    server.handle_command(Command::EndTurn).unwrap();
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::with_steps(Point::new(0, 1), vec![PathStep::Dir(Direction4::D270)]),
-                action: UnitAction::Wait,
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::with_steps(Point::new(4, 4), vec![PathStep::Dir(Direction4::D180)]),
+            action: UnitAction::Wait,
+        }))
         .unwrap();
+    server.handle_command(Command::EndTurn).unwrap();
     server
-        .handle_command(Command::EndTurn, Urc::new(|| 0.))
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::with_steps(Point::new(0, 1), vec![PathStep::Dir(Direction4::D270)]),
+            action: UnitAction::Wait,
+        }))
         .unwrap();
+    server.handle_command(Command::EndTurn).unwrap();
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::with_steps(Point::new(1, 2), vec![PathStep::Diagonal(Direction4::D90)]),
-                action: UnitAction::Take,
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::with_steps(Point::new(1, 2), vec![PathStep::Diagonal(Direction4::D90)]),
+            action: UnitAction::Take,
+        }))
         .unwrap_err();
     // en passant not possible when tried one turn later
     let mut server = unchanged.clone();
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::with_steps(
-                    Point::new(0, 0),
-                    vec![
-                        PathStep::Dir(Direction4::D270),
-                        PathStep::Dir(Direction4::D270),
-                    ],
-                ),
-                action: UnitAction::Wait,
-            }),
-            Urc::new(|| 0.),
-        )
-        .unwrap();
-    server
-        .handle_command(Command::EndTurn, Urc::new(|| 0.))
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::with_steps(
+                Point::new(0, 0),
+                vec![
+                    PathStep::Dir(Direction4::D270),
+                    PathStep::Dir(Direction4::D270),
+                ],
+            ),
+            action: UnitAction::Wait,
+        }))
         .unwrap();
+    server.handle_command(Command::EndTurn).unwrap();
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::with_steps(Point::new(4, 4), vec![PathStep::Dir(Direction4::D180)]),
-                action: UnitAction::Wait,
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::with_steps(Point::new(4, 4), vec![PathStep::Dir(Direction4::D180)]),
+            action: UnitAction::Wait,
+        }))
         .unwrap();
     assert_eq!(
         server
@@ -1525,9 +1469,7 @@ fn chess_en_passant() {
             .get_tag(TAG_EN_PASSANT),
         Some(TagValue::Point(Point::new(0, 1)))
     );
-    server
-        .handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
+    server.handle_command(Command::EndTurn).unwrap();
     assert!(
         server
             .get_unit(Point::new(0, 2))
@@ -1536,27 +1478,19 @@ fn chess_en_passant() {
             .is_none()
     );
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::with_steps(Point::new(4, 3), vec![PathStep::Dir(Direction4::D180)]),
-                action: UnitAction::Wait,
-            }),
-            Urc::new(|| 0.),
-        )
-        .unwrap();
-    server
-        .handle_command(Command::EndTurn, Urc::new(|| 0.))
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::with_steps(Point::new(4, 3), vec![PathStep::Dir(Direction4::D180)]),
+            action: UnitAction::Wait,
+        }))
         .unwrap();
+    server.handle_command(Command::EndTurn).unwrap();
     server
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path: Path::with_steps(Point::new(1, 2), vec![PathStep::Diagonal(Direction4::D90)]),
-                action: UnitAction::Take,
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path: Path::with_steps(Point::new(1, 2), vec![PathStep::Diagonal(Direction4::D90)]),
+            action: UnitAction::Take,
+        }))
         .unwrap_err();
 }
 
@@ -1596,19 +1530,17 @@ fn magnet_pulls_through_pipe() {
         ),
     );
     let settings = map.settings().unwrap();
-    let (mut game, _) = Game::new_server(map, &settings, settings.build_default(), Urc::new(|| 0.));
+    let (mut game, _) =
+        Game::new_server(map, &settings, settings.build_default(), GameRng::default());
 
-    game.handle_command(
-        Command::UnitCommand(UnitCommand {
-            unload_index: None,
-            path: Path::new(Point::new(1, 2)),
-            action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
-                Point::new(3, 0),
-                Direction4::D0,
-            ))),
-        }),
-        Urc::new(|| 0.),
-    )
+    game.handle_command(Command::UnitCommand(UnitCommand {
+        unload_index: None,
+        path: Path::new(Point::new(1, 2)),
+        action: UnitAction::Attack(AttackInput::SplashPattern(OrientedPoint::simple(
+            Point::new(3, 0),
+            Direction4::D0,
+        ))),
+    }))
     .unwrap();
     assert!(game.get_unit(Point::new(1, 1)).is_some());
     assert!(game.get_unit(Point::new(1, 2)).is_some());
@@ -1644,27 +1576,23 @@ fn fog_surprise() {
     );
     let mut settings = map.settings().unwrap();
     settings.fog_mode = FogMode::Constant(FogSetting::ExtraDark(0));
-    let (mut game, _) = Game::new_server(map, &settings, settings.build_default(), Urc::new(|| 0.));
+    let (mut game, _) =
+        Game::new_server(map, &settings, settings.build_default(), GameRng::default());
 
     // no fog during the first turn
-    game.handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
-    game.handle_command(Command::EndTurn, Urc::new(|| 0.))
-        .unwrap();
+    game.handle_command(Command::EndTurn).unwrap();
+    game.handle_command(Command::EndTurn).unwrap();
 
     let path = Path::with_steps(
         Point::new(0, 0),
         [PathStep::Dir(Direction4::D0); 6].to_vec(),
     );
     let events = game
-        .handle_command(
-            Command::UnitCommand(UnitCommand {
-                unload_index: None,
-                path,
-                action: UnitAction::Wait,
-            }),
-            Urc::new(|| 0.),
-        )
+        .handle_command(Command::UnitCommand(UnitCommand {
+            unload_index: None,
+            path,
+            action: UnitAction::Wait,
+        }))
         .unwrap();
     assert!(game.get_unit(Point::new(0, 0)).is_none());
     assert!(game.get_unit(Point::new(5, 0)).is_some());
@@ -1675,3 +1603,246 @@ fn fog_surprise() {
             .contains(&Event::Effect(Effect::new_fog_surprise(Point::new(6, 0))))
     );
 }
+
+#[test]
+fn zone_of_control_stops_movement() {
+    let mut game = zone_of_control_game(UnitType::SMALL_TANK, Point::new(4, 1), false);
+    assert_eq!(
+        game.handle_command(move_right(6)).err(),
+        Some(CommandError::InvalidPath(PathError::Unreachable(4)))
+    );
+    game.handle_command(move_right(4)).unwrap();
+    assert!(game.get_unit(Point::new(4, 0)).is_some());
+}
+
+#[test]
+fn zone_of_control_costs_movement() {
+    let mut game = zone_of_control_game(UnitType::SNIPER, Point::new(2, 1), false);
+    assert_eq!(
+        game.handle_command(move_right(5)).err(),
+        Some(CommandError::InvalidPath(PathError::Unreachable(4)))
+    );
+    game.handle_command(move_right(4)).unwrap();
+    assert!(game.get_unit(Point::new(4, 0)).is_some());
+}
+
+#[test]
+fn zone_of_control_fog_surprise() {
+    let mut game = zone_of_control_game(UnitType::SMALL_TANK, Point::new(4, 1), true);
+    let events = game.handle_command(move_right(6)).unwrap();
+    assert!(game.get_unit(Point::new(0, 0)).is_none());
+    assert!(game.get_unit(Point::new(4, 0)).is_some());
+    assert!(
+        events
+            .get(&Perspective::Team(0))
+            .unwrap()
+            .contains(&Event::Effect(Effect::new_fog_surprise(Point::new(4, 1))))
+    );
+}
+
+#[test]
+fn zone_of_control_fog_surprise_cost() {
+    let mut game = zone_of_control_game(UnitType::SNIPER, Point::new(5, 1), true);
+    let events = game.handle_command(move_right(5)).unwrap();
+    assert!(game.get_unit(Point::new(4, 0)).is_some());
+    assert!(
+        events
+            .get(&Perspective::Team(0))
+            .unwrap()
+            .contains(&Event::Effect(Effect::new_fog_surprise(Point::new(5, 1))))
+    );
+}
+
+#[test]
+fn group_move() {
+    let config = Urc::new(Config::default());
+    let map = PointMap::new(8, 4, false);
+    let wmap: WrappingMap<Direction4> = WMBuilder::new(map).build();
+    let mut map = Map::new(wmap, &config);
+    let map_env = map.environment().clone();
+    for p in [Point::new(0, 0), Point::new(1, 0)] {
+        map.set_unit(
+            p,
+            Some(UnitType::MARINE.instance(&map_env).set_owner_id(0).build()),
+        );
+    }
+    map.set_unit(
+        Point::new(7, 3),
+        Some(
+            UnitType::SMALL_TANK
+                .instance(&map_env)
+                .set_owner_id(1)
+                .build(),
+        ),
+    );
+    let settings = map.settings().unwrap();
+    let (mut game, _) =
+        Game::new_server(map, &settings, settings.build_default(), GameRng::default());
+
+    // nobody moves if one unit can't reach its destination
+    let entry = |start: Point, destination: Point| GroupMoveEntry { start, destination };
+    assert_eq!(
+        game.handle_command(Command::group_move(vec![
+            entry(Point::new(1, 0), Point::new(3, 0)),
+            entry(Point::new(0, 0), Point::new(5, 0)),
+        ]))
+        .err(),
+        Some(CommandError::InvalidPath(PathError::CannotStop(
+            Point::new(5, 0)
+        )))
+    );
+    assert!(game.get_unit(Point::new(1, 0)).is_some());
+
+    // a unit can't be part of the group twice
+    assert_eq!(
+        game.handle_command(Command::group_move(vec![
+            entry(Point::new(0, 0), Point::new(2, 0)),
+            entry(Point::new(0, 0), Point::new(0, 1)),
+        ]))
+        .err(),
+        Some(CommandError::DuplicateGroupMember(Point::new(0, 0)))
+    );
+    // or continue moving from where another entry put it
+    assert_eq!(
+        game.handle_command(Command::group_move(vec![
+            entry(Point::new(0, 0), Point::new(2, 0)),
+            entry(Point::new(2, 0), Point::new(4, 0)),
+        ]))
+        .err(),
+        Some(CommandError::DuplicateGroupMember(Point::new(2, 0)))
+    );
+    assert!(game.get_unit(Point::new(0, 0)).is_some());
+    assert!(game.get_unit(Point::new(2, 0)).is_none());
+
+    // the first unit waits until the second one left its destination
+    let events = game
+        .handle_command(Command::group_move(vec![
+            entry(Point::new(0, 0), Point::new(1, 0)),
+            entry(Point::new(1, 0), Point::new(3, 0)),
+        ]))
+        .unwrap();
+    assert!(game.get_unit(Point::new(0, 0)).is_none());
+    assert!(
+        game.get_unit(Point::new(1, 0))
+            .unwrap()
+            .has_flag(FLAG_EXHAUSTED)
+    );
+    assert!(
+        game.get_unit(Point::new(3, 0))
+            .unwrap()
+            .has_flag(FLAG_EXHAUSTED)
+    );
+    let events = events.get(&Perspective::Team(0)).unwrap();
+    assert!(!events.iter().any(|e| matches!(e, Event::Effect(_))));
+    assert_eq!(
+        events
+            .iter()
+            .filter(|e| matches!(e, Event::Effects(effects) if effects.len() >= 2))
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn group_move_fog_trap() {
+    let config = Urc::new(Config::default());
+    let map = PointMap::new(8, 4, false);
+    let wmap: WrappingMap<Direction4> = WMBuilder::new(map).build();
+    let mut map = Map::new(wmap, &config);
+    let map_env = map.environment().clone();
+    for p in [Point::new(0, 0), Point::new(0, 1)] {
+        map.set_unit(
+            p,
+            Some(
+                UnitType::SMALL_TANK
+                    .instance(&map_env)
+                    .set_owner_id(0)
+                    .build(),
+            ),
+        );
+    }
+    map.set_unit(
+        Point::new(5, 0),
+        Some(UnitType::MARINE.instance(&map_env).set_owner_id(1).build()),
+    );
+    let mut settings = map.settings().unwrap();
+    settings.fog_mode = FogMode::Constant(FogSetting::ExtraDark(0));
+    let (mut game, _) =
+        Game::new_server(map, &settings, settings.build_default(), GameRng::default());
+    // no fog during the first turn
+    game.handle_command(Command::EndTurn).unwrap();
+    game.handle_command(Command::EndTurn).unwrap();
+
+    // the second unit was planned to stop where the first one got stuck
+    let entry = |start: Point, destination: Point| GroupMoveEntry { start, destination };
+    let events = game
+        .handle_command(Command::group_move(vec![
+            entry(Point::new(0, 0), Point::new(6, 0)),
+            entry(Point::new(0, 1), Point::new(4, 0)),
+        ]))
+        .unwrap();
+    assert!(game.get_unit(Point::new(4, 0)).is_some());
+    assert!(game.get_unit(Point::new(0, 1)).is_some());
+    let surprise = Effect::new_fog_surprise(Point::new(5, 0));
+    assert!(
+        events
+            .get(&Perspective::Team(0))
+            .unwrap()
+            .iter()
+            .any(|e| match e {
+                Event::Effect(effect) => *effect == surprise,
+                Event::Effects(effects) => effects.iter().any(|effect| *effect == surprise),
+                _ => false,
+            })
+    );
+}
+
+#[test]
+fn movement_map() {
+    let config = Urc::new(Config::default());
+    let map = PointMap::new(8, 4, false);
+    let wmap: WrappingMap<Direction4> = WMBuilder::new(map).build();
+    let mut map = Map::new(wmap, &config);
+    let map_env = map.environment().clone();
+    for p in [Point::new(0, 0), Point::new(1, 0)] {
+        map.set_unit(
+            p,
+            Some(UnitType::MARINE.instance(&map_env).set_owner_id(0).build()),
+        );
+    }
+    let board = Board::from(&map);
+    let marine = map.get_unit(Point::new(0, 0)).unwrap();
+    let path_so_far = Path::new(Point::new(0, 0));
+    let movement_map = marine.movement_map(&board, &path_so_far, None);
+
+    let start = movement_map.get(Point::new(0, 0)).unwrap();
+    assert_eq!(start.movement_points, Rational32::from_integer(3));
+    assert_eq!(start.previous, None);
+    assert!(start.can_stop);
+    // the other marine can be passed, but not stopped on
+    let friend = movement_map.get(Point::new(1, 0)).unwrap();
+    assert_eq!(friend.movement_points, Rational32::from_integer(2));
+    assert!(!friend.can_stop);
+    let behind = movement_map.get(Point::new(2, 0)).unwrap();
+    assert_eq!(behind.movement_points, Rational32::from_integer(1));
+    assert_eq!(
+        behind.previous,
+        Some((Point::new(1, 0), PathStep::Dir(Direction4::D0)))
+    );
+    assert_eq!(
+        movement_map.path_to(Point::new(3, 0)),
+        Some(&Path::with_steps(
+            Point::new(0, 0),
+            vec![PathStep::Dir(Direction4::D0); 3]
+        ))
+    );
+    assert!(movement_map.get(Point::new(4, 0)).is_none());
+
+    let reachable = movement_map.reachable_positions();
+    let movable = marine.movable_positions(&board, &path_so_far, None);
+    assert_eq!(reachable.len(), movable.len());
+    assert!(movable.iter().all(|p| reachable.contains(p)));
+    let stop = movement_map.stop_positions();
+    assert_eq!(stop.len(), reachable.len() - 1);
+    assert!(!stop.contains(&Point::new(1, 0)));
+}
@@ -17,6 +17,7 @@ use crate::commander::commander_type::CommanderType;
 use crate::config::OwnershipPredicate;
 use crate::config::environment::Environment;
 use crate::config::movement_type_config::MovementPattern;
+use crate::config::reload::{ConfigDiff, ConfigMigrationError};
 use crate::game::fog::{
     FogIntensity, FogSetting, VisionMode, get_visible_unit, is_unit_attribute_visible,
 };
@@ -27,6 +28,7 @@ use crate::map::map::get_neighbors_layers;
 use crate::map::point::Point;
 use crate::map::wrapping_map::Distortion;
 use crate::player::{Owner, Player};
+use crate::script::profiler::ScriptHook;
 use crate::script::*;
 use crate::tags::{TagBag, TagValue};
 use crate::units::UnitData;
@@ -35,7 +37,9 @@ use super::UnitVisibility;
 use super::commands::UnitAction;
 use super::hero::*;
 use super::movement::*;
+use super::movement_map::MovementMap;
 use super::unit_types::UnitType;
+use super::zone_of_control::ZoneOfControl;
 
 #[derive(Clone, Eq)]
 pub struct Unit<D: Direction> {
@@ -118,6 +122,44 @@ impl<D: Direction> Unit<D> {
         }
     }
 
+    /**
+     * copy of this unit (including transported units) for the new environment's config.
+     * falls back to the default sub movement type if the old one isn't available anymore
+     */
+    pub(crate) fn migrate_config(
+        &self,
+        diff: &ConfigDiff,
+        environment: &Environment,
+    ) -> Result<Self, ConfigMigrationError> {
+        let typ = diff.unit_type(self.typ)?;
+        let sub_movement_types = environment
+            .config
+            .sub_movement_types(environment.config.base_movement_type(typ));
+        let sub_movement_type = diff
+            .movement_type(self.sub_movement_type)
+            .ok()
+            .filter(|m| sub_movement_types.contains(m))
+            .unwrap_or(sub_movement_types[0]);
+        let hero = match &self.hero {
+            Some(hero) => Some(hero.migrate_config(diff, environment)?),
+            None => None,
+        };
+        let mut transport = Vec::with_capacity(self.transport.len());
+        for unit in &self.transport {
+            transport.push(unit.migrate_config(diff, environment)?);
+        }
+        Ok(Self {
+            environment: environment.clone(),
+            typ,
+            owner: self.owner,
+            sub_movement_type,
+            hero,
+            tags: self.tags.migrate_config(diff, environment)?,
+            transport,
+            cached_visibility: None,
+        })
+    }
+
     // getters that aren't influenced by attributes
     pub fn environment(&self) -> &Environment {
         &self.environment
@@ -182,6 +224,17 @@ impl<D: Direction> Unit<D> {
             .unit_can_pass_enemy_units(game, self, unit_pos, transporter, heroes)
     }
 
+    pub fn zone_of_control(
+        &self,
+        game: &Board<D>,
+        unit_pos: Point,
+        heroes: &HeroMap<D>,
+    ) -> ZoneOfControl {
+        self.environment
+            .config
+            .unit_zone_of_control(game, self, unit_pos, heroes)
+    }
+
     pub fn can_be_moved_through(&self) -> bool {
         self.environment.config.can_be_moved_through(self.typ)
     }
@@ -509,7 +562,7 @@ impl<D: Direction> Unit<D> {
         first_argument.insert(CONST_NAME_POSITION.into(), Dynamic::from(pos));
         first_argument.insert(CONST_NAME_UNIT.into(), Dynamic::from(self.clone()));
         let executor = board.executor(first_argument);
-        match executor.run::<D, bool>(is_unit_movable_rhai, ()) {
+        match executor.run::<D, bool>(ScriptHook::Ruleset, is_unit_movable_rhai, ()) {
             Ok(movable) => movable,
             Err(e) => {
                 let environment = self.environment();
@@ -819,6 +872,16 @@ impl<D: Direction> Unit<D> {
             .collect()
     }
 
+    /// like movable_positions, but with remaining movement points and paths for every point
+    pub fn movement_map(
+        &self,
+        game: &Board<D>,
+        path_so_far: &Path<D>,
+        transporter: Option<(&Unit<D>, usize)>,
+    ) -> MovementMap<D> {
+        MovementMap::new(game, self, path_so_far, transporter)
+    }
+
     pub fn attackable_positions(
         &self,
         game: &Board<D>,
@@ -928,9 +991,8 @@ impl<D: Direction> Unit<D> {
     pub fn transformed_by_path(&mut self, map: &Board<D>, path: &Path<D>) {
         let mut current = path.start;
         for step in &path.steps {
-            let (next, distortion) = match step.progress(map, current) {
-                Ok(n) => n,
-                _ => return,
+            let Some((next, distortion)) = step.progress(map, current) else {
+                return;
             };
             self.transformed_by_movement(map, current, next, distortion);
             current = next;
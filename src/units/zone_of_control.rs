@@ -0,0 +1,141 @@
+use interfaces::ClientPerspective;
+use num_rational::Rational32;
+use rustc_hash::FxHashMap;
+
+use crate::config::ConfigParseError;
+use crate::config::file_loader::FileLoader;
+use crate::config::parse::{FromConfig, parse_tuple1, string_base};
+use crate::map::board::{Board, BoardView};
+use crate::map::direction::Direction;
+use crate::map::map::{NeighborMode, get_neighbors, valid_points};
+use crate::map::point::Point;
+
+use super::hero::HeroMap;
+
+/// How a unit hinders enemies that move next to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZoneOfControl {
+    None,
+    /// enemies have to end their turn's movement when entering an adjacent field
+    Stop,
+    /// entering an adjacent field costs enemies additional movement points
+    Cost(Rational32),
+}
+
+impl ZoneOfControl {
+    /**
+     * several zones on the same field don't add up,
+     * the strongest one applies
+     */
+    pub fn combine(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::Stop, _) | (_, Self::Stop) => Self::Stop,
+            (Self::Cost(a), Self::Cost(b)) => Self::Cost(a.max(b)),
+            (Self::Cost(cost), Self::None) | (Self::None, Self::Cost(cost)) => Self::Cost(cost),
+            (Self::None, Self::None) => Self::None,
+        }
+    }
+
+    pub fn stops_movement(&self) -> bool {
+        *self == Self::Stop
+    }
+
+    pub fn extra_cost(&self) -> Rational32 {
+        match self {
+            Self::Cost(cost) => *cost,
+            _ => Rational32::from_integer(0),
+        }
+    }
+}
+
+impl FromConfig for ZoneOfControl {
+    fn from_conf<'a>(
+        s: &'a str,
+        loader: &mut FileLoader,
+    ) -> Result<(Self, &'a str), ConfigParseError> {
+        let (base, mut remainder) = string_base(s);
+        Ok((
+            match base {
+                "" | "None" => Self::None,
+                "Stop" => Self::Stop,
+                "Cost" => {
+                    let (cost, r) = parse_tuple1::<Rational32>(remainder, loader)?;
+                    remainder = r;
+                    if cost < Rational32::from_integer(0) {
+                        return Err(ConfigParseError::InvalidNumber(format!(
+                            "ZoneOfControl::Cost({cost})"
+                        )));
+                    }
+                    Self::Cost(cost)
+                }
+                _ => {
+                    return Err(ConfigParseError::UnknownEnumMember(format!(
+                        "ZoneOfControl::{base}"
+                    )));
+                }
+            },
+            remainder,
+        ))
+    }
+}
+
+/// (team of the unit, position of the unit, its zone of control)
+pub type ZoneOfControlInfluence = (ClientPerspective, Point, ZoneOfControl);
+
+/**
+ * Zones of control of all units on the board, indexed by the fields they cover.
+ * Built from the board the path search sees, so units hidden in fog don't exert any.
+ */
+pub struct ZoneOfControlMap {
+    zones: FxHashMap<Point, Vec<ZoneOfControlInfluence>>,
+}
+
+impl ZoneOfControlMap {
+    pub fn new_empty() -> Self {
+        Self {
+            zones: FxHashMap::default(),
+        }
+    }
+
+    pub fn new<D: Direction>(map: &Board<D>) -> Self {
+        let mut zones: FxHashMap<Point, Vec<ZoneOfControlInfluence>> = FxHashMap::default();
+        if !map.environment().config.has_zone_of_control() {
+            return Self { zones };
+        }
+        let heroes = HeroMap::new(map, None);
+        for p in valid_points(map) {
+            // transported units don't exert a zone of control
+            let Some(unit) = map.get_unit(p) else {
+                continue;
+            };
+            let zone = unit.zone_of_control(map, p, &heroes);
+            if zone == ZoneOfControl::None {
+                continue;
+            }
+            for neighbor in get_neighbors(map, p, NeighborMode::FollowPipes) {
+                zones
+                    .entry(neighbor.point)
+                    .or_default()
+                    .push((unit.get_team(), p, zone));
+            }
+        }
+        Self { zones }
+    }
+
+    pub fn get(&self, position: Point) -> &[ZoneOfControlInfluence] {
+        self.zones
+            .get(&position)
+            .map(|zones| &zones[..])
+            .unwrap_or(&[])
+    }
+
+    /// the combined zone of control that enemies of `team` exert at `position`
+    pub fn effect_on(&self, position: Point, team: ClientPerspective) -> ZoneOfControl {
+        self.get(position)
+            .iter()
+            .filter(|(t, _, _)| *t != team)
+            .fold(ZoneOfControl::None, |result, (_, _, zone)| {
+                result.combine(*zone)
+            })
+    }
+}